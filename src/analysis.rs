@@ -0,0 +1,222 @@
+//! Value statistics and codec suggestion.
+//!
+//! [`analyze`] measures a sample of values and computes the exact size each
+//! of the crate's general purpose encodings would produce for it. It then
+//! suggests the smallest. Use it offline to pick a codec for a data set, or
+//! at run time to choose an encoding per block.
+//!
+//! ```rust
+//! use zigzag_rs::analysis::{analyze, Suggestion};
+//! use zigzag_rs::predictor::Predictor;
+//!
+//! // A smooth ramp is best served by linear prediction
+//! let ramp: Vec<i32> = (0..256).map(|i| 10_000 + 37 * i).collect();
+//! let analysis = analyze(&ramp);
+//! assert_eq!(analysis.max_delta_bits, 7);
+//! assert_eq!(analysis.suggestion, Suggestion::Predictor(Predictor::Order2));
+//!
+//! // Small values with rare outliers suit patched frame of reference
+//! let outliers: Vec<i32> = (0..512).map(|i| if i % 50 == 0 { 1 << 28 } else { i % 13 }).collect();
+//! let analysis = analyze(&outliers);
+//! assert_eq!(analysis.suggestion, Suggestion::FastPfor);
+//! assert!(analysis.suggested_len() < analysis.varint_len);
+//! ```
+
+use crate::delta::{self, wrapping_delta};
+use crate::fastpfor::FastPfor;
+use crate::predictor::{self, Predictor};
+use crate::varint::{self, Varint};
+use crate::{Sample, ZigZag};
+
+/// Encoding suggested by [`analyze`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suggestion {
+    /// ZigZag varints, see [`varint::encode_slice`]
+    Varint,
+    /// Delta coded ZigZag varints, see [`delta::encode`]
+    Delta,
+    /// Patched frame of reference, see [`FastPfor`]
+    FastPfor,
+    /// Rice coded prediction residuals, see [`predictor::encode_with`]
+    Predictor(Predictor),
+}
+
+impl Suggestion {
+    /// Identifiers of predictor suggestions start here, followed by the predictor id
+    const PREDICTOR_ID: u8 = 0x10;
+
+    /// Stable identifier of the encoding, suitable for storing in headers
+    ///
+    /// Readers restore the encoding with [`Suggestion::from_id`].
+    pub fn id(self) -> u8 {
+        match self {
+            Suggestion::Varint => 0,
+            Suggestion::Delta => 1,
+            Suggestion::FastPfor => 2,
+            Suggestion::Predictor(predictor) => Self::PREDICTOR_ID | predictor.id(),
+        }
+    }
+
+    /// Look up an encoding by the identifier returned from [`Suggestion::id`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Suggestion::Varint),
+            1 => Some(Suggestion::Delta),
+            2 => Some(Suggestion::FastPfor),
+            _ if id & !0x0f == Self::PREDICTOR_ID => Predictor::from_id(id & 0x0f).map(Suggestion::Predictor),
+            _ => None,
+        }
+    }
+}
+
+/// Statistics of a sequence of values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Analysis {
+    /// Number of values
+    pub count: usize,
+    /// Largest bit width of a ZigZag encoded value
+    pub max_zigzag_bits: u32,
+    /// Largest bit width of a ZigZag encoded difference between neighbors
+    pub max_delta_bits: u32,
+    /// Number of runs of equal neighboring values
+    pub runs: usize,
+    /// Length of the longest run of equal values
+    pub longest_run: usize,
+    /// Predictor with the smallest residuals
+    pub predictor: Predictor,
+    /// Encoded size with [`Suggestion::Varint`]
+    pub varint_len: usize,
+    /// Encoded size with [`Suggestion::Delta`]
+    pub delta_len: usize,
+    /// Encoded size with [`Suggestion::FastPfor`]
+    pub fastpfor_len: usize,
+    /// Encoded size with [`Suggestion::Predictor`] and [`Analysis::predictor`]
+    pub predictor_len: usize,
+    /// Smallest encoding, preferring the simpler one on ties
+    pub suggestion: Suggestion,
+}
+
+impl Analysis {
+    /// Encoded size with the suggested encoding
+    pub fn suggested_len(&self) -> usize {
+        match self.suggestion {
+            Suggestion::Varint => self.varint_len,
+            Suggestion::Delta => self.delta_len,
+            Suggestion::FastPfor => self.fastpfor_len,
+            Suggestion::Predictor(_) => self.predictor_len,
+        }
+    }
+}
+
+fn bit_width(value: u64) -> u32 {
+    64 - value.leading_zeros()
+}
+
+/// Analyze `values` and suggest an encoding
+///
+/// # Arguments
+/// * `values` - Values to analyze
+///
+/// # Returns
+/// The statistics of `values` and the encoding with the smallest output
+pub fn analyze<T>(values: &[T]) -> Analysis
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    let mut max_zigzag_bits = 0;
+    let mut max_delta_bits = 0;
+    let (mut runs, mut longest_run, mut run) = (0, 0, 0);
+    let mut prev: Option<T> = None;
+    for &value in values {
+        max_zigzag_bits = max_zigzag_bits.max(bit_width(i64::zigzag_encode(value.to_i64())));
+        match prev {
+            Some(prev) if prev.to_i64() == value.to_i64() => run += 1,
+            _ => {
+                runs += 1;
+                run = 1;
+            }
+        }
+        if let Some(prev) = prev {
+            let delta = wrapping_delta(value, prev).to_i64();
+            max_delta_bits = max_delta_bits.max(bit_width(i64::zigzag_encode(delta)));
+        }
+        longest_run = longest_run.max(run);
+        prev = Some(value);
+    }
+
+    let predictor = predictor::best_predictor(values);
+    let varint_len = varint::encoded_len(values);
+    let delta_len = delta::encoded_len(values);
+    let fastpfor_len = FastPfor.encoded_len(values);
+    let predictor_len = predictor::encoded_len(values, predictor);
+
+    let mut suggestion = (Suggestion::Varint, varint_len);
+    for candidate in [(Suggestion::Delta, delta_len), (Suggestion::FastPfor, fastpfor_len), (Suggestion::Predictor(predictor), predictor_len)] {
+        if candidate.1 < suggestion.1 {
+            suggestion = candidate;
+        }
+    }
+
+    Analysis {
+        count: values.len(),
+        max_zigzag_bits,
+        max_delta_bits,
+        runs,
+        longest_run,
+        predictor,
+        varint_len,
+        delta_len,
+        fastpfor_len,
+        predictor_len,
+        suggestion: suggestion.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_analysis_statistics() {
+        let values = [5i16, 5, 5, -3, -3, 100, 5];
+        let analysis = analyze(&values);
+        assert_eq!(analysis.count, 7);
+        assert_eq!(analysis.max_zigzag_bits, 8);
+        // -3 to 100 is a jump of 103, ZigZag encoded to 206
+        assert_eq!(analysis.max_delta_bits, 8);
+        assert_eq!((analysis.runs, analysis.longest_run), (4, 3));
+
+        let empty = analyze::<i32>(&[]);
+        assert_eq!((empty.count, empty.runs, empty.longest_run, empty.max_zigzag_bits), (0, 0, 0, 0));
+        assert_eq!(empty.suggestion, Suggestion::Varint);
+    }
+
+    #[test]
+    fn test_suggestion_ids() {
+        let mut all = std::vec![Suggestion::Varint, Suggestion::Delta, Suggestion::FastPfor];
+        all.extend(Predictor::ALL.map(Suggestion::Predictor));
+        for suggestion in all {
+            assert_eq!(Suggestion::from_id(suggestion.id()), Some(suggestion));
+        }
+        assert_eq!(Suggestion::Predictor(Predictor::Order2).id(), 0x12);
+        for id in [3, 0x0f, 0x14, 0x20, 0xff] {
+            assert_eq!(Suggestion::from_id(id), None);
+        }
+    }
+
+    #[test]
+    fn test_analysis_sizes_match_encoders() {
+        let values: Vec<i64> = (0..1000).map(|i| if i % 97 == 0 { 1 << 45 } else { 1_000_000 + (i * 7919) % 200 }).collect();
+        let analysis = analyze(&values);
+        let mut buf = vec![0u8; 16_384];
+        assert_eq!(varint::encode_slice(&values, &mut buf), Ok(analysis.varint_len));
+        assert_eq!(delta::encode(&values, &mut buf), Ok(analysis.delta_len));
+        assert_eq!(crate::codec::Codec::encode(&FastPfor, &values, &mut buf), Ok(analysis.fastpfor_len));
+        assert_eq!(predictor::encode_with(&values, analysis.predictor, &mut buf), Ok(analysis.predictor_len));
+        assert!(analysis.suggested_len() <= analysis.varint_len.min(analysis.delta_len));
+    }
+}
@@ -0,0 +1,293 @@
+//! Integers whose width is only known at run time.
+//!
+//! Columnar files and schema-driven protocols describe the width of each
+//! column in their metadata, so a reader learns it only after opening the
+//! file. [`AnyZigZag`] holds a signed value of any native width and
+//! [`AnyEncoded`] its ZigZag form; both are tagged with a [`Width`], which
+//! drives decoding. [`decode_slice`] reads a column of varints for a given
+//! width, so readers need no dispatch layer of their own.
+//!
+//! A width enabled by its Cargo feature has a variant in each of the three
+//! enums. `isize` is left out, as its width differs between the writer and
+//! the reader.
+//!
+//! ```rust
+//! # #[cfg(all(feature = "i8", feature = "i16"))] {
+//! use zigzag_rs::any::{self, AnyZigZag, Width};
+//!
+//! // The width comes from the schema, here as a stored id
+//! let width = Width::from_id(1).unwrap();
+//! assert_eq!(width, Width::I16);
+//!
+//! let mut column = [AnyZigZag::default(); 3];
+//! assert_eq!(any::decode_slice(width, &[0x01, 0x02, 0xff, 0xff, 0x03], &mut column), Ok(3));
+//! assert_eq!(column, [AnyZigZag::I16(-1), AnyZigZag::I16(1), AnyZigZag::I16(i16::MIN)]);
+//!
+//! let mut buf = [0u8; 8];
+//! assert_eq!(any::encode_slice(&column, &mut buf), Ok(5));
+//! assert_eq!(AnyZigZag::I16(-1).encode().decode(), AnyZigZag::I16(-1));
+//! # }
+//! ```
+
+use crate::varint::{at_offset, Varint};
+use crate::{ZigZag, ZigZagError};
+
+macro_rules! any_types {
+    ($($feature:literal $id:literal $variant:ident $encoded:ident $signed:ident $unsigned:ident),*) => {
+        /// Width of a signed integer column
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Width {
+            $(
+                #[doc = concat!("`", stringify!($signed), "`")]
+                #[cfg(feature = $feature)]
+                $variant,
+            )*
+        }
+
+        impl Width {
+            /// Stable numeric id of the width, for storing it in a schema
+            pub fn id(self) -> u8 {
+                match self {
+                    $(#[cfg(feature = $feature)] Width::$variant => $id,)*
+                }
+            }
+
+            /// Look up a width by the id returned from [`Width::id`]
+            ///
+            /// Returns `None` for unknown ids and for widths whose feature is disabled.
+            pub fn from_id(id: u8) -> Option<Self> {
+                match id {
+                    $(#[cfg(feature = $feature)] $id => Some(Width::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Number of bits of the width
+            pub fn bits(self) -> u32 {
+                match self {
+                    $(#[cfg(feature = $feature)] Width::$variant => <$signed>::BITS,)*
+                }
+            }
+
+            /// Longest varint a value of this width encodes to
+            pub fn max_varint_len(self) -> usize {
+                match self {
+                    $(#[cfg(feature = $feature)] Width::$variant => <$unsigned as Varint>::MAX_LEN,)*
+                }
+            }
+        }
+
+        /// Signed integer of a width chosen at run time
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum AnyZigZag {
+            $(
+                #[doc = concat!("`", stringify!($signed), "` value")]
+                #[cfg(feature = $feature)]
+                $variant($signed),
+            )*
+        }
+
+        /// ZigZag encoded form of an [`AnyZigZag`]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum AnyEncoded {
+            $(
+                #[doc = concat!("Encoded `", stringify!($signed), "` value")]
+                #[cfg(feature = $feature)]
+                $encoded($unsigned),
+            )*
+        }
+
+        impl AnyZigZag {
+            /// Width of the value
+            pub fn width(self) -> Width {
+                match self {
+                    $(#[cfg(feature = $feature)] AnyZigZag::$variant(_) => Width::$variant,)*
+                }
+            }
+
+            /// ZigZag encode the value, keeping its width
+            pub fn encode(self) -> AnyEncoded {
+                match self {
+                    $(#[cfg(feature = $feature)] AnyZigZag::$variant(value) => AnyEncoded::$encoded(<$signed>::zigzag_encode(value)),)*
+                }
+            }
+
+            /// Zero of the given width
+            pub fn zero(width: Width) -> Self {
+                match width {
+                    $(#[cfg(feature = $feature)] Width::$variant => AnyZigZag::$variant(0),)*
+                }
+            }
+
+            /// Encode the value as a ZigZag varint
+            ///
+            /// # Returns
+            /// * `Ok(len)` with the number of bytes written
+            /// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+            pub fn encode_varint(self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+                match self.encode() {
+                    $(#[cfg(feature = $feature)] AnyEncoded::$encoded(value) => value.encode_varint(out),)*
+                }
+            }
+
+            /// Decode a ZigZag varint of the given width from the start of `input`
+            ///
+            /// # Returns
+            /// * `Ok((value, len))` with the decoded value and the number of bytes consumed
+            /// * `Err(ZigZagError::UnexpectedEof)` if the input ends in the middle of the value
+            /// * `Err(ZigZagError::VarintOverflow)` if the value does not fit into `width`
+            pub fn decode_varint(width: Width, input: &[u8]) -> Result<(Self, usize), ZigZagError> {
+                match width {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Width::$variant => {
+                            let (value, len) = <$unsigned>::decode_varint(input)?;
+                            Ok((AnyZigZag::$variant(<$signed>::zigzag_decode(value)), len))
+                        }
+                    )*
+                }
+            }
+
+            /// Number of bytes [`AnyZigZag::encode_varint`] writes for the value
+            pub fn varint_len(self) -> usize {
+                match self.encode() {
+                    $(#[cfg(feature = $feature)] AnyEncoded::$encoded(value) => value.varint_len(),)*
+                }
+            }
+        }
+
+        impl AnyEncoded {
+            /// Width of the value this was encoded from
+            pub fn width(self) -> Width {
+                match self {
+                    $(#[cfg(feature = $feature)] AnyEncoded::$encoded(_) => Width::$variant,)*
+                }
+            }
+
+            /// Decode the value
+            pub fn decode(self) -> AnyZigZag {
+                match self {
+                    $(#[cfg(feature = $feature)] AnyEncoded::$encoded(value) => AnyZigZag::$variant(<$signed>::zigzag_decode(value)),)*
+                }
+            }
+        }
+
+        $(
+            #[cfg(feature = $feature)]
+            impl From<$signed> for AnyZigZag {
+                fn from(value: $signed) -> Self {
+                    AnyZigZag::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+any_types!(
+    "i8" 0 I8 U8 i8 u8,
+    "i16" 1 I16 U16 i16 u16,
+    "i32" 2 I32 U32 i32 u32,
+    "i64" 3 I64 U64 i64 u64,
+    "i128" 4 I128 U128 i128 u128
+);
+
+/// An `i8` zero, as a placeholder for output buffers
+#[cfg(feature = "i8")]
+impl Default for AnyZigZag {
+    fn default() -> Self {
+        AnyZigZag::I8(0)
+    }
+}
+
+/// Encode values of any widths as consecutive ZigZag varints
+///
+/// # Returns
+/// * `Ok(len)` with the total number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode_slice(values: &[AnyZigZag], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let needed = values.iter().map(|value| value.varint_len()).sum();
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let mut pos = 0;
+    for value in values {
+        pos += value.encode_varint(&mut out[pos..])?;
+    }
+    Ok(pos)
+}
+
+/// Decode all ZigZag varints in `input` as values of `width` into `out`
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError::UnexpectedEof)` or `Err(ZigZagError::VarintOverflow)` with the
+///   offset of the offending value if `input` is malformed
+pub fn decode_slice(width: Width, input: &[u8], out: &mut [AnyZigZag]) -> Result<usize, ZigZagError> {
+    let needed = crate::varint::count(input);
+    if needed > out.len() {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let mut pos = 0;
+    for slot in out[..needed].iter_mut() {
+        let (value, len) = AnyZigZag::decode_varint(width, &input[pos..]).map_err(|err| at_offset(err, pos))?;
+        *slot = value;
+        pos += len;
+    }
+    // A trailing incomplete varint is not counted
+    if pos < input.len() {
+        return Err(ZigZagError::UnexpectedEof { offset: pos });
+    }
+    Ok(needed)
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::varint;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_any_matches_typed_coding() {
+        let mut buf = [0u8; 19];
+        let mut typed = [0u8; 19];
+        let values = [AnyZigZag::from(i8::MIN), AnyZigZag::from(-300i16), AnyZigZag::from(i32::MAX), AnyZigZag::from(-1i64)];
+        for value in values {
+            let len = value.encode_varint(&mut buf).unwrap();
+            assert_eq!(len, value.varint_len());
+            assert_eq!(AnyZigZag::decode_varint(value.width(), &buf[..len]), Ok((value, len)));
+            assert_eq!(value.encode().width(), value.width());
+            assert_eq!(value.encode().decode(), value);
+        }
+        assert_eq!(varint::encode(-300i16, &mut typed), AnyZigZag::I16(-300).encode_varint(&mut buf));
+        assert_eq!(typed[..2], buf[..2]);
+        assert_eq!(AnyZigZag::I32(-2).encode(), AnyEncoded::U32(3));
+        #[cfg(feature = "i128")]
+        assert_eq!(Width::I128.max_varint_len(), 19);
+        assert_eq!(AnyZigZag::zero(Width::I64), AnyZigZag::I64(0));
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_any_width_ids_and_slices() {
+        for id in 0..4 {
+            let width = Width::from_id(id).unwrap();
+            assert_eq!(width.id(), id);
+            assert_eq!(width.bits(), 8 << id);
+        }
+        assert_eq!(Width::from_id(9), None);
+
+        let mut out = [AnyZigZag::default(); 2];
+        // 256 does not fit into an i8
+        assert_eq!(decode_slice(Width::I8, &[0x02, 0x80, 0x02], &mut out), Err(ZigZagError::VarintOverflow { offset: 1 }));
+        assert_eq!(decode_slice(Width::I16, &[0x02, 0x80, 0x02], &mut out), Ok(2));
+        assert_eq!(out, [AnyZigZag::I16(1), AnyZigZag::I16(128)]);
+        assert_eq!(decode_slice(Width::I16, &[0x02, 0x80], &mut out), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+        assert_eq!(
+            decode_slice(Width::I32, &[0x00; 3], &mut out),
+            Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 })
+        );
+        assert_eq!(encode_slice(&out, &mut [0u8; 2]), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+    }
+}
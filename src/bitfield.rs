@@ -0,0 +1,133 @@
+//! Signed bit fields in packed register words.
+//!
+//! Hardware status registers pack signed readings into N-bit two's complement
+//! fields. A [`Field`] describes one such field by its position and width and
+//! extracts it with sign extension, optionally ZigZag encoding the result, or
+//! inserts a value back into a word. Words narrower than 64 bits are widened
+//! with `as u64` first.
+//!
+//! ```rust
+//! use zigzag_rs::bitfield::Field;
+//!
+//! // Bits 4..16 of a status register hold a 12-bit signed temperature
+//! const TEMPERATURE: Field = Field::new(4, 12);
+//!
+//! let status: u32 = 0xfff3_ff81;
+//! assert_eq!(TEMPERATURE.extract(status as u64), -8);
+//! assert_eq!(TEMPERATURE.extract_zigzag(status as u64), 15);
+//!
+//! let word = TEMPERATURE.insert(0, 100);
+//! assert_eq!(word, 100 << 4);
+//! assert_eq!(TEMPERATURE.insert_zigzag(0, 15), TEMPERATURE.insert(0, -8));
+//! ```
+
+use crate::ZigZag;
+
+/// Position and width of a signed field within a 64-bit word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Field {
+    shift: u32,
+    bits: u32,
+}
+
+impl Field {
+    /// Describe the field of `bits` bits starting at bit `shift`
+    ///
+    /// # Panics
+    /// Panics if `bits` is zero or the field extends past bit 63
+    pub const fn new(shift: u32, bits: u32) -> Self {
+        assert!(bits > 0 && shift < 64 && bits <= 64 - shift, "field must have 1 to 64 bits within the word");
+        Field { shift, bits }
+    }
+
+    /// Position of the lowest bit of the field
+    pub const fn shift(&self) -> u32 {
+        self.shift
+    }
+
+    /// Width of the field in bits
+    pub const fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Mask selecting the field within a word
+    pub const fn mask(&self) -> u64 {
+        (u64::MAX >> (64 - self.bits)) << self.shift
+    }
+
+    /// Smallest value the field can hold
+    pub const fn min(&self) -> i64 {
+        i64::MIN >> (64 - self.bits)
+    }
+
+    /// Largest value the field can hold
+    pub const fn max(&self) -> i64 {
+        i64::MAX >> (64 - self.bits)
+    }
+
+    /// Whether `value` fits into the field
+    pub const fn fits(&self, value: i64) -> bool {
+        value >= self.min() && value <= self.max()
+    }
+
+    /// Read the field from `word` and sign-extend it
+    pub const fn extract(&self, word: u64) -> i64 {
+        // Move the field to the top, then shift it back down arithmetically
+        ((word << (64 - self.shift - self.bits)) as i64) >> (64 - self.bits)
+    }
+
+    /// Read the field from `word`, sign-extend it and ZigZag encode the result
+    ///
+    /// The result is below `2^bits`, so it is as wide as the field.
+    pub fn extract_zigzag(&self, word: u64) -> u64 {
+        i64::zigzag_encode(self.extract(word))
+    }
+
+    /// Replace the field in `word` with `value`
+    ///
+    /// # Panics
+    /// Panics if `value` does not fit into the field
+    pub fn insert(&self, word: u64, value: i64) -> u64 {
+        assert!(self.fits(value), "value does not fit into the field");
+        (word & !self.mask()) | (((value as u64) << self.shift) & self.mask())
+    }
+
+    /// Replace the field in `word` with the ZigZag decoded `encoded`
+    ///
+    /// # Panics
+    /// Panics if the decoded value does not fit into the field
+    pub fn insert_zigzag(&self, word: u64, encoded: u64) -> u64 {
+        self.insert(word, i64::zigzag_decode(encoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_extract_and_insert() {
+        for (shift, bits) in [(0, 1), (3, 5), (17, 12), (0, 64), (63, 1), (40, 24)] {
+            let field = Field::new(shift, bits);
+            for value in [field.min(), -1, 0, field.max()] {
+                // Surrounding bits are kept
+                let word = field.insert(u64::MAX, value);
+                assert_eq!(word & !field.mask(), !field.mask());
+                assert_eq!(field.extract(word), value);
+                assert_eq!(field.extract(field.insert(0, value)), value);
+
+                let encoded = field.extract_zigzag(word);
+                assert!(bits == 64 || encoded >> bits == 0);
+                assert_eq!(field.insert_zigzag(0, encoded), field.insert(0, value));
+            }
+        }
+        assert_eq!((Field::new(0, 1).min(), Field::new(0, 1).max()), (-1, 0));
+        assert!(!Field::new(2, 8).fits(128));
+    }
+
+    #[test]
+    #[should_panic(expected = "value does not fit into the field")]
+    fn test_field_insert_rejects_wide_values() {
+        Field::new(8, 4).insert(0, 8);
+    }
+}
@@ -0,0 +1,172 @@
+//! Bit-level writer and reader over borrowed byte buffers.
+//!
+//! Bits are written most significant bit first, which matches the layout used
+//! by the bit-oriented codecs in this crate.
+//!
+//! ```rust
+//! use zigzag_rs::bits::{BitReader, BitWriter};
+//!
+//! let mut buf = [0u8; 4];
+//! let mut writer = BitWriter::new(&mut buf);
+//! writer.write(0b101, 3).unwrap();
+//! writer.write(0x1ff, 9).unwrap();
+//! let len = writer.finish();
+//! assert_eq!(len, 2);
+//!
+//! let mut reader = BitReader::new(&buf[..len]);
+//! assert_eq!(reader.read(3), Ok(0b101));
+//! assert_eq!(reader.read(9), Ok(0x1ff));
+//! ```
+
+use crate::ZigZagError;
+
+/// Writes bit fields into a byte buffer, most significant bit first
+#[derive(Debug)]
+pub struct BitWriter<'a> {
+    out: &'a mut [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitWriter<'a> {
+    /// Create a writer that starts at the beginning of `out`
+    pub fn new(out: &'a mut [u8]) -> Self {
+        BitWriter { out, bit_pos: 0 }
+    }
+
+    /// Number of bits written so far
+    pub fn bit_len(&self) -> usize {
+        self.bit_pos
+    }
+
+    /// Write the lowest `bits` bits of `value`
+    ///
+    /// # Arguments
+    /// * `value` - Value to write, bits above `bits` are ignored
+    /// * `bits` - Number of bits to write, at most 64
+    ///
+    /// # Returns
+    /// * `Ok(())` if the bits were written
+    /// * `Err(ZigZagError::BufferTooSmall)` if the buffer has no room for them
+    pub fn write(&mut self, value: u64, bits: u32) -> Result<(), ZigZagError> {
+        debug_assert!(bits <= 64);
+        let needed = (self.bit_pos + bits as usize).div_ceil(8);
+        if needed > self.out.len() {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: self.out.len() });
+        }
+
+        let mut remaining = bits;
+        while remaining > 0 {
+            let byte = self.bit_pos / 8;
+            let free = 8 - (self.bit_pos % 8) as u32;
+            let take = free.min(remaining);
+            let chunk = ((value >> (remaining - take)) & ((1u64 << take) - 1)) as u8;
+            if free == 8 {
+                self.out[byte] = 0;
+            }
+            self.out[byte] |= chunk << (free - take);
+            self.bit_pos += take as usize;
+            remaining -= take;
+        }
+        Ok(())
+    }
+
+    /// Write a single bit
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), ZigZagError> {
+        self.write(bit as u64, 1)
+    }
+
+    /// Finish writing and return the number of bytes used, including a partial last byte
+    pub fn finish(self) -> usize {
+        self.bit_pos.div_ceil(8)
+    }
+}
+
+/// Reads bit fields from a byte buffer, most significant bit first
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    input: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Create a reader that starts at the beginning of `input`
+    pub fn new(input: &'a [u8]) -> Self {
+        BitReader { input, bit_pos: 0 }
+    }
+
+    /// Number of bits read so far
+    pub fn bit_pos(&self) -> usize {
+        self.bit_pos
+    }
+
+    /// Number of bytes touched so far, including a partially read last byte
+    pub fn byte_len(&self) -> usize {
+        self.bit_pos.div_ceil(8)
+    }
+
+    /// Read `bits` bits into the low bits of the result
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the bits read
+    /// * `Err(ZigZagError::UnexpectedEof)` with the current byte offset if the input is exhausted
+    pub fn read(&mut self, bits: u32) -> Result<u64, ZigZagError> {
+        debug_assert!(bits <= 64);
+        if self.bit_pos + bits as usize > self.input.len() * 8 {
+            return Err(ZigZagError::UnexpectedEof { offset: self.bit_pos / 8 });
+        }
+
+        let mut value = 0u64;
+        let mut remaining = bits;
+        while remaining > 0 {
+            let byte = self.input[self.bit_pos / 8];
+            let available = 8 - (self.bit_pos % 8) as u32;
+            let take = available.min(remaining);
+            let chunk = (byte >> (available - take)) & (((1u16 << take) - 1) as u8);
+            value = (value << take) | chunk as u64;
+            self.bit_pos += take as usize;
+            remaining -= take;
+        }
+        Ok(value)
+    }
+
+    /// Read a single bit
+    pub fn read_bit(&mut self) -> Result<bool, ZigZagError> {
+        self.read(1).map(|bit| bit == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits_round_trip() {
+        let fields = [(1u64, 1u32), (0, 1), (0x2a, 6), (u64::MAX, 64), (0x1234, 13), (0, 0), (7, 3)];
+        let mut buf = [0xffu8; 16];
+        let mut writer = BitWriter::new(&mut buf);
+        for &(value, bits) in fields.iter() {
+            writer.write(value, bits).unwrap();
+        }
+        let total_bits: u32 = fields.iter().map(|&(_, bits)| bits).sum();
+        assert_eq!(writer.bit_len(), total_bits as usize);
+        let len = writer.finish();
+
+        let mut reader = BitReader::new(&buf[..len]);
+        for &(value, bits) in fields.iter() {
+            let mask = if bits == 64 { u64::MAX } else { (1u64 << bits) - 1 };
+            assert_eq!(reader.read(bits), Ok(value & mask));
+        }
+    }
+
+    #[test]
+    fn test_bits_errors() {
+        let mut buf = [0u8; 1];
+        let mut writer = BitWriter::new(&mut buf);
+        writer.write(0, 7).unwrap();
+        assert_eq!(writer.write(0, 2), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+
+        let mut reader = BitReader::new(&buf);
+        reader.read(5).unwrap();
+        assert_eq!(reader.read(4), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+    }
+}
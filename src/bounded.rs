@@ -0,0 +1,324 @@
+//! Encoding into fixed-capacity collections.
+//!
+//! Embedded code keeps its buffers in fixed-capacity vectors such as
+//! `heapless::Vec`. [`BoundedPush`] describes such a collection: how much room
+//! it has left and how to append to it. The functions here check that the
+//! whole result fits before appending anything, so a full collection yields
+//! `BufferTooSmall` instead of a panic or a partial write.
+//!
+//! The crate has no dependencies, so the trait is implemented for its own
+//! [`BoundedVec`], for `Vec` with the `alloc` feature, and left for other
+//! collections to implement. For `heapless::Vec<T, N>` or
+//! `arrayvec::ArrayVec<T, N>` the implementation forwards
+//! `remaining_capacity` to `capacity() - len()` and `push_within_capacity`
+//! to `push`. `tinyvec::ArrayVec<[T; N]>` is implemented the same way, and
+//! `tinyvec::TinyVec`, which spills to the heap, like `Vec`. [`encode_to_vec`]
+//! and [`encode_varint_to_vec`] return a new [`BoundedVec`] for callers
+//! without a collection of their own. Like tinyvec, [`BoundedVec`] contains
+//! no unsafe code.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::bounded::{self, BoundedPush};
+//! use zigzag_rs::ZigZagError;
+//!
+//! /// A minimal fixed-capacity vector, standing in for `heapless::Vec`
+//! struct Frame { bytes: [u8; 4], len: usize }
+//!
+//! impl BoundedPush<u8> for Frame {
+//!     fn remaining_capacity(&self) -> usize {
+//!         self.bytes.len() - self.len
+//!     }
+//!
+//!     fn push_within_capacity(&mut self, value: u8) {
+//!         self.bytes[self.len] = value;
+//!         self.len += 1;
+//!     }
+//! }
+//!
+//! let mut frame = Frame { bytes: [0; 4], len: 0 };
+//! assert_eq!(bounded::encode_varint(&[-1i32, 300], &mut frame), Ok(3));
+//! assert_eq!(frame.bytes[..frame.len], [0x01, 0xd8, 0x04]);
+//! // Two more bytes do not fit, and nothing is appended
+//! assert_eq!(
+//!     bounded::encode_varint(&[-300i32], &mut frame),
+//!     Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 })
+//! );
+//! assert_eq!(frame.len, 3);
+//! # }
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// A collection with a fixed capacity that values can be appended to
+pub trait BoundedPush<T> {
+    /// Number of values that can still be appended
+    fn remaining_capacity(&self) -> usize;
+
+    /// Append a value, which the caller has checked fits
+    ///
+    /// # Panics
+    /// May panic if the collection is full
+    fn push_within_capacity(&mut self, value: T);
+
+    /// Append a value if there is room for it
+    ///
+    /// # Returns
+    /// * `Ok(())` if the value was appended
+    /// * `Err(ZigZagError::BufferTooSmall)` if the collection is full
+    fn try_push(&mut self, value: T) -> Result<(), ZigZagError> {
+        check_capacity(1, self.remaining_capacity())?;
+        self.push_within_capacity(value);
+        Ok(())
+    }
+}
+
+/// Vector of at most `N` values stored inline
+///
+/// Values are kept in an array that is initialized with `T::default()`, so
+/// the type needs no unsafe code; it suits the integer types this crate
+/// produces. It dereferences to the slice of values pushed so far.
+#[derive(Clone, Copy)]
+pub struct BoundedVec<T, const N: usize> {
+    values: [T; N],
+    len: usize,
+}
+
+impl<T: Copy + Default, const N: usize> BoundedVec<T, N> {
+    /// Create an empty vector
+    pub fn new() -> Self {
+        BoundedVec { values: [T::default(); N], len: 0 }
+    }
+
+    /// Maximum number of values, `N`
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Values pushed so far
+    pub fn as_slice(&self) -> &[T] {
+        &self.values[..self.len]
+    }
+
+    /// Values pushed so far, for changing them in place
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.values[..self.len]
+    }
+
+    /// Remove every value
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for BoundedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> core::ops::Deref for BoundedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> core::ops::DerefMut for BoundedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+// Compared by the values pushed, not by the stale ones after them
+
+impl<T: Copy + Default + PartialEq, const N: usize> PartialEq for BoundedVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Copy + Default + Eq, const N: usize> Eq for BoundedVec<T, N> {}
+
+impl<T: Copy + Default + core::hash::Hash, const N: usize> core::hash::Hash for BoundedVec<T, N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T: Copy + Default + core::fmt::Debug, const N: usize> core::fmt::Debug for BoundedVec<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> BoundedPush<T> for BoundedVec<T, N> {
+    fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    fn push_within_capacity(&mut self, value: T) {
+        self.values[self.len] = value;
+        self.len += 1;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> BoundedPush<T> for alloc::vec::Vec<T> {
+    fn remaining_capacity(&self) -> usize {
+        // A `Vec` grows up to `isize::MAX` bytes
+        (isize::MAX as usize / core::mem::size_of::<T>().max(1)).saturating_sub(self.len())
+    }
+
+    fn push_within_capacity(&mut self, value: T) {
+        self.push(value);
+    }
+}
+
+fn check_capacity(needed: usize, remaining: usize) -> Result<(), ZigZagError> {
+    if remaining < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: remaining });
+    }
+    Ok(())
+}
+
+/// Append the encoded form of every value of `values` to `out`
+///
+/// # Returns
+/// * `Ok(count)` with the number of values appended
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values, in which case nothing is appended
+pub fn encode<T, C>(values: &[T], out: &mut C) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    C: BoundedPush<T::UInt>,
+{
+    check_capacity(values.len(), out.remaining_capacity())?;
+    for &value in values {
+        out.push_within_capacity(T::zigzag_encode(value));
+    }
+    Ok(values.len())
+}
+
+/// Append the decoded form of every value of `values` to `out`
+///
+/// # Returns
+/// * `Ok(count)` with the number of values appended
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values, in which case nothing is appended
+pub fn decode<T, C>(values: &[T::UInt], out: &mut C) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Copy,
+    C: BoundedPush<T>,
+{
+    check_capacity(values.len(), out.remaining_capacity())?;
+    for &value in values {
+        out.push_within_capacity(T::zigzag_decode(value));
+    }
+    Ok(values.len())
+}
+
+/// Append `values` as consecutive ZigZag varints to a byte collection
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes appended
+/// * `Err(ZigZagError::BufferTooSmall)` with the number of bytes needed if
+///   `out` cannot hold them, in which case nothing is appended
+pub fn encode_varint<T, C>(values: &[T], out: &mut C) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+    C: BoundedPush<u8>,
+{
+    let needed = varint::encoded_len(values);
+    check_capacity(needed, out.remaining_capacity())?;
+    let mut buf = varint::scratch::<T::UInt>();
+    for &value in values {
+        let len = varint::encode(value, &mut buf)?;
+        for &byte in &buf[..len] {
+            out.push_within_capacity(byte);
+        }
+    }
+    Ok(needed)
+}
+
+/// Encode `values` into a new [`BoundedVec`] of capacity `N`
+///
+/// # Returns
+/// * `Ok(encoded)` with the encoded values
+/// * `Err(ZigZagError::BufferTooSmall)` if there are more than `N` values
+pub fn encode_to_vec<T, const N: usize>(values: &[T]) -> Result<BoundedVec<T::UInt, N>, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy + Default,
+{
+    let mut out = BoundedVec::new();
+    encode(values, &mut out)?;
+    Ok(out)
+}
+
+/// Encode `values` as ZigZag varints into a new [`BoundedVec`] of `N` bytes
+///
+/// # Returns
+/// * `Ok(bytes)` with the encoded bytes
+/// * `Err(ZigZagError::BufferTooSmall)` with the number of bytes needed if they exceed `N`
+pub fn encode_varint_to_vec<T, const N: usize>(values: &[T]) -> Result<BoundedVec<u8, N>, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let mut out = BoundedVec::new();
+    encode_varint(values, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_bounded_encode_checks_capacity_first() {
+        let mut out = BoundedVec::<u16, 4>::new();
+        assert_eq!(encode(&[-1i16, 1, -2], &mut out), Ok(3));
+        assert_eq!(*out, [1, 2, 3]);
+        assert_eq!(encode(&[5i16, 6], &mut out), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+        assert_eq!(out.len(), 3);
+        assert_eq!(out.try_push(9), Ok(()));
+        assert_eq!(out.try_push(9), Err(ZigZagError::BufferTooSmall { needed: 1, actual: 0 }));
+
+        // Cleared values are not compared
+        out.clear();
+        encode(&[-1i16], &mut out).unwrap();
+        assert_eq!(out, encode_to_vec::<i16, 4>(&[-1]).unwrap());
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32", feature = "i64"))]
+    fn test_encode_to_bounded_vec() {
+        let encoded = encode_to_vec::<i32, 8>(&[-1, 0, 1]).unwrap();
+        assert_eq!(std::format!("{:?}", encoded), "[1, 0, 2]");
+        let mut decoded = BoundedVec::<i32, 3>::new();
+        assert_eq!(decode::<i32, _>(&encoded, &mut decoded), Ok(3));
+        assert_eq!(*decoded, [-1, 0, 1]);
+
+        assert_eq!(encode_varint_to_vec::<i64, 4>(&[-1, 64]).map(|bytes| bytes.len()), Ok(3));
+        assert_eq!(encode_varint_to_vec::<i64, 2>(&[-1, 64]), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+        assert_eq!(encode_to_vec::<i8, 1>(&[1, 2]), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+    }
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64", feature = "alloc"))]
+    fn test_bounded_alloc_vec() {
+        let mut encoded = std::vec::Vec::new();
+        assert_eq!(encode(&[i32::MIN, 0], &mut encoded), Ok(2));
+        let mut decoded = std::vec::Vec::new();
+        assert_eq!(decode::<i32, _>(&encoded, &mut decoded), Ok(2));
+        assert_eq!(decoded, [i32::MIN, 0]);
+        let mut bytes = std::vec![0xaa];
+        assert_eq!(encode_varint(&[-1i64, 64], &mut bytes), Ok(3));
+        assert_eq!(bytes, [0xaa, 0x01, 0x80, 0x01]);
+    }
+}
@@ -0,0 +1,258 @@
+//! ADC readings with their calibration.
+//!
+//! [`CalibratedEncoder`] writes raw ADC codes as a [`delta`](crate::delta)
+//! stream behind a header holding the [`Calibration`] that converts them to
+//! engineering units. The raw codes are what is stored: scaling them first
+//! would multiply every difference by the scale and cost bits, while the
+//! codes and the calibration together reproduce both raw codes and
+//! engineering units exactly.
+//!
+//! ```text
+//! numerator (ZigZag varint) | denominator (varint) | offset (ZigZag varint) | delta stream of raw codes
+//! ```
+//!
+//! ```rust
+//! use zigzag_rs::calibration::{CalibratedBlock, CalibratedEncoder, Calibration};
+//!
+//! // 12-bit ADC over a 3300 mV range, with a -12 mV offset
+//! let encoder = CalibratedEncoder::new(Calibration::new(3300, 4096, -12));
+//! let raw = [2048, 2050, 2047, 2047];
+//! let mut buf = [0u8; 32];
+//! let len = encoder.encode(&raw, &mut buf).unwrap();
+//!
+//! let block = CalibratedBlock::parse(&buf[..len]).unwrap();
+//! let mut millivolts = [0i64; 4];
+//! assert_eq!(block.decode_units(&mut millivolts), Ok(4));
+//! assert_eq!(millivolts, [1638, 1639, 1637, 1637]);
+//!
+//! let mut codes = [0i32; 4];
+//! assert_eq!(block.decode_raw(&mut codes), Ok(4));
+//! assert_eq!(codes, raw);
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{delta, ZigZag, ZigZagError};
+
+/// Linear conversion `raw * numerator / denominator + offset` from raw codes to engineering units
+///
+/// The division rounds towards negative infinity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Calibration {
+    numerator: i64,
+    denominator: u64,
+    offset: i64,
+}
+
+impl Calibration {
+    /// Identity calibration, engineering units equal raw codes
+    pub const IDENTITY: Calibration = Calibration { numerator: 1, denominator: 1, offset: 0 };
+
+    /// Create a calibration
+    ///
+    /// # Panics
+    /// Panics if `denominator` is zero
+    pub const fn new(numerator: i64, denominator: u64, offset: i64) -> Self {
+        assert!(denominator != 0, "calibration denominator must not be zero");
+        Calibration { numerator, denominator, offset }
+    }
+
+    /// Scale numerator
+    pub const fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    /// Scale denominator
+    pub const fn denominator(&self) -> u64 {
+        self.denominator
+    }
+
+    /// Offset added after scaling
+    pub const fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// Convert a raw code to engineering units
+    ///
+    /// The product is computed in 128 bits and the result saturates at the
+    /// limits of `i64`.
+    pub fn apply(&self, raw: i32) -> i64 {
+        let scaled = (raw as i128 * self.numerator as i128).div_euclid(self.denominator as i128);
+        (scaled + self.offset as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    fn header_len(&self) -> usize {
+        i64::zigzag_encode(self.numerator).varint_len()
+            + self.denominator.varint_len()
+            + i64::zigzag_encode(self.offset).varint_len()
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Encoder for raw ADC codes that records their calibration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CalibratedEncoder {
+    calibration: Calibration,
+}
+
+impl CalibratedEncoder {
+    /// Create an encoder writing `calibration` into every block
+    pub fn new(calibration: Calibration) -> Self {
+        CalibratedEncoder { calibration }
+    }
+
+    /// Calibration written into the block header
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// Number of bytes [`encode`](CalibratedEncoder::encode) needs for `raw`
+    pub fn encoded_len(&self, raw: &[i32]) -> usize {
+        self.calibration.header_len() + delta::encoded_len(raw)
+    }
+
+    /// Encode raw codes as one block
+    ///
+    /// # Arguments
+    /// * `raw` - Raw ADC codes
+    /// * `out` - Output buffer, the block is written to its start
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written
+    /// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+    pub fn encode(&self, raw: &[i32], out: &mut [u8]) -> Result<usize, ZigZagError> {
+        let needed = self.encoded_len(raw);
+        if out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+        }
+        let mut pos = varint::encode(self.calibration.numerator, out)?;
+        pos += self.calibration.denominator.encode_varint(&mut out[pos..])?;
+        pos += varint::encode(self.calibration.offset, &mut out[pos..])?;
+        pos += delta::encode(raw, &mut out[pos..])?;
+        Ok(pos)
+    }
+}
+
+/// An encoded block with its calibration, read in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalibratedBlock<'a> {
+    calibration: Calibration,
+    data: &'a [u8],
+    data_start: usize,
+}
+
+impl<'a> CalibratedBlock<'a> {
+    /// Parse the header of a block written by [`CalibratedEncoder::encode`]
+    ///
+    /// # Returns
+    /// * `Ok(block)` with the calibration read from the header
+    /// * `Err(ZigZagError::InvalidData)` if the denominator is zero
+    /// * `Err(ZigZagError)` with the byte offset of the problem if the header is malformed
+    pub fn parse(input: &'a [u8]) -> Result<Self, ZigZagError> {
+        let (numerator, mut pos) = varint::decode::<i64>(input)?;
+        let (denominator, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        if denominator == 0 {
+            return Err(ZigZagError::InvalidData { offset: pos });
+        }
+        pos += len;
+        let (offset, len) = varint::decode::<i64>(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        pos += len;
+        Ok(CalibratedBlock {
+            calibration: Calibration { numerator, denominator, offset },
+            data: &input[pos..],
+            data_start: pos,
+        })
+    }
+
+    /// Calibration stored in the header
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// Number of values in the block
+    pub fn len(&self) -> usize {
+        varint::count(self.data)
+    }
+
+    /// Whether the block holds no values
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Decode the raw codes
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of values decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values
+    /// * `Err(ZigZagError)` with the byte offset of the problem if the data is malformed
+    pub fn decode_raw(&self, out: &mut [i32]) -> Result<usize, ZigZagError> {
+        delta::decode(self.data, out).map_err(|err| varint::at_offset(err, self.data_start))
+    }
+
+    /// Decode the values in engineering units
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of values decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values
+    /// * `Err(ZigZagError)` with the byte offset of the problem if the data is malformed
+    pub fn decode_units(&self, out: &mut [i64]) -> Result<usize, ZigZagError> {
+        let needed = self.len();
+        if out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+        }
+        let mut pos = 0;
+        let mut prev = 0i32;
+        for slot in out[..needed].iter_mut() {
+            let (diff, len) = varint::decode::<i32>(&self.data[pos..])
+                .map_err(|err| varint::at_offset(err, self.data_start + pos))?;
+            prev = prev.wrapping_add(diff);
+            *slot = self.calibration.apply(prev);
+            pos += len;
+        }
+        Ok(needed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_calibrated_round_trip() {
+        let raw: Vec<i32> = (0..1000).map(|i| (i * 37 % 4096) - 2048).chain([i32::MIN, i32::MAX]).collect();
+        for calibration in [Calibration::IDENTITY, Calibration::new(-5, 3, 1 << 40), Calibration::new(i64::MAX, 1, 0)] {
+            let encoder = CalibratedEncoder::new(calibration);
+            let mut buf = vec![0u8; encoder.encoded_len(&raw)];
+            assert_eq!(encoder.encode(&raw, &mut buf), Ok(buf.len()));
+
+            let block = CalibratedBlock::parse(&buf).unwrap();
+            assert_eq!(block.calibration(), calibration);
+            assert_eq!(block.len(), raw.len());
+            let mut codes = vec![0i32; raw.len()];
+            assert_eq!(block.decode_raw(&mut codes), Ok(raw.len()));
+            assert_eq!(codes, raw);
+            let mut units = vec![0i64; raw.len()];
+            assert_eq!(block.decode_units(&mut units), Ok(raw.len()));
+            assert!(units.iter().zip(&raw).all(|(&unit, &code)| unit == calibration.apply(code)));
+        }
+    }
+
+    #[test]
+    fn test_calibration_apply_and_errors() {
+        let calibration = Calibration::new(3300, 4096, 0);
+        assert_eq!(calibration.apply(4095), 3299);
+        // Floor division
+        assert_eq!(calibration.apply(-1), -1);
+        assert_eq!(Calibration::new(i64::MAX, 1, 1).apply(2), i64::MAX);
+
+        // Zero denominator in the header
+        assert_eq!(CalibratedBlock::parse(&[2, 0, 0]), Err(ZigZagError::InvalidData { offset: 1 }));
+        assert_eq!(CalibratedBlock::parse(&[2, 1]), Err(ZigZagError::UnexpectedEof { offset: 2 }));
+    }
+}
@@ -0,0 +1,131 @@
+//! Safe casts between integer slices and bytes.
+//!
+//! Encoded buffers are usually handed to code that deals in bytes: a DMA
+//! transfer, a file write or a packet builder. [`as_bytes`] views a slice of
+//! integers, or of [`ZigZagEncoded`] values, as its bytes in native byte
+//! order, and [`from_bytes`] views bytes as such a slice after checking their
+//! length and alignment. The unsafe code lives here, so callers need none.
+//!
+//! ```rust
+//! # #[cfg(feature = "i16")] {
+//! use zigzag_rs::cast;
+//! use zigzag_rs::encoded::ZigZagEncoded;
+//! use zigzag_rs::ZigZag;
+//!
+//! let mut encoded = [0u16; 3];
+//! i16::zigzag_encode_slice(&[-1, 1, 300], &mut encoded);
+//! let bytes = cast::as_bytes(&encoded);
+//! assert_eq!(bytes.len(), 6);
+//!
+//! // Back again, typed as encoded values
+//! let values: &[ZigZagEncoded<i16>] = cast::from_bytes(bytes).unwrap();
+//! assert_eq!(values[2].decode(), 300);
+//! # }
+//! ```
+
+use crate::encoded::ZigZagEncoded;
+use crate::{ZigZag, ZigZagError};
+
+/// Types that can be viewed as bytes and built from any bytes
+///
+/// # Safety
+/// Implementors must have no padding and no invalid bit patterns, and must
+/// not contain pointers or interior mutability.
+pub unsafe trait Plain: Copy {}
+
+// SAFETY: primitive integers have no padding and every bit pattern is valid
+unsafe impl Plain for u8 {}
+unsafe impl Plain for u16 {}
+unsafe impl Plain for u32 {}
+unsafe impl Plain for u64 {}
+unsafe impl Plain for u128 {}
+unsafe impl Plain for usize {}
+unsafe impl Plain for i8 {}
+unsafe impl Plain for i16 {}
+unsafe impl Plain for i32 {}
+unsafe impl Plain for i64 {}
+unsafe impl Plain for i128 {}
+unsafe impl Plain for isize {}
+
+// SAFETY: `ZigZagEncoded<T>` is a transparent wrapper of `T::UInt`
+unsafe impl<T: ZigZag> Plain for ZigZagEncoded<T> where T::UInt: Plain {}
+
+/// View a slice as its bytes in native byte order
+pub fn as_bytes<T: Plain>(values: &[T]) -> &[u8] {
+    // SAFETY: `T` has no padding, so every byte of the slice is initialized
+    unsafe { core::slice::from_raw_parts(values.as_ptr() as *const u8, core::mem::size_of_val(values)) }
+}
+
+/// View a mutable slice as its bytes in native byte order
+pub fn as_bytes_mut<T: Plain>(values: &mut [T]) -> &mut [u8] {
+    // SAFETY: as in `as_bytes`, and any bytes written form a valid `T`
+    unsafe { core::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut u8, core::mem::size_of_val(values)) }
+}
+
+/// View bytes in native byte order as a slice of `T`
+///
+/// # Returns
+/// * `Ok(values)` with the bytes viewed as values
+/// * `Err(ZigZagError::InvalidData)` if `bytes` is not aligned for `T`
+/// * `Err(ZigZagError::UnexpectedEof)` with the offset of the last value if `bytes` ends inside it
+pub fn from_bytes<T: Plain>(bytes: &[u8]) -> Result<&[T], ZigZagError> {
+    let count = check_bytes::<T>(bytes)?;
+    // SAFETY: `check_bytes` verified alignment and length, and every bit pattern is a valid `T`
+    Ok(unsafe { core::slice::from_raw_parts(bytes.as_ptr() as *const T, count) })
+}
+
+/// View mutable bytes in native byte order as a slice of `T`
+///
+/// # Returns
+/// * `Ok(values)` with the bytes viewed as values
+/// * `Err(ZigZagError::InvalidData)` if `bytes` is not aligned for `T`
+/// * `Err(ZigZagError::UnexpectedEof)` with the offset of the last value if `bytes` ends inside it
+pub fn from_bytes_mut<T: Plain>(bytes: &mut [u8]) -> Result<&mut [T], ZigZagError> {
+    let count = check_bytes::<T>(bytes)?;
+    // SAFETY: as in `from_bytes`
+    Ok(unsafe { core::slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut T, count) })
+}
+
+/// Number of values of `T` in `bytes`, if they can be viewed as such
+fn check_bytes<T: Plain>(bytes: &[u8]) -> Result<usize, ZigZagError> {
+    let size = core::mem::size_of::<T>();
+    if !bytes.as_ptr().cast::<T>().is_aligned() {
+        return Err(ZigZagError::InvalidData { offset: 0 });
+    }
+    let count = bytes.len() / size;
+    if count * size != bytes.len() {
+        return Err(ZigZagError::UnexpectedEof { offset: count * size });
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_cast_round_trip() {
+        let mut values = [-1i32, 0, i32::MAX];
+        let bytes = as_bytes(&values);
+        assert_eq!(bytes[..4], (-1i32).to_ne_bytes());
+        assert_eq!(from_bytes::<i32>(bytes), Ok(&[-1i32, 0, i32::MAX][..]));
+
+        as_bytes_mut(&mut values)[..4].copy_from_slice(&7i32.to_ne_bytes());
+        assert_eq!(values[0], 7);
+
+        let mut encoded = [ZigZagEncoded::encode(-2i64); 2];
+        let raw: &mut [u64] = from_bytes_mut(as_bytes_mut(&mut encoded)).unwrap();
+        raw[1] = 5;
+        assert_eq!(encoded[1].decode(), -3);
+    }
+
+    #[test]
+    fn test_cast_rejects_bad_bytes() {
+        let words = [0u32; 3];
+        let bytes = as_bytes(&words);
+        assert_eq!(from_bytes::<u32>(&bytes[1..5]), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(from_bytes::<u32>(&bytes[..10]), Err(ZigZagError::UnexpectedEof { offset: 8 }));
+        assert_eq!(from_bytes::<u8>(&bytes[1..4]).map(<[u8]>::len), Ok(3));
+    }
+}
@@ -0,0 +1,187 @@
+//! CBOR-style integers.
+//!
+//! CBOR (RFC 8949) does not ZigZag encode signed integers. It stores the sign
+//! in the major type of the item and writes `n` for a non-negative value and
+//! `-1 - n` for a negative one, so both halves use the full argument range.
+//! [`split`] and [`join`] convert between that form and `i64`; [`encode`]
+//! and [`decode`] read and write complete integer items, so one crate serves
+//! both ZigZag and CBOR pipelines.
+//!
+//! ```text
+//! item:  major type (3 bits) | additional info (5 bits) | argument (0, 1, 2, 4 or 8 bytes, big-endian)
+//! info:  0..=23 argument itself | 24 u8 | 25 u16 | 26 u32 | 27 u64
+//! ```
+//!
+//! ```rust
+//! use zigzag_rs::cbor::{self, Major};
+//!
+//! assert_eq!(cbor::split(-500), (Major::Negative, 499));
+//! assert_eq!(cbor::join(Major::Negative, 499), Ok(-500));
+//!
+//! let mut buf = [0u8; 9];
+//! assert_eq!(cbor::encode(-500, &mut buf), Ok(3));
+//! assert_eq!(buf[..3], [0x39, 0x01, 0xf3]);
+//! assert_eq!(cbor::decode(&buf[..3]), Ok((-500, 3)));
+//! ```
+
+use crate::ZigZagError;
+
+/// Major type of a CBOR integer item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Major {
+    /// Major type 0, the argument is the value
+    Unsigned,
+    /// Major type 1, the value is `-1 - argument`
+    Negative,
+}
+
+impl Major {
+    /// Major type number as stored in the top three bits of the initial byte
+    pub fn id(self) -> u8 {
+        match self {
+            Major::Unsigned => 0,
+            Major::Negative => 1,
+        }
+    }
+
+    /// Look up an integer major type by the number returned from [`Major::id`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Major::Unsigned),
+            1 => Some(Major::Negative),
+            _ => None,
+        }
+    }
+}
+
+/// Split `value` into its major type and argument
+pub const fn split(value: i64) -> (Major, u64) {
+    if value < 0 {
+        // -1 - value, which cannot overflow
+        (Major::Negative, !value as u64)
+    } else {
+        (Major::Unsigned, value as u64)
+    }
+}
+
+/// Rebuild a value from its major type and argument
+///
+/// # Returns
+/// * `Ok(value)` with the value
+/// * `Err(ZigZagError::OutOfRange)` if the value does not fit into an `i64`
+pub const fn join(major: Major, argument: u64) -> Result<i64, ZigZagError> {
+    if argument > i64::MAX as u64 {
+        return Err(ZigZagError::OutOfRange { offset: 0 });
+    }
+    Ok(match major {
+        Major::Unsigned => argument as i64,
+        Major::Negative => !(argument as i64),
+    })
+}
+
+/// Number of bytes [`encode`] writes for `value`
+pub const fn encoded_len(value: i64) -> usize {
+    match split(value).1 {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Write `value` as a CBOR integer item in its shortest form
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold the item
+pub fn encode(value: i64, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let len = encoded_len(value);
+    if out.len() < len {
+        return Err(ZigZagError::BufferTooSmall { needed: len, actual: out.len() });
+    }
+    let (major, argument) = split(value);
+    let info = match len {
+        1 => argument as u8,
+        2 => 24,
+        3 => 25,
+        5 => 26,
+        _ => 27,
+    };
+    out[0] = (major.id() << 5) | info;
+    out[1..len].copy_from_slice(&argument.to_be_bytes()[9 - len..]);
+    Ok(len)
+}
+
+/// Read a CBOR integer item from the start of `input`
+///
+/// Arguments written in a longer form than needed are accepted.
+///
+/// # Returns
+/// * `Ok((value, len))` with the value and the number of bytes consumed
+/// * `Err(ZigZagError::UnexpectedEof)` if the input ends inside the item
+/// * `Err(ZigZagError::InvalidData)` if the item is not an integer or its additional info is reserved
+/// * `Err(ZigZagError::OutOfRange)` if the value does not fit into an `i64`
+pub fn decode(input: &[u8]) -> Result<(i64, usize), ZigZagError> {
+    let &initial = input.first().ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+    let major = Major::from_id(initial >> 5).ok_or(ZigZagError::InvalidData { offset: 0 })?;
+    let (argument, len) = match initial & 0x1f {
+        info @ 0..=23 => (info as u64, 1),
+        info @ 24..=27 => {
+            let len = 1 + (1 << (info - 24));
+            let bytes = input.get(1..len).ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+            (bytes.iter().fold(0u64, |argument, &byte| argument << 8 | byte as u64), len)
+        }
+        _ => return Err(ZigZagError::InvalidData { offset: 0 }),
+    };
+    Ok((join(major, argument)?, len))
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_cbor_rfc_vectors() {
+        // Examples from RFC 8949, appendix A
+        let vectors: [(i64, &[u8]); 10] = [
+            (0, &[0x00]),
+            (23, &[0x17]),
+            (24, &[0x18, 0x18]),
+            (1000, &[0x19, 0x03, 0xe8]),
+            (1_000_000, &[0x1a, 0x00, 0x0f, 0x42, 0x40]),
+            (1_000_000_000_000, &[0x1b, 0x00, 0x00, 0x00, 0xe8, 0xd4, 0xa5, 0x10, 0x00]),
+            (-1, &[0x20]),
+            (-10, &[0x29]),
+            (-100, &[0x38, 0x63]),
+            (-1000, &[0x39, 0x03, 0xe7]),
+        ];
+        let mut buf = [0u8; 9];
+        for (value, bytes) in vectors {
+            assert_eq!(encode(value, &mut buf), Ok(bytes.len()));
+            assert_eq!(&buf[..bytes.len()], bytes);
+            assert_eq!(decode(bytes), Ok((value, bytes.len())));
+        }
+        for value in [i64::MIN, i64::MAX] {
+            let (major, argument) = split(value);
+            assert_eq!((argument, join(major, argument)), (i64::MAX as u64, Ok(value)));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_cbor_decode_errors() {
+        assert_eq!(decode(&[]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert_eq!(decode(&[0x19, 0x03]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        // A text string and a reserved additional info
+        assert_eq!(decode(&[0x61, 0x41]), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(decode(&[0x1c]), Err(ZigZagError::InvalidData { offset: 0 }));
+        // -2^64 is valid CBOR but does not fit into an i64
+        assert_eq!(decode(&[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]), Err(ZigZagError::OutOfRange { offset: 0 }));
+        // Overlong arguments are accepted
+        assert_eq!(decode(&[0x18, 0x05]), Ok((5, 2)));
+        assert_eq!(encode(-1000, &mut [0u8; 2]), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+    }
+}
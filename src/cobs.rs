@@ -0,0 +1,239 @@
+//! Consistent Overhead Byte Stuffing (COBS).
+//!
+//! COBS rewrites a packet so that it contains no zero bytes, at a cost of one
+//! byte per 254 bytes of input. A single zero byte can then delimit packets
+//! on UART or RS-485 links, and a receiver that joins mid-stream
+//! resynchronizes at the next zero. [`frame::encode_cobs`](crate::frame::encode_cobs)
+//! combines this with the record framing.
+//!
+//! ```rust
+//! use zigzag_rs::cobs;
+//!
+//! let packet = [0x11, 0x00, 0x00, 0x22];
+//! let mut encoded = [0u8; 8];
+//! let len = cobs::encode(&packet, &mut encoded).unwrap();
+//! assert_eq!(&encoded[..len], &[0x02, 0x11, 0x01, 0x02, 0x22]);
+//!
+//! let mut decoded = [0u8; 8];
+//! let len = cobs::decode(&encoded[..len], &mut decoded).unwrap();
+//! assert_eq!(&decoded[..len], &packet);
+//! ```
+
+use crate::ZigZagError;
+
+/// Delimiter that ends every packet on the link
+pub const DELIMITER: u8 = 0x00;
+
+/// Longest run of non-zero bytes a single code byte describes
+const MAX_RUN: usize = 254;
+
+/// Largest number of bytes [`encode`] writes for an input of `len` bytes
+pub const fn max_encoded_len(len: usize) -> usize {
+    len + len / MAX_RUN + 1
+}
+
+/// Number of bytes [`encode`] writes for `input`, without the delimiter
+pub fn encoded_len(input: &[u8]) -> usize {
+    let mut codes = 1;
+    let mut run = 0;
+    for &byte in input {
+        // A zero is replaced by the code byte of the following block
+        if byte == 0 {
+            run = 0;
+        } else {
+            run += 1;
+            if run == MAX_RUN {
+                codes += 1;
+                run = 0;
+            }
+        }
+    }
+    input.len() + codes
+}
+
+/// Encode `input`, the delimiter is not appended
+///
+/// # Arguments
+/// * `input` - Packet bytes, which may contain zeros
+/// * `out` - Output buffer, the encoded bytes are written to its start
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written, none of them zero
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode(input: &[u8], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let len = encoded_len(input);
+    if out.len() < len {
+        return Err(ZigZagError::BufferTooSmall { needed: len, actual: out.len() });
+    }
+    let start = len - input.len();
+    out[start..len].copy_from_slice(input);
+    Ok(encode_within(&mut out[..len], start))
+}
+
+/// Encode the bytes in `buf[start..]` to the start of `buf`
+///
+/// `start` must be the encoding overhead of those bytes. The write position
+/// never passes the read position, so the input is consumed before it is
+/// overwritten.
+pub(crate) fn encode_within(buf: &mut [u8], start: usize) -> usize {
+    let mut code_pos = 0;
+    let mut pos = 1;
+    let mut code = 1u8;
+    for read in start..buf.len() {
+        let byte = buf[read];
+        if byte == 0 {
+            buf[code_pos] = code;
+            code_pos = pos;
+            pos += 1;
+            code = 1;
+        } else {
+            buf[pos] = byte;
+            pos += 1;
+            code += 1;
+            if code as usize == MAX_RUN + 1 {
+                buf[code_pos] = code;
+                code_pos = pos;
+                pos += 1;
+                code = 1;
+            }
+        }
+    }
+    buf[code_pos] = code;
+    pos
+}
+
+/// Number of bytes a packet decodes to
+///
+/// # Returns
+/// * `Ok(len)` with the decoded length
+/// * `Err(ZigZagError::InvalidData)` as for [`decode`]
+pub fn decoded_len(input: &[u8]) -> Result<usize, ZigZagError> {
+    let mut read = 0;
+    let mut len = 0;
+    while read < input.len() {
+        let (run, end) = block(input, read)?;
+        len += run + usize::from(end < input.len() && run < MAX_RUN);
+        read = end;
+    }
+    Ok(len)
+}
+
+/// Decode a packet, which must not include the delimiter
+///
+/// # Arguments
+/// * `input` - Encoded packet bytes
+/// * `out` - Output buffer for the decoded packet
+///
+/// # Returns
+/// * `Ok(len)` with the number of decoded bytes
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+/// * `Err(ZigZagError::InvalidData)` with the offset of the offending byte if
+///   `input` contains a zero or a code byte points past its end
+pub fn decode(input: &[u8], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let needed = decoded_len(input)?;
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    // The blocks were validated by decoded_len
+    let mut read = 0;
+    let mut len = 0;
+    while read < input.len() {
+        let run = input[read] as usize - 1;
+        let end = read + 1 + run;
+        out[len..len + run].copy_from_slice(&input[read + 1..end]);
+        len += run;
+        if end < input.len() && run < MAX_RUN {
+            out[len] = 0;
+            len += 1;
+        }
+        read = end;
+    }
+    Ok(len)
+}
+
+/// Decode a packet in place, returning the decoded length
+///
+/// The decoded bytes are written to the start of `buf`.
+///
+/// # Returns
+/// * `Ok(len)` with the number of decoded bytes
+/// * `Err(ZigZagError::InvalidData)` as for [`decode`]
+pub fn decode_in_place(buf: &mut [u8]) -> Result<usize, ZigZagError> {
+    let mut read = 0;
+    let mut len = 0;
+    while read < buf.len() {
+        let (run, end) = block(buf, read)?;
+        buf.copy_within(read + 1..end, len);
+        len += run;
+        if end < buf.len() && run < MAX_RUN {
+            buf[len] = 0;
+            len += 1;
+        }
+        read = end;
+    }
+    Ok(len)
+}
+
+/// Validate the block whose code byte is at `pos`, returning its run length and end
+fn block(input: &[u8], pos: usize) -> Result<(usize, usize), ZigZagError> {
+    let run = input[pos] as usize;
+    if run == 0 {
+        return Err(ZigZagError::InvalidData { offset: pos });
+    }
+    let end = pos + run;
+    if end > input.len() {
+        return Err(ZigZagError::InvalidData { offset: pos });
+    }
+    if let Some(zero) = input[pos + 1..end].iter().position(|&byte| byte == 0) {
+        return Err(ZigZagError::InvalidData { offset: pos + 1 + zero });
+    }
+    Ok((run - 1, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_cobs_round_trip() {
+        let mut inputs: Vec<Vec<u8>> = vec![vec![], vec![0], vec![0, 0], vec![1, 2, 3]];
+        // Runs around the 254-byte block limit, with and without trailing zeros
+        for len in [253, 254, 255, 508, 600] {
+            let run: Vec<u8> = (0..len).map(|i| (i % 255 + 1) as u8).collect();
+            inputs.push(run.clone());
+            let mut with_zero = run;
+            with_zero.push(0);
+            inputs.push(with_zero);
+        }
+
+        for input in inputs {
+            let mut encoded = vec![0u8; max_encoded_len(input.len())];
+            let len = encode(&input, &mut encoded).unwrap();
+            assert_eq!(len, encoded_len(&input));
+            assert_eq!(decoded_len(&encoded[..len]), Ok(input.len()));
+            assert!(!encoded[..len].contains(&DELIMITER));
+
+            let mut decoded = vec![0u8; input.len()];
+            assert_eq!(decode(&encoded[..len], &mut decoded), Ok(input.len()));
+            assert_eq!(decoded, input);
+            assert_eq!(decode_in_place(&mut encoded[..len]), Ok(input.len()));
+            assert_eq!(&encoded[..input.len()], &input[..]);
+        }
+    }
+
+    #[test]
+    fn test_cobs_errors() {
+        let mut out = [0u8; 8];
+        assert_eq!(decode(&[0x03, 0x11, 0x00], &mut out), Err(ZigZagError::InvalidData { offset: 2 }));
+        assert_eq!(decode(&[0x02, 0x11, 0x04, 0x22], &mut out), Err(ZigZagError::InvalidData { offset: 2 }));
+        assert_eq!(decode(&[0x00], &mut out), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(
+            decode(&[0x02, 0x11, 0x02, 0x22], &mut out[..2]),
+            Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 })
+        );
+        assert_eq!(encode(&[1, 2, 3], &mut out[..3]), Err(ZigZagError::BufferTooSmall { needed: 4, actual: 3 }));
+    }
+}
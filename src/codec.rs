@@ -0,0 +1,28 @@
+//! Common interface for block codecs.
+//!
+//! Codecs implementing [`Codec`] produce self-describing output: the encoded
+//! bytes record how many values they contain, so the decoder only needs an
+//! output buffer that is large enough.
+
+use crate::ZigZagError;
+
+/// A codec that compresses slices of `T` into bytes
+pub trait Codec<T> {
+    /// Upper bound of the encoded size of `count` values, in bytes
+    fn max_encoded_len(&self, count: usize) -> usize;
+
+    /// Encode `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+    fn encode(&self, values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>;
+
+    /// Decode values produced by [`Codec::encode`] into `out`
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of values decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values
+    /// * `Err(ZigZagError)` describing the problem if `input` is malformed
+    fn decode(&self, input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError>;
+}
@@ -0,0 +1,613 @@
+//! Block container files.
+//!
+//! A container stores several blocks of ZigZag varints together with the
+//! checkpoint index of each block, so a reader can hand out every block as
+//! an [`EncodedSlice`] that points straight into the container bytes. Used
+//! with a memory-mapped file (see `mmap`, behind the `std` feature), this
+//! lets a reader scan files larger than memory without copying them.
+//!
+//! ```text
+//! header: magic "ZZC\x01" | stride (u32) | block count (u32) | flags (u32)
+//! table:  per block: data offset (u64) | data length (u64) | value count (u64) | index offset (u64)
+//! blocks: per block: ZigZag varints | zero padding to 4 bytes | index (u32 per checkpoint)
+//! footer: per block: name length (varint) | name (UTF-8) | data offset (varint) | value count (varint)
+//!                    | min (ZigZag varint) | max (ZigZag varint)
+//! trailer: footer length (u32) | magic "ZZT\x01"
+//! ```
+//!
+//! The footer and trailer are optional and flagged by bit 0 of the header
+//! flags. [`encode_with_toc`] writes them last, and [`Container::toc`] finds
+//! the footer through the trailing length, so a reader can look a column up by
+//! name and skip blocks by their value range without reading any block.
+//!
+//! Integers are little-endian and offsets are counted from the start of the
+//! container. Indexes are read in place, which requires a little-endian
+//! target and a container that starts at a 4-byte aligned address, as
+//! memory maps and heap allocations do.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::container::{self, Container};
+//!
+//! #[repr(align(4))]
+//! struct Aligned([u8; 256]);
+//!
+//! let temperatures = [215i32, 217, 216, 220];
+//! let pressures = [101_325i32, 101_300, 101_280];
+//! let blocks: [&[i32]; 2] = [&temperatures, &pressures];
+//! let mut file = Aligned([0; 256]);
+//! let len = container::encode(&blocks, 2, &mut file.0).unwrap();
+//!
+//! let container = Container::parse(&file.0[..len]).unwrap();
+//! assert_eq!(container.len(), 2);
+//! let pressure = container.block::<i32>(1).unwrap();
+//! assert_eq!(pressure.get(2), Some(101_280));
+//! # }
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::view::{self, EncodedSlice};
+use crate::{Sample, ZigZag, ZigZagError};
+
+/// Magic bytes at the start of every container
+pub const MAGIC: [u8; 4] = *b"ZZC\x01";
+
+/// Magic bytes at the end of a container with a table of contents
+pub const TOC_MAGIC: [u8; 4] = *b"ZZT\x01";
+
+const HEADER_LEN: usize = 16;
+const ENTRY_LEN: usize = 32;
+const TRAILER_LEN: usize = 8;
+/// Header flag marking a footer and trailer after the blocks
+const FLAG_TOC: u32 = 1;
+
+fn align4(pos: usize) -> usize {
+    pos.next_multiple_of(4)
+}
+
+/// Number of bytes [`encode`] needs for `blocks`
+pub fn encoded_len<T>(blocks: &[&[T]], stride: usize) -> usize
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let mut len = HEADER_LEN + blocks.len() * ENTRY_LEN;
+    for block in blocks {
+        len = align4(len + varint::encoded_len(block));
+        len += 4 * view::index_len(block.len(), stride);
+    }
+    len
+}
+
+/// Write blocks of values and their indexes as a container
+///
+/// # Arguments
+/// * `blocks` - Values of each block
+/// * `stride` - Number of values between index checkpoints, at least 1
+/// * `out` - Output buffer for the container
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+///
+/// # Panics
+/// Panics if `stride` is 0 or does not fit into a `u32`
+pub fn encode<T>(blocks: &[&[T]], stride: usize, out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    assert!(stride > 0 && stride <= u32::MAX as usize, "stride must be between 1 and u32::MAX");
+    let needed = encoded_len(blocks, stride);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let out = &mut out[..needed];
+    out.fill(0);
+
+    out[..4].copy_from_slice(&MAGIC);
+    out[4..8].copy_from_slice(&(stride as u32).to_le_bytes());
+    out[8..12].copy_from_slice(&(blocks.len() as u32).to_le_bytes());
+
+    let mut pos = HEADER_LEN + blocks.len() * ENTRY_LEN;
+    for (i, block) in blocks.iter().enumerate() {
+        let data_offset = pos;
+        let mut index_pos = align4(data_offset + varint::encoded_len(block));
+        let index_offset = index_pos;
+        for (j, &value) in block.iter().enumerate() {
+            if j % stride == 0 {
+                let checkpoint = (pos - data_offset) as u32;
+                out[index_pos..index_pos + 4].copy_from_slice(&checkpoint.to_le_bytes());
+                index_pos += 4;
+            }
+            pos += varint::encode(value, &mut out[pos..])?;
+        }
+
+        let entry = &mut out[HEADER_LEN + i * ENTRY_LEN..HEADER_LEN + (i + 1) * ENTRY_LEN];
+        entry[..8].copy_from_slice(&(data_offset as u64).to_le_bytes());
+        entry[8..16].copy_from_slice(&((pos - data_offset) as u64).to_le_bytes());
+        entry[16..24].copy_from_slice(&(block.len() as u64).to_le_bytes());
+        entry[24..].copy_from_slice(&(index_offset as u64).to_le_bytes());
+        pos = index_pos;
+    }
+    Ok(pos)
+}
+
+/// Offset of the encoded values of every block, in the layout [`encode`] writes
+fn data_offsets<'b, T>(blocks: &'b [&[T]], stride: usize) -> impl Iterator<Item = usize> + 'b
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let mut pos = HEADER_LEN + blocks.len() * ENTRY_LEN;
+    blocks.iter().map(move |block| {
+        let offset = pos;
+        pos = align4(pos + varint::encoded_len(block)) + 4 * view::index_len(block.len(), stride);
+        offset
+    })
+}
+
+/// Smallest and largest value of `block`, both 0 if it is empty
+fn value_range<T: Sample>(block: &[T]) -> (i64, i64) {
+    let mut values = block.iter().map(|value| value.to_i64());
+    let first = values.next().unwrap_or(0);
+    values.fold((first, first), |(min, max), value| (min.min(value), max.max(value)))
+}
+
+/// Number of bytes the footer of [`encode_with_toc`] takes for `blocks`
+fn toc_len<T>(blocks: &[&[T]], names: &[&str], stride: usize) -> usize
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    blocks.iter().zip(names).zip(data_offsets(blocks, stride)).map(|((block, name), offset)| {
+        let (min, max) = value_range(block);
+        (name.len() as u64).varint_len()
+            + name.len()
+            + (offset as u64).varint_len()
+            + (block.len() as u64).varint_len()
+            + i64::zigzag_encode(min).varint_len()
+            + i64::zigzag_encode(max).varint_len()
+    }).sum()
+}
+
+/// Number of bytes [`encode_with_toc`] needs for `blocks`
+pub fn encoded_len_with_toc<T>(blocks: &[&[T]], names: &[&str], stride: usize) -> usize
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    encoded_len(blocks, stride) + toc_len(blocks, names, stride) + TRAILER_LEN
+}
+
+/// Write a container followed by a table of contents naming its blocks
+///
+/// The footer records the name, data offset, value count and value range of
+/// every block, see [`Container::toc`].
+///
+/// # Arguments
+/// * `blocks` - Values of each block
+/// * `names` - Name of each block, such as a column name
+/// * `stride` - Number of values between index checkpoints, at least 1
+/// * `out` - Output buffer for the container
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+///
+/// # Panics
+/// Panics if `names` and `blocks` differ in length, or if `stride` is 0 or
+/// does not fit into a `u32`
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::container::{self, Container};
+///
+/// #[repr(align(4))]
+/// struct Aligned([u8; 256]);
+///
+/// let blocks: [&[i32]; 2] = [&[215, 217, 216], &[101_325, 101_300]];
+/// let mut file = Aligned([0; 256]);
+/// let len = container::encode_with_toc(&blocks, &["temperature", "pressure"], 2, &mut file.0).unwrap();
+///
+/// let container = Container::parse(&file.0[..len]).unwrap();
+/// let toc = container.toc().unwrap().unwrap();
+/// let pressure = toc.find("pressure").unwrap();
+/// assert_eq!((pressure.min, pressure.max), (101_300, 101_325));
+/// assert_eq!(container.block::<i32>(pressure.block).unwrap().get(0), Some(101_325));
+/// # }
+/// ```
+pub fn encode_with_toc<T>(blocks: &[&[T]], names: &[&str], stride: usize, out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    assert_eq!(names.len(), blocks.len(), "every block needs a name");
+    let needed = encoded_len_with_toc(blocks, names, stride);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let mut pos = encode(blocks, stride, out)?;
+    out[12..16].copy_from_slice(&FLAG_TOC.to_le_bytes());
+
+    let footer_start = pos;
+    for ((block, name), offset) in blocks.iter().zip(names).zip(data_offsets(blocks, stride)) {
+        let (min, max) = value_range(block);
+        pos += (name.len() as u64).encode_varint(&mut out[pos..])?;
+        out[pos..pos + name.len()].copy_from_slice(name.as_bytes());
+        pos += name.len();
+        pos += (offset as u64).encode_varint(&mut out[pos..])?;
+        pos += (block.len() as u64).encode_varint(&mut out[pos..])?;
+        pos += varint::encode(min, &mut out[pos..])?;
+        pos += varint::encode(max, &mut out[pos..])?;
+    }
+    out[pos..pos + 4].copy_from_slice(&((pos - footer_start) as u32).to_le_bytes());
+    out[pos + 4..pos + TRAILER_LEN].copy_from_slice(&TOC_MAGIC);
+    Ok(pos + TRAILER_LEN)
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+}
+
+fn read_u64(bytes: &[u8], pos: usize) -> u64 {
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&bytes[pos..pos + 8]);
+    u64::from_le_bytes(word)
+}
+
+/// Location of one block inside a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInfo {
+    /// Offset of the encoded values
+    pub data_offset: usize,
+    /// Length of the encoded values in bytes
+    pub data_len: usize,
+    /// Number of values
+    pub count: usize,
+    /// Offset of the checkpoint index
+    pub index_offset: usize,
+}
+
+/// A parsed container, borrowing its bytes
+#[derive(Debug, Clone, Copy)]
+pub struct Container<'a> {
+    bytes: &'a [u8],
+    stride: usize,
+    block_count: usize,
+}
+
+impl<'a> Container<'a> {
+    /// Parse the header and block table of a container
+    ///
+    /// # Returns
+    /// * `Ok(container)` if the header and every table entry are consistent
+    /// * `Err(ZigZagError::InvalidSync)` if `bytes` does not start with [`MAGIC`]
+    /// * `Err(ZigZagError::UnexpectedEof)` if the header or table is truncated
+    /// * `Err(ZigZagError::InvalidData)` with the offset of the first bad table entry
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ZigZagError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(ZigZagError::UnexpectedEof { offset: 0 });
+        }
+        if bytes[..4] != MAGIC {
+            return Err(ZigZagError::InvalidSync { offset: 0 });
+        }
+        let stride = read_u32(bytes, 4) as usize;
+        if stride == 0 {
+            return Err(ZigZagError::InvalidData { offset: 4 });
+        }
+        let block_count = read_u32(bytes, 8) as usize;
+        if bytes.len() < HEADER_LEN + block_count * ENTRY_LEN {
+            return Err(ZigZagError::UnexpectedEof { offset: HEADER_LEN });
+        }
+
+        let container = Container { bytes, stride, block_count };
+        for i in 0..block_count {
+            let info = container.entry(i);
+            let index_end = (info.count.div_ceil(stride) as u64)
+                .checked_mul(4)
+                .and_then(|len| len.checked_add(info.index_offset as u64));
+            let data_end = (info.data_offset as u64).checked_add(info.data_len as u64);
+            let valid = matches!((data_end, index_end), (Some(data_end), Some(index_end))
+                if data_end <= bytes.len() as u64 && index_end <= bytes.len() as u64)
+                && info.index_offset.is_multiple_of(4);
+            if !valid {
+                return Err(ZigZagError::InvalidData { offset: HEADER_LEN + i * ENTRY_LEN });
+            }
+        }
+        Ok(container)
+    }
+
+    fn entry(&self, i: usize) -> BlockInfo {
+        let pos = HEADER_LEN + i * ENTRY_LEN;
+        BlockInfo {
+            data_offset: read_u64(self.bytes, pos) as usize,
+            data_len: read_u64(self.bytes, pos + 8) as usize,
+            count: read_u64(self.bytes, pos + 16) as usize,
+            index_offset: read_u64(self.bytes, pos + 24) as usize,
+        }
+    }
+
+    /// Number of blocks
+    pub fn len(&self) -> usize {
+        self.block_count
+    }
+
+    /// Whether the container holds no blocks
+    pub fn is_empty(&self) -> bool {
+        self.block_count == 0
+    }
+
+    /// Number of values between index checkpoints
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Table of contents written by [`encode_with_toc`]
+    ///
+    /// # Returns
+    /// * `Ok(Some(toc))` if the container has a valid footer
+    /// * `Ok(None)` if the container was written without one
+    /// * `Err(ZigZagError::InvalidSync)` if the flagged trailer does not end with [`TOC_MAGIC`]
+    /// * `Err(ZigZagError::InvalidData)` if the footer length or an entry is inconsistent
+    /// * `Err(ZigZagError)` with the offset of the problem if an entry is malformed
+    pub fn toc(&self) -> Result<Option<Toc<'a>>, ZigZagError> {
+        if read_u32(self.bytes, 12) & FLAG_TOC == 0 {
+            return Ok(None);
+        }
+        let table_end = HEADER_LEN + self.block_count * ENTRY_LEN;
+        let trailer = self.bytes.len().checked_sub(TRAILER_LEN).filter(|&trailer| trailer >= table_end);
+        let trailer = trailer.ok_or(ZigZagError::UnexpectedEof { offset: self.bytes.len() })?;
+        if self.bytes[trailer + 4..] != TOC_MAGIC {
+            return Err(ZigZagError::InvalidSync { offset: trailer + 4 });
+        }
+        let footer_len = read_u32(self.bytes, trailer) as usize;
+        let footer_start = trailer
+            .checked_sub(footer_len)
+            .filter(|&start| start >= table_end)
+            .ok_or(ZigZagError::InvalidData { offset: trailer })?;
+
+        let footer = &self.bytes[footer_start..trailer];
+        let mut pos = 0;
+        for block in 0..self.block_count {
+            let (entry, next) = read_toc_entry(footer, pos, block).map_err(|err| varint::at_offset(err, footer_start))?;
+            if entry.data_offset != self.entry(block).data_offset || entry.count != self.entry(block).count {
+                return Err(ZigZagError::InvalidData { offset: footer_start + pos });
+            }
+            pos = next;
+        }
+        if pos != footer.len() {
+            return Err(ZigZagError::InvalidData { offset: footer_start + pos });
+        }
+        Ok(Some(Toc { footer, len: self.block_count }))
+    }
+
+    /// Location of block `i`
+    pub fn block_info(&self, i: usize) -> Option<BlockInfo> {
+        (i < self.block_count).then(|| self.entry(i))
+    }
+
+    /// View block `i` in place
+    ///
+    /// The block's values are validated once by [`EncodedSlice::new`]; nothing is copied.
+    ///
+    /// # Returns
+    /// * `Ok(slice)` with a random access view of the block
+    /// * `Err(ZigZagError::InvalidData)` if `i` is out of range, the block is inconsistent
+    ///   with its index, or the index cannot be read in place on this target
+    /// * `Err(ZigZagError)` with the offset of the offending value if the block is malformed
+    pub fn block<T>(&self, i: usize) -> Result<EncodedSlice<'a, T>, ZigZagError>
+    where
+        T: ZigZag,
+        T::UInt: Varint,
+    {
+        let info = self.block_info(i).ok_or(ZigZagError::InvalidData { offset: 8 })?;
+        let data = &self.bytes[info.data_offset..info.data_offset + info.data_len];
+        let index_bytes = &self.bytes[info.index_offset..info.index_offset + 4 * info.count.div_ceil(self.stride)];
+        let index = index_as_u32(index_bytes).ok_or(ZigZagError::InvalidData { offset: info.index_offset })?;
+        EncodedSlice::new(data, index, self.stride).map_err(|err| varint::at_offset(err, info.data_offset))
+    }
+}
+
+/// Reinterpret little-endian index bytes as `u32`s without copying
+#[cfg(target_endian = "little")]
+fn index_as_u32(bytes: &[u8]) -> Option<&[u32]> {
+    // SAFETY: every bit pattern is a valid u32, and align_to only returns the
+    // correctly aligned middle part of the slice
+    let (prefix, words, suffix) = unsafe { bytes.align_to::<u32>() };
+    (prefix.is_empty() && suffix.is_empty()).then_some(words)
+}
+
+#[cfg(target_endian = "big")]
+fn index_as_u32(_bytes: &[u8]) -> Option<&[u32]> {
+    None
+}
+
+/// Summary of one block in a table of contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TocEntry<'a> {
+    /// Name of the block
+    pub name: &'a str,
+    /// Position of the block, for [`Container::block`]
+    pub block: usize,
+    /// Offset of the encoded values
+    pub data_offset: usize,
+    /// Number of values
+    pub count: usize,
+    /// Smallest value, 0 for an empty block
+    pub min: i64,
+    /// Largest value, 0 for an empty block
+    pub max: i64,
+}
+
+/// Read the footer entry of block `block` at `pos`, returning it with the position after it
+fn read_toc_entry(footer: &[u8], mut pos: usize, block: usize) -> Result<(TocEntry<'_>, usize), ZigZagError> {
+    let next_u64 = |pos: &mut usize| {
+        let (value, len) = u64::decode_varint(&footer[*pos..]).map_err(|err| varint::at_offset(err, *pos))?;
+        *pos += len;
+        Ok::<_, ZigZagError>(value)
+    };
+    let name_len = next_u64(&mut pos)?;
+    let name_bytes = footer
+        .get(pos..)
+        .and_then(|rest| rest.get(..usize::try_from(name_len).ok()?))
+        .ok_or(ZigZagError::UnexpectedEof { offset: pos })?;
+    let name = core::str::from_utf8(name_bytes).map_err(|_| ZigZagError::InvalidData { offset: pos })?;
+    pos += name_bytes.len();
+    let data_offset = next_u64(&mut pos)? as usize;
+    let count = next_u64(&mut pos)? as usize;
+    let (min, len) = varint::decode::<i64>(&footer[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+    pos += len;
+    let (max, len) = varint::decode::<i64>(&footer[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+    pos += len;
+    Ok((TocEntry { name, block, data_offset, count, min, max }, pos))
+}
+
+/// Table of contents of a container, see [`encode_with_toc`]
+#[derive(Debug, Clone, Copy)]
+pub struct Toc<'a> {
+    footer: &'a [u8],
+    len: usize,
+}
+
+impl<'a> Toc<'a> {
+    /// Number of entries, one per block
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the table lists no blocks
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the entries in block order
+    pub fn iter(&self) -> TocIter<'a> {
+        TocIter { footer: self.footer, pos: 0, block: 0, len: self.len }
+    }
+
+    /// Entry of the first block named `name`
+    pub fn find(&self, name: &str) -> Option<TocEntry<'a>> {
+        self.iter().find(|entry| entry.name == name)
+    }
+}
+
+impl<'a> IntoIterator for Toc<'a> {
+    type Item = TocEntry<'a>;
+    type IntoIter = TocIter<'a>;
+
+    fn into_iter(self) -> TocIter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of a [`Toc`]
+#[derive(Debug, Clone)]
+pub struct TocIter<'a> {
+    footer: &'a [u8],
+    pos: usize,
+    block: usize,
+    len: usize,
+}
+
+impl<'a> Iterator for TocIter<'a> {
+    type Item = TocEntry<'a>;
+
+    fn next(&mut self) -> Option<TocEntry<'a>> {
+        if self.block == self.len {
+            return None;
+        }
+        // The footer was validated by `Container::toc`
+        let (entry, pos) = read_toc_entry(self.footer, self.pos, self.block).ok()?;
+        self.pos = pos;
+        self.block += 1;
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[repr(align(8))]
+    struct Aligned([u8; 4096]);
+
+    #[test]
+    fn test_container_round_trip() {
+        let a: Vec<i64> = (0..500).map(|i| i * i - 1000).collect();
+        let b = [i64::MIN, i64::MAX];
+        let empty: [i64; 0] = [];
+        let blocks: [&[i64]; 3] = [&a, &empty, &b];
+
+        let mut file = Aligned([0; 4096]);
+        let len = encode(&blocks, 32, &mut file.0).unwrap();
+        assert_eq!(len, encoded_len(&blocks, 32));
+
+        let container = Container::parse(&file.0[..len]).unwrap();
+        assert_eq!((container.len(), container.stride()), (3, 32));
+        for (i, block) in blocks.iter().enumerate() {
+            let slice = container.block::<i64>(i).unwrap();
+            assert!(slice.iter().eq(block.iter().copied()));
+        }
+        assert_eq!(container.block::<i64>(0).unwrap().get(321), Some(321 * 321 - 1000));
+        assert_eq!(container.block_info(3), None);
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_container_errors() {
+        let mut file = Aligned([0; 4096]);
+        let blocks: [&[i16]; 1] = [&[1, 2, 3]];
+        let len = encode(&blocks, 2, &mut file.0).unwrap();
+        assert_eq!(encode(&blocks, 2, &mut file.0[..20]), Err(ZigZagError::BufferTooSmall { needed: len, actual: 20 }));
+
+        assert_eq!(Container::parse(&file.0[..HEADER_LEN]).unwrap_err(), ZigZagError::UnexpectedEof { offset: HEADER_LEN });
+        assert_eq!(Container::parse(&file.0[..len - 1]).unwrap_err(), ZigZagError::InvalidData { offset: HEADER_LEN });
+
+        // The index cannot be viewed in place from a misaligned start
+        let mut shifted = Aligned([0; 4096]);
+        shifted.0[1..len + 1].copy_from_slice(&file.0[..len]);
+        let container = Container::parse(&shifted.0[1..len + 1]).unwrap();
+        assert!(matches!(container.block::<i16>(0), Err(ZigZagError::InvalidData { .. })));
+
+        file.0[0] = b'X';
+        assert_eq!(Container::parse(&file.0[..len]).unwrap_err(), ZigZagError::InvalidSync { offset: 0 });
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_container_toc() {
+        let a: Vec<i32> = (0..300).map(|i| i * 7 - 1000).collect();
+        let blocks: [&[i32]; 3] = [&a, &[], &[i32::MIN, i32::MAX]];
+        let names = ["ramp", "", "extremes"];
+        let mut file = Aligned([0; 4096]);
+        let len = encode_with_toc(&blocks, &names, 16, &mut file.0).unwrap();
+        assert_eq!(len, encoded_len_with_toc(&blocks, &names, 16));
+
+        let container = Container::parse(&file.0[..len]).unwrap();
+        let toc = container.toc().unwrap().unwrap();
+        assert_eq!(toc.len(), 3);
+        let entries: Vec<TocEntry> = toc.iter().collect();
+        assert_eq!((entries[0].min, entries[0].max, entries[0].count), (-1000, 1093, 300));
+        assert_eq!((entries[1].name, entries[1].min, entries[1].max), ("", 0, 0));
+        let extremes = toc.find("extremes").unwrap();
+        assert_eq!((extremes.block, extremes.min, extremes.max), (2, i32::MIN as i64, i32::MAX as i64));
+        assert_eq!(extremes.data_offset, container.block_info(2).unwrap().data_offset);
+        assert!(container.block::<i32>(extremes.block).unwrap().iter().eq([i32::MIN, i32::MAX]));
+        assert_eq!(toc.find("missing"), None);
+
+        // Containers without a footer
+        let plain_len = encode(&blocks, 16, &mut file.0).unwrap();
+        assert!(Container::parse(&file.0[..plain_len]).unwrap().toc().unwrap().is_none());
+
+        // Truncated or damaged footers
+        let len = encode_with_toc(&blocks, &names, 16, &mut file.0).unwrap();
+        let container = Container::parse(&file.0[..len - 1]).unwrap();
+        assert_eq!(container.toc().unwrap_err(), ZigZagError::InvalidSync { offset: len - 5 });
+        file.0[len - 8..len - 4].copy_from_slice(&4096u32.to_le_bytes());
+        let container = Container::parse(&file.0[..len]).unwrap();
+        assert_eq!(container.toc().unwrap_err(), ZigZagError::InvalidData { offset: len - 8 });
+    }
+}
@@ -0,0 +1,235 @@
+//! Constant-time ZigZag and varint coding for secret-derived values.
+//!
+//! The regular varint coder stops at the first byte without a continuation
+//! bit, so its running time and output length reveal the magnitude of the
+//! value. The functions here avoid data-dependent branches and table
+//! lookups instead:
+//!
+//! * [`zigzag_encode`] and [`zigzag_decode`] are shifts, XORs and masks.
+//! * [`encode`] always writes the fixed-length form: `MAX_LEN` bytes, with
+//!   every byte except the last carrying a continuation bit. Regular decoders
+//!   such as [`varint::decode`](crate::varint::decode) read it as a normal,
+//!   overlong varint.
+//! * [`decode`] reads exactly `MAX_LEN` bytes and checks all of them. The
+//!   only branch depends on whether the input was well-formed, not on the
+//!   value it holds.
+//!
+//! Branches on buffer lengths remain, since lengths are public. Rust does not
+//! guarantee constant-time code generation; these routines are written so
+//! that the obvious lowering contains no secret-dependent branches, which
+//! should be confirmed on the target if the threat model requires it.
+//!
+//! ```rust
+//! # #[cfg(feature = "i64")] {
+//! use zigzag_rs::{ct, varint};
+//!
+//! let mut buf = [0u8; 10];
+//! assert_eq!(ct::encode(-3i64, &mut buf), Ok(10));
+//! assert_eq!(ct::encode(i64::MIN, &mut buf), Ok(10));
+//! assert_eq!(ct::decode::<i64>(&buf), Ok(i64::MIN));
+//!
+//! // The fixed-length form is an ordinary varint
+//! ct::encode(-3i64, &mut buf).unwrap();
+//! assert_eq!(varint::decode::<i64>(&buf), Ok((-3, 10)));
+//! # }
+//! ```
+
+use crate::{ZigZag, ZigZagError};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Bit patterns wide enough for every enabled width
+#[cfg(feature = "i128")]
+type Bits = u128;
+#[cfg(not(feature = "i128"))]
+type Bits = u64;
+
+/// Signed integers supported by the constant-time routines
+///
+/// Values are handled as two's complement bit patterns widened to `u128`, or
+/// to `u64` without the `i128` feature so that small targets need no 128-bit
+/// shifts.
+pub trait ConstantTime: ZigZag + Copy + sealed::Sealed {
+    /// Width of the type in bits
+    const BITS: u32;
+
+    /// Length of the fixed-length varint form
+    const LEN: usize = (Self::BITS as usize).div_ceil(7);
+
+    #[doc(hidden)]
+    fn to_bits(self) -> Bits;
+    #[doc(hidden)]
+    fn from_bits(bits: Bits) -> Self;
+    #[doc(hidden)]
+    fn uint_to_bits(value: Self::UInt) -> Bits;
+    #[doc(hidden)]
+    fn uint_from_bits(bits: Bits) -> Self::UInt;
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_constant_time {
+    ($signed:ty, $unsigned:ty, $bits:expr) => {
+        impl sealed::Sealed for $signed {}
+
+        impl ConstantTime for $signed {
+            const BITS: u32 = $bits;
+
+            #[inline]
+            fn to_bits(self) -> Bits {
+                self as $unsigned as Bits
+            }
+
+            #[inline]
+            fn from_bits(bits: Bits) -> Self {
+                bits as $unsigned as $signed
+            }
+
+            #[inline]
+            fn uint_to_bits(value: $unsigned) -> Bits {
+                value as Bits
+            }
+
+            #[inline]
+            fn uint_from_bits(bits: Bits) -> $unsigned {
+                bits as $unsigned
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_constant_time!(i8, u8, 8);
+#[cfg(feature = "i16")]
+impl_constant_time!(i16, u16, 16);
+#[cfg(feature = "i32")]
+impl_constant_time!(i32, u32, 32);
+#[cfg(feature = "i64")]
+impl_constant_time!(i64, u64, 64);
+#[cfg(feature = "i128")]
+impl_constant_time!(i128, u128, 128);
+
+/// Mask of the low `bits` bits
+#[inline]
+fn mask(bits: u32) -> Bits {
+    Bits::MAX >> (Bits::BITS - bits)
+}
+
+#[inline]
+fn zigzag_encode_bits(value: Bits, bits: u32) -> Bits {
+    // The sign bit becomes an all-ones or all-zeros mask without a branch
+    let sign = (value >> (bits - 1)) & 1;
+    ((value << 1) ^ sign.wrapping_neg()) & mask(bits)
+}
+
+#[inline]
+fn zigzag_decode_bits(value: Bits, bits: u32) -> Bits {
+    ((value >> 1) ^ (value & 1).wrapping_neg()) & mask(bits)
+}
+
+/// ZigZag encode `value` without data-dependent branches
+pub fn zigzag_encode<T: ConstantTime>(value: T) -> T::UInt {
+    T::uint_from_bits(zigzag_encode_bits(value.to_bits(), T::BITS))
+}
+
+/// ZigZag decode `value` without data-dependent branches
+pub fn zigzag_decode<T: ConstantTime>(value: T::UInt) -> T {
+    T::from_bits(zigzag_decode_bits(T::uint_to_bits(value), T::BITS))
+}
+
+/// Encode `value` as a fixed-length ZigZag varint of [`ConstantTime::LEN`] bytes
+///
+/// # Arguments
+/// * `value` - Signed integer to encode
+/// * `out` - Output buffer, the encoded bytes are written to its start
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written, always `T::LEN`
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `T::LEN`
+pub fn encode<T: ConstantTime>(value: T, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if out.len() < T::LEN {
+        return Err(ZigZagError::BufferTooSmall { needed: T::LEN, actual: out.len() });
+    }
+    let bits = zigzag_encode_bits(value.to_bits(), T::BITS);
+    for (i, byte) in out[..T::LEN].iter_mut().enumerate() {
+        let continuation = if i + 1 < T::LEN { 0x80 } else { 0 };
+        *byte = ((bits >> (7 * i)) as u8 & 0x7f) | continuation;
+    }
+    Ok(T::LEN)
+}
+
+/// Decode a fixed-length ZigZag varint written by [`encode`]
+///
+/// # Returns
+/// * `Ok(value)` with the decoded value
+/// * `Err(ZigZagError::UnexpectedEof)` if `input` is shorter than `T::LEN`
+/// * `Err(ZigZagError::InvalidData)` if the first `T::LEN` bytes are not a
+///   fixed-length varint of `T`
+pub fn decode<T: ConstantTime>(input: &[u8]) -> Result<T, ZigZagError> {
+    if input.len() < T::LEN {
+        return Err(ZigZagError::UnexpectedEof { offset: 0 });
+    }
+    let mut bits: Bits = 0;
+    let mut invalid = 0u8;
+    for (i, &byte) in input[..T::LEN].iter().enumerate() {
+        let continuation = u8::from(i + 1 < T::LEN);
+        invalid |= (byte >> 7) ^ continuation;
+        bits |= ((byte & 0x7f) as Bits) << (7 * i);
+    }
+    // The last byte may only carry the bits that are left over
+    let remaining = T::BITS as usize - 7 * (T::LEN - 1);
+    invalid |= (input[T::LEN - 1] & 0x7f) >> remaining;
+    if invalid != 0 {
+        return Err(ZigZagError::InvalidData { offset: 0 });
+    }
+    Ok(T::from_bits(zigzag_decode_bits(bits, T::BITS)))
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::varint;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64"))]
+    fn test_ct_matches_regular_coding() {
+        let mut buf = [0u8; 19];
+        for value in [i8::MIN, -65, -1, 0, 1, 64, i8::MAX] {
+            assert_eq!(zigzag_encode(value), i8::zigzag_encode(value));
+            assert_eq!(zigzag_decode::<i8>(i8::zigzag_encode(value)), value);
+            assert_eq!(encode(value, &mut buf), Ok(2));
+            assert_eq!(decode::<i8>(&buf), Ok(value));
+            assert_eq!(varint::decode::<i8>(&buf), Ok((value, 2)));
+        }
+        for value in [i64::MIN, -300, 0, 299, i64::MAX] {
+            assert_eq!(zigzag_encode(value), i64::zigzag_encode(value));
+            assert_eq!(encode(value, &mut buf), Ok(10));
+            assert_eq!(decode::<i64>(&buf), Ok(value));
+            assert_eq!(varint::decode::<i64>(&buf), Ok((value, 10)));
+        }
+        #[cfg(feature = "i128")]
+        for value in [i128::MIN, -1, i128::MAX] {
+            assert_eq!(zigzag_encode(value), i128::zigzag_encode(value));
+            assert_eq!(encode(value, &mut buf), Ok(19));
+            assert_eq!(decode::<i128>(&buf), Ok(value));
+            assert_eq!(varint::decode::<i128>(&buf), Ok((value, 19)));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_ct_rejects_other_forms() {
+        let mut buf = [0u8; 5];
+        assert_eq!(encode(7i32, &mut buf[..4]), Err(ZigZagError::BufferTooSmall { needed: 5, actual: 4 }));
+        assert_eq!(decode::<i32>(&[0x0e]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+
+        encode(7i32, &mut buf).unwrap();
+        assert_eq!(buf, [0x8e, 0x80, 0x80, 0x80, 0x00]);
+        // Short form followed by other data
+        assert_eq!(decode::<i32>(&[0x0e, 0x80, 0x80, 0x80, 0x00]), Err(ZigZagError::InvalidData { offset: 0 }));
+        // Bits beyond the 32nd
+        assert_eq!(decode::<i32>(&[0x80, 0x80, 0x80, 0x80, 0x10]), Err(ZigZagError::InvalidData { offset: 0 }));
+    }
+}
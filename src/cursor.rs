@@ -0,0 +1,209 @@
+//! Cursor for hand-parsing binary messages with ZigZag fields.
+//!
+//! [`ByteCursor`] walks a borrowed byte slice and reads ZigZag varints,
+//! unsigned varints, fixed-width integers and raw byte runs in whatever order
+//! a message lays them out. Errors report the absolute offset in the slice,
+//! and a failed read leaves the position unchanged.
+//!
+//! ```rust
+//! # #[cfg(feature = "i64")] {
+//! use zigzag_rs::cursor::ByteCursor;
+//!
+//! // u16 message type, zigzag varint delta, 3-byte tag
+//! let message = [0x01, 0x02, 0xd7, 0x04, b'a', b'b', b'c'];
+//! let mut cursor = ByteCursor::new(&message);
+//! assert_eq!(cursor.read_fixed::<u16>(), Ok(0x0201));
+//! assert_eq!(cursor.read_zigzag_varint::<i64>(), Ok(-300));
+//! assert_eq!(cursor.read_bytes(3), Ok(&b"abc"[..]));
+//! assert_eq!(cursor.remaining(), 0);
+//! # }
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// Integer that can be read from a fixed number of bytes
+pub trait Fixed: Sized + Copy {
+    /// Number of bytes in the encoding
+    const SIZE: usize;
+
+    /// Build the value from `SIZE` little-endian bytes
+    fn from_le(bytes: &[u8]) -> Self;
+
+    /// Build the value from `SIZE` big-endian bytes
+    fn from_be(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_fixed {
+    ($($ty:ty),*) => {
+        $(
+            impl Fixed for $ty {
+                const SIZE: usize = core::mem::size_of::<$ty>();
+
+                #[inline]
+                fn from_le(bytes: &[u8]) -> Self {
+                    let mut buf = [0u8; core::mem::size_of::<$ty>()];
+                    buf.copy_from_slice(bytes);
+                    <$ty>::from_le_bytes(buf)
+                }
+
+                #[inline]
+                fn from_be(bytes: &[u8]) -> Self {
+                    let mut buf = [0u8; core::mem::size_of::<$ty>()];
+                    buf.copy_from_slice(bytes);
+                    <$ty>::from_be_bytes(buf)
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// Reading position in a borrowed byte slice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteCursor<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteCursor<'a> {
+    /// Create a cursor at the start of `input`
+    pub fn new(input: &'a [u8]) -> Self {
+        ByteCursor { input, pos: 0 }
+    }
+
+    /// Offset of the next byte to read
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.input.len() - self.pos
+    }
+
+    /// Whether all bytes have been read
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Bytes left to read, without consuming them
+    pub fn rest(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
+    /// Read a ZigZag varint
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the decoded value
+    /// * `Err(ZigZagError::UnexpectedEof)` if the input ends in the middle of the value
+    /// * `Err(ZigZagError::VarintOverflow)` if the value does not fit into `T`
+    pub fn read_zigzag_varint<T>(&mut self) -> Result<T, ZigZagError>
+    where
+        T: ZigZag,
+        T::UInt: Varint,
+    {
+        let (value, len) = varint::decode::<T>(self.rest()).map_err(|e| varint::at_offset(e, self.pos))?;
+        self.pos += len;
+        Ok(value)
+    }
+
+    /// Read an unsigned varint without ZigZag decoding, such as a length prefix
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the decoded value
+    /// * `Err(ZigZagError::UnexpectedEof)` if the input ends in the middle of the value
+    /// * `Err(ZigZagError::VarintOverflow)` if the value does not fit into `T`
+    pub fn read_varint<T: Varint>(&mut self) -> Result<T, ZigZagError> {
+        let (value, len) = T::decode_varint(self.rest()).map_err(|e| varint::at_offset(e, self.pos))?;
+        self.pos += len;
+        Ok(value)
+    }
+
+    /// Read a little-endian fixed-width integer
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the decoded value
+    /// * `Err(ZigZagError::UnexpectedEof)` if fewer than `T::SIZE` bytes remain
+    pub fn read_fixed<T: Fixed>(&mut self) -> Result<T, ZigZagError> {
+        self.read_bytes(T::SIZE).map(T::from_le)
+    }
+
+    /// Read a big-endian fixed-width integer
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the decoded value
+    /// * `Err(ZigZagError::UnexpectedEof)` if fewer than `T::SIZE` bytes remain
+    pub fn read_fixed_be<T: Fixed>(&mut self) -> Result<T, ZigZagError> {
+        self.read_bytes(T::SIZE).map(T::from_be)
+    }
+
+    /// Read `len` raw bytes
+    ///
+    /// # Returns
+    /// * `Ok(bytes)` borrowing from the input
+    /// * `Err(ZigZagError::UnexpectedEof)` if fewer than `len` bytes remain
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ZigZagError> {
+        if self.remaining() < len {
+            return Err(ZigZagError::UnexpectedEof { offset: self.pos });
+        }
+        let bytes = &self.input[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Skip `len` bytes
+    ///
+    /// # Returns
+    /// * `Ok(())` if the bytes were skipped
+    /// * `Err(ZigZagError::UnexpectedEof)` if fewer than `len` bytes remain
+    pub fn skip(&mut self, len: usize) -> Result<(), ZigZagError> {
+        self.read_bytes(len).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_cursor_mixed_fields() {
+        let mut buf = [0u8; 32];
+        buf[0] = 0xab;
+        buf[1..5].copy_from_slice(&(-7i32).to_le_bytes());
+        let mut len = 5 + varint::encode(i64::MIN, &mut buf[5..]).unwrap();
+        buf[len] = 3;
+        buf[len + 1..len + 4].copy_from_slice(b"xyz");
+        buf[len + 4..len + 6].copy_from_slice(&0x1234u16.to_be_bytes());
+        len += 6;
+
+        let mut cursor = ByteCursor::new(&buf[..len]);
+        assert_eq!(cursor.read_fixed::<u8>(), Ok(0xab));
+        assert_eq!(cursor.read_fixed::<i32>(), Ok(-7));
+        assert_eq!(cursor.read_zigzag_varint::<i64>(), Ok(i64::MIN));
+        let tag_len = cursor.read_varint::<u32>().unwrap() as usize;
+        assert_eq!(cursor.read_bytes(tag_len), Ok(&b"xyz"[..]));
+        assert_eq!(cursor.rest(), &[0x12, 0x34]);
+        assert_eq!(cursor.read_fixed_be::<u16>(), Ok(0x1234));
+        assert!(cursor.is_empty());
+        assert_eq!(cursor.position(), len);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16"))]
+    fn test_cursor_errors_report_absolute_offsets() {
+        let input = [0x00, 0x01, 0x02, 0xff, 0x03, 0x80];
+        let mut cursor = ByteCursor::new(&input);
+        cursor.skip(3).unwrap();
+        assert_eq!(cursor.read_zigzag_varint::<i8>(), Err(ZigZagError::VarintOverflow { offset: 3 }));
+        // A failed read does not move the cursor
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(cursor.read_zigzag_varint::<i16>(), Ok(-256));
+        assert_eq!(cursor.read_fixed::<u32>(), Err(ZigZagError::UnexpectedEof { offset: 5 }));
+        assert_eq!(cursor.read_varint::<u64>(), Err(ZigZagError::UnexpectedEof { offset: 5 }));
+        assert_eq!(cursor.remaining(), 1);
+    }
+}
@@ -0,0 +1,284 @@
+//! Downsampling of telemetry before delta coding.
+//!
+//! Uplinks that cannot carry full-rate data can reduce each group of `factor`
+//! consecutive samples to one, either by keeping the first sample of the
+//! group or by averaging it, before the result is written as a
+//! [`delta`](crate::delta) stream. The factor and the reduction are recorded
+//! in the header so the receiver knows the spacing of the samples it gets. A
+//! trailing group shorter than `factor` is reduced on its own.
+//!
+//! ```text
+//! reduction id (1 byte) | factor (varint) | delta stream of reduced samples
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::decimation::{DecimatedBlock, Decimation, Reduction};
+//!
+//! let samples = [10i32, 12, 14, 16, 20, 21, 22];
+//! let decimation = Decimation::new(3, Reduction::Average);
+//! let mut buf = [0u8; 16];
+//! let len = decimation.encode(&samples, &mut buf).unwrap();
+//!
+//! let block = DecimatedBlock::parse(&buf[..len]).unwrap();
+//! assert_eq!(block.decimation(), decimation);
+//! let mut reduced = [0i32; 3];
+//! assert_eq!(block.decode(&mut reduced), Ok(3));
+//! assert_eq!(reduced, [12, 19, 22]);
+//! # }
+//! ```
+
+use crate::delta::{self, wrapping_delta};
+use crate::varint::{self, Varint};
+use crate::{Sample, ZigZagError};
+
+/// How a group of samples is reduced to one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Reduction {
+    /// Keep the first sample of every group
+    #[default]
+    Decimate,
+    /// Average every group, rounding towards negative infinity
+    Average,
+}
+
+impl Reduction {
+    /// Stable identifier of the reduction, suitable for storing in headers
+    pub fn id(self) -> u8 {
+        match self {
+            Reduction::Decimate => 0,
+            Reduction::Average => 1,
+        }
+    }
+
+    /// Look up a reduction by the identifier returned from [`Reduction::id`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Reduction::Decimate),
+            1 => Some(Reduction::Average),
+            _ => None,
+        }
+    }
+
+    /// Reduce a non-empty group of samples to one
+    fn apply<T: Sample>(self, group: &[T]) -> T {
+        match self {
+            Reduction::Decimate => group[0],
+            Reduction::Average => {
+                // The sum of up to 2^64 `i64` values fits into 128 bits
+                let sum: i128 = group.iter().map(|value| value.to_i64() as i128).sum();
+                T::from_i64(sum.div_euclid(group.len() as i128) as i64)
+            }
+        }
+    }
+}
+
+/// Downsampling stage keeping one sample out of every `factor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimation {
+    factor: u32,
+    reduction: Reduction,
+}
+
+impl Decimation {
+    /// Create a stage reducing every `factor` samples with `reduction`
+    ///
+    /// # Panics
+    /// Panics if `factor` is zero
+    pub const fn new(factor: u32, reduction: Reduction) -> Self {
+        assert!(factor != 0, "decimation factor must not be zero");
+        Decimation { factor, reduction }
+    }
+
+    /// Number of input samples per reduced sample
+    pub const fn factor(&self) -> u32 {
+        self.factor
+    }
+
+    /// How each group is reduced
+    pub const fn reduction(&self) -> Reduction {
+        self.reduction
+    }
+
+    /// Number of reduced samples for `count` input samples
+    pub const fn reduced_len(&self, count: usize) -> usize {
+        count.div_ceil(self.factor as usize)
+    }
+
+    /// Reduced samples of `samples`, in order
+    fn reduced<'a, T: Sample>(&self, samples: &'a [T]) -> impl Iterator<Item = T> + 'a {
+        let reduction = self.reduction;
+        samples.chunks(self.factor as usize).map(move |group| reduction.apply(group))
+    }
+
+    /// Reduce `samples` without encoding them
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of reduced samples written to `out`
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than [`Decimation::reduced_len`]
+    pub fn reduce<T: Sample>(&self, samples: &[T], out: &mut [T]) -> Result<usize, ZigZagError> {
+        let needed = self.reduced_len(samples.len());
+        if out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+        }
+        for (slot, value) in out.iter_mut().zip(self.reduced(samples)) {
+            *slot = value;
+        }
+        Ok(needed)
+    }
+
+    /// Number of bytes [`encode`](Decimation::encode) needs for `samples`
+    pub fn encoded_len<T>(&self, samples: &[T]) -> usize
+    where
+        T: Sample,
+        T::UInt: Varint,
+    {
+        let mut prev = T::from_i64(0);
+        let data_len: usize = self.reduced(samples).map(|value| {
+            let len = T::zigzag_encode(wrapping_delta(value, prev)).varint_len();
+            prev = value;
+            len
+        }).sum();
+        1 + self.factor.varint_len() + data_len
+    }
+
+    /// Reduce `samples` and encode the result as one block
+    ///
+    /// # Arguments
+    /// * `samples` - Full-rate samples
+    /// * `out` - Output buffer, the block is written to its start
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written
+    /// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+    pub fn encode<T>(&self, samples: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+    where
+        T: Sample,
+        T::UInt: Varint,
+    {
+        let needed = self.encoded_len(samples);
+        if out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+        }
+        out[0] = self.reduction.id();
+        let mut pos = 1 + self.factor.encode_varint(&mut out[1..])?;
+        let mut prev = T::from_i64(0);
+        for value in self.reduced(samples) {
+            pos += varint::encode(wrapping_delta(value, prev), &mut out[pos..])?;
+            prev = value;
+        }
+        Ok(pos)
+    }
+}
+
+/// A downsampled block, read in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimatedBlock<'a> {
+    decimation: Decimation,
+    data: &'a [u8],
+    data_start: usize,
+}
+
+impl<'a> DecimatedBlock<'a> {
+    /// Parse the header of a block written by [`Decimation::encode`]
+    ///
+    /// # Returns
+    /// * `Ok(block)` with the stage read from the header
+    /// * `Err(ZigZagError::UnexpectedEof)` if `input` is empty
+    /// * `Err(ZigZagError::InvalidData)` if the reduction id is unknown or the factor is zero
+    /// * `Err(ZigZagError)` with the byte offset of the problem if the factor is malformed
+    pub fn parse(input: &'a [u8]) -> Result<Self, ZigZagError> {
+        let &id = input.first().ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+        let reduction = Reduction::from_id(id).ok_or(ZigZagError::InvalidData { offset: 0 })?;
+        let (factor, len) = u32::decode_varint(&input[1..]).map_err(|err| varint::at_offset(err, 1))?;
+        if factor == 0 {
+            return Err(ZigZagError::InvalidData { offset: 1 });
+        }
+        let data_start = 1 + len;
+        Ok(DecimatedBlock {
+            decimation: Decimation { factor, reduction },
+            data: &input[data_start..],
+            data_start,
+        })
+    }
+
+    /// Downsampling stage stored in the header
+    pub fn decimation(&self) -> Decimation {
+        self.decimation
+    }
+
+    /// Number of reduced samples in the block
+    pub fn len(&self) -> usize {
+        varint::count(self.data)
+    }
+
+    /// Whether the block holds no samples
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Decode the reduced samples
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of samples decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all samples
+    /// * `Err(ZigZagError)` with the byte offset of the problem if the data is malformed
+    pub fn decode<T>(&self, out: &mut [T]) -> Result<usize, ZigZagError>
+    where
+        T: Sample,
+        T::UInt: Varint,
+    {
+        delta::decode(self.data, out).map_err(|err| varint::at_offset(err, self.data_start))
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_decimation_round_trip() {
+        let samples: Vec<i16> = (0..1001).map(|i| ((i * 31 % 200) - 100) as i16).chain([i16::MIN, i16::MAX]).collect();
+        for decimation in [
+            Decimation::new(1, Reduction::Decimate),
+            Decimation::new(4, Reduction::Decimate),
+            Decimation::new(7, Reduction::Average),
+            Decimation::new(5000, Reduction::Average),
+        ] {
+            let mut expected = vec![0i16; decimation.reduced_len(samples.len())];
+            assert_eq!(decimation.reduce(&samples, &mut expected), Ok(expected.len()));
+
+            let mut buf = vec![0u8; decimation.encoded_len(&samples)];
+            assert_eq!(decimation.encode(&samples, &mut buf), Ok(buf.len()));
+            let block = DecimatedBlock::parse(&buf).unwrap();
+            assert_eq!(block.decimation(), decimation);
+            assert_eq!(block.len(), expected.len());
+            let mut reduced = vec![0i16; expected.len()];
+            assert_eq!(block.decode(&mut reduced), Ok(expected.len()));
+            assert_eq!(reduced, expected);
+        }
+        let mut reduced = [0i16; 2];
+        Decimation::new(2, Reduction::Average).reduce(&[i16::MAX, i16::MAX, -3, 0], &mut reduced).unwrap();
+        assert_eq!(reduced, [i16::MAX, -2]);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_decimated_block_errors() {
+        assert_eq!(DecimatedBlock::parse(&[]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert_eq!(DecimatedBlock::parse(&[2, 1]), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(DecimatedBlock::parse(&[1, 0]), Err(ZigZagError::InvalidData { offset: 1 }));
+        assert_eq!(DecimatedBlock::parse(&[1, 0x80]), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+
+        let block = DecimatedBlock::parse(&[0, 2, 0x04, 0x80]).unwrap();
+        assert_eq!(block.decode(&mut [0i32; 2]), Err(ZigZagError::UnexpectedEof { offset: 3 }));
+        assert_eq!(
+            Decimation::new(2, Reduction::Decimate).encode(&[1i32, 2, 3], &mut [0u8; 3]),
+            Err(ZigZagError::BufferTooSmall { needed: 4, actual: 3 })
+        );
+    }
+}
@@ -0,0 +1,562 @@
+//! Delta coding of ZigZag varint streams.
+//!
+//! Each value is stored as the ZigZag varint of its difference to the previous
+//! value (the first value is stored relative to zero). Differences are computed
+//! with wrapping arithmetic in the width of the value type, so any sequence
+//! round-trips exactly. [`encode_iter`] and [`decode_iter`] stream the ZigZag
+//! encoded differences, before the varint step, one value at a time, and
+//! [`prefix_sum`] reconstructs values from differences that are decoded already.
+//!
+//! ```rust
+//! # #[cfg(feature = "i64")] {
+//! use zigzag_rs::delta;
+//!
+//! let timestamps = [1_700_000_000i64, 1_700_000_010, 1_700_000_020, 1_700_000_025];
+//! let mut buf = [0u8; 32];
+//! let len = delta::encode(&timestamps, &mut buf).unwrap();
+//! assert_eq!(len, 5 + 3);
+//!
+//! let mut decoded = [0i64; 4];
+//! assert_eq!(delta::decode(&buf[..len], &mut decoded), Ok(4));
+//! assert_eq!(decoded, timestamps);
+//! # }
+//! ```
+//!
+//! # Keyframes
+//!
+//! A lost byte corrupts every later value of a plain delta stream.
+//! [`encode_keyframed`] therefore writes every `interval`-th value as a
+//! keyframe: the marker [`KEYFRAME`] followed by the absolute value, while the
+//! values in between stay deltas.
+//!
+//! ```text
+//! 0x80 0x00 | value 0 (ZigZag varint) | delta 1 | ... | delta K-1 | 0x80 0x00 | value K | delta K+1 | ...
+//! ```
+//!
+//! The marker is an overlong encoding of zero, which a minimal varint never
+//! contains, so [`keyframes`] finds every keyframe by scanning, and decoding
+//! can start at any of them: after packet loss or for a seek.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::delta;
+//!
+//! let readings = [500i32, 501, 503, 502, 504, 507];
+//! let mut buf = [0u8; 32];
+//! let len = delta::encode_keyframed(&readings, 4, &mut buf).unwrap();
+//!
+//! let keyframes: Vec<_> = delta::keyframes(&buf[..len]).collect();
+//! assert_eq!(keyframes.len(), 2);
+//! assert_eq!(keyframes[1].index, 4);
+//!
+//! // Resume at the second keyframe
+//! let mut tail = [0i32; 2];
+//! assert_eq!(delta::decode_keyframed(&buf[keyframes[1].offset..len], &mut tail), Ok(2));
+//! assert_eq!(tail, [504, 507]);
+//! # }
+//! ```
+
+use core::borrow::Borrow;
+
+use crate::varint::{self, Varint};
+use crate::{Sample, ZigZagError};
+
+/// Difference `value - prev` wrapped to the width of `T`
+#[inline]
+pub(crate) fn wrapping_delta<T: Sample>(value: T, prev: T) -> T {
+    T::from_i64(value.to_i64().wrapping_sub(prev.to_i64()))
+}
+
+/// Sum `prev + delta` wrapped to the width of `T`
+#[inline]
+pub(crate) fn wrapping_undelta<T: Sample>(prev: T, delta: T) -> T {
+    T::from_i64(prev.to_i64().wrapping_add(delta.to_i64()))
+}
+
+/// Number of bytes [`encode`] needs for `values`
+pub fn encoded_len<T>(values: &[T]) -> usize
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    let mut prev = T::from_i64(0);
+    values.iter().map(|&value| {
+        let len = T::zigzag_encode(wrapping_delta(value, prev)).varint_len();
+        prev = value;
+        len
+    }).sum()
+}
+
+/// Delta encode `values` as ZigZag varints
+///
+/// # Arguments
+/// * `values` - Values to encode
+/// * `out` - Output buffer for the encoded bytes
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    let needed = encoded_len(values);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let mut prev = T::from_i64(0);
+    let mut pos = 0;
+    for &value in values {
+        pos += varint::encode(wrapping_delta(value, prev), &mut out[pos..])?;
+        prev = value;
+    }
+    Ok(pos)
+}
+
+/// Decode all delta coded ZigZag varints in `input` into `out`
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError::UnexpectedEof)` or `Err(ZigZagError::VarintOverflow)` with the
+///   offset of the offending value if `input` is malformed
+pub fn decode<T>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    let count = varint::decode_slice(input, out)?;
+    let mut prev = T::from_i64(0);
+    for value in out[..count].iter_mut() {
+        prev = wrapping_undelta(prev, *value);
+        *value = prev;
+    }
+    Ok(count)
+}
+
+/// Marker preceding the absolute value of a keyframe
+pub const KEYFRAME: [u8; 2] = [0x80, 0x00];
+
+/// Number of bytes [`encode_keyframed`] needs for `values`
+///
+/// # Panics
+/// Panics if `interval` is 0
+pub fn encoded_len_keyframed<T>(values: &[T], interval: usize) -> usize
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    assert!(interval > 0, "keyframe interval must be at least 1");
+    let mut prev = T::from_i64(0);
+    values.iter().enumerate().map(|(i, &value)| {
+        let len = if i % interval == 0 {
+            KEYFRAME.len() + T::zigzag_encode(value).varint_len()
+        } else {
+            T::zigzag_encode(wrapping_delta(value, prev)).varint_len()
+        };
+        prev = value;
+        len
+    }).sum()
+}
+
+/// Delta encode `values` with a keyframe every `interval` values
+///
+/// # Arguments
+/// * `values` - Values to encode
+/// * `interval` - Number of values from one keyframe to the next, at least 1
+/// * `out` - Output buffer for the encoded bytes
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+///
+/// # Panics
+/// Panics if `interval` is 0
+pub fn encode_keyframed<T>(values: &[T], interval: usize, out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    let needed = encoded_len_keyframed(values, interval);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let mut prev = T::from_i64(0);
+    let mut pos = 0;
+    for (i, &value) in values.iter().enumerate() {
+        if i % interval == 0 {
+            out[pos..pos + KEYFRAME.len()].copy_from_slice(&KEYFRAME);
+            pos += KEYFRAME.len();
+            pos += varint::encode(value, &mut out[pos..])?;
+        } else {
+            pos += varint::encode(wrapping_delta(value, prev), &mut out[pos..])?;
+        }
+        prev = value;
+    }
+    Ok(pos)
+}
+
+/// Decode a stream written by [`encode_keyframed`] into `out`
+///
+/// `input` may start at any keyframe offset reported by [`keyframes`].
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError::InvalidData)` if `input` does not start with a keyframe
+/// * `Err(ZigZagError)` with the offset of the offending value if `input` is malformed
+pub fn decode_keyframed<T>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    if !input.is_empty() && !input.starts_with(&KEYFRAME) {
+        return Err(ZigZagError::InvalidData { offset: 0 });
+    }
+    let needed = varint::count(input) - keyframes(input).count();
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let mut prev = T::from_i64(0);
+    let mut pos = 0;
+    for slot in out[..needed].iter_mut() {
+        let keyframe = input[pos..].starts_with(&KEYFRAME);
+        if keyframe {
+            pos += KEYFRAME.len();
+        }
+        let (value, len) = varint::decode::<T>(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        prev = if keyframe { value } else { wrapping_undelta(prev, value) };
+        *slot = prev;
+        pos += len;
+    }
+    Ok(needed)
+}
+
+/// Position of a keyframe in a stream written by [`encode_keyframed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keyframe {
+    /// Byte offset of the [`KEYFRAME`] marker
+    pub offset: usize,
+    /// Index of the value the keyframe holds, counted from the start of the scanned bytes
+    pub index: usize,
+}
+
+/// Iterator over the keyframes of a stream, see [`keyframes`]
+#[derive(Debug, Clone)]
+pub struct Keyframes<'a> {
+    input: &'a [u8],
+    pos: usize,
+    values: usize,
+}
+
+impl Iterator for Keyframes<'_> {
+    type Item = Keyframe;
+
+    fn next(&mut self) -> Option<Keyframe> {
+        while self.pos + 1 < self.input.len() {
+            let pos = self.pos;
+            if self.input[pos..].starts_with(&KEYFRAME) {
+                self.pos += KEYFRAME.len();
+                return Some(Keyframe { offset: pos, index: self.values });
+            }
+            self.values += usize::from(self.input[pos] & 0x80 == 0);
+            self.pos += 1;
+        }
+        None
+    }
+}
+
+/// Find the keyframes in `input` by scanning for their markers
+///
+/// `input` may start anywhere, such as in the middle of a value after packet
+/// loss; the reported indexes then count the values terminated in the
+/// scanned bytes.
+pub fn keyframes(input: &[u8]) -> Keyframes<'_> {
+    Keyframes { input, pos: 0, values: 0 }
+}
+
+/// Iterator yielding the ZigZag encoded differences of the values of an inner iterator, see [`encode_iter`]
+#[derive(Debug, Clone)]
+pub struct EncodeIter<I, T> {
+    iter: I,
+    prev: T,
+}
+
+impl<I, T> Iterator for EncodeIter<I, T>
+where
+    T: Sample,
+    I: Iterator,
+    I::Item: Borrow<T>,
+{
+    type Item = T::UInt;
+
+    #[inline]
+    fn next(&mut self) -> Option<T::UInt> {
+        let value = *self.iter.next()?.borrow();
+        let delta = wrapping_delta(value, self.prev);
+        self.prev = value;
+        Some(T::zigzag_encode(delta))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, T> ExactSizeIterator for EncodeIter<I, T>
+where
+    T: Sample,
+    I: ExactSizeIterator,
+    I::Item: Borrow<T>,
+{
+}
+
+/// Iterator yielding the values whose encoded differences an inner iterator yields, see [`decode_iter`]
+#[derive(Debug, Clone)]
+pub struct DecodeIter<I, T> {
+    iter: I,
+    prev: T,
+}
+
+impl<I, T> Iterator for DecodeIter<I, T>
+where
+    T: Sample,
+    T::UInt: Copy,
+    I: Iterator,
+    I::Item: Borrow<T::UInt>,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let delta = T::zigzag_decode(*self.iter.next()?.borrow());
+        self.prev = wrapping_undelta(self.prev, delta);
+        Some(self.prev)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, T> ExactSizeIterator for DecodeIter<I, T>
+where
+    T: Sample,
+    T::UInt: Copy,
+    I: ExactSizeIterator,
+    I::Item: Borrow<T::UInt>,
+{
+}
+
+/// Iterator yielding the running sums of the values of an inner iterator, see [`prefix_sum`]
+#[derive(Debug, Clone)]
+pub struct PrefixSum<I, T> {
+    iter: I,
+    sum: T,
+}
+
+impl<I, T> Iterator for PrefixSum<I, T>
+where
+    T: Sample,
+    I: Iterator,
+    I::Item: Borrow<T>,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.sum = wrapping_undelta(self.sum, *self.iter.next()?.borrow());
+        Some(self.sum)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, T> ExactSizeIterator for PrefixSum<I, T>
+where
+    T: Sample,
+    I: ExactSizeIterator,
+    I::Item: Borrow<T>,
+{
+}
+
+/// Reconstruct absolute values from already decoded differences
+///
+/// Each item is added to the running sum, which starts at `seed`, and the
+/// sum is yielded. Sums wrap in the width of `T`, matching the differences
+/// [`encode_iter`] produces. Following [`zigzag_decode_iter`](crate::zigzag_decode_iter)
+/// with this is equivalent to [`decode_iter`].
+///
+/// # Example
+/// ```
+/// # #[cfg(all(feature = "i8", feature = "i32"))] {
+/// use zigzag_rs::{delta, zigzag_decode_iter};
+///
+/// let deltas = [5i8, 1, -2];
+/// assert_eq!(delta::prefix_sum(&deltas, 100).collect::<Vec<_>>(), [105, 106, 104]);
+///
+/// // Sums wrap around
+/// assert_eq!(delta::prefix_sum([1i8], i8::MAX).next(), Some(i8::MIN));
+///
+/// let decoded = delta::prefix_sum(zigzag_decode_iter::<i32, _>([10u32, 2]), 0);
+/// assert_eq!(decoded.collect::<Vec<_>>(), [5, 6]);
+/// # }
+/// ```
+pub fn prefix_sum<T, I>(iter: I, seed: T) -> PrefixSum<I::IntoIter, T>
+where
+    T: Sample,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
+{
+    PrefixSum { iter: iter.into_iter(), sum: seed }
+}
+
+/// ZigZag encode the differences between consecutive values of `iter`
+///
+/// The first value is encoded relative to `baseline`, which is zero for
+/// streams that match [`encode`]. Differences wrap in the width of `T`.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::delta;
+///
+/// let readings = [1000i32, 1003, 1001];
+/// let encoded: Vec<u32> = delta::encode_iter(&readings, 1000).collect();
+/// assert_eq!(encoded, [0, 6, 3]);
+/// let decoded: Vec<i32> = delta::decode_iter(encoded, 1000).collect();
+/// assert_eq!(decoded, readings);
+/// # }
+/// ```
+pub fn encode_iter<T, I>(iter: I, baseline: T) -> EncodeIter<I::IntoIter, T>
+where
+    T: Sample,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
+{
+    EncodeIter { iter: iter.into_iter(), prev: baseline }
+}
+
+/// Decode differences written by [`encode_iter`] back into values
+///
+/// `baseline` must be the one the differences were encoded with.
+pub fn decode_iter<T, I>(iter: I, baseline: T) -> DecodeIter<I::IntoIter, T>
+where
+    T: Sample,
+    T::UInt: Copy,
+    I: IntoIterator,
+    I::Item: Borrow<T::UInt>,
+{
+    DecodeIter { iter: iter.into_iter(), prev: baseline }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i64"))]
+    fn test_delta_round_trip_with_wrapping() {
+        let values = [i16::MIN, i16::MAX, 0, -1, i16::MIN, 5];
+        let mut buf = [0u8; 32];
+        let len = encode(&values, &mut buf).unwrap();
+        assert_eq!(len, encoded_len(&values));
+        let mut decoded = [0i16; 6];
+        assert_eq!(decode(&buf[..len], &mut decoded), Ok(6));
+        assert_eq!(decoded, values);
+
+        // A jump from MIN to MAX wraps to a delta of -1 and takes a single byte
+        let values = [i64::MIN, i64::MAX];
+        let len = encode(&values, &mut buf).unwrap();
+        assert_eq!(len, 10 + 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i64"))]
+    fn test_delta_iter_matches_slice_coding() {
+        let values = [i16::MIN, i16::MAX, 0, -1, 5];
+        let mut buf = [0u8; 32];
+        let len = encode(&values, &mut buf).unwrap();
+        let mut from_slice = [0u16; 5];
+        let mut pos = 0;
+        for slot in from_slice.iter_mut() {
+            let (value, used) = u16::decode_varint(&buf[pos..len]).unwrap();
+            *slot = value;
+            pos += used;
+        }
+        let encoded: Vec<u16> = encode_iter(values.iter(), 0).collect();
+        assert_eq!(encoded, from_slice);
+        let decoded = decode_iter::<i16, _>(&encoded, 0);
+        assert_eq!(decoded.len(), 5);
+        assert_eq!(decoded.collect::<Vec<_>>(), values);
+
+        let deltas = crate::zigzag_decode_iter::<i16, _>(&encoded);
+        assert!(prefix_sum(deltas, 0).eq(decode_iter::<i16, _>(&encoded, 0)));
+
+        // A baseline far from zero keeps the first delta small
+        assert_eq!(encode_iter(0..3i64, -1).collect::<Vec<_>>(), [2, 2, 2]);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_delta_errors() {
+        let mut small = [0u8; 2];
+        assert_eq!(
+            encode(&[1000i32, 2000], &mut small),
+            Err(ZigZagError::BufferTooSmall { needed: 4, actual: 2 })
+        );
+        let mut decoded = [0i32; 1];
+        assert_eq!(decode(&[2, 2], &mut decoded), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_keyframed_round_trip_and_resync() {
+        let values: [i16; 10] = [100, 101, i16::MIN, i16::MAX, 5, 0, -7, 300, 301, 299];
+        let mut buf = [0u8; 64];
+        for interval in [1, 3, 10, 100] {
+            let len = encode_keyframed(&values, interval, &mut buf).unwrap();
+            assert_eq!(len, encoded_len_keyframed(&values, interval));
+            let mut decoded = [0i16; 10];
+            assert_eq!(decode_keyframed(&buf[..len], &mut decoded), Ok(10));
+            assert_eq!(decoded, values);
+
+            // Every keyframe is a valid starting point
+            let found: Vec<Keyframe> = keyframes(&buf[..len]).collect();
+            assert_eq!(found.len(), values.len().div_ceil(interval));
+            for keyframe in found {
+                assert_eq!(keyframe.index % interval, 0);
+                let count = decode_keyframed(&buf[keyframe.offset..len], &mut decoded).unwrap();
+                assert_eq!(&decoded[..count], &values[keyframe.index..]);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_keyframes_after_loss() {
+        let values = [1000i32, 1001, 1003, 1006, 1010];
+        let mut buf = [0u8; 32];
+        let len = encode_keyframed(&values, 2, &mut buf).unwrap();
+        // Drop the first three bytes: the marker and part of the first value
+        let damaged = &buf[3..len];
+        assert_eq!(decode_keyframed(damaged, &mut [0i32; 5]), Err(ZigZagError::InvalidData { offset: 0 }));
+        let keyframe = keyframes(damaged).next().unwrap();
+        // The tail of the first value and the delta after it precede the keyframe
+        assert_eq!(keyframe, Keyframe { offset: 2, index: 2 });
+        let mut decoded = [0i32; 3];
+        assert_eq!(decode_keyframed(&damaged[keyframe.offset..], &mut decoded), Ok(3));
+        assert_eq!(decoded, [1003, 1006, 1010]);
+    }
+}
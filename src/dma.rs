@@ -0,0 +1,149 @@
+//! Encoding into regions aligned for DMA bursts.
+//!
+//! DMA controllers move data in bursts, and many require each transfer to
+//! start on a burst boundary and to cover whole bursts. The functions here
+//! find the aligned part of an output buffer and encode into it, padding the
+//! last burst with zeros, so the returned region can be handed to the
+//! peripheral as it is. [`encode_bursts`] streams inputs of any length
+//! through such a region one fill at a time.
+//!
+//! ```text
+//! out:  [ head | burst | burst | ... | burst | tail ]
+//!                ^ aligned to `align` bytes,  whole bursts only
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::dma;
+//!
+//! let mut out = [0u32; 32];
+//! // 32-byte bursts of eight u32 values
+//! let region = dma::encode_aligned(&[-1i32, 1, -2], &mut out, 32).unwrap();
+//! assert_eq!(region.as_ptr() as usize % 32, 0);
+//! assert_eq!(region, [1, 2, 3, 0, 0, 0, 0, 0]);
+//! # }
+//! ```
+
+use crate::{ZigZag, ZigZagError};
+
+/// Largest part of `buf` that starts on an `align`-byte boundary and covers
+/// whole multiples of `align` bytes
+///
+/// The result is empty if `buf` contains no such part.
+///
+/// # Panics
+/// Panics if `align` is not a power of two
+pub fn aligned_region<T>(buf: &mut [T], align: usize) -> &mut [T] {
+    let burst = burst_len::<T>(align);
+    let start = buf.as_ptr().align_offset(align).min(buf.len());
+    let buf = &mut buf[start..];
+    let len = buf.len() / burst * burst;
+    &mut buf[..len]
+}
+
+/// Encode `values` into the aligned region of `out`, padding the last burst with zeros
+///
+/// # Returns
+/// * `Ok(region)` with the aligned region holding the encoded values and the padding
+/// * `Err(ZigZagError::BufferTooSmall)` if the aligned region of `out` is too small
+///
+/// # Panics
+/// Panics if `align` is not a power of two
+pub fn encode_aligned<'a, T>(values: &[T], out: &'a mut [T::UInt], align: usize) -> Result<&'a mut [T::UInt], ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Default,
+{
+    let burst = burst_len::<T::UInt>(align);
+    let needed = values.len().div_ceil(burst) * burst;
+    let region = aligned_region(out, align);
+    if region.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: region.len() });
+    }
+    let region = &mut region[..needed];
+    let (encoded, padding) = region.split_at_mut(values.len());
+    T::zigzag_encode_slice(values, encoded);
+    for slot in padding {
+        *slot = T::UInt::default();
+    }
+    Ok(region)
+}
+
+/// Encode `values` through the aligned region of `out`, one fill at a time
+///
+/// Each fill is handed to `sink` as an aligned region of whole bursts, the
+/// last one padded with zeros, before the next one is encoded.
+///
+/// # Returns
+/// * `Ok(())` if `sink` accepted every fill
+/// * `Err(err)` with the first error returned by `sink`, after which no more values are encoded
+///
+/// # Panics
+/// Panics if `align` is not a power of two or the aligned region of `out` holds no burst
+pub fn encode_bursts<T, E, F>(values: &[T], out: &mut [T::UInt], align: usize, mut sink: F) -> Result<(), E>
+where
+    T: ZigZag + Copy,
+    T::UInt: Default,
+    F: FnMut(&[T::UInt]) -> Result<(), E>,
+{
+    let region = aligned_region(out, align);
+    assert!(!region.is_empty(), "Output slice must hold at least one aligned burst");
+    for chunk in values.chunks(region.len()) {
+        // A chunk never exceeds the region, which is aligned already
+        sink(encode_aligned(chunk, &mut *region, align).expect("chunk fits the aligned region"))?;
+    }
+    Ok(())
+}
+
+/// Number of values of `T` in a burst of `align` bytes
+fn burst_len<T>(align: usize) -> usize {
+    assert!(align.is_power_of_two(), "Alignment must be a power of two");
+    (align / core::mem::size_of::<T>().max(1)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i64"))]
+    fn test_encode_aligned_regions() {
+        let mut out = [0u16; 40];
+        let region = encode_aligned(&[-1i16; 9], &mut out, 16).unwrap();
+        assert_eq!(region.as_ptr() as usize % 16, 0);
+        assert_eq!(region.len(), 16);
+        assert_eq!(region[8..10], [1, 0]);
+
+        assert!(encode_aligned::<i16>(&[], &mut out, 16).unwrap().is_empty());
+        let actual = aligned_region(&mut out, 16).len();
+        assert_eq!(encode_aligned(&[0i16; 41], &mut out, 16), Err(ZigZagError::BufferTooSmall { needed: 48, actual }));
+        // Smaller than one value, so every value is a burst
+        assert_eq!(encode_aligned(&[1i64], &mut [0u64; 1], 4), Ok(&mut [2u64][..]));
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_encode_bursts_streams_fills() {
+        let values: [i32; 20] = core::array::from_fn(|i| i as i32 - 10);
+        let mut out = [0u32; 24];
+        let mut decoded = [0i32; 24];
+        let mut pos = 0;
+        encode_bursts::<_, (), _>(&values, &mut out, 32, |fill| {
+            assert_eq!(fill.as_ptr() as usize % 32, 0);
+            assert_eq!(fill.len() % 8, 0);
+            i32::zigzag_decode_slice(fill, &mut decoded[pos..pos + fill.len()]);
+            pos += fill.len();
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(pos, 24);
+        assert_eq!(decoded[..20], values);
+        assert_eq!(decoded[20..], [0; 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Alignment must be a power of two")]
+    fn test_aligned_region_rejects_bad_alignment() {
+        aligned_region(&mut [0u8; 8], 12);
+    }
+}
@@ -0,0 +1,200 @@
+//! Typed wrapper for ZigZag encoded values.
+//!
+//! A bare `u32` does not say whether it holds a raw count or the ZigZag form
+//! of an `i32`, and decoding a value twice or storing a raw value as encoded
+//! goes unnoticed. [`ZigZagEncoded<T>`] holds the encoded form of a `T` and
+//! is only created by encoding a `T` or by an explicit
+//! [`from_raw`](ZigZagEncoded::from_raw). It has the same layout as the
+//! unsigned integer, so slices convert in both directions without copying.
+//!
+//! ```rust
+//! # #[cfg(all(feature = "i16", feature = "i32"))] {
+//! use zigzag_rs::encoded::ZigZagEncoded;
+//!
+//! let encoded = ZigZagEncoded::encode(-3i32);
+//! assert_eq!(encoded.into_raw(), 5u32);
+//! assert_eq!(encoded.decode(), -3);
+//!
+//! let mut frame = [ZigZagEncoded::<i16>::default(); 3];
+//! ZigZagEncoded::encode_slice(&[-1, 0, 1], &mut frame).unwrap();
+//! assert_eq!(ZigZagEncoded::as_raw_slice(&frame), &[1u16, 0, 2]);
+//! # }
+//! ```
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use crate::{ZigZag, ZigZagError};
+
+/// The ZigZag encoded form of a `T`
+#[repr(transparent)]
+pub struct ZigZagEncoded<T: ZigZag>(T::UInt);
+
+impl<T: ZigZag> ZigZagEncoded<T> {
+    /// Encode a value
+    #[inline]
+    pub fn encode(value: T) -> Self {
+        ZigZagEncoded(T::zigzag_encode(value))
+    }
+
+    /// Decode the value
+    #[inline]
+    pub fn decode(self) -> T {
+        T::zigzag_decode(self.0)
+    }
+
+    /// Wrap a value that is known to be ZigZag encoded, such as one read from the wire
+    #[inline]
+    pub fn from_raw(raw: T::UInt) -> Self {
+        ZigZagEncoded(raw)
+    }
+
+    /// The encoded value as an unsigned integer
+    #[inline]
+    pub fn into_raw(self) -> T::UInt {
+        self.0
+    }
+
+    /// View encoded values as their unsigned integers
+    pub fn as_raw_slice(encoded: &[Self]) -> &[T::UInt] {
+        // SAFETY: `ZigZagEncoded<T>` is a transparent wrapper of `T::UInt`, so
+        // both slices have the same layout
+        unsafe { core::slice::from_raw_parts(encoded.as_ptr() as *const T::UInt, encoded.len()) }
+    }
+
+    /// View unsigned integers that are known to be ZigZag encoded as encoded values
+    pub fn from_raw_slice(raw: &[T::UInt]) -> &[Self] {
+        // SAFETY: as in `as_raw_slice`
+        unsafe { core::slice::from_raw_parts(raw.as_ptr() as *const Self, raw.len()) }
+    }
+
+    /// Mutable view of unsigned integers that are known to be ZigZag encoded, for filling from input
+    pub fn from_raw_slice_mut(raw: &mut [T::UInt]) -> &mut [Self] {
+        // SAFETY: as in `as_raw_slice`
+        unsafe { core::slice::from_raw_parts_mut(raw.as_mut_ptr() as *mut Self, raw.len()) }
+    }
+}
+
+impl<T> ZigZagEncoded<T>
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy,
+{
+    /// Encode every value of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were encoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    pub fn encode_slice(values: &[T], out: &mut [Self]) -> Result<(), ZigZagError> {
+        T::try_zigzag_encode_slice(values, Self::as_raw_slice_mut(out))?;
+        Ok(())
+    }
+
+    /// Decode every value of `encoded` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `encoded`
+    pub fn decode_slice(encoded: &[Self], out: &mut [T]) -> Result<(), ZigZagError> {
+        T::try_zigzag_decode_slice(Self::as_raw_slice(encoded), out)?;
+        Ok(())
+    }
+
+    fn as_raw_slice_mut(encoded: &mut [Self]) -> &mut [T::UInt] {
+        // SAFETY: as in `as_raw_slice`
+        unsafe { core::slice::from_raw_parts_mut(encoded.as_mut_ptr() as *mut T::UInt, encoded.len()) }
+    }
+}
+
+// Implemented by hand so that the bounds fall on `T::UInt` rather than `T`
+
+impl<T: ZigZag> Clone for ZigZagEncoded<T>
+where
+    T::UInt: Clone,
+{
+    fn clone(&self) -> Self {
+        ZigZagEncoded(self.0.clone())
+    }
+}
+
+impl<T: ZigZag> Copy for ZigZagEncoded<T> where T::UInt: Copy {}
+
+impl<T: ZigZag> PartialEq for ZigZagEncoded<T>
+where
+    T::UInt: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: ZigZag> Eq for ZigZagEncoded<T> where T::UInt: Eq {}
+
+impl<T: ZigZag> Hash for ZigZagEncoded<T>
+where
+    T::UInt: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: ZigZag> Default for ZigZagEncoded<T>
+where
+    T::UInt: Default,
+{
+    fn default() -> Self {
+        ZigZagEncoded(T::UInt::default())
+    }
+}
+
+impl<T: ZigZag> fmt::Debug for ZigZagEncoded<T>
+where
+    T::UInt: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ZigZagEncoded").field(&self.0).finish()
+    }
+}
+
+impl<T: ZigZag> From<T> for ZigZagEncoded<T> {
+    fn from(value: T) -> Self {
+        Self::encode(value)
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64"))]
+    fn test_encoded_round_trip() {
+        for value in [i64::MIN, -1, 0, 1, i64::MAX] {
+            let encoded: ZigZagEncoded<i64> = value.into();
+            assert_eq!(encoded.into_raw(), i64::zigzag_encode(value));
+            assert_eq!(encoded.decode(), value);
+            assert_eq!(ZigZagEncoded::<i64>::from_raw(encoded.into_raw()), encoded);
+        }
+        assert_eq!(std::format!("{:?}", ZigZagEncoded::encode(-1i8)), "ZigZagEncoded(1)");
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_encoded_slices() {
+        let mut raw = [1u32, 4, 7];
+        let encoded = ZigZagEncoded::<i32>::from_raw_slice(&raw);
+        let mut decoded = [0i32; 3];
+        ZigZagEncoded::decode_slice(encoded, &mut decoded).unwrap();
+        assert_eq!(decoded, [-1, 2, -4]);
+
+        ZigZagEncoded::<i32>::from_raw_slice_mut(&mut raw)[0] = ZigZagEncoded::encode(100);
+        assert_eq!(raw[0], 200);
+        let mut out = [ZigZagEncoded::<i32>::default(); 2];
+        assert_eq!(
+            ZigZagEncoded::encode_slice(&decoded, &mut out),
+            Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 })
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! Growable container that keeps its values compressed.
+//!
+//! [`EncodedVec`] stores values in the [`delta`](crate::delta) format: the
+//! ZigZag varint of the difference to the previous value. Slowly changing
+//! series such as timestamps or sensor readings take one or two bytes per
+//! value instead of eight, and values can still be appended one at a time.
+//!
+//! Requires the `alloc` feature.
+//!
+//! ```rust
+//! # #[cfg(feature = "i64")] {
+//! use zigzag_rs::encoded_vec::EncodedVec;
+//!
+//! let mut series = EncodedVec::<i64>::new();
+//! for second in 0..1000 {
+//!     series.push(1_700_000_000 + second);
+//! }
+//! assert_eq!(series.len(), 1000);
+//! assert_eq!(series.last(), Some(1_700_000_999));
+//! assert_eq!(series.encoded_len(), 5 + 999);
+//! assert_eq!(series.iter().nth(500), Some(1_700_000_500));
+//! # }
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::delta::{wrapping_delta, wrapping_undelta};
+use crate::varint::{self, Varint};
+use crate::Sample;
+
+/// Appendable sequence of delta, ZigZag and varint encoded values
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncodedVec<T> {
+    bytes: Vec<u8>,
+    len: usize,
+    last: Option<T>,
+}
+
+impl<T> EncodedVec<T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    /// Create an empty vector
+    pub fn new() -> Self {
+        EncodedVec { bytes: Vec::new(), len: 0, last: None }
+    }
+
+    /// Create an empty vector with room for `bytes` encoded bytes
+    pub fn with_capacity(bytes: usize) -> Self {
+        EncodedVec { bytes: Vec::with_capacity(bytes), len: 0, last: None }
+    }
+
+    /// Append a value
+    pub fn push(&mut self, value: T) {
+        let prev = self.last.unwrap_or(T::from_i64(0));
+        let mut buf = varint::scratch::<T::UInt>();
+        let len = varint::encode(wrapping_delta(value, prev), &mut buf).unwrap_or(0);
+        self.bytes.extend_from_slice(&buf[..len]);
+        self.len += 1;
+        self.last = Some(value);
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the vector holds no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Most recently pushed value
+    pub fn last(&self) -> Option<T> {
+        self.last
+    }
+
+    /// Iterate over the values in push order
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { bytes: &self.bytes, pos: 0, remaining: self.len, prev: T::from_i64(0) }
+    }
+
+    /// Encoded bytes, readable with [`delta::decode`](crate::delta::decode)
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Number of encoded bytes
+    pub fn encoded_len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Heap and inline memory the vector occupies, in bytes
+    pub fn memory_usage(&self) -> usize {
+        core::mem::size_of::<Self>() + self.bytes.capacity()
+    }
+
+    /// Remove all values, keeping the allocation
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.len = 0;
+        self.last = None;
+    }
+
+    /// Release unused capacity
+    pub fn shrink_to_fit(&mut self) {
+        self.bytes.shrink_to_fit();
+    }
+}
+
+impl<T> Default for EncodedVec<T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for EncodedVec<T>
+where
+    T: Sample + fmt::Debug,
+    T::UInt: Varint,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Extend<T> for EncodedVec<T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for EncodedVec<T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<'a, T> IntoIterator for &'a EncodedVec<T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of an [`EncodedVec`]
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    bytes: &'a [u8],
+    pos: usize,
+    remaining: usize,
+    prev: T,
+}
+
+impl<T> Iterator for Iter<'_, T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        // The bytes were written by push
+        let (delta, len) = varint::decode::<T>(&self.bytes[self.pos..]).ok()?;
+        self.pos += len;
+        self.remaining -= 1;
+        self.prev = wrapping_undelta(self.prev, delta);
+        Some(self.prev)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::delta;
+    use std::vec;
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_encoded_vec_push_and_iter() {
+        let values = [i32::MIN, i32::MAX, 0, -5, 1_000_000, 999_999];
+        let mut vec: EncodedVec<i32> = values.iter().copied().collect();
+        assert_eq!(vec.len(), 6);
+        assert_eq!(vec.last(), Some(999_999));
+        assert!(vec.iter().eq(values.iter().copied()));
+
+        // The bytes are a plain delta stream
+        let mut decoded = [0i32; 6];
+        assert_eq!(delta::decode(vec.as_bytes(), &mut decoded), Ok(6));
+        assert_eq!(decoded, values);
+
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(vec.last(), None);
+        vec.push(-1);
+        assert_eq!(std::format!("{:?}", vec), "[-1]");
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_encoded_vec_memory_usage() {
+        let mut vec = EncodedVec::<i64>::with_capacity(0);
+        vec.extend((0..10_000).map(|i| 1_000_000_000 + i * 3 + (i % 5)));
+        vec.shrink_to_fit();
+        assert!(vec.encoded_len() < 10_000 + 8);
+        assert_eq!(vec.memory_usage(), core::mem::size_of::<EncodedVec<i64>>() + vec.encoded_len());
+        assert!(vec.memory_usage() < vec![0i64; 10_000].len() * 8 / 6);
+    }
+}
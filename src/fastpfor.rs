@@ -0,0 +1,300 @@
+//! FastPFor-style patched frame-of-reference codec.
+//!
+//! Values are ZigZag encoded and split into pages of 128. Each page picks the
+//! base bit width that minimizes its size: values fitting into the base width
+//! are bit packed in the [`bitpack`](crate::bitpack) BP128 layout (and unpacked
+//! with SIMD), the few values that don't become exceptions whose positions and
+//! high bits are stored after the packed block and patched in on decode.
+//!
+//! Stream layout:
+//!
+//! ```text
+//! count (varint) | page* | tail (count % 128 ZigZag varints)
+//!
+//! page: base width (u8) | exception count (u8) | [exception width (u8)]
+//!       | packed low bits (16 * base width bytes)
+//!       | exception positions (u8 each) | exception high bits (bit packed)
+//! ```
+//!
+//! ```rust
+//! use zigzag_rs::codec::Codec;
+//! use zigzag_rs::fastpfor::FastPfor;
+//!
+//! // Mostly small values with a few outliers
+//! let values: Vec<i64> = (0..1000).map(|i| if i % 100 == 0 { 1 << 40 } else { i % 7 - 3 }).collect();
+//! let mut buf = vec![0u8; Codec::<i64>::max_encoded_len(&FastPfor, values.len())];
+//! let len = FastPfor.encode(&values, &mut buf).unwrap();
+//! assert!(len < values.len());
+//!
+//! let mut decoded = vec![0i64; values.len()];
+//! assert_eq!(FastPfor.decode(&buf[..len], &mut decoded), Ok(1000));
+//! assert_eq!(decoded, values);
+//! ```
+
+use crate::bitpack;
+use crate::bits::{BitReader, BitWriter};
+use crate::codec::Codec;
+use crate::varint::{self, Varint};
+use crate::{Sample, ZigZag, ZigZagError};
+
+/// Number of values per page
+pub const PAGE_LEN: usize = 128;
+
+/// Largest base width, limited by the 32-bit lanes of the packed layout
+const MAX_BASE_WIDTH: u32 = 32;
+
+/// Worst case size of a page: base width 32 with every value an exception of 32 high bits
+const MAX_PAGE_LEN: usize = 3 + 16 * 32 + PAGE_LEN + PAGE_LEN * 32 / 8;
+
+/// FastPFor codec for signed integers up to 64 bits
+///
+/// Values wider than 32 bits after ZigZag encoding are stored as exceptions,
+/// so the codec works best when such values are rare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FastPfor;
+
+impl FastPfor {
+    /// Number of bytes [`Codec::encode`] writes for `values`
+    pub fn encoded_len<T: Sample>(&self, values: &[T]) -> usize {
+        let mut len = (values.len() as u64).varint_len();
+        let mut pages = values.chunks_exact(PAGE_LEN);
+        for page in pages.by_ref() {
+            let mut encoded = [0u64; PAGE_LEN];
+            for (slot, value) in encoded.iter_mut().zip(page) {
+                *slot = i64::zigzag_encode(value.to_i64());
+            }
+            len += page_layout(&encoded).3;
+        }
+        len + pages.remainder().iter().map(|value| i64::zigzag_encode(value.to_i64()).varint_len()).sum::<usize>()
+    }
+}
+
+impl<T: Sample> Codec<T> for FastPfor {
+    fn max_encoded_len(&self, count: usize) -> usize {
+        u64::MAX_LEN + (count / PAGE_LEN) * MAX_PAGE_LEN + (count % PAGE_LEN) * u64::MAX_LEN
+    }
+
+    fn encode(&self, values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError> {
+        let mut pos = (values.len() as u64).encode_varint(out)?;
+        let mut pages = values.chunks_exact(PAGE_LEN);
+        for page in pages.by_ref() {
+            let mut encoded = [0u64; PAGE_LEN];
+            for (slot, value) in encoded.iter_mut().zip(page) {
+                *slot = i64::zigzag_encode(value.to_i64());
+            }
+            pos += encode_page(&encoded, &mut out[pos..])?;
+        }
+        for value in pages.remainder() {
+            pos += varint::encode(value.to_i64(), &mut out[pos..])?;
+        }
+        Ok(pos)
+    }
+
+    fn decode(&self, input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError> {
+        let (count, mut pos) = u64::decode_varint(input)?;
+        let count = count as usize;
+        if count > out.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: count, actual: out.len() });
+        }
+
+        let mut pages = out[..count].chunks_exact_mut(PAGE_LEN);
+        for page in pages.by_ref() {
+            let mut encoded = [0u64; PAGE_LEN];
+            pos += decode_page(&input[pos..], &mut encoded).map_err(|err| varint::at_offset(err, pos))?;
+            for (slot, &value) in page.iter_mut().zip(encoded.iter()) {
+                *slot = T::from_i64(i64::zigzag_decode(value));
+            }
+        }
+        for slot in pages.into_remainder() {
+            let (value, len) = varint::decode::<i64>(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+            *slot = T::from_i64(value);
+            pos += len;
+        }
+        Ok(count)
+    }
+}
+
+/// Pick the base width and exception width that minimize the page size
+fn choose_widths(values: &[u64; PAGE_LEN]) -> (u32, u32) {
+    let mut histogram = [0usize; 65];
+    for &value in values {
+        histogram[(64 - value.leading_zeros()) as usize] += 1;
+    }
+    let max_width = histogram.iter().rposition(|&count| count > 0).unwrap_or(0) as u32;
+
+    let mut best = (MAX_BASE_WIDTH.min(max_width), 0);
+    let mut best_cost = usize::MAX;
+    let mut exceptions = PAGE_LEN - histogram[0];
+    for base in 0..=MAX_BASE_WIDTH.min(max_width) {
+        if base > 0 {
+            exceptions -= histogram[base as usize];
+        }
+        let high_width = max_width - base;
+        let mut cost = PAGE_LEN * base as usize;
+        if exceptions > 0 {
+            cost += 8 + exceptions * (8 + high_width as usize);
+        }
+        if cost < best_cost {
+            best = (base, if exceptions > 0 { high_width } else { 0 });
+            best_cost = cost;
+        }
+    }
+    best
+}
+
+/// Widths, exception count and encoded size of a page
+fn page_layout(values: &[u64; PAGE_LEN]) -> (u32, u32, usize, usize) {
+    let (base, high_width) = choose_widths(values);
+    let low_mask = if base == 0 { 0 } else { u64::MAX >> (64 - base) };
+    let exceptions = values.iter().filter(|&&value| value > low_mask).count();
+
+    let header_len = if exceptions > 0 { 3 } else { 2 };
+    let packed_len = bitpack::packed_len(PAGE_LEN, base);
+    let high_len = bitpack::packed_len(exceptions, high_width);
+    (base, high_width, exceptions, header_len + packed_len + exceptions + high_len)
+}
+
+fn encode_page(values: &[u64; PAGE_LEN], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let (base, high_width, exceptions, needed) = page_layout(values);
+    let low_mask = if base == 0 { 0 } else { u64::MAX >> (64 - base) };
+    let header_len = if exceptions > 0 { 3 } else { 2 };
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    out[0] = base as u8;
+    out[1] = exceptions as u8;
+    if exceptions > 0 {
+        out[2] = high_width as u8;
+    }
+
+    let mut low = [0u32; PAGE_LEN];
+    for (slot, &value) in low.iter_mut().zip(values.iter()) {
+        *slot = (value & low_mask) as u32;
+    }
+    let mut pos = header_len;
+    pos += bitpack::pack128(&low, base, &mut out[pos..])?;
+
+    let (positions, high) = out[pos..needed].split_at_mut(exceptions);
+    let mut writer = BitWriter::new(high);
+    let exception_values = values.iter().enumerate().filter(|&(_, &value)| value > low_mask);
+    for (slot, (index, &value)) in positions.iter_mut().zip(exception_values) {
+        *slot = index as u8;
+        writer.write(value >> base, high_width)?;
+    }
+    Ok(needed)
+}
+
+fn decode_page(input: &[u8], out: &mut [u64; PAGE_LEN]) -> Result<usize, ZigZagError> {
+    if input.len() < 2 {
+        return Err(ZigZagError::UnexpectedEof { offset: 0 });
+    }
+    let base = input[0] as u32;
+    let exceptions = input[1] as usize;
+    if base > MAX_BASE_WIDTH || exceptions > PAGE_LEN {
+        return Err(ZigZagError::InvalidData { offset: 0 });
+    }
+    let (header_len, high_width) = if exceptions > 0 {
+        let high_width = *input.get(2).ok_or(ZigZagError::UnexpectedEof { offset: 0 })? as u32;
+        if high_width == 0 || base + high_width > 64 {
+            return Err(ZigZagError::InvalidData { offset: 2 });
+        }
+        (3, high_width)
+    } else {
+        (2, 0)
+    };
+
+    let mut low = [0u32; PAGE_LEN];
+    let mut pos = header_len;
+    pos += bitpack::unpack128(&input[pos..], base, &mut low).map_err(|_| ZigZagError::UnexpectedEof { offset: 0 })?;
+    for (slot, &value) in out.iter_mut().zip(low.iter()) {
+        *slot = value as u64;
+    }
+
+    let high_len = bitpack::packed_len(exceptions, high_width);
+    if input.len() < pos + exceptions + high_len {
+        return Err(ZigZagError::UnexpectedEof { offset: 0 });
+    }
+    let positions = &input[pos..pos + exceptions];
+    let mut reader = BitReader::new(&input[pos + exceptions..pos + exceptions + high_len]);
+    for (i, &index) in positions.iter().enumerate() {
+        let slot = out.get_mut(index as usize).ok_or(ZigZagError::InvalidData { offset: pos + i })?;
+        *slot |= reader.read(high_width)? << base;
+    }
+    Ok(pos + exceptions + high_len)
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[cfg(all(feature = "i8", feature = "i16"))]
+    fn round_trip<T: Sample + PartialEq + core::fmt::Debug>(values: &[T]) -> usize {
+        let mut buf = vec![0u8; Codec::<T>::max_encoded_len(&FastPfor, values.len())];
+        let len = FastPfor.encode(values, &mut buf).unwrap();
+        assert_eq!(len, FastPfor.encoded_len(values));
+        let mut decoded = vec![T::from_i64(0); values.len()];
+        assert_eq!(FastPfor.decode(&buf[..len], &mut decoded), Ok(values.len()));
+        assert_eq!(decoded, values);
+        len
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16"))]
+    fn test_fastpfor_round_trip() {
+        let small: Vec<i32> = (0..1000).map(|i| (i % 13) - 6).collect();
+        let len = round_trip(&small);
+        // 4 bits per value plus headers and a varint tail
+        assert!(len < 1000 / 2 + 200);
+
+        let outliers: Vec<i32> = (0..512).map(|i| if i % 50 == 0 { i32::MIN } else { i % 3 }).collect();
+        round_trip(&outliers);
+
+        let extremes: Vec<i64> = (0..300).map(|i| match i % 4 {
+            0 => i64::MIN,
+            1 => i64::MAX,
+            2 => -(i as i64),
+            _ => 1 << 33,
+        }).collect();
+        round_trip(&extremes);
+
+        round_trip::<i16>(&[]);
+        round_trip(&[0i8; 128]);
+    }
+
+    #[test]
+    fn test_fastpfor_patches_exceptions() {
+        let mut page = [1u64; PAGE_LEN];
+        page[7] = 1 << 20;
+        page[100] = 1 << 20;
+        // One bit for the bulk, two exceptions carrying the high bits
+        assert_eq!(choose_widths(&page), (1, 20));
+
+        let mut buf = [0u8; MAX_PAGE_LEN];
+        let len = encode_page(&page, &mut buf).unwrap();
+        assert_eq!(len, 3 + 16 + 2 + bitpack::packed_len(2, 20));
+        let mut decoded = [0u64; PAGE_LEN];
+        assert_eq!(decode_page(&buf[..len], &mut decoded), Ok(len));
+        assert_eq!(decoded, page);
+    }
+
+    #[test]
+    fn test_fastpfor_errors() {
+        let values = [5i32; 200];
+        let mut buf = [0u8; 512];
+        let len = FastPfor.encode(&values, &mut buf).unwrap();
+
+        let mut small = [0i32; 100];
+        assert_eq!(FastPfor.decode(&buf[..len], &mut small), Err(ZigZagError::BufferTooSmall { needed: 200, actual: 100 }));
+        let mut decoded = [0i32; 200];
+        assert!(matches!(FastPfor.decode(&buf[..len - 1], &mut decoded), Err(ZigZagError::UnexpectedEof { .. })));
+
+        // Base width above 32 in the first page header
+        buf[2] = 40;
+        assert_eq!(FastPfor.decode(&buf[..len], &mut decoded), Err(ZigZagError::InvalidData { offset: 2 }));
+        assert!(matches!(FastPfor.encode(&values, &mut buf[..8]), Err(ZigZagError::BufferTooSmall { .. })));
+    }
+}
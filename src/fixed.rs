@@ -0,0 +1,125 @@
+//! Signed fixed-point numbers.
+//!
+//! Control loops on targets without an FPU keep their state in fixed-point
+//! formats such as Q16.16. [`Fixed`] holds the raw two's complement bits of
+//! such a number together with its count of fractional bits and implements
+//! [`ZigZag`] and [`Sample`] on those bits, so fixed-point telemetry goes
+//! straight into the slice, iterator, varint and delta APIs.
+//!
+//! Values of the `fixed` crate convert through their bits, for example
+//! `Q16::from_bits(value.to_bits())` for an `I16F16`.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::fixed::Q16;
+//! use zigzag_rs::varint;
+//!
+//! // -1.5 and 0.25 in Q16.16
+//! let setpoints = [Q16::from_bits(-0x1_8000), Q16::from_bits(0x4000)];
+//! let mut buf = [0u8; 10];
+//! let len = varint::encode_slice(&setpoints, &mut buf).unwrap();
+//!
+//! let mut decoded = [Q16::default(); 2];
+//! assert_eq!(varint::decode_slice(&buf[..len], &mut decoded), Ok(2));
+//! assert_eq!(decoded, setpoints);
+//! assert_eq!(decoded[0].to_f64(), -1.5);
+//! # }
+//! ```
+
+use crate::{Sample, ZigZag};
+
+/// Fixed-point number stored as the integer `T` with `FRAC` fractional bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed<T, const FRAC: u32>(T);
+
+/// Q8.8, 16 bits with 8 fractional bits
+pub type Q8 = Fixed<i16, 8>;
+/// Q16.16, 32 bits with 16 fractional bits
+pub type Q16 = Fixed<i32, 16>;
+/// Q32.32, 64 bits with 32 fractional bits
+pub type Q32 = Fixed<i64, 32>;
+
+impl<T, const FRAC: u32> Fixed<T, FRAC> {
+    /// Number of fractional bits
+    pub const FRAC_BITS: u32 = FRAC;
+
+    /// Create a number from its raw bits
+    pub const fn from_bits(bits: T) -> Self {
+        Fixed(bits)
+    }
+
+    /// Raw bits of the number
+    pub fn to_bits(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Sample, const FRAC: u32> Fixed<T, FRAC> {
+    /// Value of the number as a float
+    ///
+    /// Exact for numbers of up to 53 significant bits.
+    pub fn to_f64(self) -> f64 {
+        self.0.to_i64() as f64 / (1u64 << FRAC) as f64
+    }
+}
+
+impl<T: ZigZag, const FRAC: u32> ZigZag for Fixed<T, FRAC> {
+    type UInt = T::UInt;
+
+    #[inline]
+    fn zigzag_encode(value: Self) -> Self::UInt {
+        T::zigzag_encode(value.0)
+    }
+
+    #[inline]
+    fn zigzag_decode(value: Self::UInt) -> Self {
+        Fixed(T::zigzag_decode(value))
+    }
+}
+
+impl<T: Sample, const FRAC: u32> Sample for Fixed<T, FRAC> {
+    #[inline]
+    fn to_i64(self) -> i64 {
+        self.0.to_i64()
+    }
+
+    #[inline]
+    fn from_i64(value: i64) -> Self {
+        Fixed(T::from_i64(value))
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::delta;
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_fixed_zigzag_matches_bits() {
+        for bits in [i16::MIN, -256, -1, 0, 128, i16::MAX] {
+            let value = Q8::from_bits(bits);
+            assert_eq!(Q8::zigzag_encode(value), i16::zigzag_encode(bits));
+            assert_eq!(Q8::zigzag_decode(Q8::zigzag_encode(value)), value);
+        }
+        assert_eq!(Q8::from_bits(-384).to_f64(), -1.5);
+        assert_eq!(Q32::from_bits(1).to_f64(), 1.0 / 4_294_967_296.0);
+        assert!(Q16::from_bits(-1) < Q16::from_bits(0));
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_fixed_delta_round_trip() {
+        let values: [Q16; 5] = [0x1_0000, 0x1_0040, 0x1_0080, 0x0_ffc0, -0x7fff_0000].map(Q16::from_bits);
+        let mut buf = [0u8; 32];
+        let len = delta::encode(&values, &mut buf).unwrap();
+        assert_eq!(&buf[..3], &[0x80, 0x80, 0x08]);
+        let mut decoded = [Q16::default(); 5];
+        assert_eq!(delta::decode(&buf[..len], &mut decoded), Ok(5));
+        assert_eq!(decoded, values);
+
+        let encoded: std::vec::Vec<u32> = crate::zigzag_encode_iter::<Q16, _>(&values).collect();
+        assert_eq!(encoded[1], 0x2_0080);
+    }
+}
@@ -0,0 +1,239 @@
+//! Chimp128 compression for `f32`/`f64` series.
+//!
+//! Chimp128 improves on Gorilla by XORing each value with the best of the last
+//! 128 values instead of only the previous one, and by rounding leading zero
+//! counts to a small table so they fit into three bits. Every value after the
+//! first starts with a two bit flag:
+//!
+//! - `00` - identical to a recent value, followed by its 7 bit index
+//! - `01` - XOR with a recent value has many trailing zeros, followed by the index,
+//!   the leading zero code, the number of meaningful bits and the bits themselves
+//! - `10` - XOR with the previous value, same leading zero code as before
+//! - `11` - XOR with the previous value with a new leading zero code
+//!
+//! The number of values is not stored, callers keep it alongside the encoded bytes.
+//!
+//! ```rust
+//! use zigzag_rs::float::chimp;
+//!
+//! let values = [0.5f64, 1.25, 0.5, 1.25, 0.5, 2.0];
+//! let mut buf = [0u8; 64];
+//! let len = chimp::encode(&values, &mut buf).unwrap();
+//!
+//! let mut decoded = [0f64; 6];
+//! chimp::decode(&buf[..len], &mut decoded).unwrap();
+//! assert_eq!(decoded, values);
+//! ```
+
+use super::FloatBits;
+use crate::bits::{BitReader, BitWriter};
+use crate::ZigZagError;
+
+/// Number of previous values considered as XOR reference
+const HISTORY: usize = 128;
+const INDEX_BITS: u32 = 7;
+/// Minimum trailing zero count for which a reference other than the previous value pays off
+const THRESHOLD: u32 = 6 + INDEX_BITS;
+/// Leading zero counts representable by the three bit leading zero code
+const LEADING_TABLE: [u32; 8] = [0, 8, 12, 16, 18, 20, 22, 24];
+
+/// Round a leading zero count down to the nearest table entry, returning `(code, rounded)`
+fn round_leading(leading: u32) -> (u32, u32) {
+    let code = LEADING_TABLE.iter().rposition(|&entry| entry <= leading).unwrap_or(0);
+    (code as u32, LEADING_TABLE[code])
+}
+
+/// Width of the meaningful bit count field
+fn length_field<F: FloatBits>() -> u32 {
+    F::BITS.trailing_zeros()
+}
+
+/// Encode a series of floating point values
+///
+/// # Arguments
+/// * `values` - Values to compress
+/// * `out` - Output buffer for the compressed bit stream
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode<F: FloatBits>(values: &[F], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let length_bits = length_field::<F>();
+    let mut writer = BitWriter::new(out);
+    let mut history = [0u64; HISTORY];
+
+    let first = match values.first() {
+        Some(first) => first.to_bits_u64(),
+        None => return Ok(0),
+    };
+    writer.write(first, F::BITS)?;
+    history[0] = first;
+
+    // Leading zero count of the last `11` record, `None` after other records
+    let mut stored_leading: Option<u32> = None;
+    for (i, value) in values.iter().enumerate().skip(1) {
+        let bits = value.to_bits_u64();
+
+        // Pick the recent value whose XOR has the most trailing zeros, preferring newer ones
+        let available = i.min(HISTORY);
+        let mut best_index = (i - 1) % HISTORY;
+        let mut best_trailing = 0;
+        for back in 1..=available {
+            let index = (i - back) % HISTORY;
+            let trailing = (bits ^ history[index]).trailing_zeros().min(F::BITS);
+            if trailing > best_trailing {
+                best_index = index;
+                best_trailing = trailing;
+            }
+        }
+
+        if best_trailing > THRESHOLD {
+            let xor = bits ^ history[best_index];
+            if xor == 0 {
+                writer.write(0b00, 2)?;
+                writer.write(best_index as u64, INDEX_BITS)?;
+            } else {
+                let (code, leading) = round_leading(xor.leading_zeros() - (64 - F::BITS));
+                let significant = F::BITS - leading - best_trailing;
+                writer.write(0b01, 2)?;
+                writer.write(best_index as u64, INDEX_BITS)?;
+                writer.write(code as u64, 3)?;
+                writer.write(significant as u64, length_bits)?;
+                writer.write(xor >> best_trailing, significant)?;
+            }
+            stored_leading = None;
+        } else {
+            let xor = bits ^ history[(i - 1) % HISTORY];
+            let (code, leading) = round_leading(xor.leading_zeros() - (64 - F::BITS));
+            if stored_leading == Some(leading) {
+                writer.write(0b10, 2)?;
+            } else {
+                writer.write(0b11, 2)?;
+                writer.write(code as u64, 3)?;
+                stored_leading = Some(leading);
+            }
+            writer.write(xor, F::BITS - leading)?;
+        }
+
+        history[i % HISTORY] = bits;
+    }
+
+    Ok(writer.finish())
+}
+
+/// Decode `out.len()` values from a Chimp128 bit stream
+///
+/// # Arguments
+/// * `input` - Compressed bit stream produced by [`encode`]
+/// * `out` - Output slice, its length determines how many values are decoded
+///
+/// # Returns
+/// * `Ok(len)` with the number of input bytes consumed
+/// * `Err(ZigZagError::UnexpectedEof)` if the input ends before all values were decoded
+/// * `Err(ZigZagError::InvalidData)` if a record references a value that does not exist
+pub fn decode<F: FloatBits>(input: &[u8], out: &mut [F]) -> Result<usize, ZigZagError> {
+    let length_bits = length_field::<F>();
+    let mut reader = BitReader::new(input);
+    let mut history = [0u64; HISTORY];
+
+    if out.is_empty() {
+        return Ok(0);
+    }
+    history[0] = reader.read(F::BITS)?;
+    out[0] = F::from_bits_u64(history[0]);
+
+    let mut stored_leading = 0u32;
+    for (i, slot) in out.iter_mut().enumerate().skip(1) {
+        let record_offset = reader.bit_pos() / 8;
+        let bits = match reader.read(2)? {
+            flag @ (0b00 | 0b01) => {
+                let index = reader.read(INDEX_BITS)? as usize;
+                if index >= i {
+                    return Err(ZigZagError::InvalidData { offset: record_offset });
+                }
+                let reference = history[index];
+                if flag == 0b00 {
+                    reference
+                } else {
+                    let leading = LEADING_TABLE[reader.read(3)? as usize];
+                    let significant = reader.read(length_bits)? as u32;
+                    if significant == 0 || leading + significant > F::BITS {
+                        return Err(ZigZagError::InvalidData { offset: record_offset });
+                    }
+                    let trailing = F::BITS - leading - significant;
+                    reference ^ (reader.read(significant)? << trailing)
+                }
+            }
+            flag => {
+                if flag == 0b11 {
+                    stored_leading = LEADING_TABLE[reader.read(3)? as usize];
+                }
+                history[(i - 1) % HISTORY] ^ reader.read(F::BITS - stored_leading)?
+            }
+        };
+
+        history[i % HISTORY] = bits;
+        *slot = F::from_bits_u64(bits);
+    }
+
+    Ok(reader.byte_len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chimp_round_trip_f64() {
+        let mut values = [0f64; 300];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = match i % 5 {
+                0 => 100.25,
+                1 => -0.0,
+                2 => i as f64 * 0.1,
+                3 => f64::NAN,
+                _ => 1e10 - i as f64,
+            };
+        }
+        let mut buf = [0u8; 4096];
+        let len = encode(&values, &mut buf).unwrap();
+
+        let mut decoded = [0f64; 300];
+        assert_eq!(decode(&buf[..len], &mut decoded), Ok(len));
+        for (a, b) in values.iter().zip(decoded.iter()) {
+            assert_eq!(a.to_bits(), b.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_chimp_round_trip_f32() {
+        let values = [1.0f32, 2.5, 1.0, 2.5, -7.125, f32::MAX, 0.0, 1.0];
+        let mut buf = [0u8; 64];
+        let len = encode(&values, &mut buf).unwrap();
+
+        let mut decoded = [0f32; 8];
+        assert_eq!(decode(&buf[..len], &mut decoded), Ok(len));
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_chimp_reuses_older_values() {
+        // Alternating series: every value repeats the one two steps back
+        let mut values = [0f64; 64];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = if i % 2 == 0 { 3.75 } else { -1234.5678 };
+        }
+        let mut buf = [0u8; 256];
+        let len = encode(&values, &mut buf).unwrap();
+        // First value, one full XOR record, then 9 bit back references
+        assert!(len <= (64 + 69 + 62 * 9usize).div_ceil(8));
+
+        let mut decoded = [0f64; 64];
+        decode(&buf[..len], &mut decoded).unwrap();
+        assert_eq!(decoded, values);
+
+        // Reference to a value that was never decoded
+        let corrupt = [0u8, 0, 0, 0, 0, 0, 0, 0, 0b0000_0010, 0];
+        assert_eq!(decode(&corrupt, &mut [0f64; 2]), Err(ZigZagError::InvalidData { offset: 8 }));
+    }
+}
@@ -0,0 +1,195 @@
+//! Gorilla XOR compression for `f32`/`f64` series.
+//!
+//! Implements the value encoding from Facebook's Gorilla time-series database:
+//! every value is XORed with its predecessor and only the meaningful bits of
+//! the result are stored.
+//!
+//! - The first value is stored verbatim.
+//! - A `0` bit means the value repeats the previous one.
+//! - `10` reuses the previous leading/trailing zero window and stores the bits inside it.
+//! - `11` stores a new window (leading zero count, meaningful bit count) followed by the bits.
+//!
+//! The number of values is not stored, callers keep it alongside the encoded bytes.
+//!
+//! ```rust
+//! use zigzag_rs::float::gorilla;
+//!
+//! let values = [21.5f64, 21.5, 21.75, 22.0, 21.75];
+//! let mut buf = [0u8; 64];
+//! let len = gorilla::encode(&values, &mut buf).unwrap();
+//! assert!(len < values.len() * 8);
+//!
+//! let mut decoded = [0f64; 5];
+//! gorilla::decode(&buf[..len], &mut decoded).unwrap();
+//! assert_eq!(decoded, values);
+//! ```
+
+use super::FloatBits;
+use crate::bits::{BitReader, BitWriter};
+use crate::ZigZagError;
+
+/// Width of the leading zero count field and the largest count it can hold
+fn leading_field<F: FloatBits>() -> (u32, u32) {
+    if F::BITS == 64 { (5, 31) } else { (4, 15) }
+}
+
+/// Width of the meaningful bit count field, which stores the count minus one
+fn length_field<F: FloatBits>() -> u32 {
+    F::BITS.trailing_zeros()
+}
+
+/// Encode a series of floating point values
+///
+/// # Arguments
+/// * `values` - Values to compress
+/// * `out` - Output buffer for the compressed bit stream
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode<F: FloatBits>(values: &[F], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let (leading_bits, max_leading) = leading_field::<F>();
+    let length_bits = length_field::<F>();
+    let mut writer = BitWriter::new(out);
+
+    let mut iter = values.iter();
+    let mut prev = match iter.next() {
+        Some(first) => first.to_bits_u64(),
+        None => return Ok(0),
+    };
+    writer.write(prev, F::BITS)?;
+
+    // Window of the previous XOR, `None` until the first non-zero XOR
+    let mut window: Option<(u32, u32)> = None;
+    for value in iter {
+        let bits = value.to_bits_u64();
+        let xor = bits ^ prev;
+        prev = bits;
+
+        if xor == 0 {
+            writer.write_bit(false)?;
+            continue;
+        }
+        writer.write_bit(true)?;
+
+        let leading = (xor.leading_zeros() - (64 - F::BITS)).min(max_leading);
+        let trailing = xor.trailing_zeros();
+        match window {
+            Some((prev_leading, prev_trailing)) if leading >= prev_leading && trailing >= prev_trailing => {
+                writer.write_bit(false)?;
+                writer.write(xor >> prev_trailing, F::BITS - prev_leading - prev_trailing)?;
+            }
+            _ => {
+                let significant = F::BITS - leading - trailing;
+                writer.write_bit(true)?;
+                writer.write(leading as u64, leading_bits)?;
+                writer.write((significant - 1) as u64, length_bits)?;
+                writer.write(xor >> trailing, significant)?;
+                window = Some((leading, trailing));
+            }
+        }
+    }
+
+    Ok(writer.finish())
+}
+
+/// Decode `out.len()` values from a Gorilla bit stream
+///
+/// # Arguments
+/// * `input` - Compressed bit stream produced by [`encode`]
+/// * `out` - Output slice, its length determines how many values are decoded
+///
+/// # Returns
+/// * `Ok(len)` with the number of input bytes consumed
+/// * `Err(ZigZagError::UnexpectedEof)` if the input ends before all values were decoded
+/// * `Err(ZigZagError::InvalidData)` if the input contains an impossible XOR window
+pub fn decode<F: FloatBits>(input: &[u8], out: &mut [F]) -> Result<usize, ZigZagError> {
+    let (leading_bits, _) = leading_field::<F>();
+    let length_bits = length_field::<F>();
+    let mut reader = BitReader::new(input);
+
+    let (first, rest) = match out.split_first_mut() {
+        Some(split) => split,
+        None => return Ok(0),
+    };
+    let mut prev = reader.read(F::BITS)?;
+    *first = F::from_bits_u64(prev);
+
+    let mut window = (0u32, 0u32);
+    for slot in rest.iter_mut() {
+        if reader.read_bit()? {
+            if reader.read_bit()? {
+                let leading = reader.read(leading_bits)? as u32;
+                let significant = reader.read(length_bits)? as u32 + 1;
+                if leading + significant > F::BITS {
+                    return Err(ZigZagError::InvalidData { offset: reader.bit_pos() / 8 });
+                }
+                window = (leading, F::BITS - leading - significant);
+            }
+            let (leading, trailing) = window;
+            let meaningful = reader.read(F::BITS - leading - trailing)?;
+            prev ^= meaningful << trailing;
+        }
+        *slot = F::from_bits_u64(prev);
+    }
+
+    Ok(reader.byte_len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_bitwise_eq<F: FloatBits>(a: &[F], b: &[F]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert_eq!(x.to_bits_u64(), y.to_bits_u64());
+        }
+    }
+
+    #[test]
+    fn test_gorilla_round_trip_f64() {
+        let values = [
+            12.0f64, 12.0, 12.5, 12.25, -0.0, 0.0, f64::NAN, f64::INFINITY,
+            f64::MIN_POSITIVE, f64::MAX, -1e-300, 1234.5678, 1234.5678,
+        ];
+        let mut buf = [0u8; 256];
+        let len = encode(&values, &mut buf).unwrap();
+
+        let mut decoded = [0f64; 13];
+        assert_eq!(decode(&buf[..len], &mut decoded), Ok(len));
+        assert_bitwise_eq(&values, &decoded);
+    }
+
+    #[test]
+    fn test_gorilla_round_trip_f32() {
+        let mut values = [0f32; 100];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = 20.0 + (i % 7) as f32 * 0.25 - (i % 3) as f32;
+        }
+        values[50] = f32::NEG_INFINITY;
+        let mut buf = [0u8; 512];
+        let len = encode(&values, &mut buf).unwrap();
+        assert!(len < values.len() * 4);
+
+        let mut decoded = [0f32; 100];
+        assert_eq!(decode(&buf[..len], &mut decoded), Ok(len));
+        assert_bitwise_eq(&values, &decoded);
+    }
+
+    #[test]
+    fn test_gorilla_constant_series_and_errors() {
+        let values = [1.5f64; 64];
+        let mut buf = [0u8; 32];
+        let len = encode(&values, &mut buf).unwrap();
+        // 64 bits for the first value plus a single bit per repeat
+        assert_eq!(len, (64 + 63usize).div_ceil(8));
+
+        let mut decoded = [0f64; 66];
+        assert!(matches!(decode(&buf[..len], &mut decoded), Err(ZigZagError::UnexpectedEof { .. })));
+
+        let mut small = [0u8; 4];
+        assert!(matches!(encode(&values, &mut small), Err(ZigZagError::BufferTooSmall { .. })));
+        assert_eq!(encode::<f64>(&[], &mut small), Ok(0));
+    }
+}
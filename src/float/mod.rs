@@ -0,0 +1,115 @@
+//! Codecs for floating point values.
+//!
+//! The integer codecs in this crate cover timestamps and counters; the codecs
+//! in this module compress the accompanying floating point measurements
+//! losslessly by working on their raw bit patterns. [`ordered`] maps those
+//! bit patterns to integers that sort like the values, for use with the
+//! integer codecs.
+
+pub mod chimp;
+pub mod gorilla;
+pub mod ordered;
+
+use crate::ZigZagError;
+
+/// Selects the codec used for a column of floating point values
+///
+/// Storing [`FloatCodec::id`] next to the encoded data lets readers pick the
+/// matching decoder with [`FloatCodec::from_id`].
+///
+/// # Example
+/// ```
+/// use zigzag_rs::float::FloatCodec;
+///
+/// let values = [20.5f32, 20.5, 20.75, 21.0];
+/// let mut buf = [0u8; 32];
+/// for codec in [FloatCodec::Gorilla, FloatCodec::Chimp128] {
+///     let len = codec.encode(&values, &mut buf).unwrap();
+///     let mut decoded = [0f32; 4];
+///     FloatCodec::from_id(codec.id()).unwrap().decode(&buf[..len], &mut decoded).unwrap();
+///     assert_eq!(decoded, values);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatCodec {
+    /// Gorilla XOR encoding against the previous value, see [`gorilla`]
+    Gorilla,
+    /// Chimp128 encoding against the best of the last 128 values, see [`chimp`]
+    #[default]
+    Chimp128,
+}
+
+impl FloatCodec {
+    /// Stable identifier of the codec, suitable for storing in headers
+    pub fn id(self) -> u8 {
+        match self {
+            FloatCodec::Gorilla => 0,
+            FloatCodec::Chimp128 => 1,
+        }
+    }
+
+    /// Look up a codec by the identifier returned from [`FloatCodec::id`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(FloatCodec::Gorilla),
+            1 => Some(FloatCodec::Chimp128),
+            _ => None,
+        }
+    }
+
+    /// Encode `values` with this codec, returning the number of bytes written
+    pub fn encode<F: FloatBits>(self, values: &[F], out: &mut [u8]) -> Result<usize, ZigZagError> {
+        match self {
+            FloatCodec::Gorilla => gorilla::encode(values, out),
+            FloatCodec::Chimp128 => chimp::encode(values, out),
+        }
+    }
+
+    /// Decode `out.len()` values with this codec, returning the number of bytes consumed
+    pub fn decode<F: FloatBits>(self, input: &[u8], out: &mut [F]) -> Result<usize, ZigZagError> {
+        match self {
+            FloatCodec::Gorilla => gorilla::decode(input, out),
+            FloatCodec::Chimp128 => chimp::decode(input, out),
+        }
+    }
+}
+
+/// Floating point types whose bit patterns can be processed by the float codecs
+pub trait FloatBits: Copy {
+    /// Number of bits in the floating point representation
+    const BITS: u32;
+
+    /// Raw bit pattern, zero-extended to 64 bits
+    fn to_bits_u64(self) -> u64;
+
+    /// Rebuild a value from a raw bit pattern produced by [`FloatBits::to_bits_u64`]
+    fn from_bits_u64(bits: u64) -> Self;
+}
+
+impl FloatBits for f32 {
+    const BITS: u32 = 32;
+
+    #[inline]
+    fn to_bits_u64(self) -> u64 {
+        self.to_bits() as u64
+    }
+
+    #[inline]
+    fn from_bits_u64(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl FloatBits for f64 {
+    const BITS: u32 = 64;
+
+    #[inline]
+    fn to_bits_u64(self) -> u64 {
+        self.to_bits()
+    }
+
+    #[inline]
+    fn from_bits_u64(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
@@ -0,0 +1,167 @@
+//! Order-preserving mapping of floating point bit patterns.
+//!
+//! The raw bits of a float sort negative values backwards and after the
+//! positive ones. [`FloatZigZag`] maps them to unsigned integers whose order
+//! matches the order of the floats: positive values get their sign bit set,
+//! negative values have all bits flipped. Neighbouring values map to
+//! neighbouring integers, so differences between them are small and suit
+//! delta and XOR coding, and the mapped values can be compared, sorted or
+//! range-checked as integers.
+//!
+//! ```text
+//! -inf < -1.0 < -0.0 < +0.0 < 1.0 < +inf
+//!  maps to strictly increasing integers; NaNs sort beyond the infinities
+//! ```
+//!
+//! ```rust
+//! use zigzag_rs::float::ordered::{ordered_decode_iter, FloatZigZag};
+//!
+//! let values = [-2.5f32, -0.0, 0.0, 1.0, f32::INFINITY];
+//! let mut mapped = [0u32; 5];
+//! f32::ordered_encode_slice(&values, &mut mapped).unwrap();
+//! assert!(mapped.windows(2).all(|pair| pair[0] < pair[1]));
+//!
+//! let decoded: Vec<f32> = ordered_decode_iter::<f32, _>(&mapped).collect();
+//! assert_eq!(decoded, values);
+//! ```
+
+use core::borrow::Borrow;
+
+use crate::ZigZagError;
+
+/// Floating point types with an order-preserving mapping to unsigned integers
+pub trait FloatZigZag: Copy {
+    /// Unsigned integer of the same width
+    type UInt: Copy;
+
+    /// Map a value to an unsigned integer ordered like the value
+    fn ordered_encode(value: Self) -> Self::UInt;
+
+    /// Invert [`FloatZigZag::ordered_encode`], restoring the exact bit pattern
+    fn ordered_decode(value: Self::UInt) -> Self;
+
+    /// Map every value of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were mapped
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    fn ordered_encode_slice(values: &[Self], out: &mut [Self::UInt]) -> Result<(), ZigZagError> {
+        if out.len() < values.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: values.len(), actual: out.len() });
+        }
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::ordered_encode(value);
+        }
+        Ok(())
+    }
+
+    /// Restore every value of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were restored
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    fn ordered_decode_slice(values: &[Self::UInt], out: &mut [Self]) -> Result<(), ZigZagError> {
+        if out.len() < values.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: values.len(), actual: out.len() });
+        }
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::ordered_decode(value);
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_float_zigzag {
+    ($float:ty, $unsigned:ty) => {
+        impl FloatZigZag for $float {
+            type UInt = $unsigned;
+
+            #[inline]
+            fn ordered_encode(value: Self) -> Self::UInt {
+                const SIGN: $unsigned = 1 << (<$unsigned>::BITS - 1);
+                let bits = value.to_bits();
+                if bits & SIGN != 0 { !bits } else { bits | SIGN }
+            }
+
+            #[inline]
+            fn ordered_decode(value: Self::UInt) -> Self {
+                const SIGN: $unsigned = 1 << (<$unsigned>::BITS - 1);
+                <$float>::from_bits(if value & SIGN != 0 { value ^ SIGN } else { !value })
+            }
+        }
+    };
+}
+
+impl_float_zigzag!(f32, u32);
+impl_float_zigzag!(f64, u64);
+
+/// Map each value of an iterator on the fly
+///
+/// # Arguments
+/// * `iter` - Anything iterable over floats or references to them
+pub fn ordered_encode_iter<F, I>(iter: I) -> impl Iterator<Item = F::UInt>
+where
+    F: FloatZigZag,
+    I: IntoIterator,
+    I::Item: Borrow<F>,
+{
+    iter.into_iter().map(|value| F::ordered_encode(*value.borrow()))
+}
+
+/// Restore each value of an iterator on the fly
+///
+/// # Arguments
+/// * `iter` - Anything iterable over mapped integers or references to them
+pub fn ordered_decode_iter<F, I>(iter: I) -> impl Iterator<Item = F>
+where
+    F: FloatZigZag,
+    I: IntoIterator,
+    I::Item: Borrow<F::UInt>,
+{
+    iter.into_iter().map(|value| F::ordered_decode(*value.borrow()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_ordered_mapping_preserves_order() {
+        let values = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1.0,
+            -f64::MIN_POSITIVE,
+            -0.0,
+            0.0,
+            f64::from_bits(1),
+            1.0,
+            1.0 + f64::EPSILON,
+            f64::MAX,
+            f64::INFINITY,
+        ];
+        let mapped: Vec<u64> = ordered_encode_iter::<f64, _>(&values).collect();
+        assert!(mapped.windows(2).all(|pair| pair[0] < pair[1]));
+        // Adjacent floats map to adjacent integers
+        assert_eq!(mapped[7] + 1, mapped[8]);
+        assert_eq!(mapped[4] + 1, mapped[5]);
+
+        let mut decoded = [0f64; 11];
+        f64::ordered_decode_slice(&mapped, &mut decoded).unwrap();
+        assert!(decoded.iter().zip(&values).all(|(a, b)| a.to_bits() == b.to_bits()));
+    }
+
+    #[test]
+    fn test_ordered_mapping_round_trips_bits() {
+        for bits in [0u32, 1, 0x7f80_0000, 0x7fc0_0001, 0x8000_0000, 0xffc0_0000, u32::MAX] {
+            let value = f32::from_bits(bits);
+            assert_eq!(f32::ordered_decode(f32::ordered_encode(value)).to_bits(), bits);
+        }
+        assert_eq!(f32::ordered_encode(0.0), 0x8000_0000);
+        assert_eq!(
+            f32::ordered_encode_slice(&[1.0, 2.0], &mut [0u32; 1]),
+            Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 })
+        );
+    }
+}
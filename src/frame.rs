@@ -0,0 +1,445 @@
+//! CRC-protected record framing for lossy byte-stream links such as UART.
+//!
+//! Each frame carries a payload of ZigZag varints and has the following layout:
+//!
+//! ```text
+//! +------+------+-------------+-----------------+-------------+
+//! | 0xA5 | 0x5A | len (u16 LE) | payload (len B) | crc (u16 LE) |
+//! +------+------+-------------+-----------------+-------------+
+//! ```
+//!
+//! The CRC (CRC-16/CCITT-FALSE) covers the length field and the payload. When a
+//! frame is corrupted, [`FrameScanner`] discards bytes up to the next sync marker
+//! and continues with the following frame.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::frame::{self, FrameScanner};
+//!
+//! let mut link = [0u8; 64];
+//! let first = frame::encode(&[-1i32, 0, 1], &mut link).unwrap();
+//! let second = frame::encode(&[100i32, -100], &mut link[first..]).unwrap();
+//!
+//! // Corrupt the payload of the first frame
+//! link[4] ^= 0xff;
+//!
+//! let mut scanner = FrameScanner::new(&link[..first + second]);
+//! let frame = scanner.next().unwrap();
+//! let mut values = [0i32; 2];
+//! assert_eq!(frame.decode_values(&mut values), Ok(2));
+//! assert_eq!(values, [100, -100]);
+//! assert!(scanner.next().is_none());
+//! # }
+//! ```
+//!
+//! For links that delimit packets with zero bytes, [`encode_cobs`] stuffs the
+//! frame with [COBS](crate::cobs) and appends the delimiter:
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::frame;
+//!
+//! let mut link = [0u8; 64];
+//! let first = frame::encode_cobs(&[-1i32, 0, 1], &mut link).unwrap();
+//! let second = frame::encode_cobs(&[1000i32], &mut link[first..]).unwrap();
+//!
+//! let mut packets = link[..first + second].split_mut(|&byte| byte == 0);
+//! let frame = frame::decode_cobs(packets.next().unwrap()).unwrap();
+//! let mut values = [0i32; 3];
+//! assert_eq!(frame.decode_values(&mut values), Ok(3));
+//! assert_eq!(values, [-1, 0, 1]);
+//! # }
+//! ```
+
+use crate::cobs;
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// Sync marker that starts every frame
+pub const SYNC: [u8; 2] = [0xA5, 0x5A];
+
+/// Number of bytes a frame adds around its payload
+pub const OVERHEAD: usize = SYNC.len() + 2 + 2;
+
+/// Largest payload accepted by the decoder
+///
+/// Length fields above this limit are treated as corruption so that a damaged
+/// header cannot stall the receiver waiting for a huge frame.
+pub const MAX_PAYLOAD_LEN: usize = 4096;
+
+/// A decoded frame borrowing its payload from the input buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    payload: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Raw payload bytes
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Decode the ZigZag varints in the payload into `out`
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of values decoded
+    /// * `Err(ZigZagError)` if `out` is too small or the payload is malformed
+    pub fn decode_values<T>(&self, out: &mut [T]) -> Result<usize, ZigZagError>
+    where
+        T: ZigZag,
+        T::UInt: Varint,
+    {
+        varint::decode_slice(self.payload, out)
+    }
+}
+
+/// Compute the CRC-16/CCITT-FALSE checksum of `data`
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Encode `values` as a single frame
+///
+/// # Arguments
+/// * `values` - Signed integers to place in the payload
+/// * `out` - Output buffer, the frame is written to its start
+///
+/// # Returns
+/// * `Ok(len)` with the total frame length in bytes
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold the frame or the
+///   payload would exceed [`MAX_PAYLOAD_LEN`]
+pub fn encode<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let payload_len = varint::encoded_len(values);
+    if payload_len > MAX_PAYLOAD_LEN {
+        return Err(ZigZagError::BufferTooSmall { needed: payload_len, actual: MAX_PAYLOAD_LEN });
+    }
+    let frame_len = payload_len + OVERHEAD;
+    if out.len() < frame_len {
+        return Err(ZigZagError::BufferTooSmall { needed: frame_len, actual: out.len() });
+    }
+
+    out[..2].copy_from_slice(&SYNC);
+    out[2..4].copy_from_slice(&(payload_len as u16).to_le_bytes());
+    varint::encode_slice(values, &mut out[4..4 + payload_len])?;
+    let crc = crc16(&out[2..4 + payload_len]);
+    out[4 + payload_len..frame_len].copy_from_slice(&crc.to_le_bytes());
+    Ok(frame_len)
+}
+
+/// Encode `values` as a frame, COBS-encode it and append the zero delimiter
+///
+/// The result contains no other zero bytes and can be written to the link
+/// as is. Receivers split the stream at zeros and pass each packet to
+/// [`decode_cobs`].
+///
+/// # Arguments
+/// * `values` - Signed integers to place in the payload
+/// * `out` - Output buffer, the packet is written to its start
+///
+/// # Returns
+/// * `Ok(len)` with the packet length in bytes, including the delimiter
+/// * `Err(ZigZagError::BufferTooSmall)` as for [`encode`]. If `out` cannot even
+///   hold the unstuffed frame, `needed` is the worst case for its length.
+pub fn encode_cobs<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let frame_len = varint::encoded_len(values) + OVERHEAD;
+    if out.len() < frame_len {
+        let needed = cobs::max_encoded_len(frame_len) + 1;
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    // Build the frame at the end of the buffer, then stuff it towards the start
+    let tail = out.len() - frame_len;
+    encode(values, &mut out[tail..])?;
+    let stuffed_len = cobs::encoded_len(&out[tail..]);
+    let overhead = stuffed_len - frame_len;
+    if out.len() < stuffed_len + 1 {
+        return Err(ZigZagError::BufferTooSmall { needed: stuffed_len + 1, actual: out.len() });
+    }
+    out.copy_within(tail.., overhead);
+    cobs::encode_within(&mut out[..stuffed_len], overhead);
+    out[stuffed_len] = cobs::DELIMITER;
+    Ok(stuffed_len + 1)
+}
+
+/// Decode a COBS packet written by [`encode_cobs`] in place
+///
+/// A trailing delimiter is ignored. The packet is unstuffed into the start of
+/// `packet` and the returned frame borrows from it.
+///
+/// # Returns
+/// * `Ok(frame)` with the decoded frame
+/// * `Err(ZigZagError::InvalidData)` if the stuffing is corrupted or bytes follow the frame
+/// * the errors of [`decode`] otherwise
+pub fn decode_cobs(packet: &mut [u8]) -> Result<Frame<'_>, ZigZagError> {
+    let end = packet.len() - usize::from(packet.last() == Some(&cobs::DELIMITER));
+    let packet = &mut packet[..end];
+    let len = cobs::decode_in_place(packet)?;
+    let (frame, frame_len) = decode(&packet[..len])?;
+    if frame_len != len {
+        return Err(ZigZagError::InvalidData { offset: frame_len });
+    }
+    Ok(frame)
+}
+
+/// Decode the frame at the start of `input`
+///
+/// # Returns
+/// * `Ok((frame, len))` with the frame and the number of bytes it occupies
+/// * `Err(ZigZagError::InvalidSync)` if `input` does not start with [`SYNC`] or
+///   the length field exceeds [`MAX_PAYLOAD_LEN`]
+/// * `Err(ZigZagError::UnexpectedEof)` if `input` ends before the frame is complete
+/// * `Err(ZigZagError::ChecksumMismatch)` if the frame is corrupted
+pub fn decode(input: &[u8]) -> Result<(Frame<'_>, usize), ZigZagError> {
+    if input.len() < 4 {
+        if !SYNC.starts_with(&input[..input.len().min(2)]) {
+            return Err(ZigZagError::InvalidSync { offset: 0 });
+        }
+        return Err(ZigZagError::UnexpectedEof { offset: 0 });
+    }
+    if input[..2] != SYNC {
+        return Err(ZigZagError::InvalidSync { offset: 0 });
+    }
+
+    let payload_len = u16::from_le_bytes([input[2], input[3]]) as usize;
+    if payload_len > MAX_PAYLOAD_LEN {
+        return Err(ZigZagError::InvalidSync { offset: 0 });
+    }
+    let frame_len = payload_len + OVERHEAD;
+    if input.len() < frame_len {
+        return Err(ZigZagError::UnexpectedEof { offset: 0 });
+    }
+
+    let expected = u16::from_le_bytes([input[frame_len - 2], input[frame_len - 1]]);
+    let actual = crc16(&input[2..4 + payload_len]);
+    if expected != actual {
+        return Err(ZigZagError::ChecksumMismatch { expected, actual });
+    }
+
+    Ok((Frame { payload: &input[4..4 + payload_len] }, frame_len))
+}
+
+/// Receiver that extracts valid frames from a byte buffer, resynchronizing after corruption
+///
+/// The scanner yields every intact frame in order. Bytes that do not belong to
+/// a valid frame are skipped by searching for the next sync marker. A frame
+/// whose length field runs past the end of the buffer is skipped the same way
+/// if a valid frame follows within those bytes. Otherwise it is taken as an
+/// incomplete frame at the end of the buffer, which stops the iteration; its bytes are
+/// reported by [`FrameScanner::remaining`] so streaming receivers can keep them
+/// and retry once more data arrived.
+#[derive(Debug, Clone)]
+pub struct FrameScanner<'a> {
+    input: &'a [u8],
+    pos: usize,
+    skipped: usize,
+}
+
+impl<'a> FrameScanner<'a> {
+    /// Create a scanner over `input`
+    pub fn new(input: &'a [u8]) -> Self {
+        FrameScanner { input, pos: 0, skipped: 0 }
+    }
+
+    /// Number of bytes consumed so far, including skipped bytes
+    pub fn consumed(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bytes discarded while resynchronizing
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Bytes that have not been consumed yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.pos += count;
+        self.skipped += count;
+    }
+}
+
+impl<'a> Iterator for FrameScanner<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Frame<'a>> {
+        while self.pos < self.input.len() {
+            let rest = &self.input[self.pos..];
+            match decode(rest) {
+                Ok((frame, len)) => {
+                    self.pos += len;
+                    return Some(frame);
+                }
+                Err(ZigZagError::UnexpectedEof { .. }) => {
+                    // A corrupted length field can claim more bytes than the buffer
+                    // holds. If a valid frame starts within them, the length was
+                    // wrong; otherwise the frame is incomplete and kept for later.
+                    let next_frame = (1..rest.len())
+                        .find(|&i| rest[i..].starts_with(&SYNC) && decode(&rest[i..]).is_ok());
+                    match next_frame {
+                        Some(offset) => self.skip(offset),
+                        None => return None,
+                    }
+                }
+                Err(_) => {
+                    // Resume the search one byte after the current position so a
+                    // sync marker hidden inside a corrupted frame is not missed
+                    let next_sync = rest[1..]
+                        .windows(2)
+                        .position(|window| window == SYNC)
+                        .map(|i| i + 1);
+                    match next_sync {
+                        Some(offset) => self.skip(offset),
+                        None => {
+                            // Keep a trailing first sync byte, it may start the next frame
+                            let keep = usize::from(rest.last() == Some(&SYNC[0]) && rest.len() > 1);
+                            self.skip(rest.len() - keep);
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_frame_round_trip() {
+        let values = [-1000i64, -1, 0, 1, 1000];
+        let mut buf = [0u8; 32];
+        let len = encode(&values, &mut buf).unwrap();
+        assert_eq!(len, OVERHEAD + varint::encoded_len(&values));
+
+        let (frame, frame_len) = decode(&buf[..len]).unwrap();
+        assert_eq!(frame_len, len);
+        let mut decoded = [0i64; 5];
+        assert_eq!(frame.decode_values(&mut decoded), Ok(5));
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_crc16_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_frame_errors() {
+        let mut buf = [0u8; 32];
+        let len = encode(&[1i32, 2, 3], &mut buf).unwrap();
+
+        assert_eq!(decode(&buf[..len - 1]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert_eq!(decode(&buf[1..len]), Err(ZigZagError::InvalidSync { offset: 0 }));
+
+        buf[5] ^= 0x01;
+        assert!(matches!(decode(&buf[..len]), Err(ZigZagError::ChecksumMismatch { .. })));
+
+        let mut small = [0u8; 4];
+        assert_eq!(
+            encode(&[1i32, 2, 3], &mut small),
+            Err(ZigZagError::BufferTooSmall { needed: len, actual: 4 })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_cobs_frame_round_trip() {
+        let values = [0i64, 0, 0, -1, i64::MIN];
+        let mut packet = [0u8; 32];
+        let len = encode_cobs(&values, &mut packet).unwrap();
+        assert_eq!(packet[len - 1], cobs::DELIMITER);
+        assert!(!packet[..len - 1].contains(&cobs::DELIMITER));
+
+        let mut plain = [0u8; 32];
+        let plain_len = encode(&values, &mut plain).unwrap();
+        assert_eq!(len, cobs::encoded_len(&plain[..plain_len]) + 1);
+
+        let frame = decode_cobs(&mut packet[..len]).unwrap();
+        let mut decoded = [0i64; 5];
+        assert_eq!(frame.decode_values(&mut decoded), Ok(5));
+        assert_eq!(decoded, values);
+
+        let mut small = [0u8; 20];
+        assert_eq!(
+            encode_cobs(&values, &mut small),
+            Err(ZigZagError::BufferTooSmall { needed: len, actual: 20 })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_scanner_resynchronizes() {
+        let mut link = [0u8; 64];
+        let mut pos = 0;
+        // Leading garbage, including a stray first sync byte
+        link[..3].copy_from_slice(&[0x00, 0xA5, 0x13]);
+        pos += 3;
+        let first = encode(&[1i16, 2], &mut link[pos..]).unwrap();
+        let first_start = pos;
+        pos += first;
+        let second = encode(&[-3i16], &mut link[pos..]).unwrap();
+        pos += second;
+        // Truncated third frame at the end of the buffer
+        let third_start = pos;
+        pos += encode(&[4i16], &mut link[pos..]).unwrap() - 1;
+
+        // Corrupt the crc of the first frame
+        link[first_start + first - 1] ^= 0xff;
+
+        let mut scanner = FrameScanner::new(&link[..pos]);
+        let frame = scanner.next().unwrap();
+        let mut values = [0i16; 1];
+        assert_eq!(frame.decode_values(&mut values), Ok(1));
+        assert_eq!(values, [-3]);
+        assert!(scanner.next().is_none());
+        assert_eq!(scanner.skipped(), 3 + first);
+        assert_eq!(scanner.consumed(), third_start);
+        assert_eq!(scanner.remaining(), &link[third_start..pos]);
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_scanner_skips_corrupted_length() {
+        let mut link = [0u8; 64];
+        let first = encode(&[1i16, 2], &mut link).unwrap();
+        let second = encode(&[-3i16], &mut link[first..]).unwrap();
+        let third_start = first + second;
+        let pos = third_start + encode(&[4i16], &mut link[third_start..]).unwrap() - 1;
+
+        // A bit flip in the high length byte makes the first frame claim 258 bytes
+        link[3] ^= 0x01;
+        assert_eq!(decode(&link[..pos]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+
+        let mut scanner = FrameScanner::new(&link[..pos]);
+        let mut values = [0i16; 1];
+        assert_eq!(scanner.next().unwrap().decode_values(&mut values), Ok(1));
+        assert_eq!(values, [-3]);
+        // The truncated last frame is still kept for more data
+        assert!(scanner.next().is_none());
+        assert_eq!(scanner.skipped(), first);
+        assert_eq!(scanner.remaining(), &link[third_start..pos]);
+    }
+}
@@ -0,0 +1,411 @@
+//! WebGraph-style adjacency list compression.
+//!
+//! Each node's sorted neighbor list is stored as one record. A record can
+//! name an earlier node as its reference. Neighbors shared with the
+//! reference's list are then described by copy blocks: alternating run
+//! lengths of copied and skipped reference entries. The run after the last
+//! stored block is implied by parity, so it is copied if an even number of
+//! blocks is stored. The remaining neighbors are residuals:
+//!
+//! - The first residual is the ZigZag varint of its distance from the node
+//!   itself. This keeps it small for the local edges typical of real graphs.
+//! - Each later residual is the varint of its gap minus one.
+//!
+//! ```text
+//! record: degree (varint) | reference distance (varint, 0 = none)
+//!         | [block count (varint) | block length (varint)*]
+//!         | residual count (varint) | residual*
+//! ```
+//!
+//! ```rust
+//! use zigzag_rs::graph::{self, NodeRecord, Reference};
+//!
+//! let mut buf = [0u8; 64];
+//! let len = graph::encode_node(10, &[3, 9, 11, 12, 40], None, &mut buf).unwrap();
+//! let (record, _) = NodeRecord::parse(10, &buf[..len]).unwrap();
+//! let neighbors: Vec<u64> = record.neighbors(&[]).unwrap().collect();
+//! assert_eq!(neighbors, [3, 9, 11, 12, 40]);
+//!
+//! // Node 11 shares most neighbors with node 10
+//! let reference = Reference { distance: 1, neighbors: &[3, 9, 11, 12, 40] };
+//! let len = graph::encode_node(11, &[3, 9, 12, 40, 41], Some(reference), &mut buf).unwrap();
+//! let (record, _) = NodeRecord::parse(11, &buf[..len]).unwrap();
+//! assert_eq!(record.reference(), Some(10));
+//! let neighbors: Vec<u64> = record.neighbors(&[3, 9, 11, 12, 40]).unwrap().collect();
+//! assert_eq!(neighbors, [3, 9, 12, 40, 41]);
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// An earlier node whose neighbor list a record copies from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference<'r> {
+    /// Distance from the encoded node back to the reference node, at least 1
+    pub distance: u64,
+    /// Sorted neighbors of the reference node
+    pub neighbors: &'r [u64],
+}
+
+/// Byte sink that only counts when there is no output buffer
+struct Sink<'a> {
+    out: Option<&'a mut [u8]>,
+    len: usize,
+}
+
+impl Sink<'_> {
+    fn varint(&mut self, value: u64) {
+        if let Some(out) = self.out.as_deref_mut() {
+            // The caller checked the total length up front
+            let _ = value.encode_varint(&mut out[self.len..]);
+        }
+        self.len += value.varint_len();
+    }
+}
+
+/// Call `f` with the lengths of the copy blocks of `neighbors` against `reference`,
+/// leaving out the last block, which the decoder infers
+fn copy_blocks(neighbors: &[u64], reference: &[u64], mut f: impl FnMut(u64)) {
+    let mut copying = true;
+    let mut run = 0;
+    let mut i = 0;
+    for &candidate in reference {
+        while i < neighbors.len() && neighbors[i] < candidate {
+            i += 1;
+        }
+        let copied = i < neighbors.len() && neighbors[i] == candidate;
+        if copied != copying {
+            f(run);
+            copying = copied;
+            run = 0;
+        }
+        run += 1;
+    }
+}
+
+/// Neighbors that are not in the sorted `reference` list
+fn residuals<'n>(neighbors: &'n [u64], reference: &'n [u64]) -> impl Iterator<Item = u64> + 'n {
+    let mut j = 0;
+    neighbors.iter().copied().filter(move |&neighbor| {
+        while j < reference.len() && reference[j] < neighbor {
+            j += 1;
+        }
+        j == reference.len() || reference[j] != neighbor
+    })
+}
+
+fn write_node(node: u64, neighbors: &[u64], reference: Option<Reference<'_>>, sink: &mut Sink<'_>) {
+    let reference = reference.filter(|reference| reference.distance > 0);
+    sink.varint(neighbors.len() as u64);
+    sink.varint(reference.map_or(0, |reference| reference.distance));
+
+    let reference_neighbors = reference.map_or(&[][..], |reference| reference.neighbors);
+    if reference.is_some() {
+        let mut blocks = 0;
+        copy_blocks(neighbors, reference_neighbors, |_| blocks += 1);
+        sink.varint(blocks);
+        copy_blocks(neighbors, reference_neighbors, |len| sink.varint(len));
+    }
+
+    sink.varint(residuals(neighbors, reference_neighbors).count() as u64);
+    let mut prev = None;
+    for neighbor in residuals(neighbors, reference_neighbors) {
+        match prev {
+            None => sink.varint(i64::zigzag_encode(neighbor.wrapping_sub(node) as i64)),
+            Some(prev) => sink.varint(neighbor - prev - 1),
+        }
+        prev = Some(neighbor);
+    }
+}
+
+/// Number of bytes [`encode_node`] writes for a record
+pub fn encoded_node_len(node: u64, neighbors: &[u64], reference: Option<Reference<'_>>) -> usize {
+    let mut sink = Sink { out: None, len: 0 };
+    write_node(node, neighbors, reference, &mut sink);
+    sink.len
+}
+
+/// Encode the adjacency record of one node
+///
+/// # Arguments
+/// * `node` - Id of the node
+/// * `neighbors` - Strictly increasing neighbor ids
+/// * `reference` - Optional earlier node to copy neighbors from
+/// * `out` - Output buffer for the record
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::InvalidData)` with the index of the first neighbor that is not in increasing order
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode_node(node: u64, neighbors: &[u64], reference: Option<Reference<'_>>, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if let Some(index) = neighbors.windows(2).position(|pair| pair[0] >= pair[1]) {
+        return Err(ZigZagError::InvalidData { offset: index + 1 });
+    }
+    let needed = encoded_node_len(node, neighbors, reference);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let mut sink = Sink { out: Some(out), len: 0 };
+    write_node(node, neighbors, reference, &mut sink);
+    Ok(sink.len)
+}
+
+/// A parsed adjacency record
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRecord<'a> {
+    node: u64,
+    input: &'a [u8],
+    degree: usize,
+    distance: u64,
+    block_count: usize,
+    blocks_start: usize,
+    residual_count: usize,
+    residuals_start: usize,
+}
+
+fn read_varint(input: &[u8], pos: &mut usize) -> Result<u64, ZigZagError> {
+    let (value, len) = u64::decode_varint(&input[*pos..]).map_err(|err| varint::at_offset(err, *pos))?;
+    *pos += len;
+    Ok(value)
+}
+
+impl<'a> NodeRecord<'a> {
+    /// Parse the record of `node` at the start of `input`
+    ///
+    /// # Returns
+    /// * `Ok((record, len))` with the record and its length in bytes
+    /// * `Err(ZigZagError)` with the byte offset of the problem if the record is malformed
+    pub fn parse(node: u64, input: &'a [u8]) -> Result<(Self, usize), ZigZagError> {
+        let mut pos = 0;
+        let degree = read_varint(input, &mut pos)? as usize;
+        let distance_offset = pos;
+        let distance = read_varint(input, &mut pos)?;
+        if distance > node {
+            return Err(ZigZagError::InvalidData { offset: distance_offset });
+        }
+
+        let (mut block_count, mut blocks_start) = (0, pos);
+        if distance > 0 {
+            block_count = read_varint(input, &mut pos)? as usize;
+            blocks_start = pos;
+            for _ in 0..block_count {
+                read_varint(input, &mut pos)?;
+            }
+        }
+
+        let count_offset = pos;
+        let residual_count = read_varint(input, &mut pos)? as usize;
+        if residual_count > degree {
+            return Err(ZigZagError::InvalidData { offset: count_offset });
+        }
+        let residuals_start = pos;
+        for _ in 0..residual_count {
+            read_varint(input, &mut pos)?;
+        }
+
+        let record = NodeRecord { node, input, degree, distance, block_count, blocks_start, residual_count, residuals_start };
+        Ok((record, pos))
+    }
+
+    /// Number of neighbors
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Id of the reference node, if the record copies from one
+    pub fn reference(&self) -> Option<u64> {
+        (self.distance > 0).then(|| self.node - self.distance)
+    }
+
+    /// Iterate lazily over the neighbors in increasing order
+    ///
+    /// # Arguments
+    /// * `reference_neighbors` - Neighbors of [`NodeRecord::reference`], ignored if there is no reference
+    ///
+    /// # Returns
+    /// * `Ok(iter)` with the neighbor iterator
+    /// * `Err(ZigZagError::InvalidData)` if the copy blocks don't match `reference_neighbors`
+    pub fn neighbors<'r>(&self, reference_neighbors: &'r [u64]) -> Result<Neighbors<'a, 'r>, ZigZagError> {
+        let reference = if self.distance > 0 { reference_neighbors } else { &[] };
+
+        // Count the copied entries to check the blocks against the reference list
+        let mut pos = self.blocks_start;
+        let (mut copied, mut covered) = (0, 0);
+        for i in 0..self.block_count {
+            let len = read_varint(self.input, &mut pos)? as usize;
+            covered += len;
+            if i % 2 == 0 {
+                copied += len;
+            }
+        }
+        if covered > reference.len() {
+            return Err(ZigZagError::InvalidData { offset: self.blocks_start });
+        }
+        if self.block_count.is_multiple_of(2) {
+            copied += reference.len() - covered;
+        }
+        if copied + self.residual_count != self.degree {
+            return Err(ZigZagError::InvalidData { offset: 0 });
+        }
+
+        let mut neighbors = Neighbors {
+            node: self.node,
+            input: self.input,
+            reference,
+            reference_pos: 0,
+            copying: false,
+            run_left: 0,
+            blocks_left: self.block_count,
+            block_pos: self.blocks_start,
+            residual_pos: self.residuals_start,
+            residuals_left: self.residual_count,
+            prev_residual: None,
+            next_copied: None,
+            next_residual: None,
+        };
+        neighbors.next_copied = neighbors.advance_copied();
+        neighbors.next_residual = neighbors.advance_residual();
+        Ok(neighbors)
+    }
+}
+
+/// Lazy iterator over the neighbors of a [`NodeRecord`]
+#[derive(Debug, Clone)]
+pub struct Neighbors<'a, 'r> {
+    node: u64,
+    input: &'a [u8],
+    reference: &'r [u64],
+    reference_pos: usize,
+    copying: bool,
+    run_left: usize,
+    blocks_left: usize,
+    block_pos: usize,
+    residual_pos: usize,
+    residuals_left: usize,
+    prev_residual: Option<u64>,
+    next_copied: Option<u64>,
+    next_residual: Option<u64>,
+}
+
+impl Neighbors<'_, '_> {
+    fn advance_copied(&mut self) -> Option<u64> {
+        while self.reference_pos < self.reference.len() {
+            if self.run_left == 0 {
+                if self.blocks_left > 0 {
+                    // Validated by NodeRecord::neighbors
+                    self.run_left = read_varint(self.input, &mut self.block_pos).unwrap_or(0) as usize;
+                    self.blocks_left -= 1;
+                } else {
+                    self.run_left = self.reference.len() - self.reference_pos;
+                }
+                // Blocks alternate, starting with a copy block
+                self.copying = !self.copying;
+                if self.run_left == 0 {
+                    continue;
+                }
+            }
+            let candidate = self.reference[self.reference_pos];
+            self.reference_pos += 1;
+            self.run_left -= 1;
+            if self.copying {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn advance_residual(&mut self) -> Option<u64> {
+        if self.residuals_left == 0 {
+            return None;
+        }
+        self.residuals_left -= 1;
+        // Validated by NodeRecord::parse
+        let value = read_varint(self.input, &mut self.residual_pos).unwrap_or(0);
+        let residual = match self.prev_residual {
+            None => self.node.wrapping_add(i64::zigzag_decode(value) as u64),
+            Some(prev) => prev.wrapping_add(value).wrapping_add(1),
+        };
+        self.prev_residual = Some(residual);
+        Some(residual)
+    }
+}
+
+impl Iterator for Neighbors<'_, '_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        match (self.next_copied, self.next_residual) {
+            (Some(copied), Some(residual)) if copied < residual => {
+                self.next_copied = self.advance_copied();
+                Some(copied)
+            }
+            (Some(copied), None) => {
+                self.next_copied = self.advance_copied();
+                Some(copied)
+            }
+            (_, Some(residual)) => {
+                self.next_residual = self.advance_residual();
+                Some(residual)
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn round_trip(node: u64, neighbors: &[u64], reference: Option<Reference<'_>>) -> usize {
+        let mut buf = [0u8; 256];
+        let len = encode_node(node, neighbors, reference, &mut buf).unwrap();
+        assert_eq!(len, encoded_node_len(node, neighbors, reference));
+        let (record, parsed_len) = NodeRecord::parse(node, &buf[..len]).unwrap();
+        assert_eq!(parsed_len, len);
+        assert_eq!(record.degree(), neighbors.len());
+        let reference_neighbors = reference.map_or(&[][..], |reference| reference.neighbors);
+        let decoded: Vec<u64> = record.neighbors(reference_neighbors).unwrap().collect();
+        assert_eq!(decoded, neighbors);
+        len
+    }
+
+    #[test]
+    fn test_graph_residuals() {
+        // Neighbors below the node give a negative first residual
+        assert_eq!(round_trip(1000, &[998, 999, 1001, 1005], None), 1 + 1 + 1 + 4);
+        round_trip(0, &[], None);
+        round_trip(5, &[0, u64::MAX], None);
+    }
+
+    #[test]
+    fn test_graph_copy_blocks() {
+        let reference = [1, 2, 3, 10, 11, 12, 20, 30];
+        // Copy, skip and copy runs, with the trailing run implied
+        for neighbors in [&[1, 2, 3, 20, 30][..], &[2, 3, 11, 12, 15], &[1, 2, 3, 10, 11, 12, 20, 30], &[4, 5], &[30, 31]] {
+            round_trip(50, neighbors, Some(Reference { distance: 3, neighbors: &reference }));
+        }
+
+        // Copying shrinks a record that repeats its reference
+        let neighbors: Vec<u64> = (100..160).map(|i| i * 7).collect();
+        let plain = round_trip(200, &neighbors, None);
+        let copied = round_trip(200, &neighbors, Some(Reference { distance: 1, neighbors: &neighbors }));
+        assert!(copied < plain / 10);
+    }
+
+    #[test]
+    fn test_graph_errors() {
+        let mut buf = [0u8; 32];
+        assert_eq!(encode_node(0, &[1, 3, 3], None, &mut buf), Err(ZigZagError::InvalidData { offset: 2 }));
+        assert_eq!(encode_node(0, &[1, 300], None, &mut buf[..2]), Err(ZigZagError::BufferTooSmall { needed: 6, actual: 2 }));
+
+        let reference = Reference { distance: 2, neighbors: &[1, 2, 3] };
+        let len = encode_node(4, &[1, 2, 3], Some(reference), &mut buf).unwrap();
+        let (record, _) = NodeRecord::parse(4, &buf[..len]).unwrap();
+        assert_eq!(record.reference(), Some(2));
+        // The reference list passed on decode does not match the copy blocks
+        assert!(record.neighbors(&[1, 2]).is_err());
+        assert!(matches!(NodeRecord::parse(4, &buf[..len - 1]), Err(ZigZagError::UnexpectedEof { .. })));
+        assert_eq!(NodeRecord::parse(1, &buf[..len]).unwrap_err(), ZigZagError::InvalidData { offset: 1 });
+    }
+}
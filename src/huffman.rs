@@ -0,0 +1,341 @@
+//! Static canonical Huffman coding of byte streams.
+//!
+//! An optional last stage for any of the byte-oriented codecs in this crate.
+//! Varints of skewed difference distributions are dominated by a few byte
+//! values; coding each byte with a prefix code built from the block's own
+//! byte frequencies claims the ratio the varint layer leaves behind. Codes
+//! are canonical and limited to [`MAX_CODE_LEN`] bits, so the header only
+//! needs the code length of each byte value that occurs.
+//!
+//! ```text
+//! symbol count (varint) | (symbol (u8), code length (u8))* | decoded length (varint) | codes, MSB first
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "i64")] {
+//! use zigzag_rs::{delta, huffman};
+//!
+//! // Mostly +1 steps with occasional jitter
+//! let values: Vec<i64> = (0..2000).map(|i| i + (i % 7 == 0) as i64 - (i % 11 == 0) as i64).collect();
+//! let mut varints = vec![0u8; delta::encoded_len(&values)];
+//! let varint_len = delta::encode(&values, &mut varints).unwrap();
+//!
+//! let mut packed = vec![0u8; huffman::encoded_len(&varints[..varint_len])];
+//! let len = huffman::encode(&varints[..varint_len], &mut packed).unwrap();
+//! assert!(len * 2 < varint_len);
+//!
+//! let mut unpacked = vec![0u8; huffman::decoded_len(&packed[..len]).unwrap()];
+//! assert_eq!(huffman::decode(&packed[..len], &mut unpacked), Ok(varint_len));
+//! assert_eq!(unpacked, &varints[..varint_len]);
+//! # }
+//! ```
+
+use crate::bits::{BitReader, BitWriter};
+use crate::varint::{self, Varint};
+use crate::ZigZagError;
+
+/// Longest code assigned to a byte value
+pub const MAX_CODE_LEN: usize = 15;
+
+/// Canonical prefix code for the 256 byte values
+struct Code {
+    /// Code length of each byte value, 0 for values that do not occur
+    lengths: [u8; 256],
+    /// Code of each byte value, right-aligned
+    codes: [u16; 256],
+}
+
+impl Code {
+    /// Build a length-limited Huffman code for the byte frequencies of `input`
+    fn for_input(input: &[u8]) -> Self {
+        let mut freq = [0u64; 256];
+        for &byte in input {
+            freq[byte as usize] += 1;
+        }
+        Self::from_lengths(huffman_lengths(&freq))
+    }
+
+    /// Assign canonical codes: shorter codes first, equal lengths by symbol
+    fn from_lengths(lengths: [u8; 256]) -> Self {
+        let mut codes = [0u16; 256];
+        let mut code = 0u32;
+        for len in 1..=MAX_CODE_LEN as u8 {
+            for symbol in 0..256 {
+                if lengths[symbol] == len {
+                    codes[symbol] = code as u16;
+                    code += 1;
+                }
+            }
+            code <<= 1;
+        }
+        Code { lengths, codes }
+    }
+
+    fn symbol_count(&self) -> usize {
+        self.lengths.iter().filter(|&&len| len > 0).count()
+    }
+
+    fn header_len(&self, decoded_len: usize) -> usize {
+        let symbols = self.symbol_count();
+        (symbols as u64).varint_len() + 2 * symbols + (decoded_len as u64).varint_len()
+    }
+
+    fn bit_len(&self, input: &[u8]) -> usize {
+        input.iter().map(|&byte| self.lengths[byte as usize] as usize).sum()
+    }
+}
+
+/// Code lengths of an optimal prefix code limited to [`MAX_CODE_LEN`] bits
+fn huffman_lengths(freq: &[u64; 256]) -> [u8; 256] {
+    let mut lengths = [0u8; 256];
+    let mut symbols = [0u8; 256];
+    let mut n = 0;
+    for (symbol, &count) in freq.iter().enumerate() {
+        if count > 0 {
+            symbols[n] = symbol as u8;
+            n += 1;
+        }
+    }
+    if n == 1 {
+        lengths[symbols[0] as usize] = 1;
+    }
+    if n <= 1 {
+        return lengths;
+    }
+
+    // Merge the two lightest nodes until one tree is left. Leaves are nodes
+    // 0..n, merged nodes follow. With at most 256 symbols a linear scan for
+    // the minimum is fast enough.
+    let mut weight = [0u64; 511];
+    let mut parent = [0u16; 511];
+    let mut active = [false; 511];
+    for i in 0..n {
+        weight[i] = freq[symbols[i] as usize];
+        active[i] = true;
+    }
+    for node in n..2 * n - 1 {
+        for _ in 0..2 {
+            let lightest = (0..node).filter(|&i| active[i]).min_by_key(|&i| weight[i]).unwrap_or(0);
+            active[lightest] = false;
+            parent[lightest] = node as u16;
+            weight[node] += weight[lightest];
+        }
+        active[node] = true;
+    }
+
+    // Depth of every leaf, and the number of leaves per depth
+    let mut depth = [0usize; 256];
+    let mut count = [0u32; 256];
+    for (i, leaf_depth) in depth.iter_mut().enumerate().take(n) {
+        let mut node = i;
+        while node != 2 * n - 2 {
+            node = parent[node] as usize;
+            *leaf_depth += 1;
+        }
+        count[*leaf_depth] += 1;
+    }
+
+    // Move leaves deeper than the limit up, keeping the code complete. Two
+    // leaves at depth i are replaced by one at i - 1, which frees a slot to
+    // split a shallower leaf at j into two at j + 1 (JPEG Annex K.3).
+    for i in (MAX_CODE_LEN + 1..256).rev() {
+        while count[i] > 0 {
+            let mut j = i - 2;
+            while count[j] == 0 {
+                j -= 1;
+            }
+            count[i] -= 2;
+            count[i - 1] += 1;
+            count[j + 1] += 2;
+            count[j] -= 1;
+        }
+    }
+
+    // Hand out the limited lengths, shortest to the most frequent symbols
+    let mut order = symbols;
+    order[..n].sort_unstable_by_key(|&symbol| (core::cmp::Reverse(freq[symbol as usize]), symbol));
+    let mut next = 0;
+    for (len, &leaves) in count.iter().enumerate().take(MAX_CODE_LEN + 1) {
+        for _ in 0..leaves {
+            lengths[order[next] as usize] = len as u8;
+            next += 1;
+        }
+    }
+    lengths
+}
+
+/// Number of bytes [`encode`] writes for `input`
+pub fn encoded_len(input: &[u8]) -> usize {
+    let code = Code::for_input(input);
+    code.header_len(input.len()) + code.bit_len(input).div_ceil(8)
+}
+
+/// Huffman code `input` with a code built from its own byte frequencies
+///
+/// # Arguments
+/// * `input` - Bytes to compress, typically the output of another codec
+/// * `out` - Output buffer for the block
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode(input: &[u8], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let code = Code::for_input(input);
+    let needed = code.header_len(input.len()) + code.bit_len(input).div_ceil(8);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let mut pos = (code.symbol_count() as u64).encode_varint(out)?;
+    for (symbol, &len) in code.lengths.iter().enumerate() {
+        if len > 0 {
+            out[pos] = symbol as u8;
+            out[pos + 1] = len;
+            pos += 2;
+        }
+    }
+    pos += (input.len() as u64).encode_varint(&mut out[pos..])?;
+
+    let mut writer = BitWriter::new(&mut out[pos..needed]);
+    for &byte in input {
+        writer.write(code.codes[byte as usize] as u64, code.lengths[byte as usize] as u32)?;
+    }
+    Ok(pos + writer.finish())
+}
+
+/// Number of bytes [`decode`] writes for the block in `input`
+///
+/// # Returns
+/// * `Ok(len)` with the decoded length stored in the header
+/// * `Err(ZigZagError)` with the byte offset of the problem if the header is malformed
+pub fn decoded_len(input: &[u8]) -> Result<usize, ZigZagError> {
+    read_header(input).map(|(_, len, _)| len)
+}
+
+/// Decode a block written by [`encode`]
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+/// * `Err(ZigZagError::InvalidData)` if the code table is invalid or the bits
+///   contain a code that is not in it
+/// * `Err(ZigZagError::UnexpectedEof)` if the bits end early
+pub fn decode(input: &[u8], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let (lengths, len, data_start) = read_header(input)?;
+    if out.len() < len {
+        return Err(ZigZagError::BufferTooSmall { needed: len, actual: out.len() });
+    }
+
+    // Symbols in canonical order and the number of codes of each length
+    let mut sorted = [0u8; 256];
+    let mut count = [0u16; MAX_CODE_LEN + 1];
+    let mut next = 0;
+    for code_len in 1..=MAX_CODE_LEN as u8 {
+        for (symbol, _) in lengths.iter().enumerate().filter(|&(_, &l)| l == code_len) {
+            sorted[next] = symbol as u8;
+            next += 1;
+            count[code_len as usize] += 1;
+        }
+    }
+
+    // Codes longer than the longest one in use cannot match
+    let max_len = count.iter().rposition(|&codes| codes > 0).unwrap_or(0);
+
+    let mut reader = BitReader::new(&input[data_start..]);
+    for slot in out[..len].iter_mut() {
+        // Canonical codes of each length are consecutive, starting at `first`
+        let (mut code, mut first, mut index) = (0u32, 0u32, 0u32);
+        let mut symbol = None;
+        for &codes in &count[1..=max_len] {
+            let bit = reader.read_bit().map_err(|err| varint::at_offset(err, data_start))?;
+            code |= bit as u32;
+            if code - first < codes as u32 {
+                symbol = Some(sorted[(index + code - first) as usize]);
+                break;
+            }
+            index += codes as u32;
+            first = (first + codes as u32) << 1;
+            code <<= 1;
+        }
+        *slot = symbol.ok_or(ZigZagError::InvalidData { offset: data_start + reader.bit_pos().saturating_sub(1) / 8 })?;
+    }
+    Ok(len)
+}
+
+/// Read the code lengths, the decoded length and the start of the code bits
+fn read_header(input: &[u8]) -> Result<([u8; 256], usize, usize), ZigZagError> {
+    let (symbols, mut pos) = u64::decode_varint(input)?;
+    if symbols > 256 {
+        return Err(ZigZagError::InvalidData { offset: 0 });
+    }
+    let mut lengths = [0u8; 256];
+    // Kraft sum in units of 2^-MAX_CODE_LEN, at most 1 for a decodable code
+    let mut kraft = 0u32;
+    for _ in 0..symbols {
+        let pair = input.get(pos..pos + 2).ok_or(ZigZagError::UnexpectedEof { offset: pos })?;
+        let (symbol, len) = (pair[0], pair[1]);
+        if len == 0 || len as usize > MAX_CODE_LEN || lengths[symbol as usize] != 0 {
+            return Err(ZigZagError::InvalidData { offset: pos });
+        }
+        lengths[symbol as usize] = len;
+        kraft += 1 << (MAX_CODE_LEN - len as usize);
+        if kraft > 1 << MAX_CODE_LEN {
+            return Err(ZigZagError::InvalidData { offset: pos });
+        }
+        pos += 2;
+    }
+    let (len, size) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+    if symbols == 0 && len > 0 {
+        return Err(ZigZagError::InvalidData { offset: pos });
+    }
+    Ok((lengths, len as usize, pos + size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn round_trip(input: &[u8]) -> usize {
+        let mut packed = vec![0u8; encoded_len(input)];
+        let len = encode(input, &mut packed).unwrap();
+        assert_eq!(len, packed.len());
+        assert_eq!(decoded_len(&packed), Ok(input.len()));
+        let mut decoded = vec![0u8; input.len()];
+        assert_eq!(decode(&packed, &mut decoded), Ok(input.len()));
+        assert_eq!(decoded, input);
+        len
+    }
+
+    #[test]
+    fn test_huffman_round_trip() {
+        assert_eq!(round_trip(&[]), 2);
+        // A single symbol takes one bit per byte
+        assert_eq!(round_trip(&[7; 80]), 1 + 2 + 1 + 10);
+        let all: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        assert_eq!(round_trip(&all), 2 + 512 + 2 + 4096);
+
+        // Fibonacci frequencies make the unrestricted tree 19 levels deep
+        let mut skewed = Vec::new();
+        let (mut a, mut b) = (1usize, 1usize);
+        for symbol in 0..20u8 {
+            skewed.extend(core::iter::repeat_n(symbol, a));
+            (a, b) = (b, a + b);
+        }
+        let len = round_trip(&skewed);
+        assert!(len < skewed.len() / 3);
+    }
+
+    #[test]
+    fn test_huffman_errors() {
+        let mut out = [0u8; 16];
+        // Three 1-bit codes
+        assert_eq!(decode(&[3, 0, 1, 1, 1, 2, 1, 1, 0], &mut out), Err(ZigZagError::InvalidData { offset: 5 }));
+        assert_eq!(decode(&[1, 9, 16, 1, 0], &mut out), Err(ZigZagError::InvalidData { offset: 1 }));
+        // Codes 0 and 10 leave 11 unassigned
+        assert_eq!(decode(&[2, 0, 1, 1, 2, 2, 0xc0], &mut out), Err(ZigZagError::InvalidData { offset: 6 }));
+        assert_eq!(decode(&[1, 9, 1, 9, 0x00], &mut out), Err(ZigZagError::UnexpectedEof { offset: 5 }));
+        assert_eq!(decode(&[1, 9, 1, 9, 0x00], &mut out[..2]), Err(ZigZagError::BufferTooSmall { needed: 9, actual: 2 }));
+    }
+}
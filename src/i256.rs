@@ -0,0 +1,268 @@
+//! 256-bit integers for ZigZag and varint encoding.
+//!
+//! Blockchain amounts and similar values do not fit into 128 bits. [`I256`]
+//! and [`U256`] are minimal two's complement 256-bit integers that only carry
+//! what encoding needs: [`ZigZag`] maps an [`I256`] to a [`U256`], which
+//! implements [`Varint`], so the slice, iterator and `varint` functions work
+//! as for the native widths. Arithmetic is left to a big-integer crate;
+//! values move in and out of one through their little-endian bytes.
+//!
+//! A varint of a [`U256`] takes at most 37 bytes, the last of which may only
+//! carry the remaining 4 bits.
+//!
+//! ```rust
+//! use zigzag_rs::i256::I256;
+//! use zigzag_rs::varint;
+//!
+//! let amount = I256::from(-5_000_000_000_000_000_000i128);
+//! let mut buf = [0u8; 37];
+//! let len = varint::encode(amount, &mut buf).unwrap();
+//! assert_eq!(len, 10);
+//! assert_eq!(varint::decode::<I256>(&buf[..len]), Ok((amount, len)));
+//!
+//! // Bytes as produced by `to_le_bytes` of a big-integer crate
+//! let max = I256::from_le_bytes([0xff; 32]);
+//! assert_eq!(max, I256::from(-1i128));
+//! ```
+
+use crate::varint::Varint;
+use crate::{ZigZag, ZigZagError, ZigZagUnsigned};
+
+/// Unsigned 256-bit integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+/// Signed 256-bit integer in two's complement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct I256 {
+    hi: i128,
+    lo: u128,
+}
+
+impl U256 {
+    /// Zero
+    pub const ZERO: Self = U256 { hi: 0, lo: 0 };
+    /// Largest value, 2^256 - 1
+    pub const MAX: Self = U256 { hi: u128::MAX, lo: u128::MAX };
+
+    /// Create a value from its high and low 128 bits
+    pub const fn from_halves(hi: u128, lo: u128) -> Self {
+        U256 { hi, lo }
+    }
+
+    /// High and low 128 bits of the value
+    pub const fn to_halves(self) -> (u128, u128) {
+        (self.hi, self.lo)
+    }
+
+    /// Create a value from its little-endian bytes
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        let (lo, hi) = split_le(bytes);
+        U256 { hi: u128::from_le_bytes(hi), lo: u128::from_le_bytes(lo) }
+    }
+
+    /// Little-endian bytes of the value
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        join_le(self.lo.to_le_bytes(), self.hi.to_le_bytes())
+    }
+
+    /// Number of leading zero bits
+    pub const fn leading_zeros(self) -> u32 {
+        if self.hi == 0 { 128 + self.lo.leading_zeros() } else { self.hi.leading_zeros() }
+    }
+}
+
+impl I256 {
+    /// Zero
+    pub const ZERO: Self = I256 { hi: 0, lo: 0 };
+    /// Smallest value, -2^255
+    pub const MIN: Self = I256 { hi: i128::MIN, lo: 0 };
+    /// Largest value, 2^255 - 1
+    pub const MAX: Self = I256 { hi: i128::MAX, lo: u128::MAX };
+
+    /// Create a value from its high and low 128 bits
+    pub const fn from_halves(hi: i128, lo: u128) -> Self {
+        I256 { hi, lo }
+    }
+
+    /// High and low 128 bits of the value
+    pub const fn to_halves(self) -> (i128, u128) {
+        (self.hi, self.lo)
+    }
+
+    /// Create a value from its little-endian two's complement bytes
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        let (lo, hi) = split_le(bytes);
+        I256 { hi: i128::from_le_bytes(hi), lo: u128::from_le_bytes(lo) }
+    }
+
+    /// Little-endian two's complement bytes of the value
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        join_le(self.lo.to_le_bytes(), self.hi.to_le_bytes())
+    }
+
+    /// Whether the value is below zero
+    pub const fn is_negative(self) -> bool {
+        self.hi < 0
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256 { hi: 0, lo: value }
+    }
+}
+
+impl From<i128> for I256 {
+    fn from(value: i128) -> Self {
+        I256 { hi: value >> 127, lo: value as u128 }
+    }
+}
+
+fn split_le(bytes: [u8; 32]) -> ([u8; 16], [u8; 16]) {
+    let mut lo = [0u8; 16];
+    let mut hi = [0u8; 16];
+    lo.copy_from_slice(&bytes[..16]);
+    hi.copy_from_slice(&bytes[16..]);
+    (lo, hi)
+}
+
+fn join_le(lo: [u8; 16], hi: [u8; 16]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&lo);
+    bytes[16..].copy_from_slice(&hi);
+    bytes
+}
+
+impl ZigZag for I256 {
+    type UInt = U256;
+
+    #[inline]
+    fn zigzag_encode(value: Self) -> Self::UInt {
+        // Shift the 256 bits left by one and flip them all for negative values
+        let sign = (value.hi >> 127) as u128;
+        let hi = ((value.hi as u128) << 1) | (value.lo >> 127);
+        U256 { hi: hi ^ sign, lo: (value.lo << 1) ^ sign }
+    }
+
+    #[inline]
+    fn zigzag_decode(value: Self::UInt) -> Self {
+        let sign = 0u128.wrapping_sub(value.lo & 1);
+        let lo = (value.lo >> 1) | (value.hi << 127);
+        I256 { hi: ((value.hi >> 1) ^ sign) as i128, lo: lo ^ sign }
+    }
+}
+
+impl ZigZagUnsigned for U256 {
+    type SInt = I256;
+
+    #[inline]
+    fn zigzag_to_signed(self) -> Self::SInt {
+        I256::zigzag_decode(self)
+    }
+}
+
+impl Varint for U256 {
+    const MAX_LEN: usize = 256usize.div_ceil(7);
+
+    #[inline]
+    fn varint_len(self) -> usize {
+        (256 - self.leading_zeros() as usize).div_ceil(7).max(1)
+    }
+
+    fn encode_varint(self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+        let len = self.varint_len();
+        if out.len() < len {
+            return Err(ZigZagError::BufferTooSmall { needed: len, actual: out.len() });
+        }
+        let U256 { mut hi, mut lo } = self;
+        for byte in out[..len - 1].iter_mut() {
+            *byte = (lo as u8 & 0x7f) | 0x80;
+            lo = (lo >> 7) | (hi << 121);
+            hi >>= 7;
+        }
+        out[len - 1] = lo as u8;
+        Ok(len)
+    }
+
+    fn decode_varint(input: &[u8]) -> Result<(Self, usize), ZigZagError> {
+        let mut result = U256::ZERO;
+        for (i, &byte) in input.iter().enumerate().take(Self::MAX_LEN) {
+            let group = (byte & 0x7f) as u128;
+            if i == Self::MAX_LEN - 1 {
+                let remaining_bits = 256 - 7 * (Self::MAX_LEN - 1);
+                if byte & 0x80 != 0 || (group >> remaining_bits) != 0 {
+                    return Err(ZigZagError::VarintOverflow { offset: 0 });
+                }
+            }
+            let shift = 7 * i as u32;
+            if shift < 128 {
+                result.lo |= group << shift;
+                // A group straddling the halves carries its top bits into `hi`
+                if shift > 121 {
+                    result.hi |= group >> (128 - shift);
+                }
+            } else {
+                result.hi |= group << (shift - 128);
+            }
+            if byte & 0x80 == 0 {
+                return Ok((result, i + 1));
+            }
+        }
+        Err(ZigZagError::UnexpectedEof { offset: 0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::varint;
+
+    #[test]
+    fn test_i256_zigzag_round_trip() {
+        let values = [
+            I256::MIN,
+            I256::from(i128::MIN),
+            I256::from(-1i128),
+            I256::ZERO,
+            I256::from(1i128),
+            I256::from_halves(0, u128::MAX),
+            I256::from_halves(1, 1 << 127),
+            I256::MAX,
+        ];
+        let mut buf = [0u8; 37];
+        for value in values {
+            let encoded = I256::zigzag_encode(value);
+            assert_eq!(I256::zigzag_decode(encoded), value);
+            let len = varint::encode(value, &mut buf).unwrap();
+            assert_eq!(len, encoded.varint_len());
+            assert_eq!(varint::decode::<I256>(&buf[..len]), Ok((value, len)));
+            assert_eq!(I256::from_le_bytes(value.to_le_bytes()), value);
+        }
+        assert_eq!(I256::zigzag_encode(I256::from(-2i128)), U256::from(3u128));
+        assert_eq!(I256::zigzag_encode(I256::MIN), U256::MAX);
+        assert_eq!(U256::MAX.zigzag_to_signed(), I256::MIN);
+        assert_eq!(I256::zigzag_encode(I256::MAX), U256::from_halves(u128::MAX, u128::MAX - 1));
+        assert!(I256::MIN < I256::from(-1i128) && I256::from(-1i128) < I256::ZERO);
+    }
+
+    #[test]
+    fn test_u256_varint_bytes_and_errors() {
+        let mut buf = [0u8; 37];
+        assert_eq!(U256::MAX.encode_varint(&mut buf), Ok(U256::MAX_LEN));
+        assert_eq!(buf[36], 0x0f);
+        // 2^128 sets bit 2 of the 19th byte
+        let len = U256::from_halves(1, 0).encode_varint(&mut buf).unwrap();
+        assert_eq!((len, buf[18]), (19, 0x04));
+        assert_eq!(U256::decode_varint(&buf[..len]), Ok((U256::from_halves(1, 0), len)));
+
+        let mut overflow = [0xffu8; 37];
+        overflow[36] = 0x10;
+        assert_eq!(U256::decode_varint(&overflow), Err(ZigZagError::VarintOverflow { offset: 0 }));
+        assert_eq!(U256::decode_varint(&[0x80; 20]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert_eq!(U256::MAX.encode_varint(&mut buf[..36]), Err(ZigZagError::BufferTooSmall { needed: 37, actual: 36 }));
+    }
+}
@@ -9,19 +9,33 @@
 //! ## Features
 //!
 //! - Completely dependency-free, usable in `#![no_std]` environments
-//! - Supports all Rust native signed integer types (i8, i16, i32, i64, i128)
+//! - Supports all Rust native signed integer types (i8, i16, i32, i64, i128, isize), plus 256-bit integers
+//! - Non-zero signed integers (`NonZeroI32` etc.) encode to their non-zero unsigned counterparts
+//! - `Wrapping` and `Saturating` signed integers encode to their unsigned counterparts
+//! - Fixed-size arrays such as `[i32; 4]` encode element-wise to arrays of the unsigned type
 //! - Simple and easy-to-use API with both single value and batch processing
 //! - Iterator-based API for memory-constrained environments
+//! - In-place slice coding that turns an `[i32]` buffer into a `[u32]` one without a second array
+//! - Strided coding of one component of interleaved samples such as `[x0, y0, z0, x1, ...]`
+//! - Field-wise coding of a slice of structs into columns, for columnar storage of telemetry records
 //! - Efficient implementation optimized for embedded systems
 //! - Error handling with Result types for robust application development
 //!
+//! ## Cargo features
+//!
+//! Each integer width and each codec module has a Cargo feature, all enabled
+//! by default. The core modules such as [`varint`], [`delta`] and [`view`] are
+//! always compiled: they are generic over [`ZigZag`] or work on plain bytes,
+//! so they generate no code until they are used, and the codec modules are
+//! built on them. Their width-specific items follow the width features.
+//!
 //! ## Usage
 //!
 //! Add the dependency to your `Cargo.toml`:
 //!
 //! ```toml
 //! [dependencies]
-//! zigzag-rs = "0.2.0"
+//! zigzag-rs = "0.3.0"
 //! ```
 //!
 //! Example code:
@@ -30,6 +44,7 @@
 //! use zigzag_rs::ZigZag;
 //!
 //! fn main() {
+//!     # #[cfg(feature = "i32")] {
 //!     // Single value encoding/decoding
 //!     let encoded = i32::zigzag_encode(-1);
 //!     assert_eq!(encoded, 1u32);
@@ -62,6 +77,7 @@
 //!     for (original, encoded) in values.iter().zip(encoded_iter) {
 //!         assert_eq!(encoded, i32::zigzag_encode(*original));
 //!     }
+//!     # }
 //! }
 //! ```
 //!
@@ -73,13 +89,150 @@
 //! - 1 -> 2
 //! - -2 -> 3
 //! - 2 -> 4
-//! ...
+//! - ...
 //!
 //! This encoding method ensures that small absolute values (whether positive or negative)
 //! are mapped to small unsigned integers, which is ideal for subsequent variable-length encoding.
 
+#[cfg(feature = "analysis")]
+pub mod analysis;
+#[cfg(any(feature = "i8", feature = "i16", feature = "i32", feature = "i64", feature = "i128"))]
+pub mod any;
+#[cfg(feature = "bitfield")]
+pub mod bitfield;
+#[cfg(feature = "bitpack")]
+pub mod bitpack;
+pub mod bits;
+pub mod bounded;
+#[cfg(feature = "calibration")]
+pub mod calibration;
+pub mod cast;
+pub mod codec;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "cobs")]
+pub mod cobs;
+#[cfg(feature = "container")]
+pub mod container;
+pub mod ct;
+pub mod cursor;
+#[cfg(feature = "decimation")]
+pub mod decimation;
+pub mod delta;
+pub mod dma;
+pub mod encoded;
+#[cfg(feature = "alloc")]
+pub mod encoded_vec;
+#[cfg(feature = "fastpfor")]
+pub mod fastpfor;
+#[cfg(feature = "fixed")]
+pub mod fixed;
+#[cfg(feature = "float")]
+pub mod float;
+#[cfg(feature = "frame")]
+pub mod frame;
+#[cfg(feature = "graph")]
+pub mod graph;
+#[cfg(feature = "huffman")]
+pub mod huffman;
+#[cfg(feature = "i256")]
+pub mod i256;
+pub mod mapping;
+#[cfg(feature = "memcomparable")]
+pub mod memcomparable;
+#[cfg(feature = "std")]
+pub mod mmap;
+#[cfg(feature = "mvt")]
+pub mod mvt;
+#[cfg(feature = "nibble")]
+pub mod nibble;
+#[cfg(feature = "osm")]
+pub mod osm;
+#[cfg(feature = "payload")]
+pub mod payload;
+#[cfg(feature = "polyline")]
+pub mod polyline;
+#[cfg(feature = "postings")]
+pub mod postings;
+#[cfg(feature = "predictor")]
+pub mod predictor;
+pub mod scan;
+pub mod selftest;
+#[cfg(feature = "spans")]
+pub mod spans;
+#[cfg(feature = "timestamp")]
+pub mod timestamp;
+pub mod unaligned;
+pub mod varint;
+pub mod verify;
+pub mod view;
+pub mod width;
+
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+/// Derive [`ZigZag`] for a newtype over a signed integer
+///
+/// The struct must have exactly one field, whose type implements [`ZigZag`];
+/// the newtype encodes to the same unsigned type.
+///
+/// ```rust
+/// use zigzag_rs::{varint, ZigZag};
+///
+/// #[derive(ZigZag, Debug, Clone, Copy, PartialEq)]
+/// #[repr(transparent)]
+/// struct CentiDegrees(i16);
+///
+/// assert_eq!(CentiDegrees::zigzag_encode(CentiDegrees(-3)), 5u16);
+/// let mut buf = [0u8; 3];
+/// let len = varint::encode(CentiDegrees(-2150), &mut buf).unwrap();
+/// assert_eq!(varint::decode(&buf[..len]), Ok((CentiDegrees(-2150), len)));
+/// ```
+///
+/// Structs with more than one field are rejected:
+///
+/// ```compile_fail
+/// #[derive(zigzag_rs::ZigZag)]
+/// struct Point(i32, i32);
+/// ```
+#[cfg(feature = "derive")]
+pub use zigzag_rs_derive::ZigZag;
+
+/// Derive a ZigZag encoded mirror of a struct of signed integers
+///
+/// For a struct `Name`, this generates `NameEncoded` with the same fields,
+/// each of the unsigned type its field encodes to, together with
+/// `Name::encode(&self) -> NameEncoded` and `Name::decode(NameEncoded) -> Name`.
+/// Every field must be `Copy` and implement [`ZigZag`].
+///
+/// ```rust
+/// use zigzag_rs::ZigZagRecord;
+///
+/// #[derive(ZigZagRecord, Debug, PartialEq)]
+/// pub struct Telemetry {
+///     pub temperature: i16,
+///     pub current: i32,
+///     pub altitude: i64,
+/// }
+///
+/// let record = Telemetry { temperature: -215, current: 1200, altitude: -3 };
+/// let encoded = record.encode();
+/// assert_eq!(encoded, TelemetryEncoded { temperature: 429, current: 2400, altitude: 5 });
+/// assert_eq!(Telemetry::decode(encoded), record);
+/// ```
+#[cfg(feature = "derive")]
+pub use zigzag_rs_derive::ZigZagRecord;
+
+// Lets the derive macros refer to `::zigzag_rs` in this crate's own tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as zigzag_rs;
+
 /// Error type for ZigZag operations
+///
+/// New variants may be added as codecs grow, so matches need a wildcard arm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ZigZagError {
     /// Output buffer is too small to hold all converted values
     BufferTooSmall {
@@ -88,6 +241,149 @@ pub enum ZigZagError {
         /// Actual buffer size
         actual: usize,
     },
+    /// Input ended before a complete value could be read
+    UnexpectedEof {
+        /// Offset in the input at which the incomplete value starts
+        offset: usize,
+    },
+    /// A variable-length value does not fit into the target integer type
+    VarintOverflow {
+        /// Offset in the input at which the oversized value starts
+        offset: usize,
+    },
+    /// Input contains a field that no valid encoder produces
+    InvalidData {
+        /// Offset in the input at which the invalid field was found
+        offset: usize,
+    },
+    /// A value does not fit into the bit width it is encoded with
+    OutOfRange {
+        /// Index of the value in the input
+        offset: usize,
+    },
+    /// Input does not start with the expected sync marker
+    InvalidSync {
+        /// Offset in the input at which the marker was expected
+        offset: usize,
+    },
+    /// Stored checksum does not match the checksum computed over the data
+    ChecksumMismatch {
+        /// Checksum stored in the input
+        expected: u16,
+        /// Checksum computed over the received data
+        actual: u16,
+    },
+    /// Output buffer length differs from the input length in a strict-length call
+    LengthMismatch {
+        /// Number of elements required
+        expected: usize,
+        /// Actual buffer size
+        actual: usize,
+    },
+}
+
+// Add methods to ZigZagError to access fields without requiring std
+impl ZigZagError {
+    /// Get the needed buffer size
+    ///
+    /// Returns 0 for errors that are not caused by a wrongly sized buffer.
+    pub fn needed(&self) -> usize {
+        match self {
+            ZigZagError::BufferTooSmall { needed, .. } => *needed,
+            ZigZagError::LengthMismatch { expected, .. } => *expected,
+            _ => 0,
+        }
+    }
+    
+    /// Get the actual buffer size
+    ///
+    /// Returns 0 for errors that are not caused by a wrongly sized buffer.
+    pub fn actual(&self) -> usize {
+        match self {
+            ZigZagError::BufferTooSmall { actual, .. }
+            | ZigZagError::LengthMismatch { actual, .. } => *actual,
+            _ => 0,
+        }
+    }
+    
+    /// Get the offset in the input at which the error was detected
+    ///
+    /// Offsets count bytes for byte-oriented inputs and elements for inputs made
+    /// of wider integers. Returns `None` for errors that are not tied to an input position.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ZigZagError::UnexpectedEof { offset }
+            | ZigZagError::VarintOverflow { offset }
+            | ZigZagError::InvalidData { offset }
+            | ZigZagError::OutOfRange { offset }
+            | ZigZagError::InvalidSync { offset } => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for ZigZagError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ZigZagError::BufferTooSmall { needed, actual } => {
+                write!(f, "buffer too small: {} needed, {} available", needed, actual)
+            }
+            ZigZagError::UnexpectedEof { offset } => write!(f, "unexpected end of input at offset {}", offset),
+            ZigZagError::VarintOverflow { offset } => write!(f, "varint overflow at offset {}", offset),
+            ZigZagError::InvalidData { offset } => write!(f, "invalid data at offset {}", offset),
+            ZigZagError::OutOfRange { offset } => write!(f, "value out of range at offset {}", offset),
+            ZigZagError::InvalidSync { offset } => write!(f, "missing sync marker at offset {}", offset),
+            ZigZagError::ChecksumMismatch { expected, actual } => {
+                write!(f, "checksum mismatch: expected {:#06x}, computed {:#06x}", expected, actual)
+            }
+            ZigZagError::LengthMismatch { expected, actual } => {
+                write!(f, "length mismatch: {} expected, {} provided", expected, actual)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ZigZagError {}
+
+// The slice methods below are instantiated once per width. Their error paths
+// live in these non-generic functions so every width shares a single copy.
+
+/// Check that an output of `actual` elements can hold `needed` elements
+#[inline]
+fn check_output_len(needed: usize, actual: usize) -> Result<(), ZigZagError> {
+    if actual < needed {
+        return Err(buffer_too_small(needed, actual));
+    }
+    Ok(())
+}
+
+#[cold]
+#[inline(never)]
+fn buffer_too_small(needed: usize, actual: usize) -> ZigZagError {
+    ZigZagError::BufferTooSmall { needed, actual }
+}
+
+/// Check that an output of `actual` elements holds exactly `expected` elements
+#[inline]
+fn check_exact_len(expected: usize, actual: usize) -> Result<(), ZigZagError> {
+    if actual != expected {
+        return Err(length_mismatch(expected, actual));
+    }
+    Ok(())
+}
+
+#[cold]
+#[inline(never)]
+fn length_mismatch(expected: usize, actual: usize) -> ZigZagError {
+    ZigZagError::LengthMismatch { expected, actual }
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn output_too_small() -> ! {
+    panic!("Output slice must be at least as large as input slice")
 }
 
 /// Trait for ZigZag encoding, used to convert signed integers to unsigned integers
@@ -107,16 +403,24 @@ pub trait ZigZag {
     /// * `values` - Slice of signed integers to encode
     /// * `out` - Output slice to store encoded unsigned integers
     /// 
+    /// # Returns
+    /// The prefix of `out` holding the encoded values
+    ///
     /// # Panics
     /// Panics if `out` is smaller than `values` 
-    fn zigzag_encode_slice(values: &[Self], out: &mut [Self::UInt]) 
+    fn zigzag_encode_slice<'a>(values: &[Self], out: &'a mut [Self::UInt]) -> &'a mut [Self::UInt]
     where 
         Self: Sized + Copy
     {
-        assert!(out.len() >= values.len(), "Output slice must be at least as large as input slice");
-        for (i, &value) in values.iter().enumerate() {
-            out[i] = Self::zigzag_encode(value);
+        if out.len() < values.len() {
+            output_too_small();
+        }
+        // Zipping with an exact-length output slice lets the compiler drop the bounds checks
+        let out = &mut out[..values.len()];
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_encode(value);
         }
+        out
     }
     
     /// Decode a slice of unsigned integers back to signed integers
@@ -125,17 +429,24 @@ pub trait ZigZag {
     /// * `values` - Slice of unsigned integers to decode
     /// * `out` - Output slice to store decoded signed integers
     /// 
+    /// # Returns
+    /// The prefix of `out` holding the decoded values
+    ///
     /// # Panics
     /// Panics if `out` is smaller than `values`
-    fn zigzag_decode_slice(values: &[Self::UInt], out: &mut [Self]) 
+    fn zigzag_decode_slice<'a>(values: &[Self::UInt], out: &'a mut [Self]) -> &'a mut [Self]
     where 
         Self: Sized + Copy,
         Self::UInt: Copy
     {
-        assert!(out.len() >= values.len(), "Output slice must be at least as large as input slice");
-        for (i, &value) in values.iter().enumerate() {
-            out[i] = Self::zigzag_decode(value);
+        if out.len() < values.len() {
+            output_too_small();
         }
+        let out = &mut out[..values.len()];
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_decode(value);
+        }
+        out
     }
     
     /// Try to encode a slice of signed integers to unsigned integers, returning
@@ -146,24 +457,20 @@ pub trait ZigZag {
     /// * `out` - Output slice to store encoded unsigned integers
     /// 
     /// # Returns
-    /// * `Ok(())` if all values were encoded successfully
+    /// * `Ok(encoded)` with the prefix of `out` holding the encoded values
     /// * `Err(ZigZagError::BufferTooSmall)` if output buffer is too small
-    fn try_zigzag_encode_slice(values: &[Self], out: &mut [Self::UInt]) -> Result<(), ZigZagError> 
+    fn try_zigzag_encode_slice<'a>(values: &[Self], out: &'a mut [Self::UInt]) -> Result<&'a mut [Self::UInt], ZigZagError> 
     where 
         Self: Sized + Copy
     {
-        if out.len() < values.len() {
-            return Err(ZigZagError::BufferTooSmall { 
-                needed: values.len(), 
-                actual: out.len(),
-            });
-        }
-        
-        for (i, &value) in values.iter().enumerate() {
-            out[i] = Self::zigzag_encode(value);
+        check_output_len(values.len(), out.len())?;
+
+        let out = &mut out[..values.len()];
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_encode(value);
         }
         
-        Ok(())
+        Ok(out)
     }
     
     /// Try to decode a slice of unsigned integers back to signed integers, returning
@@ -174,26 +481,564 @@ pub trait ZigZag {
     /// * `out` - Output slice to store decoded signed integers
     /// 
     /// # Returns
-    /// * `Ok(())` if all values were decoded successfully
+    /// * `Ok(decoded)` with the prefix of `out` holding the decoded values
     /// * `Err(ZigZagError::BufferTooSmall)` if output buffer is too small
-    fn try_zigzag_decode_slice(values: &[Self::UInt], out: &mut [Self]) -> Result<(), ZigZagError> 
+    fn try_zigzag_decode_slice<'a>(values: &[Self::UInt], out: &'a mut [Self]) -> Result<&'a mut [Self], ZigZagError> 
     where 
         Self: Sized + Copy,
         Self::UInt: Copy
     {
-        if out.len() < values.len() {
-            return Err(ZigZagError::BufferTooSmall { 
-                needed: values.len(), 
-                actual: out.len(),
-            });
+        check_output_len(values.len(), out.len())?;
+
+        let out = &mut out[..values.len()];
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_decode(value);
         }
         
-        for (i, &value) in values.iter().enumerate() {
-            out[i] = Self::zigzag_decode(value);
+        Ok(out)
+    }
+    
+    /// Encode as many values as fit into `out`
+    ///
+    /// This fills fixed-size packets from a longer input: the remaining
+    /// values are left for the next packet instead of causing an error.
+    ///
+    /// # Arguments
+    /// * `values` - Slice of signed integers to encode
+    /// * `out` - Output slice to store encoded unsigned integers
+    ///
+    /// # Returns
+    /// The number of values encoded, the smaller of both lengths
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i32")] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let values = [-1i32, 1, -2, 2, 0];
+    /// let mut packet = [0u32; 2];
+    /// let mut sent = 0;
+    /// while sent < values.len() {
+    ///     sent += i32::zigzag_encode_slice_partial(&values[sent..], &mut packet);
+    /// }
+    /// assert_eq!(packet, [0, 4]);
+    /// # }
+    /// ```
+    fn zigzag_encode_slice_partial(values: &[Self], out: &mut [Self::UInt]) -> usize
+    where
+        Self: Sized + Copy
+    {
+        let count = values.len().min(out.len());
+        for (slot, &value) in out[..count].iter_mut().zip(&values[..count]) {
+            *slot = Self::zigzag_encode(value);
+        }
+        count
+    }
+
+    /// Decode as many values as fit into `out`
+    ///
+    /// # Arguments
+    /// * `values` - Slice of unsigned integers to decode
+    /// * `out` - Output slice to store decoded signed integers
+    ///
+    /// # Returns
+    /// The number of values decoded, the smaller of both lengths
+    fn zigzag_decode_slice_partial(values: &[Self::UInt], out: &mut [Self]) -> usize
+    where
+        Self: Sized + Copy,
+        Self::UInt: Copy
+    {
+        let count = values.len().min(out.len());
+        for (slot, &value) in out[..count].iter_mut().zip(&values[..count]) {
+            *slot = Self::zigzag_decode(value);
+        }
+        count
+    }
+
+    /// Encode a slice of signed integers into an output of exactly the same length
+    ///
+    /// Unlike [`try_zigzag_encode_slice`](ZigZag::try_zigzag_encode_slice), an
+    /// oversized output is rejected, so no stale elements can remain after the
+    /// encoded values.
+    ///
+    /// # Arguments
+    /// * `values` - Slice of signed integers to encode
+    /// * `out` - Output slice to store encoded unsigned integers
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were encoded successfully
+    /// * `Err(ZigZagError::LengthMismatch)` if `out.len() != values.len()`
+    fn try_zigzag_encode_slice_exact(values: &[Self], out: &mut [Self::UInt]) -> Result<(), ZigZagError>
+    where
+        Self: Sized + Copy
+    {
+        check_exact_len(values.len(), out.len())?;
+
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_encode(value);
+        }
+
+        Ok(())
+    }
+    
+    /// Decode a slice of unsigned integers into an output of exactly the same length
+    ///
+    /// # Arguments
+    /// * `values` - Slice of unsigned integers to decode
+    /// * `out` - Output slice to store decoded signed integers
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were decoded successfully
+    /// * `Err(ZigZagError::LengthMismatch)` if `out.len() != values.len()`
+    fn try_zigzag_decode_slice_exact(values: &[Self::UInt], out: &mut [Self]) -> Result<(), ZigZagError>
+    where
+        Self: Sized + Copy,
+        Self::UInt: Copy
+    {
+        check_exact_len(values.len(), out.len())?;
+
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_decode(value);
+        }
+
+        Ok(())
+    }
+
+    /// Encode a slice of signed integers without checking the output length
+    ///
+    /// For callers that have already validated the lengths, this skips the
+    /// length check and the panic path of
+    /// [`zigzag_encode_slice`](ZigZag::zigzag_encode_slice).
+    ///
+    /// # Arguments
+    /// * `values` - Slice of signed integers to encode
+    /// * `out` - Output slice to store encoded unsigned integers
+    ///
+    /// # Returns
+    /// The prefix of `out` holding the encoded values
+    ///
+    /// # Safety
+    /// `out.len()` must be at least `values.len()`
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i32")] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let values = [-1i32, 1];
+    /// let mut out = [0u32; 2];
+    /// // SAFETY: `out` is as long as `values`
+    /// let encoded = unsafe { i32::zigzag_encode_slice_unchecked(&values, &mut out) };
+    /// assert_eq!(encoded, [1, 2]);
+    /// # }
+    /// ```
+    unsafe fn zigzag_encode_slice_unchecked<'a>(values: &[Self], out: &'a mut [Self::UInt]) -> &'a mut [Self::UInt]
+    where
+        Self: Sized + Copy
+    {
+        debug_assert!(out.len() >= values.len());
+        // SAFETY: the caller guarantees that `out` holds `values.len()` elements
+        let out = unsafe { out.get_unchecked_mut(..values.len()) };
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_encode(value);
+        }
+        out
+    }
+
+    /// Decode a slice of unsigned integers without checking the output length
+    ///
+    /// # Arguments
+    /// * `values` - Slice of unsigned integers to decode
+    /// * `out` - Output slice to store decoded signed integers
+    ///
+    /// # Returns
+    /// The prefix of `out` holding the decoded values
+    ///
+    /// # Safety
+    /// `out.len()` must be at least `values.len()`
+    unsafe fn zigzag_decode_slice_unchecked<'a>(values: &[Self::UInt], out: &'a mut [Self]) -> &'a mut [Self]
+    where
+        Self: Sized + Copy,
+        Self::UInt: Copy
+    {
+        debug_assert!(out.len() >= values.len());
+        // SAFETY: as in `zigzag_encode_slice_unchecked`
+        let out = unsafe { out.get_unchecked_mut(..values.len()) };
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::zigzag_decode(value);
+        }
+        out
+    }
+
+    /// Encode a slice of signed integers into uninitialized memory
+    ///
+    /// This fills buffers such as the spare capacity of a `Vec` without
+    /// zeroing them first.
+    ///
+    /// # Arguments
+    /// * `values` - Slice of signed integers to encode
+    /// * `out` - Output slice, of which the first `values.len()` elements are written
+    ///
+    /// # Returns
+    /// The initialized prefix of `out`, holding the encoded values
+    ///
+    /// # Panics
+    /// Panics if `out` is smaller than `values`
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i16")] {
+    /// use core::mem::MaybeUninit;
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let mut out = [MaybeUninit::<u16>::uninit(); 4];
+    /// let encoded = i16::zigzag_encode_slice_uninit(&[-1, 1], &mut out);
+    /// assert_eq!(encoded, [1, 2]);
+    /// # }
+    /// ```
+    fn zigzag_encode_slice_uninit<'a>(values: &[Self], out: &'a mut [MaybeUninit<Self::UInt>]) -> &'a mut [Self::UInt]
+    where
+        Self: Sized + Copy
+    {
+        if out.len() < values.len() {
+            output_too_small();
+        }
+        let out = &mut out[..values.len()];
+        for (slot, &value) in out.iter_mut().zip(values) {
+            slot.write(Self::zigzag_encode(value));
+        }
+        // SAFETY: the loop initialized every element of `out`, and
+        // `MaybeUninit<T>` has the same layout as `T`
+        unsafe { core::slice::from_raw_parts_mut(out.as_mut_ptr() as *mut Self::UInt, out.len()) }
+    }
+
+    /// Decode a slice of unsigned integers into uninitialized memory
+    ///
+    /// # Arguments
+    /// * `values` - Slice of unsigned integers to decode
+    /// * `out` - Output slice, of which the first `values.len()` elements are written
+    ///
+    /// # Returns
+    /// The initialized prefix of `out`, holding the decoded values
+    ///
+    /// # Panics
+    /// Panics if `out` is smaller than `values`
+    fn zigzag_decode_slice_uninit<'a>(values: &[Self::UInt], out: &'a mut [MaybeUninit<Self>]) -> &'a mut [Self]
+    where
+        Self: Sized,
+        Self::UInt: Copy
+    {
+        if out.len() < values.len() {
+            output_too_small();
+        }
+        let out = &mut out[..values.len()];
+        for (slot, &value) in out.iter_mut().zip(values) {
+            slot.write(Self::zigzag_decode(value));
+        }
+        // SAFETY: as in `zigzag_encode_slice_uninit`
+        unsafe { core::slice::from_raw_parts_mut(out.as_mut_ptr() as *mut Self, out.len()) }
+    }
+
+    /// Encode a slice in chunks of `N` values through a buffer on the stack
+    ///
+    /// Each encoded chunk is handed to `sink` before the next one is encoded,
+    /// so inputs of any length can be streamed to a UART or flash writer
+    /// without an output buffer of their size. The last chunk may be shorter.
+    ///
+    /// # Arguments
+    /// * `values` - Slice of signed integers to encode
+    /// * `sink` - Called with each encoded chunk in order
+    ///
+    /// # Returns
+    /// * `Ok(())` if `sink` accepted every chunk
+    /// * `Err(err)` with the first error returned by `sink`, after which no more chunks are encoded
+    ///
+    /// `N` must be non-zero, which is checked at compile time.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i32")] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let mut sent = Vec::new();
+    /// i32::zigzag_encode_chunks::<2, (), _>(&[-1, 1, -2, 2, 0], |chunk| {
+    ///     sent.push(chunk.to_vec());
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(sent, [vec![1, 2], vec![3, 4], vec![0]]);
+    /// # }
+    /// ```
+    ///
+    /// A chunk length of zero does not compile:
+    ///
+    /// ```compile_fail
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// i32::zigzag_encode_chunks::<0, (), _>(&[1], |_| Ok(())).unwrap();
+    /// ```
+    fn zigzag_encode_chunks<const N: usize, E, F>(values: &[Self], mut sink: F) -> Result<(), E>
+    where
+        Self: Sized + Copy,
+        Self::UInt: Copy + Default,
+        F: FnMut(&[Self::UInt]) -> Result<(), E>
+    {
+        const { assert!(N > 0, "Chunk length must be non-zero") };
+        let mut buffer = [Self::UInt::default(); N];
+        for chunk in values.chunks(N) {
+            sink(Self::zigzag_encode_slice(chunk, &mut buffer))?;
+        }
+        Ok(())
+    }
+
+    /// Decode a slice in chunks of `N` values through a buffer on the stack
+    ///
+    /// # Arguments
+    /// * `values` - Slice of unsigned integers to decode
+    /// * `sink` - Called with each decoded chunk in order
+    ///
+    /// # Returns
+    /// * `Ok(())` if `sink` accepted every chunk
+    /// * `Err(err)` with the first error returned by `sink`, after which no more chunks are decoded
+    ///
+    /// `N` must be non-zero, which is checked at compile time.
+    fn zigzag_decode_chunks<const N: usize, E, F>(values: &[Self::UInt], mut sink: F) -> Result<(), E>
+    where
+        Self: Sized + Copy + Default,
+        Self::UInt: Copy,
+        F: FnMut(&[Self]) -> Result<(), E>
+    {
+        const { assert!(N > 0, "Chunk length must be non-zero") };
+        let mut buffer = [Self::default(); N];
+        for chunk in values.chunks(N) {
+            sink(Self::zigzag_decode_slice(chunk, &mut buffer))?;
         }
-        
         Ok(())
     }
+
+    /// Encode one component of an interleaved slice into a contiguous output
+    ///
+    /// The values read are `values[offset]`, `values[offset + stride]` and so
+    /// on, so the `y` component of `[x0, y0, z0, x1, y1, z1]` has offset 1 and
+    /// stride 3. No temporary deinterleaved copy is needed. Column `j` of a
+    /// row-major matrix with `n` columns, such as the backing slice of a
+    /// standard-layout 2-D `ndarray` array, has offset `j` and stride `n`.
+    ///
+    /// # Arguments
+    /// * `values` - Interleaved slice of signed integers
+    /// * `offset` - Index of the first value of the component
+    /// * `stride` - Distance between consecutive values of the component
+    /// * `out` - Output slice to store the encoded component
+    ///
+    /// # Returns
+    /// The prefix of `out` holding the encoded values
+    ///
+    /// # Panics
+    /// Panics if `stride` is zero or `out` is smaller than the component
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i16")] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let samples = [1i16, -1, 5, 2, -2, 6];
+    /// let mut y = [0u16; 2];
+    /// assert_eq!(i16::zigzag_encode_strided(&samples, 1, 3, &mut y), [1, 3]);
+    ///
+    /// let mut restored = [0i16; 6];
+    /// i16::zigzag_decode_strided(&y, 1, 3, &mut restored);
+    /// assert_eq!(restored, [0, -1, 0, 0, -2, 0]);
+    /// # }
+    /// ```
+    fn zigzag_encode_strided<'a>(values: &[Self], offset: usize, stride: usize, out: &'a mut [Self::UInt]) -> &'a mut [Self::UInt]
+    where
+        Self: Sized + Copy
+    {
+        assert!(stride != 0, "Stride must be non-zero");
+        let component = values.get(offset..).unwrap_or(&[]).iter().step_by(stride);
+        if out.len() < component.len() {
+            output_too_small();
+        }
+        let out = &mut out[..component.len()];
+        for (slot, &value) in out.iter_mut().zip(component) {
+            *slot = Self::zigzag_encode(value);
+        }
+        out
+    }
+
+    /// Decode a contiguous slice into one component of an interleaved output
+    ///
+    /// The decoded values are written to `out[offset]`, `out[offset + stride]`
+    /// and so on; the other elements of `out` are left untouched, so each
+    /// component can be decoded in turn.
+    ///
+    /// # Arguments
+    /// * `values` - Slice of unsigned integers to decode
+    /// * `offset` - Index of the first value of the component
+    /// * `stride` - Distance between consecutive values of the component
+    /// * `out` - Interleaved output slice
+    ///
+    /// # Panics
+    /// Panics if `stride` is zero or the component in `out` has fewer slots than `values`
+    fn zigzag_decode_strided(values: &[Self::UInt], offset: usize, stride: usize, out: &mut [Self])
+    where
+        Self: Sized,
+        Self::UInt: Copy
+    {
+        assert!(stride != 0, "Stride must be non-zero");
+        let component = out.get_mut(offset..).unwrap_or(&mut []).iter_mut().step_by(stride);
+        if component.len() < values.len() {
+            output_too_small();
+        }
+        for (slot, &value) in component.zip(values) {
+            *slot = Self::zigzag_decode(value);
+        }
+    }
+
+    /// Encode one field of every record of a slice into a column
+    ///
+    /// This turns an array of structs into columnar storage one field at a
+    /// time, without copying the field out into a temporary array first.
+    ///
+    /// # Arguments
+    /// * `records` - Slice of records
+    /// * `field` - Returns the field to encode from a record
+    /// * `out` - Output slice to store the encoded column
+    ///
+    /// # Returns
+    /// The prefix of `out` holding the encoded column
+    ///
+    /// # Panics
+    /// Panics if `out` is smaller than `records`
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(all(feature = "i16", feature = "i32"))] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// struct Sample { temperature: i16, current: i32 }
+    ///
+    /// let samples = [Sample { temperature: -1, current: 300 }, Sample { temperature: 2, current: -300 }];
+    /// let mut column = [0u16; 2];
+    /// assert_eq!(i16::zigzag_encode_field(&samples, |s| s.temperature, &mut column), [1, 4]);
+    ///
+    /// let mut restored = [Sample { temperature: 0, current: 0 }, Sample { temperature: 0, current: 0 }];
+    /// i16::zigzag_decode_field(&column, &mut restored, |s| &mut s.temperature);
+    /// assert_eq!(restored[0].temperature, -1);
+    /// # }
+    /// ```
+    fn zigzag_encode_field<'a, R, F>(records: &[R], mut field: F, out: &'a mut [Self::UInt]) -> &'a mut [Self::UInt]
+    where
+        Self: Sized,
+        F: FnMut(&R) -> Self
+    {
+        if out.len() < records.len() {
+            output_too_small();
+        }
+        let out = &mut out[..records.len()];
+        for (slot, record) in out.iter_mut().zip(records) {
+            *slot = Self::zigzag_encode(field(record));
+        }
+        out
+    }
+
+    /// Decode a column into one field of every record of a slice
+    ///
+    /// The other fields of the records are left untouched, so each column
+    /// can be decoded in turn.
+    ///
+    /// # Arguments
+    /// * `values` - Encoded column
+    /// * `records` - Slice of records to write the field of
+    /// * `field` - Returns the field to write in a record
+    ///
+    /// # Panics
+    /// Panics if `records` is smaller than `values`
+    fn zigzag_decode_field<R, F>(values: &[Self::UInt], records: &mut [R], mut field: F)
+    where
+        Self: Sized,
+        Self::UInt: Copy,
+        F: FnMut(&mut R) -> &mut Self
+    {
+        if records.len() < values.len() {
+            output_too_small();
+        }
+        for (record, &value) in records.iter_mut().zip(values) {
+            *field(record) = Self::zigzag_decode(value);
+        }
+    }
+
+    /// Encode the values of a slice or iterator into a new `Vec`
+    ///
+    /// Requires the `alloc` feature.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i32")] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let values = [-1i32, 0, 1];
+    /// let encoded: Vec<u32> = i32::zigzag_encode_vec(&values);
+    /// assert_eq!(encoded, [1, 0, 2]);
+    /// assert_eq!(i32::zigzag_decode_vec(&encoded), values);
+    ///
+    /// let squares = i32::zigzag_encode_vec((-2..=2).map(|x: i32| x * x.abs()));
+    /// assert_eq!(squares, [7, 1, 0, 2, 8]);
+    /// # }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn zigzag_encode_vec<I>(values: I) -> alloc::vec::Vec<Self::UInt>
+    where
+        Self: Sized + Copy,
+        I: IntoIterator,
+        I::Item: Borrow<Self>
+    {
+        values.into_iter().map(|value| Self::zigzag_encode(*value.borrow())).collect()
+    }
+
+    /// Decode the values of a slice or iterator into a new `Vec`
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    fn zigzag_decode_vec<I>(values: I) -> alloc::vec::Vec<Self>
+    where
+        Self: Sized,
+        Self::UInt: Copy,
+        I: IntoIterator,
+        I::Item: Borrow<Self::UInt>
+    {
+        values.into_iter().map(|value| Self::zigzag_decode(*value.borrow())).collect()
+    }
+
+    /// Encode a fixed-size array, returning an array of the same size
+    ///
+    /// The lengths are part of the types, so no length check is needed.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i32")] {
+    /// use zigzag_rs::ZigZag;
+    ///
+    /// let frame: [u32; 3] = i32::zigzag_encode_array(&[-1, 0, 1]);
+    /// assert_eq!(frame, [1, 0, 2]);
+    /// assert_eq!(i32::zigzag_decode_array(&frame), [-1, 0, 1]);
+    /// # }
+    /// ```
+    fn zigzag_encode_array<const N: usize>(values: &[Self; N]) -> [Self::UInt; N]
+    where
+        Self: Sized + Copy
+    {
+        values.map(Self::zigzag_encode)
+    }
+
+    /// Decode a fixed-size array, returning an array of the same size
+    fn zigzag_decode_array<const N: usize>(values: &[Self::UInt; N]) -> [Self; N]
+    where
+        Self: Sized,
+        Self::UInt: Copy
+    {
+        values.map(Self::zigzag_decode)
+    }
 }
 
 /// Creates an iterator that encodes each signed integer from the source iterator.
@@ -202,13 +1047,15 @@ pub trait ZigZag {
 /// on-the-fly as the iterator is consumed, without requiring an intermediate buffer.
 ///
 /// # Arguments
-/// * `iter` - An iterator that yields references to signed integers
+/// * `iter` - Anything iterable over signed integers or references to them,
+///   such as `values.iter()`, a range or a mapped iterator
 ///
 /// # Returns
 /// An iterator that yields encoded unsigned integers
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "i32")] {
 /// use zigzag_rs::{ZigZag, zigzag_encode_iter};
 ///
 /// let values = [-10, -1, 0, 1, 10];
@@ -217,10 +1064,12 @@ pub trait ZigZag {
 /// for (original, encoded) in values.iter().zip(encoded_iter) {
 ///     assert_eq!(encoded, i32::zigzag_encode(*original));
 /// }
+/// # }
 /// ```
 ///
 /// # Advanced Example
 /// ```
+/// # #[cfg(feature = "i32")] {
 /// use zigzag_rs::{ZigZag, zigzag_encode_iter};
 ///
 /// // Filtering and encoding in one pass
@@ -240,13 +1089,22 @@ pub trait ZigZag {
 /// ).collect();
 ///
 /// assert_eq!(positive_encoded2, vec![2, 20, 200]);
+///
+/// // Owned values work as well, so ranges need no intermediate buffer
+/// let range_encoded: Vec<u32> = zigzag_encode_iter::<i32, _>(-2..2).collect();
+/// assert_eq!(range_encoded, vec![3, 1, 0, 2]);
+/// # }
 /// ```
-pub fn zigzag_encode_iter<'a, T, I>(iter: I) -> impl Iterator<Item = T::UInt> + 'a
+///
+/// Since both `T` and `&T` are accepted, the integer type has to be named
+/// explicitly, as in the examples above.
+pub fn zigzag_encode_iter<T, I>(iter: I) -> ZigZagEncodeIter<I::IntoIter, T>
 where
-    T: ZigZag + Copy + 'a,
-    I: Iterator<Item = &'a T> + 'a,
+    T: ZigZag + Copy,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
 {
-    iter.map(|&value| T::zigzag_encode(value))
+    ZigZagEncodeIter { iter: iter.into_iter(), marker: PhantomData }
 }
 
 /// Creates an iterator that decodes each unsigned integer from the source iterator.
@@ -255,13 +1113,14 @@ where
 /// on-the-fly as the iterator is consumed, without requiring an intermediate buffer.
 ///
 /// # Arguments
-/// * `iter` - An iterator that yields references to unsigned integers
+/// * `iter` - Anything iterable over unsigned integers or references to them
 ///
 /// # Returns
 /// An iterator that yields decoded signed integers
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "i32")] {
 /// use zigzag_rs::{ZigZag, zigzag_decode_iter};
 ///
 /// let encoded = [1u32, 0, 2, 3, 20];
@@ -271,10 +1130,12 @@ where
 /// for (expected, decoded) in expected.iter().zip(decoded_iter) {
 ///     assert_eq!(*expected, decoded);
 /// }
+/// # }
 /// ```
 ///
 /// # Chaining Example
 /// ```
+/// # #[cfg(feature = "i32")] {
 /// use zigzag_rs::{ZigZag, zigzag_encode_iter, zigzag_decode_iter};
 ///
 /// // Encode, then immediately decode without creating intermediate storage
@@ -286,54 +1147,1063 @@ where
 /// 
 /// // Verify values are preserved
 /// assert_eq!(values.to_vec(), decoded);
+/// # }
 /// ```
-pub fn zigzag_decode_iter<'a, T, I>(iter: I) -> impl Iterator<Item = T> + 'a
+pub fn zigzag_decode_iter<T, I>(iter: I) -> ZigZagDecodeIter<I::IntoIter, T>
 where
-    T: ZigZag + Copy + 'a,
-    I: Iterator<Item = &'a T::UInt> + 'a,
-    T::UInt: Copy + 'a,
+    T: ZigZag,
+    T::UInt: Copy,
+    I: IntoIterator,
+    I::Item: Borrow<T::UInt>,
 {
-    iter.map(|&value| T::zigzag_decode(value))
+    ZigZagDecodeIter { iter: iter.into_iter(), marker: PhantomData }
 }
 
-macro_rules! impl_zigzag {
-    ($signed:ty, $unsigned:ty, $bits:expr) => {
-        impl ZigZag for $signed {
-            type UInt = $unsigned;
-            
-            #[inline]
-            fn zigzag_encode(value: Self) -> Self::UInt {
-                // Left shift by one bit, then XOR with arithmetic right shift result
-                ((value << 1) ^ (value >> ($bits - 1))) as $unsigned
-            }
-            
-            #[inline]
-            fn zigzag_decode(value: Self::UInt) -> Self {
+/// Iterator that ZigZag encodes the values of an inner iterator
+///
+/// Returned by [`zigzag_encode_iter`]. Being a named type, it can be stored
+/// in struct fields and named in trait bounds. It is an `ExactSizeIterator`,
+/// `DoubleEndedIterator` or `FusedIterator` whenever the inner iterator is.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::{zigzag_encode_iter, ZigZagEncodeIter};
+///
+/// struct Encoder<'a> {
+///     values: ZigZagEncodeIter<core::slice::Iter<'a, i32>, i32>,
+/// }
+///
+/// let samples = [-1, 1];
+/// let mut encoder = Encoder { values: zigzag_encode_iter(samples.iter()) };
+/// assert_eq!(encoder.values.next(), Some(1));
+/// # }
+/// ```
+pub struct ZigZagEncodeIter<I, T> {
+    iter: I,
+    // `fn() -> T` keeps the adapter `Send` and `Sync` whatever `T` is
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Iterator that ZigZag decodes the values of an inner iterator
+///
+/// Returned by [`zigzag_decode_iter`].
+pub struct ZigZagDecodeIter<I, T> {
+    iter: I,
+    marker: PhantomData<fn() -> T>,
+}
+
+macro_rules! impl_iter_adapter {
+    ($name:ident, $item:ty, $from:ty, $code:ident, $($bound:tt)*) => {
+        impl<I, T> $name<I, T> {
+            /// Unwrap the inner iterator
+            pub fn into_inner(self) -> I {
+                self.iter
+            }
+        }
+
+        impl<I, T> Iterator for $name<I, T>
+        where
+            I: Iterator,
+            I::Item: Borrow<$from>,
+            $($bound)*
+        {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<$item> {
+                self.iter.next().map(|value| T::$code(*value.borrow()))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<I, T> DoubleEndedIterator for $name<I, T>
+        where
+            I: DoubleEndedIterator,
+            I::Item: Borrow<$from>,
+            $($bound)*
+        {
+            #[inline]
+            fn next_back(&mut self) -> Option<$item> {
+                self.iter.next_back().map(|value| T::$code(*value.borrow()))
+            }
+        }
+
+        impl<I, T> ExactSizeIterator for $name<I, T>
+        where
+            I: ExactSizeIterator,
+            I::Item: Borrow<$from>,
+            $($bound)*
+        {
+            #[inline]
+            fn len(&self) -> usize {
+                self.iter.len()
+            }
+        }
+
+        impl<I, T> core::iter::FusedIterator for $name<I, T>
+        where
+            I: core::iter::FusedIterator,
+            I::Item: Borrow<$from>,
+            $($bound)*
+        {
+        }
+
+        impl<I: Clone, T> Clone for $name<I, T> {
+            fn clone(&self) -> Self {
+                $name { iter: self.iter.clone(), marker: PhantomData }
+            }
+        }
+
+        impl<I: core::fmt::Debug, T> core::fmt::Debug for $name<I, T> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!($name)).field("iter", &self.iter).finish()
+            }
+        }
+    };
+}
+
+impl_iter_adapter!(ZigZagEncodeIter, T::UInt, T, zigzag_encode, T: ZigZag + Copy);
+impl_iter_adapter!(ZigZagDecodeIter, T, T::UInt, zigzag_decode, T: ZigZag, T::UInt: Copy);
+
+/// Creates an iterator that encodes the `Ok` values of a fallible iterator
+///
+/// Errors from the source, such as failed sensor reads, are passed through
+/// untouched, so they can be handled downstream without collecting first.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::try_zigzag_encode_iter;
+///
+/// let readings = [Ok(-1i32), Err("sensor timeout"), Ok(2)];
+/// let encoded: Vec<Result<u32, &str>> = try_zigzag_encode_iter(readings).collect();
+/// assert_eq!(encoded, [Ok(1), Err("sensor timeout"), Ok(4)]);
+///
+/// // `collect` into a `Result` stops at the first error
+/// let all: Result<Vec<u32>, &str> = try_zigzag_encode_iter(readings).collect();
+/// assert_eq!(all, Err("sensor timeout"));
+/// # }
+/// ```
+pub fn try_zigzag_encode_iter<T, E, I>(iter: I) -> TryZigZagEncodeIter<I::IntoIter, T>
+where
+    T: ZigZag,
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    TryZigZagEncodeIter { iter: iter.into_iter(), marker: PhantomData }
+}
+
+/// Creates an iterator that decodes the `Ok` values of a fallible iterator
+///
+/// Errors from the source are passed through untouched.
+pub fn try_zigzag_decode_iter<T, E, I>(iter: I) -> TryZigZagDecodeIter<I::IntoIter, T>
+where
+    T: ZigZag,
+    I: IntoIterator<Item = Result<T::UInt, E>>,
+{
+    TryZigZagDecodeIter { iter: iter.into_iter(), marker: PhantomData }
+}
+
+/// Iterator that ZigZag encodes the `Ok` values of an inner iterator
+///
+/// Returned by [`try_zigzag_encode_iter`].
+pub struct TryZigZagEncodeIter<I, T> {
+    iter: I,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Iterator that ZigZag decodes the `Ok` values of an inner iterator
+///
+/// Returned by [`try_zigzag_decode_iter`].
+pub struct TryZigZagDecodeIter<I, T> {
+    iter: I,
+    marker: PhantomData<fn() -> T>,
+}
+
+macro_rules! impl_try_iter_adapter {
+    ($name:ident, $item:ty, $from:ty, $code:ident) => {
+        impl<I, T> $name<I, T> {
+            /// Unwrap the inner iterator
+            pub fn into_inner(self) -> I {
+                self.iter
+            }
+        }
+
+        impl<I, T, E> Iterator for $name<I, T>
+        where
+            T: ZigZag,
+            I: Iterator<Item = Result<$from, E>>,
+        {
+            type Item = Result<$item, E>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next().map(|value| value.map(T::$code))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<I, T, E> DoubleEndedIterator for $name<I, T>
+        where
+            T: ZigZag,
+            I: DoubleEndedIterator<Item = Result<$from, E>>,
+        {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back().map(|value| value.map(T::$code))
+            }
+        }
+
+        impl<I, T, E> ExactSizeIterator for $name<I, T>
+        where
+            T: ZigZag,
+            I: ExactSizeIterator<Item = Result<$from, E>>,
+        {
+        }
+
+        impl<I, T, E> core::iter::FusedIterator for $name<I, T>
+        where
+            T: ZigZag,
+            I: core::iter::FusedIterator<Item = Result<$from, E>>,
+        {
+        }
+
+        impl<I: Clone, T> Clone for $name<I, T> {
+            fn clone(&self) -> Self {
+                $name { iter: self.iter.clone(), marker: PhantomData }
+            }
+        }
+
+        impl<I: core::fmt::Debug, T> core::fmt::Debug for $name<I, T> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!($name)).field("iter", &self.iter).finish()
+            }
+        }
+    };
+}
+
+impl_try_iter_adapter!(TryZigZagEncodeIter, T::UInt, T, zigzag_encode);
+impl_try_iter_adapter!(TryZigZagDecodeIter, T, T::UInt, zigzag_decode);
+
+/// What [`ZigZagArrayChunks`] does with a last chunk of fewer than `N` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PartialChunk {
+    /// Fill the rest of the chunk with zeros, the encoding of zero
+    #[default]
+    Pad,
+    /// Yield no last chunk; its values remain available through
+    /// [`ZigZagArrayChunks::remainder`]
+    Hold,
+}
+
+/// Iterator yielding the encoded values of an inner iterator in arrays of `N`
+///
+/// Returned by [`zigzag_encode_array_chunks`].
+#[derive(Debug, Clone)]
+pub struct ZigZagArrayChunks<I, T: ZigZag, const N: usize> {
+    iter: I,
+    partial: PartialChunk,
+    remainder: [T::UInt; N],
+    remainder_len: usize,
+    done: bool,
+}
+
+impl<I, T: ZigZag, const N: usize> ZigZagArrayChunks<I, T, N> {
+    /// Encoded values of a last chunk held back by [`PartialChunk::Hold`]
+    ///
+    /// Empty until the iterator has returned `None`.
+    pub fn remainder(&self) -> &[T::UInt] {
+        &self.remainder[..self.remainder_len]
+    }
+}
+
+impl<I, T, const N: usize> Iterator for ZigZagArrayChunks<I, T, N>
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy + Default,
+    I: Iterator,
+    I::Item: Borrow<T>,
+{
+    type Item = [T::UInt; N];
+
+    fn next(&mut self) -> Option<[T::UInt; N]> {
+        if self.done {
+            return None;
+        }
+        let mut chunk = [T::UInt::default(); N];
+        let mut len = 0;
+        while len < N {
+            match self.iter.next() {
+                Some(value) => chunk[len] = T::zigzag_encode(*value.borrow()),
+                None => break,
+            }
+            len += 1;
+        }
+        if len == N && N > 0 {
+            return Some(chunk);
+        }
+        self.done = true;
+        match self.partial {
+            _ if len == 0 => None,
+            PartialChunk::Pad => Some(chunk),
+            PartialChunk::Hold => {
+                self.remainder = chunk;
+                self.remainder_len = len;
+                None
+            }
+        }
+    }
+}
+
+impl<I, T, const N: usize> core::iter::FusedIterator for ZigZagArrayChunks<I, T, N>
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy + Default,
+    I: Iterator,
+    I::Item: Borrow<T>,
+{
+}
+
+/// Creates an iterator that encodes the values of `iter` into arrays of `N` values
+///
+/// Radio frames and flash pages hold fixed-size records; each array is one
+/// such record. `partial` selects how a last chunk of fewer than `N` values
+/// is handled. With `N` of zero, nothing is yielded.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i16")] {
+/// use zigzag_rs::{zigzag_encode_array_chunks, PartialChunk};
+///
+/// let values = [-1i16, 1, -2, 2, 5];
+/// let pages: Vec<[u16; 2]> = zigzag_encode_array_chunks::<i16, 2, _>(&values, PartialChunk::Pad).collect();
+/// assert_eq!(pages, [[1, 2], [3, 4], [10, 0]]);
+///
+/// let mut chunks = zigzag_encode_array_chunks::<i16, 2, _>(&values, PartialChunk::Hold);
+/// assert_eq!(chunks.by_ref().count(), 2);
+/// assert_eq!(chunks.remainder(), [10]);
+/// # }
+/// ```
+pub fn zigzag_encode_array_chunks<T, const N: usize, I>(iter: I, partial: PartialChunk) -> ZigZagArrayChunks<I::IntoIter, T, N>
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy + Default,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
+{
+    ZigZagArrayChunks { iter: iter.into_iter(), partial, remainder: [T::UInt::default(); N], remainder_len: 0, done: false }
+}
+
+/// Iterator methods for ZigZag coding in the middle of iterator chains
+///
+/// `zigzag_encoded` infers the integer type from the items, which may be
+/// signed integers or references to them. `zigzag_decoded` takes the signed
+/// type to decode to, since one unsigned type encodes several signed ones.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::ZigZagIterExt;
+///
+/// let samples = [-1i32, 1, -2];
+/// let encoded: Vec<u32> = samples.iter().zigzag_encoded().collect();
+/// assert_eq!(encoded, [1, 2, 3]);
+///
+/// let decoded: Vec<i32> = encoded.into_iter().zigzag_decoded::<i32>().filter(|v| *v < 0).collect();
+/// assert_eq!(decoded, [-1, -2]);
+/// # }
+/// ```
+pub trait ZigZagIterExt: Iterator + Sized {
+    /// ZigZag encode every item
+    fn zigzag_encoded(self) -> ZigZagEncodeIter<Self, <Self::Item as ZigZagItem>::Signed>
+    where
+        Self::Item: ZigZagItem,
+    {
+        zigzag_encode_iter(self)
+    }
+
+    /// ZigZag decode every item into `T`
+    fn zigzag_decoded<T>(self) -> ZigZagDecodeIter<Self, T>
+    where
+        T: ZigZag,
+        T::UInt: Copy,
+        Self::Item: Borrow<T::UInt>,
+    {
+        zigzag_decode_iter(self)
+    }
+}
+
+impl<I: Iterator> ZigZagIterExt for I {}
+
+/// Items [`ZigZagIterExt::zigzag_encoded`] accepts: the signed integers and references to them
+pub trait ZigZagItem: Borrow<Self::Signed> {
+    /// Signed integer type of the item
+    type Signed: ZigZag + Copy;
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_item {
+    ($signed:ty) => {
+        impl ZigZagItem for $signed {
+            type Signed = $signed;
+        }
+
+        impl ZigZagItem for &$signed {
+            type Signed = $signed;
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_zigzag_item!(i8);
+#[cfg(feature = "i16")]
+impl_zigzag_item!(i16);
+#[cfg(feature = "i32")]
+impl_zigzag_item!(i32);
+#[cfg(feature = "i64")]
+impl_zigzag_item!(i64);
+#[cfg(feature = "i128")]
+impl_zigzag_item!(i128);
+#[cfg(feature = "isize")]
+impl_zigzag_item!(isize);
+
+#[allow(unused_macros)] // Unused when every width feature is disabled
+macro_rules! impl_zigzag {
+    ($signed:ty, $unsigned:ty, $bits:expr) => {
+        impl ZigZag for $signed {
+            type UInt = $unsigned;
+            
+            #[inline]
+            fn zigzag_encode(value: Self) -> Self::UInt {
+                // Left shift by one bit, then XOR with arithmetic right shift result
+                ((value << 1) ^ (value >> ($bits - 1))) as $unsigned
+            }
+            
+            #[inline]
+            fn zigzag_decode(value: Self::UInt) -> Self {
                 // Optimized version: combine right shift, negation and XOR in one expression
                 ((value >> 1) as Self) ^ (-((value & 1) as Self))
             }
         }
+
+        impl ZigZagUnsigned for $unsigned {
+            type SInt = $signed;
+
+            #[inline]
+            fn zigzag_to_signed(self) -> Self::SInt {
+                <$signed>::zigzag_decode(self)
+            }
+        }
     };
 }
 
 // Implement ZigZag trait for various integer types
+#[cfg(feature = "i8")]
 impl_zigzag!(i8, u8, 8);
+#[cfg(feature = "i16")]
 impl_zigzag!(i16, u16, 16);
+#[cfg(feature = "i32")]
 impl_zigzag!(i32, u32, 32);
+#[cfg(feature = "i64")]
 impl_zigzag!(i64, u64, 64);
+#[cfg(feature = "i128")]
 impl_zigzag!(i128, u128, 128);
+#[cfg(feature = "isize")]
+impl_zigzag!(isize, usize, usize::BITS);
 
-#[cfg(test)]
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_nonzero {
+    ($signed:ty, $unsigned:ty, $inner:ty) => {
+        impl ZigZag for $signed {
+            type UInt = $unsigned;
+
+            #[inline]
+            fn zigzag_encode(value: Self) -> Self::UInt {
+                // Only zero maps to zero, so a non-zero value stays non-zero
+                <$unsigned>::new(<$inner>::zigzag_encode(value.get())).expect("ZigZag maps only zero to zero")
+            }
+
+            #[inline]
+            fn zigzag_decode(value: Self::UInt) -> Self {
+                <$signed>::new(<$inner>::zigzag_decode(value.get())).expect("ZigZag maps only zero to zero")
+            }
+        }
+    };
+}
+
+// Non-zero integers map to non-zero integers of the same width
+#[cfg(feature = "i8")]
+impl_zigzag_nonzero!(core::num::NonZeroI8, core::num::NonZeroU8, i8);
+#[cfg(feature = "i16")]
+impl_zigzag_nonzero!(core::num::NonZeroI16, core::num::NonZeroU16, i16);
+#[cfg(feature = "i32")]
+impl_zigzag_nonzero!(core::num::NonZeroI32, core::num::NonZeroU32, i32);
+#[cfg(feature = "i64")]
+impl_zigzag_nonzero!(core::num::NonZeroI64, core::num::NonZeroU64, i64);
+#[cfg(feature = "i128")]
+impl_zigzag_nonzero!(core::num::NonZeroI128, core::num::NonZeroU128, i128);
+#[cfg(feature = "isize")]
+impl_zigzag_nonzero!(core::num::NonZeroIsize, core::num::NonZeroUsize, isize);
+
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_wrapper {
+    ($wrapper:ident, $signed:ty, $unsigned:ty) => {
+        impl ZigZag for core::num::$wrapper<$signed> {
+            type UInt = core::num::$wrapper<$unsigned>;
+
+            #[inline]
+            fn zigzag_encode(value: Self) -> Self::UInt {
+                core::num::$wrapper(<$signed>::zigzag_encode(value.0))
+            }
+
+            #[inline]
+            fn zigzag_decode(value: Self::UInt) -> Self {
+                core::num::$wrapper(<$signed>::zigzag_decode(value.0))
+            }
+        }
+    };
+}
+
+// `Wrapping` integers encode to `Wrapping` unsigned integers
+#[cfg(feature = "i8")]
+impl_zigzag_wrapper!(Wrapping, i8, u8);
+#[cfg(feature = "i16")]
+impl_zigzag_wrapper!(Wrapping, i16, u16);
+#[cfg(feature = "i32")]
+impl_zigzag_wrapper!(Wrapping, i32, u32);
+#[cfg(feature = "i64")]
+impl_zigzag_wrapper!(Wrapping, i64, u64);
+#[cfg(feature = "i128")]
+impl_zigzag_wrapper!(Wrapping, i128, u128);
+#[cfg(feature = "isize")]
+impl_zigzag_wrapper!(Wrapping, isize, usize);
+
+// `Saturating` integers encode to `Saturating` unsigned integers
+#[cfg(feature = "i8")]
+impl_zigzag_wrapper!(Saturating, i8, u8);
+#[cfg(feature = "i16")]
+impl_zigzag_wrapper!(Saturating, i16, u16);
+#[cfg(feature = "i32")]
+impl_zigzag_wrapper!(Saturating, i32, u32);
+#[cfg(feature = "i64")]
+impl_zigzag_wrapper!(Saturating, i64, u64);
+#[cfg(feature = "i128")]
+impl_zigzag_wrapper!(Saturating, i128, u128);
+#[cfg(feature = "isize")]
+impl_zigzag_wrapper!(Saturating, isize, usize);
+
+/// Arrays encode element by element, so fixed-size fields of a struct, and
+/// arrays of arrays, encode with a single call.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i16")] {
+/// use zigzag_rs::ZigZag;
+///
+/// let matrix = [[-1i16, 2], [0, -3]];
+/// let encoded: [[u16; 2]; 2] = ZigZag::zigzag_encode(matrix);
+/// assert_eq!(encoded, [[1, 4], [0, 5]]);
+/// assert_eq!(<[[i16; 2]; 2]>::zigzag_decode(encoded), matrix);
+/// # }
+/// ```
+impl<T: ZigZag, const N: usize> ZigZag for [T; N] {
+    type UInt = [T::UInt; N];
+
+    #[inline]
+    fn zigzag_encode(value: Self) -> Self::UInt {
+        value.map(T::zigzag_encode)
+    }
+
+    #[inline]
+    fn zigzag_decode(value: Self::UInt) -> Self {
+        value.map(T::zigzag_decode)
+    }
+}
+
+/// Decoding from the unsigned side, for when the signed type is not named
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i16")] {
+/// use zigzag_rs::ZigZagUnsigned;
+///
+/// let encoded: u16 = 3;
+/// assert_eq!(encoded.zigzag_to_signed(), -2i16);
+/// # }
+/// ```
+pub trait ZigZagUnsigned: Sized {
+    /// The signed type this type decodes to
+    type SInt: ZigZag<UInt = Self>;
+
+    /// Decode a ZigZag encoded value
+    fn zigzag_to_signed(self) -> Self::SInt;
+}
+
+/// ZigZag encoding into a `u64`, as protobuf does for `sint32` fields
+///
+/// The value is ZigZag encoded at its own width, then zero-extended. This is
+/// the mapping protobuf uses, and the same bytes [`varint::encode`] writes for
+/// the value. Decoding checks that the wire value fits the narrower type
+/// instead of truncating it.
+///
+/// # Example
+/// ```
+/// # #[cfg(all(feature = "i16", feature = "i32", feature = "i64"))] {
+/// use zigzag_rs::ZigZagWiden;
+///
+/// assert_eq!((-1i32).zigzag_encode_widened(), 1u64);
+/// assert_eq!(i32::MIN.zigzag_encode_widened(), u32::MAX as u64);
+/// // Sign-extending first would give a different, 64-bit wide value
+/// assert_ne!(i32::MIN.zigzag_encode_widened(), (i32::MIN as i64 as u64) << 1);
+///
+/// assert_eq!(i16::try_zigzag_decode_narrow(599), Ok(-300));
+/// assert!(i16::try_zigzag_decode_narrow(1 << 16).is_err());
+/// # }
+/// ```
+pub trait ZigZagWiden: ZigZag + Sized {
+    /// ZigZag encode the value and zero-extend the result to 64 bits
+    fn zigzag_encode_widened(self) -> u64;
+
+    /// Decode a 64-bit wire value written by [`ZigZagWiden::zigzag_encode_widened`]
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the decoded value
+    /// * `Err(ZigZagError::OutOfRange)` if `value` does not fit the unsigned type of `Self`
+    fn try_zigzag_decode_narrow(value: u64) -> Result<Self, ZigZagError>;
+
+    /// Decode every wire value of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    /// * `Err(ZigZagError::OutOfRange)` with the index of the first value that does not fit
+    fn try_zigzag_decode_narrow_slice(values: &[u64], out: &mut [Self]) -> Result<(), ZigZagError> {
+        check_output_len(values.len(), out.len())?;
+        for (index, (&value, slot)) in values.iter().zip(out.iter_mut()).enumerate() {
+            *slot = Self::try_zigzag_decode_narrow(value).map_err(|_| ZigZagError::OutOfRange { offset: index })?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_widen {
+    ($signed:ty) => {
+        impl ZigZagWiden for $signed {
+            #[inline]
+            fn zigzag_encode_widened(self) -> u64 {
+                <$signed>::zigzag_encode(self) as u64
+            }
+
+            #[inline]
+            fn try_zigzag_decode_narrow(value: u64) -> Result<Self, ZigZagError> {
+                match value.try_into() {
+                    Ok(value) => Ok(<$signed>::zigzag_decode(value)),
+                    Err(_) => Err(ZigZagError::OutOfRange { offset: 0 }),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_zigzag_widen!(i8);
+#[cfg(feature = "i16")]
+impl_zigzag_widen!(i16);
+#[cfg(feature = "i32")]
+impl_zigzag_widen!(i32);
+#[cfg(feature = "i64")]
+impl_zigzag_widen!(i64);
+
+/// ZigZag coding that reuses the input buffer
+///
+/// A signed integer and its unsigned counterpart have the same size and
+/// alignment, so a buffer of one can be transformed into a buffer of the
+/// other without a second array. The returned slice borrows the input,
+/// which can no longer be used as the old type while it is alive.
+///
+/// The trait is sealed: it relies on the layout of the native integers, and
+/// its `alloc` methods are only present with that feature, which would break
+/// other implementations whenever any crate enabled it.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::ZigZagInPlace;
+///
+/// let mut samples = [-1i32, 0, 1, -2];
+/// let encoded: &mut [u32] = i32::zigzag_encode_in_place(&mut samples);
+/// assert_eq!(encoded, [1, 0, 2, 3]);
+///
+/// let decoded = i32::zigzag_decode_in_place(encoded);
+/// assert_eq!(decoded, [-1, 0, 1, -2]);
+/// # }
+/// ```
+pub trait ZigZagInPlace: ZigZag + Sized + sealed::Sealed {
+    /// Encode every value of `values` in place and view the buffer as encoded values
+    fn zigzag_encode_in_place(values: &mut [Self]) -> &mut [Self::UInt];
+
+    /// Decode every value of `values` in place and view the buffer as signed values
+    fn zigzag_decode_in_place(values: &mut [Self::UInt]) -> &mut [Self];
+
+    /// Encode every value of a `Vec`, reusing its allocation for the result
+    ///
+    /// No second vector is allocated, so peak memory stays at one column.
+    /// Requires the `alloc` feature.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "i32")] {
+    /// use zigzag_rs::ZigZagInPlace;
+    ///
+    /// let samples = vec![-1i32, 0, 1];
+    /// let encoded: Vec<u32> = i32::zigzag_encode_vec_in_place(samples);
+    /// assert_eq!(encoded, [1, 0, 2]);
+    /// assert_eq!(i32::zigzag_decode_vec_in_place(encoded), [-1, 0, 1]);
+    /// # }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn zigzag_encode_vec_in_place(values: alloc::vec::Vec<Self>) -> alloc::vec::Vec<Self::UInt>;
+
+    /// Decode every value of a `Vec`, reusing its allocation for the result
+    ///
+    /// Requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    fn zigzag_decode_vec_in_place(values: alloc::vec::Vec<Self::UInt>) -> alloc::vec::Vec<Self>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_in_place {
+    ($signed:ty, $unsigned:ty) => {
+        impl sealed::Sealed for $signed {}
+
+        impl ZigZagInPlace for $signed {
+            fn zigzag_encode_in_place(values: &mut [Self]) -> &mut [$unsigned] {
+                // SAFETY: signed and unsigned integers of one width have the
+                // same size and alignment, and every bit pattern is valid for both
+                let encoded = unsafe { core::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut $unsigned, values.len()) };
+                for value in encoded.iter_mut() {
+                    *value = <$signed>::zigzag_encode(*value as $signed);
+                }
+                encoded
+            }
+
+            fn zigzag_decode_in_place(values: &mut [$unsigned]) -> &mut [Self] {
+                // SAFETY: as in `zigzag_encode_in_place`
+                let decoded = unsafe { core::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut $signed, values.len()) };
+                for value in decoded.iter_mut() {
+                    *value = <$signed>::zigzag_decode(*value as $unsigned);
+                }
+                decoded
+            }
+
+            #[cfg(feature = "alloc")]
+            fn zigzag_encode_vec_in_place(values: alloc::vec::Vec<Self>) -> alloc::vec::Vec<$unsigned> {
+                let mut values = core::mem::ManuallyDrop::new(values);
+                let (len, capacity) = (values.len(), values.capacity());
+                let ptr = Self::zigzag_encode_in_place(&mut values).as_mut_ptr();
+                // SAFETY: the allocation came from a `Vec` of a type with the same
+                // size and alignment, and its first `len` elements are initialized
+                unsafe { alloc::vec::Vec::from_raw_parts(ptr, len, capacity) }
+            }
+
+            #[cfg(feature = "alloc")]
+            fn zigzag_decode_vec_in_place(values: alloc::vec::Vec<$unsigned>) -> alloc::vec::Vec<Self> {
+                let mut values = core::mem::ManuallyDrop::new(values);
+                let (len, capacity) = (values.len(), values.capacity());
+                let ptr = Self::zigzag_decode_in_place(&mut values).as_mut_ptr();
+                // SAFETY: as in `zigzag_encode_vec_in_place`
+                unsafe { alloc::vec::Vec::from_raw_parts(ptr, len, capacity) }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_zigzag_in_place!(i8, u8);
+#[cfg(feature = "i16")]
+impl_zigzag_in_place!(i16, u16);
+#[cfg(feature = "i32")]
+impl_zigzag_in_place!(i32, u32);
+#[cfg(feature = "i64")]
+impl_zigzag_in_place!(i64, u64);
+#[cfg(feature = "i128")]
+impl_zigzag_in_place!(i128, u128);
+#[cfg(feature = "isize")]
+impl_zigzag_in_place!(isize, usize);
+
+/// Byte order of fixed-width values in a byte buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Endianness {
+    /// Least significant byte first, as most on-disk formats and CPUs use
+    #[default]
+    Little,
+    /// Most significant byte first, the network byte order
+    Big,
+    /// The byte order of the target
+    Native,
+}
+
+impl Endianness {
+    /// Whether values are stored least significant byte first on this target
+    pub const fn is_little(self) -> bool {
+        match self {
+            Endianness::Little => true,
+            Endianness::Big => false,
+            Endianness::Native => cfg!(target_endian = "little"),
+        }
+    }
+}
+
+/// ZigZag coding straight to and from byte buffers
+///
+/// Encoded values are usually written to a byte stream right away. These
+/// functions write each encoded value as its fixed-width bytes in the given
+/// [`Endianness`], and read them back, without an intermediate buffer of
+/// unsigned integers. The `le_bytes` functions are shorthands for
+/// [`Endianness::Little`]. `isize` is left out, as its byte width depends on
+/// the platform.
+///
+/// # Example
+/// ```
+/// # #[cfg(all(feature = "i16", feature = "i32"))] {
+/// use zigzag_rs::{Endianness, ZigZagBytes};
+///
+/// let mut bytes = [0u8; 8];
+/// assert_eq!(i32::zigzag_encode_slice_to_le_bytes(&[-1, 300], &mut bytes), Ok(8));
+/// assert_eq!(bytes, [1, 0, 0, 0, 0x58, 0x02, 0, 0]);
+///
+/// let mut values = [0i32; 2];
+/// assert_eq!(i32::zigzag_decode_slice_from_le_bytes(&bytes, &mut values), Ok(2));
+/// assert_eq!(values, [-1, 300]);
+///
+/// // Network byte order
+/// assert_eq!(i16::zigzag_encode_slice_to_bytes(&[300], &mut bytes, Endianness::Big), Ok(2));
+/// assert_eq!(bytes[..2], [0x02, 0x58]);
+/// # }
+/// ```
+pub trait ZigZagBytes: ZigZag + Sized {
+    /// Number of bytes of each encoded value
+    const SIZE: usize;
+
+    /// Encode `values` into `out` as consecutive values in the byte order `endianness`
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written, `values.len() * SIZE`
+    /// * `Err(ZigZagError::BufferTooSmall)` with the number of bytes needed if `out` is too small
+    fn zigzag_encode_slice_to_bytes(values: &[Self], out: &mut [u8], endianness: Endianness) -> Result<usize, ZigZagError>;
+
+    /// Decode consecutive values in the byte order `endianness`, written by
+    /// [`ZigZagBytes::zigzag_encode_slice_to_bytes`], into `out`
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of values decoded
+    /// * `Err(ZigZagError::UnexpectedEof)` with the offset of the last value if `input` ends inside it
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+    fn zigzag_decode_slice_from_bytes(input: &[u8], out: &mut [Self], endianness: Endianness) -> Result<usize, ZigZagError>;
+
+    /// Encode `values` into `out` as consecutive little-endian values
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written, `values.len() * SIZE`
+    /// * `Err(ZigZagError::BufferTooSmall)` with the number of bytes needed if `out` is too small
+    fn zigzag_encode_slice_to_le_bytes(values: &[Self], out: &mut [u8]) -> Result<usize, ZigZagError> {
+        Self::zigzag_encode_slice_to_bytes(values, out, Endianness::Little)
+    }
+
+    /// Decode consecutive little-endian values written by
+    /// [`ZigZagBytes::zigzag_encode_slice_to_le_bytes`] into `out`
+    ///
+    /// # Returns
+    /// * `Ok(count)` with the number of values decoded
+    /// * `Err(ZigZagError::UnexpectedEof)` with the offset of the last value if `input` ends inside it
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+    fn zigzag_decode_slice_from_le_bytes(input: &[u8], out: &mut [Self]) -> Result<usize, ZigZagError> {
+        Self::zigzag_decode_slice_from_bytes(input, out, Endianness::Little)
+    }
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_bytes {
+    ($signed:ty, $unsigned:ty) => {
+        impl ZigZagBytes for $signed {
+            const SIZE: usize = core::mem::size_of::<$unsigned>();
+
+            fn zigzag_encode_slice_to_bytes(values: &[Self], out: &mut [u8], endianness: Endianness) -> Result<usize, ZigZagError> {
+                let len = values.len() * Self::SIZE;
+                check_output_len(len, out.len())?;
+                let little = endianness.is_little();
+                for (bytes, &value) in out[..len].chunks_exact_mut(Self::SIZE).zip(values) {
+                    let encoded = <$signed>::zigzag_encode(value);
+                    bytes.copy_from_slice(&if little { encoded.to_le_bytes() } else { encoded.to_be_bytes() });
+                }
+                Ok(len)
+            }
+
+            fn zigzag_decode_slice_from_bytes(input: &[u8], out: &mut [Self], endianness: Endianness) -> Result<usize, ZigZagError> {
+                let count = input.len() / Self::SIZE;
+                if count * Self::SIZE != input.len() {
+                    return Err(ZigZagError::UnexpectedEof { offset: count * Self::SIZE });
+                }
+                check_output_len(count, out.len())?;
+                let little = endianness.is_little();
+                for (slot, bytes) in out.iter_mut().zip(input.chunks_exact(Self::SIZE)) {
+                    let mut word = [0u8; core::mem::size_of::<$unsigned>()];
+                    word.copy_from_slice(bytes);
+                    let encoded = if little { <$unsigned>::from_le_bytes(word) } else { <$unsigned>::from_be_bytes(word) };
+                    *slot = <$signed>::zigzag_decode(encoded);
+                }
+                Ok(count)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_zigzag_bytes!(i8, u8);
+#[cfg(feature = "i16")]
+impl_zigzag_bytes!(i16, u16);
+#[cfg(feature = "i32")]
+impl_zigzag_bytes!(i32, u32);
+#[cfg(feature = "i64")]
+impl_zigzag_bytes!(i64, u64);
+#[cfg(feature = "i128")]
+impl_zigzag_bytes!(i128, u128);
+
+/// Primitive signed integers, as one bound for generic numeric code
+///
+/// Generic compression code usually needs integer arithmetic and comparison
+/// next to [`ZigZag`], and varint support for the encoded form. This trait
+/// bundles those bounds for every native signed integer, so one `T: ZigZagPrim`
+/// replaces a list of bounds or per-type dispatch. Crates that use
+/// `num-traits` can combine it with their bounds, as in `T: PrimInt + ZigZagPrim`.
+///
+/// # Example
+/// ```
+/// # #[cfg(all(feature = "i32", feature = "i64"))] {
+/// use zigzag_rs::{varint::Varint, ZigZag, ZigZagPrim};
+///
+/// /// Bytes needed for the differences between consecutive values
+/// fn delta_cost<T: ZigZagPrim>(values: &[T]) -> usize {
+///     values.windows(2).map(|pair| T::zigzag_encode(pair[1] - pair[0]).varint_len()).sum()
+/// }
+///
+/// assert_eq!(delta_cost(&[100i32, 101, 99, 300]), 4);
+/// assert_eq!(delta_cost(&[0i64, i64::MAX]), 10);
+/// # }
+/// ```
+pub trait ZigZagPrim:
+    ZigZag<UInt = Self::Unsigned>
+    + Copy
+    + Ord
+    + Default
+    + core::hash::Hash
+    + core::fmt::Debug
+    + core::ops::Add<Output = Self>
+    + core::ops::Sub<Output = Self>
+    + core::ops::Mul<Output = Self>
+    + core::ops::Neg<Output = Self>
+    + Send
+    + Sync
+    + 'static
+{
+    /// The unsigned type of the same width, which values encode to
+    type Unsigned: varint::Varint + Copy + Ord + Default + core::hash::Hash + core::fmt::Debug + Send + Sync + 'static;
+
+    /// Width of the type in bits
+    const BITS: u32;
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_zigzag_prim {
+    ($signed:ty, $unsigned:ty) => {
+        impl ZigZagPrim for $signed {
+            type Unsigned = $unsigned;
+            const BITS: u32 = <$signed>::BITS;
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_zigzag_prim!(i8, u8);
+#[cfg(feature = "i16")]
+impl_zigzag_prim!(i16, u16);
+#[cfg(feature = "i32")]
+impl_zigzag_prim!(i32, u32);
+#[cfg(feature = "i64")]
+impl_zigzag_prim!(i64, u64);
+#[cfg(feature = "i128")]
+impl_zigzag_prim!(i128, u128);
+#[cfg(feature = "isize")]
+impl_zigzag_prim!(isize, usize);
+
+/// Signed integer types that fit into an `i64`
+///
+/// Codecs that predict values from their predecessors do their arithmetic in
+/// `i64` with wrapping semantics, which is lossless for every implementing type.
+pub trait Sample: ZigZag + Copy {
+    /// Widen the value to an `i64`
+    fn to_i64(self) -> i64;
+
+    /// Narrow an `i64` back to this type, truncating high bits
+    fn from_i64(value: i64) -> Self;
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_sample {
+    ($signed:ty) => {
+        impl Sample for $signed {
+            #[inline]
+            fn to_i64(self) -> i64 {
+                self as i64
+            }
+
+            #[inline]
+            fn from_i64(value: i64) -> Self {
+                value as Self
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_sample!(i8);
+#[cfg(feature = "i16")]
+impl_sample!(i16);
+#[cfg(feature = "i32")]
+impl_sample!(i32);
+#[cfg(feature = "i64")]
+impl_sample!(i64);
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
 #[cfg(test)]
+#[allow(unused_imports)]
 mod tests {
     use super::*;
     
     #[cfg(test)]
+    use std::vec;
     use std::vec::Vec;
     
     #[test]
+    #[cfg(feature = "i32")]
     fn test_encode_decode_i32() {
         // Test specific values
         assert_eq!(i32::zigzag_encode(0), 0u32);
@@ -354,6 +2224,7 @@ mod tests {
     }
     
     #[test]
+    #[cfg(feature = "i32")]
     fn test_encode_decode_slice_i32() {
         let values = [-100i32, -10, -1, 0, 1, 10, 100];
         let mut encoded = [0u32; 7];
@@ -371,6 +2242,7 @@ mod tests {
     }
     
     #[test]
+    #[cfg(feature = "i32")]
     fn test_try_encode_decode_slice() {
         let values = [-100i32, -10, -1, 0, 1, 10, 100];
         
@@ -401,6 +2273,7 @@ mod tests {
     }
     
     #[test]
+    #[cfg(feature = "i8")]
     fn test_encode_decode_i8() {
         // Test round-trip conversion for i8 type
         for i in i8::MIN..=i8::MAX {
@@ -411,6 +2284,7 @@ mod tests {
     }
     
     #[test]
+    #[cfg(feature = "i16")]
     fn test_encode_decode_i16() {
         // Test some i16 values
         for i in [-1000, -100, -1, 0, 1, 100, 1000].iter() {
@@ -421,6 +2295,7 @@ mod tests {
     }
     
     #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i64", feature = "isize"))]
     fn test_encode_decode_slice_all_types() {
         // Test i8
         let i8_values = [-100i8, -10, -1, 0, 1, 10, 100];
@@ -445,17 +2320,529 @@ mod tests {
         i64::zigzag_encode_slice(&i64_values, &mut i64_encoded);
         i64::zigzag_decode_slice(&i64_encoded, &mut i64_decoded);
         assert_eq!(i64_values, i64_decoded);
+
+        // Test isize
+        let isize_values = [isize::MIN, -100, -1, 0, 1, 100, isize::MAX];
+        let mut isize_encoded = [0usize; 7];
+        let mut isize_decoded = [0isize; 7];
+        isize::zigzag_encode_slice(&isize_values, &mut isize_encoded);
+        isize::zigzag_decode_slice(&isize_encoded, &mut isize_decoded);
+        assert_eq!(isize_values, isize_decoded);
+        assert_eq!(isize_encoded[0], usize::MAX);
     }
     
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32"))]
+    fn test_encode_decode_nonzero() {
+        use core::num::{NonZeroI32, NonZeroI8, NonZeroU32};
+
+        for i in (i8::MIN..=i8::MAX).filter_map(NonZeroI8::new) {
+            assert_eq!(NonZeroI8::zigzag_decode(NonZeroI8::zigzag_encode(i)), i);
+        }
+        let values = [NonZeroI32::MIN, NonZeroI32::new(-1).unwrap(), NonZeroI32::new(1).unwrap(), NonZeroI32::MAX];
+        let mut encoded = [NonZeroU32::MAX; 4];
+        let mut decoded = [NonZeroI32::MAX; 4];
+        NonZeroI32::zigzag_encode_slice(&values, &mut encoded);
+        assert_eq!(encoded.map(NonZeroU32::get), [u32::MAX, 1, 2, u32::MAX - 1]);
+        NonZeroI32::zigzag_decode_slice(&encoded, &mut decoded);
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_encode_decode_wrapping() {
+        use core::num::Wrapping;
+
+        let values = [Wrapping(i16::MIN), Wrapping(-1), Wrapping(0), Wrapping(1), Wrapping(i16::MAX)];
+        let mut encoded = [Wrapping(0u16); 5];
+        let mut decoded = [Wrapping(0i16); 5];
+        Wrapping::<i16>::zigzag_encode_slice(&values, &mut encoded);
+        assert_eq!(encoded, [Wrapping(u16::MAX), Wrapping(1), Wrapping(0), Wrapping(2), Wrapping(u16::MAX - 1)]);
+        Wrapping::<i16>::zigzag_decode_slice(&encoded, &mut decoded);
+        assert_eq!(decoded, values);
+
+        let sum: Wrapping<i16> = zigzag_decode_iter::<Wrapping<i16>, _>(&encoded).sum();
+        assert_eq!(sum, Wrapping(-1));
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_encode_decode_saturating() {
+        use core::num::Saturating;
+
+        let values = [Saturating(i32::MIN), Saturating(-70_000), Saturating(0), Saturating(i32::MAX)];
+        let encoded: Vec<Saturating<u32>> = zigzag_encode_iter::<Saturating<i32>, _>(&values).collect();
+        assert_eq!(encoded[1], Saturating(139_999));
+        let mut decoded = [Saturating(0i32); 4];
+        Saturating::<i32>::zigzag_decode_slice(&encoded, &mut decoded);
+        assert_eq!(decoded, values);
+        // Accumulating decoded values clamps instead of wrapping
+        assert_eq!(decoded[0] + decoded[1], Saturating(i32::MIN));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_derive_newtypes() {
+        #[derive(ZigZag, Debug, Clone, Copy, PartialEq)]
+        struct Tuple(pub i32);
+
+        #[derive(ZigZag, Debug, Clone, Copy, PartialEq)]
+        #[repr(transparent)]
+        pub(crate) struct Named {
+            /// Offset in bytes
+            pub(crate) bytes: core::num::Wrapping<i64>,
+        }
+
+        let values = [Tuple(i32::MIN), Tuple(-1), Tuple(7)];
+        let mut encoded = [0u32; 3];
+        Tuple::zigzag_encode_slice(&values, &mut encoded);
+        assert_eq!(encoded, [u32::MAX, 1, 14]);
+        let decoded: Vec<Tuple> = zigzag_decode_iter::<Tuple, _>(&encoded).collect();
+        assert_eq!(decoded, values);
+
+        let named = Named { bytes: core::num::Wrapping(-2) };
+        assert_eq!(Named::zigzag_encode(named), core::num::Wrapping(3u64));
+        assert_eq!(Named::zigzag_decode(core::num::Wrapping(3)), named);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64", feature = "i128"))]
+    fn test_derive_records() {
+        #[derive(ZigZagRecord, Debug, Clone, Copy, PartialEq)]
+        struct Sample3(i8, pub core::num::Wrapping<i64>, i128);
+
+        #[derive(ZigZagRecord, Debug, PartialEq)]
+        struct Imu {
+            /// Acceleration in milli-g on each axis
+            accel: [i16; 3],
+            gyro: i32,
+        }
+
+        let sample = Sample3(i8::MIN, core::num::Wrapping(-1), i128::MAX);
+        let encoded = sample.encode();
+        assert_eq!(encoded, Sample3Encoded(u8::MAX, core::num::Wrapping(1), u128::MAX - 1));
+        assert_eq!(Sample3::decode(encoded), sample);
+
+        let imu = Imu { accel: [-1000, 0, 1], gyro: -70_000 };
+        assert_eq!(imu.encode().accel, [1999, 0, 2]);
+        assert_eq!(imu.encode().gyro, 139_999);
+        assert_eq!(Imu::decode(imu.encode()), imu);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32", feature = "i64", feature = "isize"))]
+    fn test_zigzag_to_signed() {
+        assert_eq!(255u8.zigzag_to_signed(), i8::MIN);
+        assert_eq!(u64::MAX.zigzag_to_signed(), i64::MIN);
+        assert_eq!(4usize.zigzag_to_signed(), 2isize);
+        let encoded = [1u32, 0, 20];
+        let decoded: Vec<i32> = encoded.iter().map(|value| value.zigzag_to_signed()).collect();
+        assert_eq!(decoded, [-1, 0, 10]);
+        #[cfg(feature = "i128")]
+        for value in [i128::MIN, -1, i128::MAX] {
+            assert_eq!(i128::zigzag_encode(value).zigzag_to_signed(), value);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_zigzag_encode_widened() {
+        // Protobuf sint32 test vectors
+        for (value, expected) in [(0i32, 0u64), (-1, 1), (1, 2), (-2, 3), (i32::MAX, 0xffff_fffe), (i32::MIN, 0xffff_ffff)] {
+            assert_eq!(value.zigzag_encode_widened(), expected);
+            assert_eq!(i64::zigzag_encode(value as i64), expected);
+        }
+        assert_eq!(i8::MIN.zigzag_encode_widened(), 0xff);
+        assert_eq!((-300i16).zigzag_encode_widened(), 599);
+        assert_eq!(i64::MIN.zigzag_encode_widened(), u64::MAX);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32", feature = "i64"))]
+    fn test_try_zigzag_decode_narrow() {
+        assert_eq!(i8::try_zigzag_decode_narrow(0xff), Ok(i8::MIN));
+        assert_eq!(i8::try_zigzag_decode_narrow(0x100), Err(ZigZagError::OutOfRange { offset: 0 }));
+        assert_eq!(i32::try_zigzag_decode_narrow(0xffff_ffff), Ok(i32::MIN));
+        assert_eq!(i64::try_zigzag_decode_narrow(u64::MAX), Ok(i64::MIN));
+
+        let mut out = [0i32; 3];
+        assert_eq!(i32::try_zigzag_decode_narrow_slice(&[1, 2, 3], &mut out), Ok(()));
+        assert_eq!(out, [-1, 1, -2]);
+        assert_eq!(
+            i32::try_zigzag_decode_narrow_slice(&[1, 1 << 32, 3], &mut out),
+            Err(ZigZagError::OutOfRange { offset: 1 })
+        );
+        assert_eq!(
+            i32::try_zigzag_decode_narrow_slice(&[1, 2, 3], &mut out[..2]),
+            Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i64"))]
+    fn test_encode_decode_array() {
+        let values = [i16::MIN, -2, -1, 0, 1, i16::MAX];
+        let encoded = i16::zigzag_encode_array(&values);
+        let mut expected = [0u16; 6];
+        i16::zigzag_encode_slice(&values, &mut expected);
+        assert_eq!(encoded, expected);
+        assert_eq!(i16::zigzag_decode_array(&encoded), values);
+        assert_eq!(i64::zigzag_encode_array::<0>(&[]), [0u64; 0]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32"))]
+    fn test_encode_decode_array_type() {
+        let values = [[i8::MIN, -1], [0, i8::MAX]];
+        assert_eq!(<[[i8; 2]; 2]>::zigzag_encode(values), [[255, 1], [0, 254]]);
+
+        // Slices of arrays go through the same slice functions as integers
+        let mut encoded = [[0u32; 3]; 2];
+        <[i32; 3]>::zigzag_encode_slice(&[[1, -1, 2], [-2, 3, -3]], &mut encoded);
+        assert_eq!(encoded, [[2, 1, 4], [3, 6, 5]]);
+        let mut decoded = [[0i32; 3]; 2];
+        <[i32; 3]>::zigzag_decode_slice(&encoded, &mut decoded);
+        assert_eq!(decoded, [[1, -1, 2], [-2, 3, -3]]);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_encode_decode_slice_uninit() {
+        let values = [i32::MIN, -1, 0, 1, i32::MAX];
+        let mut encoded: Vec<u32> = Vec::with_capacity(values.len());
+        let written = i32::zigzag_encode_slice_uninit(&values, encoded.spare_capacity_mut()).len();
+        // SAFETY: `zigzag_encode_slice_uninit` initialized the first `written` elements
+        unsafe { encoded.set_len(written) };
+        assert_eq!(encoded, [u32::MAX, 1, 0, 2, u32::MAX - 1]);
+
+        let mut out = [core::mem::MaybeUninit::uninit(); 8];
+        assert_eq!(i32::zigzag_decode_slice_uninit(&encoded, &mut out), values);
+    }
+
+    #[test]
+    #[cfg(feature = "i8")]
+    #[should_panic(expected = "Output slice must be at least as large as input slice")]
+    fn test_encode_slice_uninit_output_too_small() {
+        let mut out = [core::mem::MaybeUninit::uninit(); 1];
+        i8::zigzag_encode_slice_uninit(&[1, 2], &mut out);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64", feature = "alloc"))]
+    fn test_encode_decode_vec() {
+        let values: Vec<i64> = vec![i64::MIN, -1, 0, i64::MAX];
+        let encoded = i64::zigzag_encode_vec(&values);
+        assert_eq!(encoded, [u64::MAX, 1, 0, u64::MAX - 1]);
+        assert_eq!(i64::zigzag_decode_vec(encoded.iter().copied()), values);
+        assert!(i8::zigzag_encode_vec(core::iter::empty::<i8>()).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_encode_decode_slice_partial() {
+        let values = [-1i16, 1, -2];
+        let mut packet = [0u16; 2];
+        assert_eq!(i16::zigzag_encode_slice_partial(&values, &mut packet), 2);
+        assert_eq!(packet, [1, 2]);
+        assert_eq!(i16::zigzag_encode_slice_partial(&values[2..], &mut packet), 1);
+        assert_eq!(packet, [3, 2]);
+
+        let mut decoded = [0i16; 4];
+        assert_eq!(i16::zigzag_decode_slice_partial(&packet, &mut decoded), 2);
+        assert_eq!(decoded, [-2, 1, 0, 0]);
+        assert_eq!(i16::zigzag_decode_slice_partial(&packet, &mut []), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_encode_decode_slice_unchecked() {
+        let values = [i16::MIN, -1, 0, 1, i16::MAX];
+        let mut encoded = [0u16; 6];
+        let mut decoded = [0i16; 5];
+        // SAFETY: both outputs hold at least five values
+        unsafe {
+            assert_eq!(i16::zigzag_encode_slice_unchecked(&values, &mut encoded), [u16::MAX, 1, 0, 2, u16::MAX - 1]);
+            assert_eq!(i16::zigzag_decode_slice_unchecked(&encoded[..5], &mut decoded), values);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_encode_decode_strided() {
+        let samples = [1i32, -1, 7, 2, -2, 8, 3, -3];
+        let mut encoded = [0u32; 3];
+        assert_eq!(i32::zigzag_encode_strided(&samples, 0, 3, &mut encoded), [2, 4, 6]);
+        // The last sample is incomplete, so the z component is shorter
+        assert_eq!(i32::zigzag_encode_strided(&samples, 2, 3, &mut encoded), [14, 16]);
+        assert!(i32::zigzag_encode_strided(&samples, 9, 3, &mut encoded).is_empty());
+
+        let mut restored = [0i32; 8];
+        for offset in 0..3 {
+            let mut column = [0u32; 3];
+            let column = i32::zigzag_encode_strided(&samples, offset, 3, &mut column);
+            i32::zigzag_decode_strided(column, offset, 3, &mut restored);
+        }
+        assert_eq!(restored, samples);
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_encode_decode_matrix_columns() {
+        // A 3x2 row-major matrix
+        let matrix = [1i64, -10, 2, -20, 3, -30];
+        let mut column = [0u64; 3];
+        assert_eq!(i64::zigzag_encode_strided(&matrix, 1, 2, &mut column), [19, 39, 59]);
+        let mut restored = [0i64; 6];
+        i64::zigzag_decode_strided(&column, 1, 2, &mut restored);
+        assert_eq!(restored, [0, -10, 0, -20, 0, -30]);
+    }
+
+    #[test]
+    #[cfg(feature = "i8")]
+    #[should_panic(expected = "Output slice must be at least as large as input slice")]
+    fn test_decode_strided_panics_on_short_component() {
+        let mut out = [0i8; 5];
+        i8::zigzag_decode_strided(&[1, 2, 3], 1, 2, &mut out);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i32"))]
+    fn test_encode_decode_field_columns() {
+        #[derive(Debug, Clone, Copy, Default, PartialEq)]
+        struct Fix {
+            lat: i32,
+            lon: i32,
+            alt: i16,
+        }
+
+        let fixes = [Fix { lat: -1, lon: 2, alt: -3 }, Fix { lat: i32::MIN, lon: 0, alt: i16::MAX }];
+        let mut lat = [0u32; 4];
+        let mut lon = [0u32; 2];
+        let mut alt = [0u16; 2];
+        assert_eq!(i32::zigzag_encode_field(&fixes, |fix| fix.lat, &mut lat), [1, u32::MAX]);
+        i32::zigzag_encode_field(&fixes, |fix| fix.lon, &mut lon);
+        i16::zigzag_encode_field(&fixes, |fix| fix.alt, &mut alt);
+        assert_eq!(alt, [5, u16::MAX - 1]);
+
+        let mut restored = [Fix::default(); 2];
+        i32::zigzag_decode_field(&lat[..2], &mut restored, |fix| &mut fix.lat);
+        i32::zigzag_decode_field(&lon, &mut restored, |fix| &mut fix.lon);
+        assert_eq!(restored[1], Fix { lat: i32::MIN, lon: 0, alt: 0 });
+        i16::zigzag_decode_field(&alt, &mut restored, |fix| &mut fix.alt);
+        assert_eq!(restored, fixes);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64", feature = "isize"))]
+    fn test_encode_decode_in_place() {
+        let values = [i8::MIN, -1, 0, 1, i8::MAX];
+        let mut buffer = values;
+        let mut expected = [0u8; 5];
+        i8::zigzag_encode_slice(&values, &mut expected);
+        assert_eq!(i8::zigzag_encode_in_place(&mut buffer), expected);
+        // The buffer now holds the encoded bit patterns
+        assert_eq!(buffer[0], -1);
+
+        let mut wide = [i64::MIN, -3, 3];
+        let encoded = i64::zigzag_encode_in_place(&mut wide);
+        assert_eq!(encoded, [u64::MAX, 5, 6]);
+        assert_eq!(i64::zigzag_decode_in_place(encoded), [i64::MIN, -3, 3]);
+        assert!(isize::zigzag_encode_in_place(&mut []).is_empty());
+    }
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "alloc"))]
+    fn test_encode_decode_vec_in_place() {
+        let mut values = Vec::with_capacity(8);
+        values.extend_from_slice(&[i32::MIN, -1, 0, i32::MAX]);
+        let ptr = values.as_ptr() as usize;
+        let encoded = i32::zigzag_encode_vec_in_place(values);
+        assert_eq!(encoded, [u32::MAX, 1, 0, u32::MAX - 1]);
+        assert_eq!((encoded.as_ptr() as usize, encoded.capacity()), (ptr, 8));
+        let decoded = i32::zigzag_decode_vec_in_place(encoded);
+        assert_eq!(decoded, [i32::MIN, -1, 0, i32::MAX]);
+        assert_eq!(decoded.as_ptr() as usize, ptr);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i64"))]
+    fn test_encode_decode_le_bytes() {
+        let values = [i16::MIN, -1, 0, 1, i16::MAX];
+        let mut bytes = [0u8; 12];
+        assert_eq!(i16::zigzag_encode_slice_to_le_bytes(&values, &mut bytes), Ok(10));
+        assert_eq!(bytes[..4], [0xff, 0xff, 0x01, 0x00]);
+        let mut decoded = [0i16; 5];
+        assert_eq!(i16::zigzag_decode_slice_from_le_bytes(&bytes[..10], &mut decoded), Ok(5));
+        assert_eq!(decoded, values);
+
+        assert_eq!(
+            i64::zigzag_encode_slice_to_le_bytes(&[1, 2], &mut bytes),
+            Err(ZigZagError::BufferTooSmall { needed: 16, actual: 12 })
+        );
+        assert_eq!(i16::zigzag_decode_slice_from_le_bytes(&bytes[..9], &mut decoded), Err(ZigZagError::UnexpectedEof { offset: 8 }));
+        assert_eq!(
+            i16::zigzag_decode_slice_from_le_bytes(&bytes[..10], &mut decoded[..4]),
+            Err(ZigZagError::BufferTooSmall { needed: 5, actual: 4 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_encode_decode_bytes_endianness() {
+        let values = [-2i32, 0x1234];
+        let mut little = [0u8; 8];
+        let mut big = [0u8; 8];
+        let mut native = [0u8; 8];
+        i32::zigzag_encode_slice_to_bytes(&values, &mut little, Endianness::Little).unwrap();
+        i32::zigzag_encode_slice_to_bytes(&values, &mut big, Endianness::Big).unwrap();
+        i32::zigzag_encode_slice_to_bytes(&values, &mut native, Endianness::Native).unwrap();
+        assert_eq!(big, [0, 0, 0, 3, 0, 0, 0x24, 0x68]);
+        assert_eq!(little[..4], [3, 0, 0, 0]);
+        assert_eq!(native, if cfg!(target_endian = "little") { little } else { big });
+
+        for endianness in [Endianness::Little, Endianness::Big, Endianness::Native] {
+            let mut bytes = [0u8; 8];
+            i32::zigzag_encode_slice_to_bytes(&values, &mut bytes, endianness).unwrap();
+            let mut decoded = [0i32; 2];
+            assert_eq!(i32::zigzag_decode_slice_from_bytes(&bytes, &mut decoded, endianness), Ok(2));
+            assert_eq!(decoded, values);
+        }
+        assert_eq!(
+            i64::zigzag_decode_slice_from_bytes(&big[..7], &mut [0i64; 1], Endianness::Big),
+            Err(ZigZagError::UnexpectedEof { offset: 0 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "isize"))]
+    fn test_zigzag_prim_generic_code() {
+        use crate::varint::Varint;
+
+        fn round_trip<T: ZigZagPrim>(values: &[T]) -> usize {
+            let mut buf = [0u8; 32];
+            let mut pos = 0;
+            for &value in values {
+                pos += T::zigzag_encode(value).encode_varint(&mut buf[pos..]).unwrap();
+            }
+            let mut read = 0;
+            for &value in values {
+                let (encoded, len) = T::Unsigned::decode_varint(&buf[read..]).unwrap();
+                assert_eq!(T::zigzag_decode(encoded), value);
+                read += len;
+            }
+            assert_eq!(read, pos);
+            pos
+        }
+        assert_eq!(round_trip(&[-1i8, i8::MIN]), 3);
+        #[cfg(feature = "i128")]
+        assert_eq!(round_trip(&[i128::MAX]), 19);
+        assert_eq!(round_trip(&[isize::MIN]), usize::MAX.varint_len());
+        assert_eq!(<i16 as ZigZagPrim>::BITS, 16);
+    }
+
     #[test]
     fn test_zigzag_error() {
         // Just test that we can create the error type and access its fields
         let error = ZigZagError::BufferTooSmall { needed: 10, actual: 5 };
         assert_eq!(error.needed(), 10);
         assert_eq!(error.actual(), 5);
+        assert_eq!(std::format!("{}", error), "buffer too small: 10 needed, 5 available");
+        let error = ZigZagError::OutOfRange { offset: 3 };
+        assert_eq!((error.offset(), error.needed()), (Some(3), 0));
+        assert_eq!(std::format!("{}", error), "value out of range at offset 3");
+    }
+    
+    #[test]
+    #[cfg(feature = "i32")]
+    #[should_panic(expected = "Output slice must be at least as large as input slice")]
+    fn test_encode_slice_panics_on_short_output() {
+        let mut out = [0u32; 1];
+        i32::zigzag_encode_slice(&[1, 2], &mut out);
+    }
+    
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_slice_functions_return_written_prefix() {
+        let mut encoded = [0u16; 8];
+        let written = i16::zigzag_encode_slice(&[-1, 2, -3], &mut encoded);
+        assert_eq!(written, [1, 4, 5]);
+        written[0] = 0;
+
+        let mut decoded = [0i16; 8];
+        let decoded = i16::try_zigzag_decode_slice(&encoded[..3], &mut decoded).unwrap();
+        assert_eq!(decoded, [0, 2, -3]);
+        // The result feeds straight into the next stage
+        let encoded = i16::try_zigzag_encode_slice(decoded, &mut encoded).unwrap();
+        assert_eq!(i16::zigzag_decode_slice(encoded, &mut [0; 3]), [0, 2, -3]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16"))]
+    fn test_encode_decode_chunks() {
+        let values: Vec<i16> = (-50..50).collect();
+        let mut encoded = Vec::new();
+        let mut lengths = Vec::new();
+        let result: Result<(), ()> = i16::zigzag_encode_chunks::<16, _, _>(&values, |chunk| {
+            lengths.push(chunk.len());
+            encoded.extend_from_slice(chunk);
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(lengths, [16, 16, 16, 16, 16, 16, 4]);
+
+        let mut decoded = Vec::new();
+        let result: Result<(), ()> = i16::zigzag_decode_chunks::<7, _, _>(&encoded, |chunk| {
+            decoded.extend_from_slice(chunk);
+            Ok(())
+        });
+        assert_eq!((result, decoded), (Ok(()), values));
+
+        // The first error from the sink stops the stream
+        let mut calls = 0;
+        let result = i8::zigzag_encode_chunks::<2, _, _>(&[1, 2, 3, 4, 5], |_| {
+            calls += 1;
+            if calls == 2 { Err("flash full") } else { Ok(()) }
+        });
+        assert_eq!((result, calls), (Err("flash full"), 2));
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_try_slice_error_reports_lengths() {
+        let mut out = [0i64; 2];
+        assert_eq!(
+            i64::try_zigzag_decode_slice(&[1, 2, 3], &mut out),
+            Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 })
+        );
+    }
+    
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_exact_slice_rejects_other_lengths() {
+        let values = [-2i16, 7, i16::MIN];
+        let mut encoded = [0u16; 3];
+        assert_eq!(i16::try_zigzag_encode_slice_exact(&values, &mut encoded), Ok(()));
+        assert_eq!(encoded, [3, 14, u16::MAX]);
+
+        let mut oversized = [0i16; 4];
+        let error = i16::try_zigzag_decode_slice_exact(&encoded, &mut oversized).unwrap_err();
+        assert_eq!(error, ZigZagError::LengthMismatch { expected: 3, actual: 4 });
+        assert_eq!((error.needed(), error.actual()), (3, 4));
+        assert_eq!(oversized, [0; 4]);
+        assert_eq!(
+            i16::try_zigzag_encode_slice_exact(&values, &mut encoded[..2]),
+            Err(ZigZagError::LengthMismatch { expected: 3, actual: 2 })
+        );
+
+        let mut decoded = [0i16; 3];
+        i16::try_zigzag_decode_slice_exact(&encoded, &mut decoded).unwrap();
+        assert_eq!(decoded, values);
     }
     
     #[test]
+    #[cfg(all(feature = "i8", feature = "i32"))]
     fn test_zigzag_encode_iter() {
         let values = [-100i32, -10, -1, 0, 1, 10, 100];
         
@@ -479,6 +2866,79 @@ mod tests {
     }
     
     #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32"))]
+    fn test_named_iter_adapters() {
+        struct Column<'a> {
+            encoded: ZigZagEncodeIter<core::slice::Iter<'a, i16>, i16>,
+        }
+
+        let values = [-1i16, 2, -3];
+        let mut column = Column { encoded: zigzag_encode_iter(values.iter()) };
+        assert_eq!(column.encoded.next(), Some(1));
+        let rest = column.encoded.clone();
+        assert_eq!(rest.collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(column.encoded.into_inner().as_slice(), [2, -3]);
+
+        let decoded: ZigZagDecodeIter<core::ops::Range<u8>, i8> = zigzag_decode_iter(0..3);
+        assert_eq!(std::format!("{:?}", decoded), "ZigZagDecodeIter { iter: 0..3 }");
+        assert_eq!(decoded.size_hint(), (3, Some(3)));
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded.rev().collect::<Vec<_>>(), [1, -1, 0]);
+
+        let mut encoded = zigzag_encode_iter::<i32, _>(&[-1, 1]);
+        assert_eq!(encoded.next_back(), Some(2));
+        assert_eq!(encoded.next(), Some(1));
+        assert_eq!(encoded.next(), None);
+        assert_eq!(encoded.next_back(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i64"))]
+    fn test_iter_ext_methods() {
+        let encoded: Vec<u64> = (-2i64..2).zigzag_encoded().collect();
+        assert_eq!(encoded, [3, 1, 0, 2]);
+        let total: i64 = encoded.iter().zigzag_decoded::<i64>().sum();
+        assert_eq!(total, -2);
+        let wrapped: Vec<core::num::Wrapping<i8>> =
+            [core::num::Wrapping(255u8)].iter().zigzag_decoded::<core::num::Wrapping<i8>>().collect();
+        assert_eq!(wrapped, [core::num::Wrapping(i8::MIN)]);
+        assert_eq!([i16::MIN].iter().zigzag_encoded().len(), 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32", feature = "i64"))]
+    fn test_encode_array_chunks() {
+        let chunks: Vec<[u32; 3]> = zigzag_encode_array_chunks::<i32, 3, _>(-3..3, PartialChunk::Hold).collect();
+        assert_eq!(chunks, [[5, 3, 1], [0, 2, 4]]);
+
+        let mut chunks = zigzag_encode_array_chunks::<i64, 4, _>([i64::MIN, -1], PartialChunk::Hold);
+        assert_eq!(chunks.next(), None);
+        assert_eq!(chunks.remainder(), [u64::MAX, 1]);
+        assert_eq!(chunks.next(), None);
+
+        let mut padded = zigzag_encode_array_chunks::<i8, 4, _>([1i8], PartialChunk::default());
+        assert_eq!(padded.next(), Some([2, 0, 0, 0]));
+        assert_eq!(padded.next(), None);
+        assert!(padded.remainder().is_empty());
+        assert_eq!(zigzag_encode_array_chunks::<i8, 0, _>([1i8], PartialChunk::Pad).next(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i64"))]
+    fn test_try_iter_adapters_pass_errors_through() {
+        let source = [Ok(i16::MIN), Err(ZigZagError::ChecksumMismatch { expected: 1, actual: 2 }), Ok(7)];
+        let encoded: Vec<_> = try_zigzag_encode_iter(source).collect();
+        assert_eq!(encoded, [Ok(u16::MAX), source[1].map(|_| 0), Ok(14)]);
+        let decoded: Vec<Result<i16, _>> = try_zigzag_decode_iter(encoded).rev().collect();
+        assert_eq!(decoded, [Ok(7), source[1], Ok(i16::MIN)]);
+
+        let ok: Result<Vec<u64>, ()> = try_zigzag_encode_iter([Ok(-1i64), Ok(1)]).collect();
+        assert_eq!(ok, Ok(std::vec![1, 2]));
+        assert_eq!(try_zigzag_decode_iter::<i8, (), _>([Ok(3u8)]).len(), 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i32"))]
     fn test_zigzag_decode_iter() {
         let encoded = [199u32, 19, 1, 0, 2, 20, 200];
         let expected = [-100i32, -10, -1, 0, 1, 10, 100];
@@ -495,8 +2955,24 @@ mod tests {
         let i16_decoded: Vec<i16> = zigzag_decode_iter::<i16, _>(i16_encoded.iter()).collect();
         assert_eq!(i16_decoded, i16_expected);
     }
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_iterators_accept_owned_values() {
+        // Ranges and mapped iterators yield values rather than references
+        let encoded: Vec<u32> = zigzag_encode_iter::<i32, _>((-3..3).map(|v| v * 10)).collect();
+        assert_eq!(encoded, [59, 39, 19, 0, 20, 40]);
+        let decoded: Vec<i32> = zigzag_decode_iter::<i32, _>(encoded.iter().copied()).collect();
+        assert_eq!(decoded, [-30, -20, -10, 0, 10, 20]);
+
+        // Owned collections and references to them
+        let values = vec![-1i64, 1];
+        assert_eq!(zigzag_encode_iter::<i64, _>(&values).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(zigzag_decode_iter::<i64, _>(vec![1u64, 2]).collect::<Vec<_>>(), values);
+    }
     
     #[test]
+    #[cfg(feature = "i16")]
     fn test_iterator_based_round_trip() {
         let original = [-1000i16, -100, -10, -1, 0, 1, 10, 100, 1000];
         
@@ -510,20 +2986,3 @@ mod tests {
         assert_eq!(original.to_vec(), decoded);
     }
 }
-
-// Add methods to ZigZagError to access fields without requiring std
-impl ZigZagError {
-    /// Get the needed buffer size
-    pub fn needed(&self) -> usize {
-        match self {
-            ZigZagError::BufferTooSmall { needed, .. } => *needed,
-        }
-    }
-    
-    /// Get the actual buffer size
-    pub fn actual(&self) -> usize {
-        match self {
-            ZigZagError::BufferTooSmall { actual, .. } => *actual,
-        }
-    }
-}
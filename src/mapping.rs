@@ -0,0 +1,288 @@
+//! Alternative mappings from signed to unsigned integers.
+//!
+//! ZigZag interleaves negative and positive values so that small magnitudes
+//! get small codes, at the cost of numeric order. Where encoded values are
+//! compared or sorted, as database keys are, [`OrderPreserving`] maps them in
+//! offset-binary instead: the sign bit is flipped, which adds `2^(N-1)`, so
+//! the codes sort like the values. [`SignMagnitude`] matches hardware
+//! protocols that send a sign bit followed by the magnitude.
+//!
+//! ```text
+//! value             MIN         -1           0         1           MAX
+//! ZigZagMapping     2^N - 1     1            0         2           2^N - 2
+//! OrderPreserving   0           2^(N-1) - 1  2^(N-1)   2^(N-1) + 1 2^N - 1
+//! SignMagnitude     -           2^(N-1) + 1  0         1           2^(N-1) - 1
+//! ```
+//!
+//! Every mapping implements [`Mapping`], which provides the slice and
+//! iterator functions, so code generic over the mapping lets the caller
+//! choose. Decoding cannot infer the signed type from a code, so it is named
+//! through the result or the trait. Sign-magnitude has no code for `MIN`
+//! and two for zero; the extra one, negative zero, decodes to 0.
+//!
+//! ```rust
+//! # #[cfg(all(feature = "i16", feature = "i32"))] {
+//! use zigzag_rs::mapping::{Mapping, OrderPreserving, ZigZagMapping};
+//!
+//! assert_eq!(OrderPreserving::encode(-1i16), 0x7fff);
+//! assert_eq!(ZigZagMapping::encode(-1i16), 1);
+//!
+//! let keys = [i32::MIN, -5, 0, 7, i32::MAX];
+//! let mut encoded = [0u32; 5];
+//! OrderPreserving::encode_slice(&keys, &mut encoded).unwrap();
+//! assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+//!
+//! let decoded: Vec<i32> = <OrderPreserving as Mapping<i32>>::decode_iter(encoded).collect();
+//! assert_eq!(decoded, keys);
+//! # }
+//! ```
+
+use core::borrow::Borrow;
+
+use crate::{ZigZag, ZigZagError};
+
+/// A bijection between the signed type `T` and its unsigned counterpart
+///
+/// The unsigned type is the one [`ZigZag`] uses for `T`.
+pub trait Mapping<T>
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy,
+{
+    /// Map a signed value to its unsigned code
+    ///
+    /// # Panics
+    /// Panics if the mapping has no code for `value`
+    fn encode(value: T) -> T::UInt;
+
+    /// Map a signed value to its unsigned code, if the mapping has one
+    ///
+    /// # Returns
+    /// * `Ok(code)` with the code of `value`
+    /// * `Err(ZigZagError::OutOfRange)` if the mapping cannot represent `value`
+    fn try_encode(value: T) -> Result<T::UInt, ZigZagError> {
+        Ok(Self::encode(value))
+    }
+
+    /// Map an unsigned code back to its signed value
+    fn decode(value: T::UInt) -> T;
+
+    /// Encode every value of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were encoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    /// * `Err(ZigZagError::OutOfRange)` with the index of the first value the mapping cannot represent
+    fn encode_slice(values: &[T], out: &mut [T::UInt]) -> Result<(), ZigZagError> {
+        if out.len() < values.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: values.len(), actual: out.len() });
+        }
+        for (index, (slot, &value)) in out.iter_mut().zip(values).enumerate() {
+            *slot = Self::try_encode(value).map_err(|_| ZigZagError::OutOfRange { offset: index })?;
+        }
+        Ok(())
+    }
+
+    /// Decode every code of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all codes were decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    fn decode_slice(values: &[T::UInt], out: &mut [T]) -> Result<(), ZigZagError> {
+        if out.len() < values.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: values.len(), actual: out.len() });
+        }
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = Self::decode(value);
+        }
+        Ok(())
+    }
+
+    /// Encode each value of an iterator on the fly
+    ///
+    /// # Panics
+    /// The iterator panics on a value the mapping cannot represent
+    fn encode_iter<I>(iter: I) -> impl Iterator<Item = T::UInt>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        iter.into_iter().map(|value| Self::encode(*value.borrow()))
+    }
+
+    /// Decode each code of an iterator on the fly
+    fn decode_iter<I>(iter: I) -> impl Iterator<Item = T>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T::UInt>,
+    {
+        iter.into_iter().map(|value| Self::decode(*value.borrow()))
+    }
+}
+
+/// The ZigZag mapping, through the [`ZigZag`] trait
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ZigZagMapping;
+
+impl<T> Mapping<T> for ZigZagMapping
+where
+    T: ZigZag + Copy,
+    T::UInt: Copy,
+{
+    #[inline]
+    fn encode(value: T) -> T::UInt {
+        T::zigzag_encode(value)
+    }
+
+    #[inline]
+    fn decode(value: T::UInt) -> T {
+        T::zigzag_decode(value)
+    }
+}
+
+/// Offset-binary mapping that keeps the order of the values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct OrderPreserving;
+
+#[allow(unused_macros)]
+macro_rules! impl_order_preserving {
+    ($signed:ty, $unsigned:ty) => {
+        impl Mapping<$signed> for OrderPreserving {
+            #[inline]
+            fn encode(value: $signed) -> $unsigned {
+                (value as $unsigned) ^ (1 << (<$unsigned>::BITS - 1))
+            }
+
+            #[inline]
+            fn decode(value: $unsigned) -> $signed {
+                (value ^ (1 << (<$unsigned>::BITS - 1))) as $signed
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_order_preserving!(i8, u8);
+#[cfg(feature = "i16")]
+impl_order_preserving!(i16, u16);
+#[cfg(feature = "i32")]
+impl_order_preserving!(i32, u32);
+#[cfg(feature = "i64")]
+impl_order_preserving!(i64, u64);
+#[cfg(feature = "i128")]
+impl_order_preserving!(i128, u128);
+#[cfg(feature = "isize")]
+impl_order_preserving!(isize, usize);
+
+/// Sign bit in the top bit, followed by the magnitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SignMagnitude;
+
+#[allow(unused_macros)]
+macro_rules! impl_sign_magnitude {
+    ($signed:ty, $unsigned:ty) => {
+        impl Mapping<$signed> for SignMagnitude {
+            #[inline]
+            fn encode(value: $signed) -> $unsigned {
+                match Self::try_encode(value) {
+                    Ok(code) => code,
+                    Err(_) => panic!("sign-magnitude cannot represent the minimum value"),
+                }
+            }
+
+            #[inline]
+            fn try_encode(value: $signed) -> Result<$unsigned, ZigZagError> {
+                const SIGN: $unsigned = 1 << (<$unsigned>::BITS - 1);
+                if value == <$signed>::MIN {
+                    return Err(ZigZagError::OutOfRange { offset: 0 });
+                }
+                Ok(if value < 0 { value.unsigned_abs() | SIGN } else { value as $unsigned })
+            }
+
+            #[inline]
+            fn decode(value: $unsigned) -> $signed {
+                const SIGN: $unsigned = 1 << (<$unsigned>::BITS - 1);
+                let magnitude = (value & !SIGN) as $signed;
+                if value & SIGN != 0 { -magnitude } else { magnitude }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_sign_magnitude!(i8, u8);
+#[cfg(feature = "i16")]
+impl_sign_magnitude!(i16, u16);
+#[cfg(feature = "i32")]
+impl_sign_magnitude!(i32, u32);
+#[cfg(feature = "i64")]
+impl_sign_magnitude!(i64, u64);
+#[cfg(feature = "i128")]
+impl_sign_magnitude!(i128, u128);
+#[cfg(feature = "isize")]
+impl_sign_magnitude!(isize, usize);
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64", feature = "isize"))]
+    fn test_order_preserving_sorts_like_values() {
+        let mut previous = None;
+        for value in i8::MIN..=i8::MAX {
+            let encoded = OrderPreserving::encode(value);
+            let decoded: i8 = OrderPreserving::decode(encoded);
+            assert_eq!(decoded, value);
+            assert!(previous.is_none_or(|previous| previous < encoded));
+            previous = Some(encoded);
+        }
+        assert_eq!(OrderPreserving::encode(0i64), 1 << 63);
+        #[cfg(feature = "i128")]
+        assert_eq!(OrderPreserving::encode(i128::MIN), 0);
+        assert_eq!(OrderPreserving::encode(isize::MAX), usize::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_mappings_share_slice_and_iterator_api() {
+        fn round_trip<M: Mapping<i32>>(values: &[i32]) -> Vec<i32> {
+            let mut encoded = [0u32; 4];
+            M::encode_slice(values, &mut encoded).unwrap();
+            M::decode_iter(&encoded[..values.len()]).collect()
+        }
+        let values = [i32::MIN, -1, 0, i32::MAX];
+        assert_eq!(round_trip::<ZigZagMapping>(&values), values);
+        assert_eq!(round_trip::<OrderPreserving>(&values), values);
+        assert_eq!(round_trip::<SignMagnitude>(&values[1..]), values[1..]);
+
+        let zigzag: Vec<u32> = ZigZagMapping::encode_iter(values).collect();
+        assert_eq!(zigzag, [u32::MAX, 1, 0, u32::MAX - 1]);
+        assert_eq!(
+            OrderPreserving::decode_slice(&[0u32; 3], &mut [0i32; 2]),
+            Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_sign_magnitude() {
+        for value in i8::MIN + 1..=i8::MAX {
+            let encoded = SignMagnitude::encode(value);
+            assert_eq!(encoded & 0x7f, value.unsigned_abs());
+            assert_eq!(encoded >> 7, (value < 0) as u8);
+            let decoded: i8 = SignMagnitude::decode(encoded);
+            assert_eq!(decoded, value);
+        }
+        // Negative zero
+        let decoded: i16 = SignMagnitude::decode(0x8000);
+        assert_eq!(decoded, 0);
+        assert_eq!(SignMagnitude::try_encode(i64::MIN), Err(ZigZagError::OutOfRange { offset: 0 }));
+        assert_eq!(
+            SignMagnitude::encode_slice(&[-3i32, i32::MIN], &mut [0u32; 2]),
+            Err(ZigZagError::OutOfRange { offset: 1 })
+        );
+    }
+}
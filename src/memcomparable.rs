@@ -0,0 +1,190 @@
+//! Memcomparable keys for signed integers.
+//!
+//! Key-value stores such as RocksDB and LMDB order keys by comparing their
+//! bytes, which matches neither the order of two's complement integers nor
+//! that of ZigZag varints. The keys written here compare like the values:
+//! each value is mapped with [`OrderPreserving`] and written big-endian at
+//! its full width, so composite keys are built by concatenating fields.
+//! Descending keys invert every byte, for indexes that scan newest first.
+//!
+//! ```text
+//! i16 key:  big-endian (value XOR 0x8000)         -1 -> 7f ff, 0 -> 80 00, 1 -> 80 01
+//! descending:  every byte of the ascending key inverted
+//! ```
+//!
+//! ```rust
+//! # #[cfg(all(feature = "i32", feature = "i64"))] {
+//! use zigzag_rs::memcomparable;
+//!
+//! let mut a = [0u8; 4];
+//! let mut b = [0u8; 4];
+//! memcomparable::encode(-2i32, &mut a).unwrap();
+//! memcomparable::encode(1i32, &mut b).unwrap();
+//! assert!(a < b);
+//! assert_eq!(memcomparable::decode::<i32>(&a), Ok((-2, 4)));
+//!
+//! // Composite key of a user id and a descending timestamp
+//! let mut key = [0u8; 12];
+//! let len = memcomparable::encode(42i32, &mut key).unwrap();
+//! memcomparable::encode_descending(1_700_000_000i64, &mut key[len..]).unwrap();
+//! assert_eq!(memcomparable::decode_descending::<i64>(&key[len..]), Ok((1_700_000_000, 8)));
+//! # }
+//! ```
+
+#[allow(unused_imports)]
+use crate::mapping::{Mapping, OrderPreserving};
+use crate::{ZigZag, ZigZagError};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Signed integers with a memcomparable key form
+///
+/// `isize` is left out, as its key length would depend on the platform.
+pub trait Memcomparable: ZigZag + Copy + sealed::Sealed {
+    /// Length of the key in bytes
+    const LEN: usize;
+
+    #[doc(hidden)]
+    fn write_key(self, out: &mut [u8]);
+    #[doc(hidden)]
+    fn read_key(input: &[u8]) -> Self;
+}
+
+#[allow(unused_macros)]
+macro_rules! impl_memcomparable {
+    ($signed:ty, $unsigned:ty) => {
+        impl sealed::Sealed for $signed {}
+
+        impl Memcomparable for $signed {
+            const LEN: usize = core::mem::size_of::<$signed>();
+
+            #[inline]
+            fn write_key(self, out: &mut [u8]) {
+                let code: $unsigned = OrderPreserving::encode(self);
+                out.copy_from_slice(&code.to_be_bytes());
+            }
+
+            #[inline]
+            fn read_key(input: &[u8]) -> Self {
+                let mut bytes = [0u8; core::mem::size_of::<$signed>()];
+                bytes.copy_from_slice(input);
+                OrderPreserving::decode(<$unsigned>::from_be_bytes(bytes))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+impl_memcomparable!(i8, u8);
+#[cfg(feature = "i16")]
+impl_memcomparable!(i16, u16);
+#[cfg(feature = "i32")]
+impl_memcomparable!(i32, u32);
+#[cfg(feature = "i64")]
+impl_memcomparable!(i64, u64);
+#[cfg(feature = "i128")]
+impl_memcomparable!(i128, u128);
+
+/// Write the ascending key of `value` to the start of `out`
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written, always `T::LEN`
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `T::LEN`
+pub fn encode<T: Memcomparable>(value: T, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if out.len() < T::LEN {
+        return Err(ZigZagError::BufferTooSmall { needed: T::LEN, actual: out.len() });
+    }
+    value.write_key(&mut out[..T::LEN]);
+    Ok(T::LEN)
+}
+
+/// Read an ascending key written by [`encode`] from the start of `input`
+///
+/// # Returns
+/// * `Ok((value, len))` with the value and the number of bytes consumed, always `T::LEN`
+/// * `Err(ZigZagError::UnexpectedEof)` if `input` is shorter than `T::LEN`
+pub fn decode<T: Memcomparable>(input: &[u8]) -> Result<(T, usize), ZigZagError> {
+    let key = input.get(..T::LEN).ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+    Ok((T::read_key(key), T::LEN))
+}
+
+/// Write the descending key of `value`, which sorts larger values first
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written, always `T::LEN`
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `T::LEN`
+pub fn encode_descending<T: Memcomparable>(value: T, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let len = encode(value, out)?;
+    invert(&mut out[..len]);
+    Ok(len)
+}
+
+/// Read a descending key written by [`encode_descending`] from the start of `input`
+///
+/// # Returns
+/// * `Ok((value, len))` with the value and the number of bytes consumed, always `T::LEN`
+/// * `Err(ZigZagError::UnexpectedEof)` if `input` is shorter than `T::LEN`
+pub fn decode_descending<T: Memcomparable>(input: &[u8]) -> Result<(T, usize), ZigZagError> {
+    let key = input.get(..T::LEN).ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+    // 16 bytes hold the key of any width
+    let mut bytes = [0u8; 16];
+    bytes[..T::LEN].copy_from_slice(key);
+    invert(&mut bytes[..T::LEN]);
+    Ok((T::read_key(&bytes[..T::LEN]), T::LEN))
+}
+
+fn invert(bytes: &mut [u8]) {
+    for byte in bytes {
+        *byte = !*byte;
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_keys_sort_like_values() {
+        let mut previous = [0u8; 2];
+        let mut previous_descending = [0u8; 2];
+        for value in i16::MIN..=i16::MAX {
+            let mut key = [0u8; 2];
+            let mut descending = [0u8; 2];
+            encode(value, &mut key).unwrap();
+            encode_descending(value, &mut descending).unwrap();
+            assert_eq!(decode::<i16>(&key), Ok((value, 2)));
+            assert_eq!(decode_descending::<i16>(&descending), Ok((value, 2)));
+            if value > i16::MIN {
+                assert!(previous < key);
+                assert!(previous_descending > descending);
+            }
+            previous = key;
+            previous_descending = descending;
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_key_bytes_and_errors() {
+        let mut key = [0u8; 16];
+        assert_eq!(encode(-1i32, &mut key), Ok(4));
+        assert_eq!(key[..4], [0x7f, 0xff, 0xff, 0xff]);
+        assert_eq!(encode(i64::MIN, &mut key), Ok(8));
+        assert_eq!(key[..8], [0; 8]);
+        assert_eq!(encode_descending(0i8, &mut key), Ok(1));
+        assert_eq!(key[0], 0x7f);
+        #[cfg(feature = "i128")]
+        {
+            assert_eq!(encode(i128::MAX, &mut key), Ok(16));
+            assert_eq!(decode::<i128>(&key), Ok((i128::MAX, 16)));
+        }
+
+        assert_eq!(encode(1i64, &mut key[..7]), Err(ZigZagError::BufferTooSmall { needed: 8, actual: 7 }));
+        assert_eq!(decode::<i32>(&key[..3]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert_eq!(decode_descending::<i16>(&[]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+    }
+}
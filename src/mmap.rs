@@ -0,0 +1,201 @@
+//! Memory-mapped container files.
+//!
+//! [`MappedFile`] maps a file read-only into memory. The blocks of a
+//! [`Container`] inside it are then viewed in place: pages are loaded by the
+//! operating system as they are touched, and nothing is copied into
+//! `read()` buffers. On 64-bit Unix targets the file is mapped with `mmap`.
+//! Elsewhere it is read into memory, which keeps the same API.
+//!
+//! Requires the `std` feature.
+//!
+//! ```rust,no_run
+//! use zigzag_rs::mmap::MappedFile;
+//!
+//! // SAFETY: nothing modifies or truncates the file while it is mapped
+//! let file = unsafe { MappedFile::open("readings.zzc")? };
+//! let container = file.container()?;
+//! for i in 0..container.len() {
+//!     let block = container.block::<i64>(i)?;
+//!     let sum: i64 = block.iter().sum();
+//!     println!("block {i}: {} values, sum {sum}", block.len());
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use crate::container::Container;
+use crate::ZigZagError;
+
+/// A file mapped read-only into memory
+#[derive(Debug)]
+pub struct MappedFile {
+    map: Map,
+}
+
+impl MappedFile {
+    /// Map the file at `path`
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated, by this process or any
+    /// other, while the returned value is alive. The mapping is private, but
+    /// changes to the file can still show through pages not yet copied, so the
+    /// bytes behind [`as_bytes`](Self::as_bytes) would change under a shared
+    /// reference, and truncation makes reads of them fault.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(MappedFile { map: Map::new(&file)? })
+    }
+
+    /// Contents of the file
+    pub fn as_bytes(&self) -> &[u8] {
+        self.map.as_bytes()
+    }
+
+    /// Parse the file as a [`Container`]
+    pub fn container(&self) -> Result<Container<'_>, ZigZagError> {
+        Container::parse(self.as_bytes())
+    }
+}
+
+#[cfg(all(unix, target_pointer_width = "64"))]
+use unix::Map;
+
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod unix {
+    use core::ffi::{c_int, c_void};
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Map {
+        ptr: *mut c_void,
+        len: usize,
+    }
+
+    // SAFETY: the mapping is read-only and owned by this value, so it can be
+    // moved to and shared between threads like a `Box<[u8]>`
+    unsafe impl Send for Map {}
+    // SAFETY: see `Send`
+    unsafe impl Sync for Map {}
+
+    impl Map {
+        pub(super) fn new(file: &File) -> io::Result<Self> {
+            let len = usize::try_from(file.metadata()?.len()).map_err(|_| io::Error::from(io::ErrorKind::OutOfMemory))?;
+            if len == 0 {
+                // mmap rejects empty mappings
+                return Ok(Map { ptr: core::ptr::null_mut(), len: 0 });
+            }
+            // SAFETY: a private read-only mapping of a valid descriptor, checked for failure below
+            let ptr = unsafe { mmap(core::ptr::null_mut(), len, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0) };
+            if ptr as isize == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Map { ptr, len })
+        }
+
+        pub(super) fn as_bytes(&self) -> &[u8] {
+            if self.len == 0 {
+                return &[];
+            }
+            // SAFETY: the mapping covers `len` readable bytes until it is dropped,
+            // and the caller of `MappedFile::open` promised not to modify or
+            // truncate the file while it is mapped
+            unsafe { core::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+
+    impl Drop for Map {
+        fn drop(&mut self) {
+            if self.len > 0 {
+                // SAFETY: `ptr` and `len` describe a mapping created by `new`
+                unsafe { munmap(self.ptr, self.len) };
+            }
+        }
+    }
+}
+
+#[cfg(not(all(unix, target_pointer_width = "64")))]
+use fallback::Map;
+
+#[cfg(not(all(unix, target_pointer_width = "64")))]
+mod fallback {
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::vec::Vec;
+
+    /// File contents read into memory, with words to keep the start 8-byte aligned
+    #[derive(Debug)]
+    pub(super) struct Map {
+        words: Vec<u64>,
+        len: usize,
+    }
+
+    impl Map {
+        pub(super) fn new(mut file: &File) -> io::Result<Self> {
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            let mut words = std::vec![0u64; bytes.len().div_ceil(8)];
+            for (word, chunk) in words.iter_mut().zip(bytes.chunks(8)) {
+                let mut buf = [0u8; 8];
+                buf[..chunk.len()].copy_from_slice(chunk);
+                *word = u64::from_ne_bytes(buf);
+            }
+            Ok(Map { words, len: bytes.len() })
+        }
+
+        pub(super) fn as_bytes(&self) -> &[u8] {
+            // SAFETY: the words hold at least `len` initialized bytes
+            unsafe { core::slice::from_raw_parts(self.words.as_ptr() as *const u8, self.len) }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::container;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_mapped_container() {
+        let values: Vec<i32> = (0..10_000).map(|i| (i % 251) - 125).collect();
+        let blocks: Vec<&[i32]> = values.chunks(4096).collect();
+        let mut bytes = vec![0u8; container::encoded_len(&blocks, 64)];
+        container::encode(&blocks, 64, &mut bytes).unwrap();
+
+        let path = std::env::temp_dir().join(std::format!("zigzag-rs-mmap-{}.zzc", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        // SAFETY: the file is only rewritten after `file` is dropped
+        let file = unsafe { MappedFile::open(&path) }.unwrap();
+        assert_eq!(file.as_bytes(), &bytes[..]);
+
+        let container = file.container().unwrap();
+        assert_eq!(container.len(), 3);
+        assert_eq!(container.block::<i32>(2).unwrap().get(10_000 - 8192 - 1), Some(values[9999]));
+        let decoded: Vec<i32> = (0..3).flat_map(|i| container.block::<i32>(i).unwrap().iter()).collect();
+        assert_eq!(decoded, values);
+        drop(file);
+
+        std::fs::write(&path, b"").unwrap();
+        // SAFETY: the file is removed only after `empty` is last used
+        let empty = unsafe { MappedFile::open(&path) }.unwrap();
+        assert_eq!(empty.as_bytes(), b"");
+        assert_eq!(empty.container().unwrap_err(), ZigZagError::UnexpectedEof { offset: 0 });
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,308 @@
+//! Mapbox Vector Tile geometry encoding.
+//!
+//! MVT geometries are streams of `u32` integers. A command integer holds the
+//! command id in its low 3 bits and a repeat count above them; `MoveTo` and
+//! `LineTo` are followed by `count` pairs of ZigZag encoded coordinate deltas
+//! relative to the current cursor, `ClosePath` has no parameters. The cursor
+//! carries over between commands (and between the parts of a multi-geometry).
+//!
+//! Error offsets reported by the decoder are indices into the integer stream.
+//!
+//! ```rust
+//! use zigzag_rs::mvt::{GeometryCommand, GeometryReader, GeometryWriter};
+//!
+//! // The polygon from the MVT specification examples
+//! let mut geometry = [0u32; 16];
+//! let mut writer = GeometryWriter::new(&mut geometry);
+//! writer.polygon_ring(&[(3, 6), (8, 12), (20, 34)]).unwrap();
+//! let len = writer.finish();
+//! assert_eq!(&geometry[..len], &[9, 6, 12, 18, 10, 12, 24, 44, 15]);
+//!
+//! let commands: Vec<_> = GeometryReader::new(&geometry[..len]).collect::<Result<_, _>>().unwrap();
+//! assert_eq!(commands, [
+//!     GeometryCommand::MoveTo(3, 6),
+//!     GeometryCommand::LineTo(8, 12),
+//!     GeometryCommand::LineTo(20, 34),
+//!     GeometryCommand::ClosePath,
+//! ]);
+//! ```
+
+use crate::{ZigZag, ZigZagError};
+
+/// Geometry command identifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Start a new part at the given position
+    MoveTo = 1,
+    /// Draw a line to the given position
+    LineTo = 2,
+    /// Close the current ring
+    ClosePath = 7,
+}
+
+impl Command {
+    fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(Command::MoveTo),
+            2 => Some(Command::LineTo),
+            7 => Some(Command::ClosePath),
+            _ => None,
+        }
+    }
+}
+
+/// Largest repeat count that fits into a command integer
+pub const MAX_COMMAND_COUNT: u32 = u32::MAX >> 3;
+
+/// Build a command integer from a command and its repeat count
+pub fn command_integer(command: Command, count: u32) -> u32 {
+    debug_assert!(count <= MAX_COMMAND_COUNT);
+    (command as u32 & 0x7) | (count << 3)
+}
+
+/// Split a command integer into its command and repeat count
+///
+/// # Returns
+/// * `Ok((command, count))` for a known command
+/// * `Err(ZigZagError::InvalidData)` if the command id is unknown
+pub fn parse_command_integer(value: u32) -> Result<(Command, u32), ZigZagError> {
+    let command = Command::from_id(value & 0x7).ok_or(ZigZagError::InvalidData { offset: 0 })?;
+    Ok((command, value >> 3))
+}
+
+/// Writes MVT geometry commands with absolute coordinates into a `u32` buffer
+#[derive(Debug)]
+pub struct GeometryWriter<'a> {
+    out: &'a mut [u32],
+    len: usize,
+    cursor: (i32, i32),
+}
+
+impl<'a> GeometryWriter<'a> {
+    /// Create a writer with the cursor at the tile origin
+    pub fn new(out: &'a mut [u32]) -> Self {
+        GeometryWriter { out, len: 0, cursor: (0, 0) }
+    }
+
+    /// Current cursor position
+    pub fn cursor(&self) -> (i32, i32) {
+        self.cursor
+    }
+
+    fn reserve(&mut self, count: usize) -> Result<(), ZigZagError> {
+        let needed = self.len + count;
+        if needed > self.out.len() {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: self.out.len() });
+        }
+        Ok(())
+    }
+
+    fn command(&mut self, command: Command, points: &[(i32, i32)]) -> Result<(), ZigZagError> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        assert!(points.len() as u64 <= MAX_COMMAND_COUNT as u64, "too many points for one command");
+        self.reserve(1 + 2 * points.len())?;
+
+        self.out[self.len] = command_integer(command, points.len() as u32);
+        self.len += 1;
+        for &(x, y) in points {
+            self.out[self.len] = i32::zigzag_encode(x.wrapping_sub(self.cursor.0));
+            self.out[self.len + 1] = i32::zigzag_encode(y.wrapping_sub(self.cursor.1));
+            self.len += 2;
+            self.cursor = (x, y);
+        }
+        Ok(())
+    }
+
+    /// Emit a `MoveTo` command for each point, as used by (multi)point geometries
+    pub fn move_to(&mut self, points: &[(i32, i32)]) -> Result<(), ZigZagError> {
+        self.command(Command::MoveTo, points)
+    }
+
+    /// Emit a `LineTo` command through all points
+    pub fn line_to(&mut self, points: &[(i32, i32)]) -> Result<(), ZigZagError> {
+        self.command(Command::LineTo, points)
+    }
+
+    /// Emit a `ClosePath` command
+    pub fn close_path(&mut self) -> Result<(), ZigZagError> {
+        self.reserve(1)?;
+        self.out[self.len] = command_integer(Command::ClosePath, 1);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Emit a line string: `MoveTo` to the first point and `LineTo` through the rest
+    pub fn line_string(&mut self, points: &[(i32, i32)]) -> Result<(), ZigZagError> {
+        if let Some((first, rest)) = points.split_first() {
+            let line_to_len = if rest.is_empty() { 0 } else { 1 + 2 * rest.len() };
+            self.reserve(3 + line_to_len)?;
+            self.move_to(core::slice::from_ref(first))?;
+            self.line_to(rest)?;
+        }
+        Ok(())
+    }
+
+    /// Emit a polygon ring and close it
+    ///
+    /// A trailing point equal to the first one is dropped, since `ClosePath`
+    /// returns to the start of the ring implicitly.
+    pub fn polygon_ring(&mut self, points: &[(i32, i32)]) -> Result<(), ZigZagError> {
+        let points = match points {
+            [first, .., last] if first == last => &points[..points.len() - 1],
+            _ => points,
+        };
+        if points.is_empty() {
+            return Ok(());
+        }
+        let line_to_len = if points.len() > 1 { 1 + 2 * (points.len() - 1) } else { 0 };
+        self.reserve(3 + line_to_len + 1)?;
+        self.line_string(points)?;
+        self.close_path()
+    }
+
+    /// Finish writing and return the number of integers written
+    pub fn finish(self) -> usize {
+        self.len
+    }
+}
+
+/// A decoded geometry command with absolute coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryCommand {
+    /// Start a new part at `(x, y)`
+    MoveTo(i32, i32),
+    /// Draw a line to `(x, y)`
+    LineTo(i32, i32),
+    /// Close the current ring
+    ClosePath,
+}
+
+/// Iterator over the commands of an MVT geometry stream
+///
+/// Repeated commands are expanded, so every `MoveTo`/`LineTo` item carries a
+/// single absolute position. Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct GeometryReader<'a> {
+    input: &'a [u32],
+    pos: usize,
+    command: Command,
+    remaining: u32,
+    cursor: (i32, i32),
+}
+
+impl<'a> GeometryReader<'a> {
+    /// Create a reader over a geometry stream
+    pub fn new(input: &'a [u32]) -> Self {
+        GeometryReader { input, pos: 0, command: Command::ClosePath, remaining: 0, cursor: (0, 0) }
+    }
+
+    fn fail(&mut self, err: ZigZagError) -> Option<Result<GeometryCommand, ZigZagError>> {
+        self.pos = self.input.len();
+        self.remaining = 0;
+        Some(Err(err))
+    }
+}
+
+impl Iterator for GeometryReader<'_> {
+    type Item = Result<GeometryCommand, ZigZagError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            let &value = self.input.get(self.pos)?;
+            match parse_command_integer(value) {
+                Ok((command, count)) if count > 0 => {
+                    self.command = command;
+                    self.remaining = count;
+                    self.pos += 1;
+                }
+                _ => return self.fail(ZigZagError::InvalidData { offset: self.pos }),
+            }
+        }
+
+        self.remaining -= 1;
+        if self.command == Command::ClosePath {
+            return Some(Ok(GeometryCommand::ClosePath));
+        }
+        if self.pos + 2 > self.input.len() {
+            let offset = self.pos;
+            return self.fail(ZigZagError::UnexpectedEof { offset });
+        }
+        let dx = i32::zigzag_decode(self.input[self.pos]);
+        let dy = i32::zigzag_decode(self.input[self.pos + 1]);
+        self.pos += 2;
+        self.cursor = (self.cursor.0.wrapping_add(dx), self.cursor.1.wrapping_add(dy));
+        let (x, y) = self.cursor;
+        Some(Ok(match self.command {
+            Command::MoveTo => GeometryCommand::MoveTo(x, y),
+            _ => GeometryCommand::LineTo(x, y),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn read_all(geometry: &[u32]) -> Result<Vec<GeometryCommand>, ZigZagError> {
+        GeometryReader::new(geometry).collect()
+    }
+
+    #[test]
+    fn test_mvt_specification_examples() {
+        // Multi point: (5, 7) and (3, 2)
+        let mut geometry = [0u32; 16];
+        let mut writer = GeometryWriter::new(&mut geometry);
+        writer.move_to(&[(5, 7), (3, 2)]).unwrap();
+        let len = writer.finish();
+        assert_eq!(&geometry[..len], &[17, 10, 14, 3, 9]);
+
+        // Line string: (2, 2), (2, 10), (10, 10)
+        let mut writer = GeometryWriter::new(&mut geometry);
+        writer.line_string(&[(2, 2), (2, 10), (10, 10)]).unwrap();
+        let len = writer.finish();
+        assert_eq!(&geometry[..len], &[9, 4, 4, 18, 0, 16, 16, 0]);
+        assert_eq!(
+            read_all(&geometry[..len]),
+            Ok(std::vec![
+                GeometryCommand::MoveTo(2, 2),
+                GeometryCommand::LineTo(2, 10),
+                GeometryCommand::LineTo(10, 10),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_mvt_cursor_carries_over_parts() {
+        let mut geometry = [0u32; 32];
+        let mut writer = GeometryWriter::new(&mut geometry);
+        writer.polygon_ring(&[(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]).unwrap();
+        writer.polygon_ring(&[(11, 11), (20, 11), (20, 20)]).unwrap();
+        assert_eq!(writer.cursor(), (20, 20));
+        let len = writer.finish();
+
+        let commands = read_all(&geometry[..len]).unwrap();
+        assert_eq!(commands.len(), 4 + 1 + 3 + 1);
+        assert_eq!(commands[5], GeometryCommand::MoveTo(11, 11));
+        assert_eq!(commands[8], GeometryCommand::ClosePath);
+    }
+
+    #[test]
+    fn test_mvt_errors() {
+        assert_eq!(parse_command_integer(command_integer(Command::LineTo, 3)), Ok((Command::LineTo, 3)));
+        assert_eq!(parse_command_integer(3), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(read_all(&[9, 4]), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+        assert_eq!(read_all(&[9, 4, 4, 4]), Err(ZigZagError::InvalidData { offset: 3 }));
+
+        let mut small = [0u32; 4];
+        let mut writer = GeometryWriter::new(&mut small);
+        assert_eq!(
+            writer.line_string(&[(1, 1), (2, 2)]),
+            Err(ZigZagError::BufferTooSmall { needed: 6, actual: 4 })
+        );
+        // Nothing was written for the failed command
+        assert_eq!(writer.finish(), 0);
+    }
+}
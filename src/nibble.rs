@@ -0,0 +1,200 @@
+//! Nibble varints for tightly constrained radio payloads.
+//!
+//! A regular varint spends at least a byte per value. Where values are
+//! usually tiny, as in LoRa or BLE advertising payloads, this module writes
+//! ZigZag values in base 8 instead: each nibble holds a continuation bit and
+//! three data bits, least significant group first, and two nibbles share a
+//! byte. Values from -4 to 3 take half a byte.
+//!
+//! ```text
+//! nibble: continuation (1 bit) | data (3 bits)
+//! byte:   first nibble (high half) | second nibble (low half)
+//! ```
+//!
+//! Nibbles go through [`BitWriter`] and [`BitReader`], so they can be mixed
+//! with other bit fields. A stream does not record how many values it holds,
+//! the reader has to know; an odd number of nibbles leaves the low half of
+//! the last byte zero.
+//!
+//! ```rust
+//! # #[cfg(feature = "i16")] {
+//! use zigzag_rs::nibble;
+//!
+//! let readings = [0i16, -1, 2, 3, -20];
+//! let mut buf = [0u8; 8];
+//! let len = nibble::encode_slice(&readings, &mut buf).unwrap();
+//! assert_eq!(len, 3);
+//!
+//! let mut decoded = [0i16; 5];
+//! assert_eq!(nibble::decode_slice(&buf[..len], &mut decoded), Ok(len));
+//! assert_eq!(decoded, readings);
+//! # }
+//! ```
+
+use crate::bits::{BitReader, BitWriter};
+use crate::{ZigZag, ZigZagError};
+
+/// Data bits per nibble
+const DATA_BITS: u32 = 3;
+const CONTINUATION: u64 = 1 << DATA_BITS;
+
+/// Width of the unsigned ZigZag form of `T` in bits
+fn uint_bits<T: ZigZag>() -> u32 {
+    (core::mem::size_of::<T::UInt>() * 8) as u32
+}
+
+/// Number of nibbles `value` occupies
+pub fn nibble_len<T>(value: T) -> usize
+where
+    T: ZigZag,
+    T::UInt: Into<u64>,
+{
+    let used = 64 - T::zigzag_encode(value).into().leading_zeros();
+    (used.max(1) as usize).div_ceil(DATA_BITS as usize)
+}
+
+/// Number of bytes [`encode_slice`] needs for `values`
+pub fn encoded_len<T>(values: &[T]) -> usize
+where
+    T: ZigZag + Copy,
+    T::UInt: Into<u64>,
+{
+    values.iter().map(|&value| nibble_len(value)).sum::<usize>().div_ceil(2)
+}
+
+/// Write `value` as a nibble varint
+///
+/// # Returns
+/// * `Ok(())` if the nibbles were written
+/// * `Err(ZigZagError::BufferTooSmall)` if the writer has no room for them
+pub fn write<T>(writer: &mut BitWriter<'_>, value: T) -> Result<(), ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Into<u64>,
+{
+    let mut rest = T::zigzag_encode(value).into();
+    loop {
+        let group = rest & (CONTINUATION - 1);
+        rest >>= DATA_BITS;
+        if rest == 0 {
+            return writer.write(group, 4);
+        }
+        writer.write(group | CONTINUATION, 4)?;
+    }
+}
+
+/// Read a nibble varint
+///
+/// # Returns
+/// * `Ok(value)` with the decoded value
+/// * `Err(ZigZagError::UnexpectedEof)` if the input ends in the middle of the value
+/// * `Err(ZigZagError::VarintOverflow)` with the offset of the byte holding the first
+///   nibble if the value does not fit into `T`
+pub fn read<T>(reader: &mut BitReader<'_>) -> Result<T, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: TryFrom<u64>,
+{
+    let start = reader.bit_pos() / 8;
+    let bits = uint_bits::<T>();
+    let max_len = bits.div_ceil(DATA_BITS);
+    let mut value = 0u64;
+    for i in 0..max_len {
+        let nibble = reader.read(4)?;
+        let group = nibble & (CONTINUATION - 1);
+        if i == max_len - 1 {
+            // The last possible nibble may only carry the remaining high bits
+            let remaining = bits - DATA_BITS * (max_len - 1);
+            if nibble & CONTINUATION != 0 || group >> remaining != 0 {
+                return Err(ZigZagError::VarintOverflow { offset: start });
+            }
+        }
+        value |= group << (DATA_BITS * i);
+        if nibble & CONTINUATION == 0 {
+            break;
+        }
+    }
+    let value = T::UInt::try_from(value).map_err(|_| ZigZagError::VarintOverflow { offset: start })?;
+    Ok(T::zigzag_decode(value))
+}
+
+/// Encode `values` as consecutive nibble varints
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written, including a half-used last byte
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode_slice<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Into<u64>,
+{
+    let needed = encoded_len(values);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let mut writer = BitWriter::new(&mut out[..needed]);
+    for &value in values {
+        write(&mut writer, value)?;
+    }
+    Ok(writer.finish())
+}
+
+/// Decode `out.len()` nibble varints from the start of `input`
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes consumed, including a half-used last byte
+/// * `Err(ZigZagError)` with the byte offset of the offending value if `input` is malformed
+pub fn decode_slice<T>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: TryFrom<u64>,
+{
+    let mut reader = BitReader::new(input);
+    for slot in out.iter_mut() {
+        *slot = read(&mut reader)?;
+    }
+    Ok(reader.byte_len())
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64"))]
+    fn test_nibble_round_trip() {
+        let values = [0i64, -4, 3, 4, -5, 1000, -1000, i64::MIN, i64::MAX, 1];
+        let mut buf = [0u8; 64];
+        let len = encode_slice(&values, &mut buf).unwrap();
+        assert_eq!(len, encoded_len(&values));
+        let mut decoded = [0i64; 10];
+        assert_eq!(decode_slice(&buf[..len], &mut decoded), Ok(len));
+        assert_eq!(decoded, values);
+
+        assert_eq!(nibble_len(3i8), 1);
+        assert_eq!(nibble_len(-5i8), 2);
+        assert_eq!(nibble_len(i64::MIN), 22);
+        for value in [i8::MIN, -1, 0, i8::MAX] {
+            let len = encode_slice(&[value, value], &mut buf).unwrap();
+            let mut decoded = [0i8; 2];
+            assert_eq!(decode_slice(&buf[..len], &mut decoded), Ok(len));
+            assert_eq!(decoded, [value, value]);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32"))]
+    fn test_nibble_packing_and_errors() {
+        // 1 -> 0x2, -3 -> 5 = 0x5, 4 -> 8 = 0x8 0x1
+        let mut buf = [0u8; 2];
+        assert_eq!(encode_slice(&[1i32, -3, 4], &mut buf), Ok(2));
+        assert_eq!(buf, [0x25, 0x81]);
+        assert_eq!(encode_slice(&[1i32, -3, 4], &mut buf[..1]), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+
+        assert_eq!(decode_slice(&[0x88], &mut [0i32; 1]), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+        // Three nibbles carry 9 bits, one more than the 8 of an i8
+        assert_eq!(decode_slice(&[0xff, 0x40], &mut [0i8; 1]), Err(ZigZagError::VarintOverflow { offset: 0 }));
+        assert_eq!(decode_slice(&[0xff, 0x30], &mut [0i8; 1]), Ok(2));
+    }
+}
@@ -0,0 +1,326 @@
+//! OpenStreetMap PBF `DenseNodes` compatibility.
+//!
+//! `DenseNodes` store node ids, latitudes and longitudes as parallel packed
+//! `sint64` arrays, each delta coded: a packed field is a run of ZigZag varints
+//! of the differences between consecutive entries. `keys_vals` is a packed
+//! `int32` array of string table indices (not delta coded), with `0` ending
+//! the tags of each node.
+//!
+//! [`DenseNodes::encode`] writes the protobuf message body and [`decode`]
+//! parses it again. `DenseInfo` (field 5) is skipped by the decoder; its
+//! delta coded columns can be produced and read with [`encode_packed_delta`]
+//! and [`decode_packed_delta`].
+//!
+//! ```rust
+//! use zigzag_rs::osm::{self, DenseNodes, DenseNodesBuf};
+//!
+//! let nodes = DenseNodes {
+//!     ids: &[100, 101, 105],
+//!     lats: &[515_000_000, 515_000_100, 514_999_950],
+//!     lons: &[-1_000_000, -999_800, -999_900],
+//!     keys_vals: &[1, 2, 0, 0, 0],
+//! };
+//! let mut message = [0u8; 64];
+//! let len = nodes.encode(&mut message).unwrap();
+//!
+//! let (mut ids, mut lats, mut lons, mut keys_vals) = ([0; 3], [0; 3], [0; 3], [0; 5]);
+//! let mut buf = DenseNodesBuf { ids: &mut ids, lats: &mut lats, lons: &mut lons, keys_vals: &mut keys_vals };
+//! assert_eq!(osm::decode(&message[..len], &mut buf), Ok((3, 5)));
+//! assert_eq!(lats, [515_000_000, 515_000_100, 514_999_950]);
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{delta, Sample, ZigZagError};
+
+const FIELD_ID: u64 = 1;
+const FIELD_LAT: u64 = 8;
+const FIELD_LON: u64 = 9;
+const FIELD_KEYS_VALS: u64 = 10;
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_FIXED64: u64 = 1;
+const WIRE_LENGTH_DELIMITED: u64 = 2;
+const WIRE_FIXED32: u64 = 5;
+
+/// Default coordinate granularity of OSM PBF files, in nanodegrees
+pub const DEFAULT_GRANULARITY: i32 = 100;
+
+/// Convert a stored latitude or longitude to degrees
+///
+/// # Arguments
+/// * `raw` - Decoded `lat`/`lon` entry
+/// * `granularity` - Granularity of the enclosing `PrimitiveBlock`, in nanodegrees
+/// * `offset` - `lat_offset`/`lon_offset` of the enclosing `PrimitiveBlock`, in nanodegrees
+pub fn to_degrees(raw: i64, granularity: i32, offset: i64) -> f64 {
+    1e-9 * (offset + granularity as i64 * raw) as f64
+}
+
+/// Borrowed columns of a `DenseNodes` message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DenseNodes<'a> {
+    /// Node ids
+    pub ids: &'a [i64],
+    /// Latitudes in units of the block granularity
+    pub lats: &'a [i64],
+    /// Longitudes in units of the block granularity
+    pub lons: &'a [i64],
+    /// String table indices of tag keys and values, `0` terminates each node
+    pub keys_vals: &'a [i32],
+}
+
+/// Output buffers for [`decode`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct DenseNodesBuf<'a> {
+    /// Node ids
+    pub ids: &'a mut [i64],
+    /// Latitudes in units of the block granularity
+    pub lats: &'a mut [i64],
+    /// Longitudes in units of the block granularity
+    pub lons: &'a mut [i64],
+    /// String table indices of tag keys and values
+    pub keys_vals: &'a mut [i32],
+}
+
+/// Number of bytes of a length-delimited field with a payload of `payload_len` bytes
+fn field_len(field: u64, payload_len: usize) -> usize {
+    if payload_len == 0 {
+        return 0;
+    }
+    (field << 3).varint_len() + (payload_len as u64).varint_len() + payload_len
+}
+
+fn keys_vals_len(keys_vals: &[i32]) -> usize {
+    keys_vals.iter().map(|&value| (value as i64 as u64).varint_len()).sum()
+}
+
+impl DenseNodes<'_> {
+    /// Number of bytes [`DenseNodes::encode`] writes
+    pub fn encoded_len(&self) -> usize {
+        field_len(FIELD_ID, delta::encoded_len(self.ids))
+            + field_len(FIELD_LAT, delta::encoded_len(self.lats))
+            + field_len(FIELD_LON, delta::encoded_len(self.lons))
+            + field_len(FIELD_KEYS_VALS, keys_vals_len(self.keys_vals))
+    }
+
+    /// Write the `DenseNodes` message body
+    ///
+    /// Empty columns are omitted, as protobuf encoders do for empty packed fields.
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+    ///
+    /// # Panics
+    /// Panics if `ids`, `lats` and `lons` have different lengths
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+        assert!(
+            self.ids.len() == self.lats.len() && self.ids.len() == self.lons.len(),
+            "DenseNodes columns must have the same length"
+        );
+        let needed = self.encoded_len();
+        if out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+        }
+
+        let mut pos = 0;
+        pos += write_delta_field(FIELD_ID, self.ids, &mut out[pos..])?;
+        pos += write_delta_field(FIELD_LAT, self.lats, &mut out[pos..])?;
+        pos += write_delta_field(FIELD_LON, self.lons, &mut out[pos..])?;
+
+        let payload_len = keys_vals_len(self.keys_vals);
+        if payload_len > 0 {
+            pos += write_field_header(FIELD_KEYS_VALS, payload_len, &mut out[pos..])?;
+            for &value in self.keys_vals {
+                // int32 fields sign extend negative values to 64 bits
+                pos += (value as i64 as u64).encode_varint(&mut out[pos..])?;
+            }
+        }
+        Ok(pos)
+    }
+}
+
+fn write_field_header(field: u64, payload_len: usize, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let len = ((field << 3) | WIRE_LENGTH_DELIMITED).encode_varint(out)?;
+    Ok(len + (payload_len as u64).encode_varint(&mut out[len..])?)
+}
+
+fn write_delta_field(field: u64, values: &[i64], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let payload_len = delta::encoded_len(values);
+    if payload_len == 0 {
+        return Ok(0);
+    }
+    let header_len = write_field_header(field, payload_len, out)?;
+    Ok(header_len + delta::encode(values, &mut out[header_len..])?)
+}
+
+/// Encode a delta coded packed `sint64`/`sint32` payload
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode_packed_delta<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    delta::encode(values, out)
+}
+
+/// Decode a delta coded packed `sint64`/`sint32` payload
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError)` if `out` is too small or the payload is malformed
+pub fn decode_packed_delta<T>(payload: &[u8], out: &mut [T]) -> Result<usize, ZigZagError>
+where
+    T: Sample,
+    T::UInt: Varint,
+{
+    delta::decode(payload, out)
+}
+
+/// Parse a `DenseNodes` message body into `buf`
+///
+/// Unknown fields, including `denseinfo`, are skipped.
+///
+/// # Returns
+/// * `Ok((nodes, keys_vals))` with the number of nodes and `keys_vals` entries decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if a buffer in `buf` is too small
+/// * `Err(ZigZagError::InvalidData)` if the columns have different lengths or a field is malformed
+/// * `Err(ZigZagError::UnexpectedEof)` if the message is truncated
+pub fn decode(input: &[u8], buf: &mut DenseNodesBuf<'_>) -> Result<(usize, usize), ZigZagError> {
+    let (mut ids, mut lats, mut lons, mut keys_vals) = (0, 0, 0, 0);
+    let mut pos = 0;
+    while pos < input.len() {
+        let field_start = pos;
+        let (key, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        pos += len;
+
+        let field = key >> 3;
+        match key & 0x7 {
+            WIRE_LENGTH_DELIMITED => {
+                let (payload_len, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+                pos += len;
+                let end = pos.checked_add(payload_len as usize).filter(|&end| end <= input.len())
+                    .ok_or(ZigZagError::UnexpectedEof { offset: field_start })?;
+                let payload = &input[pos..end];
+                let result = match field {
+                    FIELD_ID => decode_column(payload, buf.ids, &mut ids),
+                    FIELD_LAT => decode_column(payload, buf.lats, &mut lats),
+                    FIELD_LON => decode_column(payload, buf.lons, &mut lons),
+                    FIELD_KEYS_VALS => decode_keys_vals(payload, buf.keys_vals, &mut keys_vals),
+                    _ => Ok(()),
+                };
+                result.map_err(|err| varint::at_offset(err, pos))?;
+                pos = end;
+            }
+            WIRE_VARINT => {
+                let (_, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+                pos += len;
+            }
+            WIRE_FIXED64 | WIRE_FIXED32 => {
+                pos += if key & 0x7 == WIRE_FIXED64 { 8 } else { 4 };
+                if pos > input.len() {
+                    return Err(ZigZagError::UnexpectedEof { offset: field_start });
+                }
+            }
+            _ => return Err(ZigZagError::InvalidData { offset: field_start }),
+        }
+    }
+
+    if ids != lats || ids != lons {
+        return Err(ZigZagError::InvalidData { offset: input.len() });
+    }
+    Ok((ids, keys_vals))
+}
+
+/// Decode a packed delta column, continuing after entries of an earlier occurrence of the field
+fn decode_column(payload: &[u8], out: &mut [i64], count: &mut usize) -> Result<(), ZigZagError> {
+    let start = *count;
+    let decoded = delta::decode(payload, &mut out[start..]).map_err(|err| match err {
+        ZigZagError::BufferTooSmall { needed, .. } => ZigZagError::BufferTooSmall { needed: start + needed, actual: out.len() },
+        other => other,
+    })?;
+    // Repeated packed fields are concatenated, and the deltas continue across them
+    if let Some(&prev) = start.checked_sub(1).map(|i| &out[i]) {
+        for value in out[start..start + decoded].iter_mut() {
+            *value = value.wrapping_add(prev);
+        }
+    }
+    *count += decoded;
+    Ok(())
+}
+
+fn decode_keys_vals(payload: &[u8], out: &mut [i32], count: &mut usize) -> Result<(), ZigZagError> {
+    let mut pos = 0;
+    while pos < payload.len() {
+        let (value, len) = u64::decode_varint(&payload[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        let slot = out.get_mut(*count).ok_or(ZigZagError::BufferTooSmall {
+            needed: *count + varint::count(&payload[pos..]),
+            actual: *count,
+        })?;
+        *slot = value as i32;
+        *count += 1;
+        pos += len;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_nodes_wire_format() {
+        let nodes = DenseNodes { ids: &[1, 2], lats: &[-1, -1], lons: &[3, 3], keys_vals: &[] };
+        let mut message = [0u8; 32];
+        let len = nodes.encode(&mut message).unwrap();
+        // Field 1 (id): deltas 1, 1; field 8 (lat): deltas -1, 0; field 9 (lon): deltas 3, 0
+        assert_eq!(&message[..len], &[0x0a, 2, 2, 2, 0x42, 2, 1, 0, 0x4a, 2, 6, 0]);
+        assert_eq!(len, nodes.encoded_len());
+    }
+
+    #[test]
+    fn test_dense_nodes_round_trip_and_unknown_fields() {
+        let nodes = DenseNodes {
+            ids: &[9_000_000_000, 9_000_000_001, 8_999_999_000],
+            lats: &[-900_000_000, 900_000_000, 0],
+            lons: &[1_800_000_000, -1_800_000_000, 1],
+            keys_vals: &[3, 4, 0, 0, 5, 6, 7, 8, 0],
+        };
+        let mut message = [0u8; 128];
+        // A denseinfo field (5) with an arbitrary payload and a varint field (20) in front
+        message[..5].copy_from_slice(&[0x2a, 2, 0xff, 0x01, 0xa0]);
+        message[5..7].copy_from_slice(&[0x01, 0x7f]);
+        let len = 7 + nodes.encode(&mut message[7..]).unwrap();
+
+        let (mut ids, mut lats, mut lons, mut keys_vals) = ([0; 3], [0; 3], [0; 3], [0; 9]);
+        let mut buf = DenseNodesBuf { ids: &mut ids, lats: &mut lats, lons: &mut lons, keys_vals: &mut keys_vals };
+        assert_eq!(decode(&message[..len], &mut buf), Ok((3, 9)));
+        assert_eq!(&ids, nodes.ids);
+        assert_eq!(&lats, nodes.lats);
+        assert_eq!(&lons, nodes.lons);
+        assert_eq!(&keys_vals, nodes.keys_vals);
+
+        assert!((to_degrees(515_000_000, DEFAULT_GRANULARITY, 0) - 51.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dense_nodes_errors() {
+        let nodes = DenseNodes { ids: &[1, 2], lats: &[1, 2], lons: &[1, 2], keys_vals: &[] };
+        let mut message = [0u8; 32];
+        let len = nodes.encode(&mut message).unwrap();
+
+        let (mut ids, mut lats, mut lons, mut keys_vals) = ([0; 1], [0; 2], [0; 2], [0; 0]);
+        let mut buf = DenseNodesBuf { ids: &mut ids, lats: &mut lats, lons: &mut lons, keys_vals: &mut keys_vals };
+        assert_eq!(decode(&message[..len], &mut buf), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+
+        let (mut ids, mut lats, mut lons) = ([0; 2], [0; 2], [0; 2]);
+        let mut buf = DenseNodesBuf { ids: &mut ids, lats: &mut lats, lons: &mut lons, keys_vals: &mut keys_vals };
+        assert_eq!(decode(&message[..len - 1], &mut buf), Err(ZigZagError::UnexpectedEof { offset: 8 }));
+        // Missing lon column
+        assert_eq!(decode(&message[..8], &mut buf), Err(ZigZagError::InvalidData { offset: 8 }));
+        // Wire type 3 (start group) is not supported
+        assert_eq!(decode(&[0x0b], &mut buf), Err(ZigZagError::InvalidData { offset: 0 }));
+    }
+}
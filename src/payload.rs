@@ -0,0 +1,273 @@
+//! Compact sensor payloads for LoRaWAN and similar uplinks.
+//!
+//! Modeled on Cayenne LPP, a payload is a sequence of readings, each tagged
+//! with a channel and a [`Kind`] that fixes the number of values and their
+//! unit. Where Cayenne LPP stores every value in a fixed two bytes, values
+//! here are ZigZag varints, so typical readings take one or two bytes and
+//! large ones still fit.
+//!
+//! ```text
+//! per reading: channel (u8) | kind id (u8) | value (ZigZag varint) * kind.arity()
+//! ```
+//!
+//! [`PayloadWriter`] packs readings on the device and [`PayloadReader`]
+//! unpacks them on the network server.
+//!
+//! ```rust
+//! use zigzag_rs::payload::{Kind, PayloadReader, PayloadWriter};
+//!
+//! let mut frame = [0u8; 51];
+//! let mut writer = PayloadWriter::new(&mut frame);
+//! writer.push(1, Kind::Temperature, &[-42]).unwrap(); // -4.2 °C
+//! writer.push(2, Kind::Battery, &[3300]).unwrap();
+//! writer.push(3, Kind::Accelerometer, &[12, -980, 3]).unwrap();
+//! let len = writer.finish();
+//! assert_eq!(len, 3 + 4 + 6);
+//!
+//! let mut readings = PayloadReader::new(&frame[..len]);
+//! let temperature = readings.next().unwrap().unwrap();
+//! assert_eq!((temperature.channel, temperature.kind, temperature.values()), (1, Kind::Temperature, &[-42][..]));
+//! assert_eq!(readings.count(), 2);
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// Quantity a reading holds, which fixes its number of values and their unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Any signed value without a defined unit
+    Generic,
+    /// Temperature in 0.1 °C
+    Temperature,
+    /// Relative humidity in 0.5 %
+    Humidity,
+    /// Battery voltage in mV
+    Battery,
+    /// Analog reading in 0.01 units
+    Analog,
+    /// Acceleration along x, y and z in milli-g
+    Accelerometer,
+    /// Angular rate along x, y and z in 0.01 °/s
+    Gyroscope,
+    /// Latitude and longitude in 10^-7 degrees and altitude in cm
+    Location,
+}
+
+impl Kind {
+    /// Stable identifier of the kind, suitable for storing in headers
+    pub fn id(self) -> u8 {
+        match self {
+            Kind::Generic => 0,
+            Kind::Temperature => 1,
+            Kind::Humidity => 2,
+            Kind::Battery => 3,
+            Kind::Analog => 4,
+            Kind::Accelerometer => 5,
+            Kind::Gyroscope => 6,
+            Kind::Location => 7,
+        }
+    }
+
+    /// Look up a kind by the identifier returned from [`Kind::id`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Kind::Generic),
+            1 => Some(Kind::Temperature),
+            2 => Some(Kind::Humidity),
+            3 => Some(Kind::Battery),
+            4 => Some(Kind::Analog),
+            5 => Some(Kind::Accelerometer),
+            6 => Some(Kind::Gyroscope),
+            7 => Some(Kind::Location),
+            _ => None,
+        }
+    }
+
+    /// Number of values in a reading of this kind
+    pub fn arity(self) -> usize {
+        match self {
+            Kind::Accelerometer | Kind::Gyroscope | Kind::Location => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// Most values a reading can hold
+pub const MAX_ARITY: usize = 3;
+
+/// One decoded reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reading {
+    /// Channel the reading was sent on
+    pub channel: u8,
+    /// Quantity of the reading
+    pub kind: Kind,
+    values: [i32; MAX_ARITY],
+}
+
+impl Reading {
+    /// Values of the reading, [`Kind::arity`] of them
+    pub fn values(&self) -> &[i32] {
+        &self.values[..self.kind.arity()]
+    }
+}
+
+/// Number of bytes a reading of `values` takes
+pub fn reading_len(values: &[i32]) -> usize {
+    2 + varint::encoded_len(values)
+}
+
+/// Packs readings into a payload buffer
+#[derive(Debug)]
+pub struct PayloadWriter<'a> {
+    out: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> PayloadWriter<'a> {
+    /// Create a writer that starts at the beginning of `out`
+    pub fn new(out: &'a mut [u8]) -> Self {
+        PayloadWriter { out, pos: 0 }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    /// Whether no reading has been written
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /// Append a reading
+    ///
+    /// A reading that does not fit leaves the payload unchanged, so a caller
+    /// can fill a frame up to its airtime limit and send the rest later.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the reading was written
+    /// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if it does not fit
+    ///
+    /// # Panics
+    /// Panics if `values` does not hold exactly `kind.arity()` values
+    pub fn push(&mut self, channel: u8, kind: Kind, values: &[i32]) -> Result<(), ZigZagError> {
+        assert_eq!(values.len(), kind.arity(), "reading needs one value per axis of its kind");
+        let needed = self.pos + reading_len(values);
+        if self.out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: self.out.len() });
+        }
+        self.out[self.pos] = channel;
+        self.out[self.pos + 1] = kind.id();
+        self.pos += 2;
+        self.pos += varint::encode_slice(values, &mut self.out[self.pos..])?;
+        Ok(())
+    }
+
+    /// Finish writing and return the payload length
+    pub fn finish(self) -> usize {
+        self.pos
+    }
+}
+
+/// Iterator over the readings of a payload
+///
+/// Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct PayloadReader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PayloadReader<'a> {
+    /// Create a reader over a payload
+    pub fn new(input: &'a [u8]) -> Self {
+        PayloadReader { input, pos: 0 }
+    }
+
+    fn read_reading(&self) -> Result<(Reading, usize), ZigZagError> {
+        let mut pos = self.pos;
+        let channel = self.input[pos];
+        let &id = self.input.get(pos + 1).ok_or(ZigZagError::UnexpectedEof { offset: pos + 1 })?;
+        let kind = Kind::from_id(id).ok_or(ZigZagError::InvalidData { offset: pos + 1 })?;
+        pos += 2;
+        let mut values = [0i32; MAX_ARITY];
+        for value in values[..kind.arity()].iter_mut() {
+            let (encoded, len) =
+                u32::decode_varint(&self.input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+            *value = i32::zigzag_decode(encoded);
+            pos += len;
+        }
+        Ok((Reading { channel, kind, values }, pos))
+    }
+}
+
+impl Iterator for PayloadReader<'_> {
+    type Item = Result<Reading, ZigZagError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.input.len() {
+            return None;
+        }
+        match self.read_reading() {
+            Ok((reading, pos)) => {
+                self.pos = pos;
+                Some(Ok(reading))
+            }
+            Err(err) => {
+                self.pos = self.input.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_payload_round_trip() {
+        let readings: [(u8, Kind, &[i32]); 5] = [
+            (0, Kind::Generic, &[i32::MIN]),
+            (1, Kind::Humidity, &[131]),
+            (7, Kind::Location, &[525_200_080, 134_049_540, -1250]),
+            (255, Kind::Gyroscope, &[0, -1, 1]),
+            (1, Kind::Analog, &[i32::MAX]),
+        ];
+        let mut frame = [0u8; 64];
+        let mut writer = PayloadWriter::new(&mut frame);
+        for &(channel, kind, values) in &readings {
+            writer.push(channel, kind, values).unwrap();
+        }
+        let len = writer.finish();
+        assert_eq!(len, readings.iter().map(|&(_, _, values)| reading_len(values)).sum::<usize>());
+
+        let decoded: Vec<Reading> = PayloadReader::new(&frame[..len]).collect::<Result<_, _>>().unwrap();
+        assert_eq!(decoded.len(), readings.len());
+        for (reading, &(channel, kind, values)) in decoded.iter().zip(&readings) {
+            assert_eq!((reading.channel, reading.kind, reading.values()), (channel, kind, values));
+        }
+        assert!((0..=255).filter_map(Kind::from_id).all(|kind| Kind::from_id(kind.id()) == Some(kind)));
+    }
+
+    #[test]
+    fn test_payload_errors() {
+        let mut frame = [0u8; 5];
+        let mut writer = PayloadWriter::new(&mut frame);
+        writer.push(1, Kind::Battery, &[3300]).unwrap();
+        // A reading that does not fit is not written
+        assert_eq!(writer.push(2, Kind::Temperature, &[-42]), Err(ZigZagError::BufferTooSmall { needed: 7, actual: 5 }));
+        assert_eq!(writer.finish(), 4);
+
+        let mut reader = PayloadReader::new(&[1, 9, 0]);
+        assert_eq!(reader.next(), Some(Err(ZigZagError::InvalidData { offset: 1 })));
+        assert_eq!(reader.next(), None);
+        let mut reader = PayloadReader::new(&[1, 1, 0x02, 2, 5, 0x02, 0x80]);
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(reader.next(), Some(Err(ZigZagError::UnexpectedEof { offset: 6 })));
+        assert_eq!(PayloadReader::new(&[3]).next(), Some(Err(ZigZagError::UnexpectedEof { offset: 1 })));
+    }
+}
@@ -0,0 +1,226 @@
+//! Google Encoded Polyline Algorithm Format.
+//!
+//! Coordinates are scaled by `10^precision`, delta encoded against the previous
+//! point, ZigZag encoded and split into 5-bit chunks that are written as
+//! printable ASCII characters. Precision 5 is used by the Google Maps APIs,
+//! precision 6 by OSRM and Valhalla.
+//!
+//! Points are `(latitude, longitude)` pairs. The `*_to_slice` functions work
+//! without allocation; [`encode`] and [`decode`] need the `alloc` feature.
+//!
+//! ```rust
+//! use zigzag_rs::polyline;
+//!
+//! let points = [(38.5, -120.2), (40.7, -120.95), (43.252, -126.453)];
+//! let mut buf = [0u8; 64];
+//! let len = polyline::encode_to_slice(&points, 5, &mut buf).unwrap();
+//! assert_eq!(&buf[..len], b"_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+//!
+//! let mut decoded = [(0.0, 0.0); 3];
+//! assert_eq!(polyline::decode_to_slice(&buf[..len], 5, &mut decoded), Ok(3));
+//! assert_eq!(decoded, points);
+//! ```
+
+use crate::{ZigZag, ZigZagError};
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+/// Scale factor `10^precision`
+fn factor(precision: u32) -> f64 {
+    (0..precision).fold(1.0, |factor, _| factor * 10.0)
+}
+
+/// Scale a coordinate and round it half away from zero
+fn scale(coordinate: f64, factor: f64) -> i64 {
+    let scaled = coordinate * factor;
+    if scaled < 0.0 { (scaled - 0.5) as i64 } else { (scaled + 0.5) as i64 }
+}
+
+/// Number of characters needed to encode a delta
+fn delta_len(delta: i64) -> usize {
+    let bits = 64 - i64::zigzag_encode(delta).leading_zeros() as usize;
+    bits.div_ceil(5).max(1)
+}
+
+fn write_delta(delta: i64, out: &mut [u8]) -> usize {
+    let mut value = i64::zigzag_encode(delta);
+    let mut len = 0;
+    while value >= 0x20 {
+        out[len] = ((value & 0x1f) as u8 | 0x20) + 63;
+        value >>= 5;
+        len += 1;
+    }
+    out[len] = value as u8 + 63;
+    len + 1
+}
+
+fn read_delta(input: &[u8], pos: usize) -> Result<(i64, usize), ZigZagError> {
+    let mut value = 0u64;
+    for (i, &byte) in input[pos..].iter().enumerate() {
+        if !(63..=126).contains(&byte) || i >= 13 {
+            return Err(ZigZagError::InvalidData { offset: pos + i });
+        }
+        let chunk = (byte - 63) as u64;
+        value |= (chunk & 0x1f) << (5 * i);
+        if chunk & 0x20 == 0 {
+            return Ok((i64::zigzag_decode(value), i + 1));
+        }
+    }
+    Err(ZigZagError::UnexpectedEof { offset: pos })
+}
+
+/// Number of bytes [`encode_to_slice`] needs for `points`
+pub fn encoded_len(points: &[(f64, f64)], precision: u32) -> usize {
+    let factor = factor(precision);
+    let mut prev = (0, 0);
+    points.iter().map(|&(lat, lng)| {
+        let current = (scale(lat, factor), scale(lng, factor));
+        let len = delta_len(current.0.wrapping_sub(prev.0)) + delta_len(current.1.wrapping_sub(prev.1));
+        prev = current;
+        len
+    }).sum()
+}
+
+/// Encode points as a polyline into a byte buffer
+///
+/// # Arguments
+/// * `points` - `(latitude, longitude)` pairs
+/// * `precision` - Number of decimal digits to keep, usually 5 or 6
+/// * `out` - Output buffer for the ASCII polyline
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode_to_slice(points: &[(f64, f64)], precision: u32, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let needed = encoded_len(points, precision);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let factor = factor(precision);
+    let mut prev = (0i64, 0i64);
+    let mut pos = 0;
+    for &(lat, lng) in points {
+        let current = (scale(lat, factor), scale(lng, factor));
+        pos += write_delta(current.0.wrapping_sub(prev.0), &mut out[pos..]);
+        pos += write_delta(current.1.wrapping_sub(prev.1), &mut out[pos..]);
+        prev = current;
+    }
+    Ok(pos)
+}
+
+/// Decode a polyline from a byte buffer
+///
+/// # Arguments
+/// * `input` - ASCII polyline
+/// * `precision` - Precision the polyline was encoded with
+/// * `out` - Output slice for the decoded `(latitude, longitude)` pairs
+///
+/// # Returns
+/// * `Ok(count)` with the number of points decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all points
+/// * `Err(ZigZagError::InvalidData)` if `input` contains a character outside the polyline alphabet
+/// * `Err(ZigZagError::UnexpectedEof)` if `input` ends in the middle of a point
+pub fn decode_to_slice(input: &[u8], precision: u32, out: &mut [(f64, f64)]) -> Result<usize, ZigZagError> {
+    let factor = factor(precision);
+    let mut current = (0i64, 0i64);
+    let mut pos = 0;
+    let mut count = 0;
+    while pos < input.len() {
+        let point_start = pos;
+        let (lat, len) = read_delta(input, pos)?;
+        pos += len;
+        if pos == input.len() {
+            return Err(ZigZagError::UnexpectedEof { offset: point_start });
+        }
+        let (lng, len) = read_delta(input, pos)?;
+        pos += len;
+
+        current = (current.0.wrapping_add(lat), current.1.wrapping_add(lng));
+        if count == out.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: count + 1, actual: out.len() });
+        }
+        out[count] = (current.0 as f64 / factor, current.1 as f64 / factor);
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Encode points as a polyline string
+///
+/// # Example
+/// ```
+/// use zigzag_rs::polyline;
+///
+/// assert_eq!(polyline::encode(&[(38.5, -120.2)], 5), "_p~iF~ps|U");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encode(points: &[(f64, f64)], precision: u32) -> String {
+    let mut buf = alloc::vec![0u8; encoded_len(points, precision)];
+    let len = encode_to_slice(points, precision, &mut buf).unwrap_or(0);
+    buf.truncate(len);
+    // The polyline alphabet is printable ASCII
+    String::from_utf8(buf).unwrap_or_default()
+}
+
+/// Decode a polyline string into `(latitude, longitude)` pairs
+///
+/// # Example
+/// ```
+/// use zigzag_rs::polyline;
+///
+/// assert_eq!(polyline::decode("_p~iF~ps|U", 5), Ok(vec![(38.5, -120.2)]));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode(polyline: &str, precision: u32) -> Result<Vec<(f64, f64)>, ZigZagError> {
+    // Every point needs at least two characters
+    let mut points = alloc::vec![(0.0, 0.0); polyline.len() / 2];
+    let count = decode_to_slice(polyline.as_bytes(), precision, &mut points)?;
+    points.truncate(count);
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polyline_reference_vector() {
+        let points = [(38.5, -120.2), (40.7, -120.95), (43.252, -126.453)];
+        let mut buf = [0u8; 64];
+        let len = encode_to_slice(&points, 5, &mut buf).unwrap();
+        assert_eq!(&buf[..len], b"_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+        assert_eq!(len, encoded_len(&points, 5));
+    }
+
+    #[test]
+    fn test_polyline_precision_round_trip() {
+        let points = [(52.520008, 13.404954), (-33.868820, 151.209296), (0.0, -0.000001), (90.0, 180.0)];
+        let mut buf = [0u8; 128];
+        let len = encode_to_slice(&points, 6, &mut buf).unwrap();
+        let mut decoded = [(0.0, 0.0); 4];
+        assert_eq!(decode_to_slice(&buf[..len], 6, &mut decoded), Ok(4));
+        for (a, b) in points.iter().zip(decoded.iter()) {
+            assert!((a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_polyline_errors() {
+        let mut small = [0u8; 4];
+        assert_eq!(
+            encode_to_slice(&[(38.5, -120.2)], 5, &mut small),
+            Err(ZigZagError::BufferTooSmall { needed: 10, actual: 4 })
+        );
+
+        let mut out = [(0.0, 0.0); 1];
+        assert_eq!(decode_to_slice(b"_p~iF", 5, &mut out), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert_eq!(decode_to_slice(b"_p~iF~ps|", 5, &mut out), Err(ZigZagError::UnexpectedEof { offset: 5 }));
+        assert_eq!(decode_to_slice(b"_p~i F", 5, &mut out), Err(ZigZagError::InvalidData { offset: 4 }));
+        assert_eq!(
+            decode_to_slice(b"_p~iF~ps|U_ulLnnqC", 5, &mut out),
+            Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 })
+        );
+    }
+}
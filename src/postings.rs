@@ -0,0 +1,478 @@
+//! Posting lists of document ids.
+//!
+//! Sorted lists store the gaps between consecutive ids as plain varints. No
+//! ZigZag step is needed because the gaps are never negative. The gaps are
+//! grouped into blocks of [`BLOCK_LEN`], and each block starts with the
+//! largest id it contains and its byte length. That lets [`PostingIter::advance_to`]
+//! jump over whole blocks, which is what makes [`intersect`] fast on lists
+//! of very different lengths.
+//!
+//! Unsorted lists store ZigZag varints of the signed differences instead.
+//! They can be decoded but not searched.
+//!
+//! ```text
+//! mode (u8) | count (varint) | sorted: block* | unsorted: ZigZag delta*
+//!
+//! block: last id - previous block's last id (varint) | gap bytes (varint) | gap*
+//! ```
+//!
+//! ```rust
+//! use zigzag_rs::postings::{self, Mode, PostingList};
+//!
+//! let (mut a, mut b) = ([0u8; 64], [0u8; 64]);
+//! let a_len = postings::encode(&[3u32, 7, 9, 100, 1000], Mode::Sorted, &mut a).unwrap();
+//! let b_len = postings::encode(&[1u32, 7, 100, 101], Mode::Sorted, &mut b).unwrap();
+//! let a = PostingList::new(&a[..a_len]).unwrap();
+//! let b = PostingList::new(&b[..b_len]).unwrap();
+//!
+//! let mut both = [0u32; 4];
+//! let count = postings::intersect(&a, &b, &mut both).unwrap();
+//! assert_eq!(&both[..count], &[7, 100]);
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// Number of ids per block of a sorted list
+pub const BLOCK_LEN: usize = 128;
+
+/// Storage mode of a posting list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Non-decreasing ids stored as gaps in skippable blocks
+    #[default]
+    Sorted = 0,
+    /// Arbitrary ids stored as ZigZag encoded differences
+    Unsorted = 1,
+}
+
+/// Unsigned integer types usable as document ids
+pub trait DocId: Varint + Ord {
+    /// Widen to `u64`
+    fn to_u64(self) -> u64;
+
+    /// Narrow from `u64`, keeping the low bits
+    fn from_u64(value: u64) -> Self;
+}
+
+impl DocId for u32 {
+    #[inline]
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+
+    #[inline]
+    fn from_u64(value: u64) -> Self {
+        value as u32
+    }
+}
+
+impl DocId for u64 {
+    #[inline]
+    fn to_u64(self) -> u64 {
+        self
+    }
+
+    #[inline]
+    fn from_u64(value: u64) -> Self {
+        value
+    }
+}
+
+/// Upper bound of the encoded size of `count` ids
+pub fn max_encoded_len(count: usize) -> usize {
+    1 + u64::MAX_LEN + count.div_ceil(BLOCK_LEN) * 2 * u64::MAX_LEN + count * u64::MAX_LEN
+}
+
+/// Number of bytes the gaps of one sorted block take
+fn block_gaps_len(block: &[u64], prev: u64) -> usize {
+    let mut prev = prev;
+    block.iter().map(|&id| {
+        let len = (id - prev).varint_len();
+        prev = id;
+        len
+    }).sum()
+}
+
+/// Encode a list of document ids
+///
+/// # Arguments
+/// * `ids` - Document ids, non-decreasing for [`Mode::Sorted`]
+/// * `mode` - Storage mode
+/// * `out` - Output buffer, [`max_encoded_len`] bytes are always enough
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::InvalidData)` with the index of the first out of order id in sorted mode
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode<T: DocId>(ids: &[T], mode: Mode, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if out.is_empty() {
+        return Err(ZigZagError::BufferTooSmall { needed: 1, actual: 0 });
+    }
+    out[0] = mode as u8;
+    let mut pos = 1;
+    pos += (ids.len() as u64).encode_varint(&mut out[pos..])?;
+
+    match mode {
+        Mode::Sorted => {
+            if let Some(index) = ids.windows(2).position(|pair| pair[0] > pair[1]) {
+                return Err(ZigZagError::InvalidData { offset: index + 1 });
+            }
+            let mut prev = 0u64;
+            for chunk in ids.chunks(BLOCK_LEN) {
+                let mut block = [0u64; BLOCK_LEN];
+                for (slot, &id) in block.iter_mut().zip(chunk) {
+                    *slot = id.to_u64();
+                }
+                let block = &block[..chunk.len()];
+                let last = block[block.len() - 1];
+
+                pos += (last - prev).encode_varint(&mut out[pos..])?;
+                pos += (block_gaps_len(block, prev) as u64).encode_varint(&mut out[pos..])?;
+                for &id in block {
+                    pos += (id - prev).encode_varint(&mut out[pos..])?;
+                    prev = id;
+                }
+            }
+        }
+        Mode::Unsorted => {
+            let mut prev = 0u64;
+            for &id in ids {
+                let id = id.to_u64();
+                let delta = id.wrapping_sub(prev) as i64;
+                pos += i64::zigzag_encode(delta).encode_varint(&mut out[pos..])?;
+                prev = id;
+            }
+        }
+    }
+    Ok(pos)
+}
+
+/// Decode a posting list in either mode
+///
+/// # Returns
+/// * `Ok(count)` with the number of ids decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all ids
+/// * `Err(ZigZagError)` with the byte offset of the problem if `input` is malformed
+pub fn decode<T: DocId>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError> {
+    let (mode, count, mut pos) = read_header(input)?;
+    if count > out.len() {
+        return Err(ZigZagError::BufferTooSmall { needed: count, actual: out.len() });
+    }
+
+    match mode {
+        Mode::Sorted => {
+            let list = PostingList { input, count, data_start: pos };
+            for (slot, id) in out.iter_mut().zip(list.iter()) {
+                *slot = T::from_u64(id?);
+            }
+        }
+        Mode::Unsorted => {
+            let mut prev = 0u64;
+            for slot in out[..count].iter_mut() {
+                let (value, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+                prev = prev.wrapping_add(i64::zigzag_decode(value) as u64);
+                *slot = T::from_u64(prev);
+                pos += len;
+            }
+        }
+    }
+    Ok(count)
+}
+
+fn read_header(input: &[u8]) -> Result<(Mode, usize, usize), ZigZagError> {
+    let mode = match input.first() {
+        Some(0) => Mode::Sorted,
+        Some(1) => Mode::Unsorted,
+        Some(_) => return Err(ZigZagError::InvalidData { offset: 0 }),
+        None => return Err(ZigZagError::UnexpectedEof { offset: 0 }),
+    };
+    let (count, len) = u64::decode_varint(&input[1..]).map_err(|err| varint::at_offset(err, 1))?;
+    Ok((mode, count as usize, 1 + len))
+}
+
+/// A sorted posting list that is read in place
+#[derive(Debug, Clone, Copy)]
+pub struct PostingList<'a> {
+    input: &'a [u8],
+    count: usize,
+    data_start: usize,
+}
+
+impl<'a> PostingList<'a> {
+    /// Parse the header of an encoded list
+    ///
+    /// # Returns
+    /// * `Ok(list)` for a list encoded with [`Mode::Sorted`]
+    /// * `Err(ZigZagError::InvalidData)` for an unsorted list or an unknown mode
+    /// * `Err(ZigZagError::UnexpectedEof)` if the header is truncated
+    pub fn new(input: &'a [u8]) -> Result<Self, ZigZagError> {
+        match read_header(input)? {
+            (Mode::Sorted, count, data_start) => Ok(PostingList { input, count, data_start }),
+            (Mode::Unsorted, ..) => Err(ZigZagError::InvalidData { offset: 0 }),
+        }
+    }
+
+    /// Number of ids in the list
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether the list holds no ids
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Iterate over the ids in order
+    pub fn iter(&self) -> PostingIter<'a> {
+        PostingIter {
+            input: self.input,
+            pos: self.data_start,
+            remaining: self.count,
+            block_remaining: 0,
+            block_end: self.data_start,
+            block_last: 0,
+            prev: 0,
+        }
+    }
+}
+
+/// Iterator over the ids of a [`PostingList`]
+///
+/// Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct PostingIter<'a> {
+    input: &'a [u8],
+    pos: usize,
+    remaining: usize,
+    block_remaining: usize,
+    block_end: usize,
+    block_last: u64,
+    prev: u64,
+}
+
+impl PostingIter<'_> {
+    fn fail(&mut self, err: ZigZagError) -> Option<Result<u64, ZigZagError>> {
+        self.remaining = 0;
+        self.block_remaining = 0;
+        Some(Err(err))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, ZigZagError> {
+        let (value, len) = u64::decode_varint(&self.input[self.pos..]).map_err(|err| varint::at_offset(err, self.pos))?;
+        self.pos += len;
+        Ok(value)
+    }
+
+    fn read_block_header(&mut self) -> Result<(), ZigZagError> {
+        let header_start = self.pos;
+        self.block_last = self.prev.wrapping_add(self.read_varint()?);
+        let gaps_len = self.read_varint()? as usize;
+        self.block_end = self.pos.checked_add(gaps_len).filter(|&end| end <= self.input.len())
+            .ok_or(ZigZagError::UnexpectedEof { offset: header_start })?;
+        self.block_remaining = self.remaining.min(BLOCK_LEN);
+        Ok(())
+    }
+
+    /// Skip to the first id that is at least `target`
+    ///
+    /// Blocks whose last id is below `target` are skipped without decoding them.
+    pub fn advance_to(&mut self, target: u64) -> Option<Result<u64, ZigZagError>> {
+        while self.remaining > 0 {
+            if self.block_remaining == 0 {
+                if let Err(err) = self.read_block_header() {
+                    return self.fail(err);
+                }
+            }
+            if self.block_last >= target {
+                break;
+            }
+            self.remaining -= self.block_remaining;
+            self.block_remaining = 0;
+            self.pos = self.block_end;
+            self.prev = self.block_last;
+        }
+
+        loop {
+            match self.next()? {
+                Ok(id) if id < target => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+impl Iterator for PostingIter<'_> {
+    type Item = Result<u64, ZigZagError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.block_remaining == 0 {
+            if let Err(err) = self.read_block_header() {
+                return self.fail(err);
+            }
+        }
+        let gap_start = self.pos;
+        match self.read_varint() {
+            Ok(gap) if self.pos <= self.block_end => {
+                self.prev = self.prev.wrapping_add(gap);
+                self.remaining -= 1;
+                self.block_remaining -= 1;
+                Some(Ok(self.prev))
+            }
+            Ok(_) => self.fail(ZigZagError::InvalidData { offset: gap_start }),
+            Err(err) => self.fail(err),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+fn push<T: DocId>(out: &mut [T], count: &mut usize, id: u64) -> Result<(), ZigZagError> {
+    if *count == out.len() {
+        return Err(ZigZagError::BufferTooSmall { needed: *count + 1, actual: out.len() });
+    }
+    out[*count] = T::from_u64(id);
+    *count += 1;
+    Ok(())
+}
+
+/// Write the ids present in both lists to `out`
+///
+/// # Returns
+/// * `Ok(count)` with the number of ids written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold the intersection
+/// * `Err(ZigZagError)` if either list is malformed
+pub fn intersect<T: DocId>(a: &PostingList<'_>, b: &PostingList<'_>, out: &mut [T]) -> Result<usize, ZigZagError> {
+    // Drive the search from the shorter list
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let (mut short, mut long) = (short.iter(), long.iter());
+    let mut count = 0;
+
+    let mut candidate = short.next().transpose()?;
+    while let Some(id) = candidate {
+        let Some(found) = long.advance_to(id).transpose()? else { break };
+        if found == id {
+            if count == 0 || out[count - 1].to_u64() != id {
+                push(out, &mut count, id)?;
+            }
+            candidate = short.next().transpose()?;
+        } else {
+            candidate = short.advance_to(found).transpose()?;
+        }
+    }
+    Ok(count)
+}
+
+/// Write the ids present in either list to `out`, without duplicates
+///
+/// # Returns
+/// * `Ok(count)` with the number of ids written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold the union
+/// * `Err(ZigZagError)` if either list is malformed
+pub fn union<T: DocId>(a: &PostingList<'_>, b: &PostingList<'_>, out: &mut [T]) -> Result<usize, ZigZagError> {
+    let (mut a, mut b) = (a.iter(), b.iter());
+    let (mut x, mut y) = (a.next().transpose()?, b.next().transpose()?);
+    let mut count = 0;
+
+    loop {
+        let id = match (x, y) {
+            (Some(i), Some(j)) => i.min(j),
+            (Some(i), None) => i,
+            (None, Some(j)) => j,
+            (None, None) => break,
+        };
+        if x == Some(id) {
+            x = a.next().transpose()?;
+        }
+        if y == Some(id) {
+            y = b.next().transpose()?;
+        }
+        if count == 0 || out[count - 1].to_u64() != id {
+            push(out, &mut count, id)?;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn encoded<T: DocId>(ids: &[T], mode: Mode) -> Vec<u8> {
+        let mut buf = vec![0u8; max_encoded_len(ids.len())];
+        let len = encode(ids, mode, &mut buf).unwrap();
+        buf.truncate(len);
+        buf
+    }
+
+    #[test]
+    fn test_postings_round_trip() {
+        let ids: Vec<u64> = (0..1000).map(|i| i * i * 3 + (1 << 40)).collect();
+        for mode in [Mode::Sorted, Mode::Unsorted] {
+            let buf = encoded(&ids, mode);
+            let mut decoded = vec![0u64; ids.len()];
+            assert_eq!(decode(&buf, &mut decoded), Ok(ids.len()));
+            assert_eq!(decoded, ids);
+        }
+
+        // Unsorted ids with large backward jumps
+        let ids = [u32::MAX, 0, 17, 3, u32::MAX - 1];
+        let mut decoded = [0u32; 5];
+        assert_eq!(decode(&encoded(&ids, Mode::Unsorted), &mut decoded), Ok(5));
+        assert_eq!(decoded, ids);
+
+        // Consecutive ids take one byte each, after the list and block headers
+        let dense: Vec<u32> = (1..=BLOCK_LEN as u32).collect();
+        assert_eq!(encoded(&dense, Mode::Sorted).len(), (1 + 2) + (2 + 2) + BLOCK_LEN);
+    }
+
+    #[test]
+    fn test_postings_set_operations() {
+        let evens: Vec<u32> = (0..5000).map(|i| i * 2).collect();
+        let threes: Vec<u32> = (0..3000).map(|i| i * 3).collect();
+        let sparse = [6u32, 601, 9000, 9001, 20_000];
+        let (evens, threes, sparse) = (encoded(&evens, Mode::Sorted), encoded(&threes, Mode::Sorted), encoded(&sparse, Mode::Sorted));
+        let (evens, threes, sparse) = (PostingList::new(&evens).unwrap(), PostingList::new(&threes).unwrap(), PostingList::new(&sparse).unwrap());
+
+        let mut out = vec![0u32; 10_000];
+        let count = intersect(&evens, &threes, &mut out).unwrap();
+        let expected: Vec<u32> = (0..1500).map(|i| i * 6).collect();
+        assert_eq!(&out[..count], &expected[..]);
+
+        let count = intersect(&sparse, &evens, &mut out).unwrap();
+        assert_eq!(&out[..count], &[6, 9000]);
+
+        let count = union(&sparse, &threes, &mut out).unwrap();
+        assert_eq!(count, 3000 + 4);
+        assert!(out[..count].windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut iter = evens.iter();
+        assert_eq!(iter.advance_to(7777), Some(Ok(7778)));
+        assert_eq!(iter.next(), Some(Ok(7780)));
+        assert_eq!(iter.advance_to(1_000_000), None);
+    }
+
+    #[test]
+    fn test_postings_errors() {
+        let mut buf = [0u8; 16];
+        assert_eq!(encode(&[1u32, 5, 3], Mode::Sorted, &mut buf), Err(ZigZagError::InvalidData { offset: 2 }));
+        let len = encode(&[1u32, 5, 3], Mode::Unsorted, &mut buf).unwrap();
+        assert_eq!(PostingList::new(&buf[..len]).unwrap_err(), ZigZagError::InvalidData { offset: 0 });
+
+        let len = encode(&[1u32, 5, 300], Mode::Sorted, &mut buf).unwrap();
+        let mut small = [0u32; 2];
+        assert_eq!(decode(&buf[..len], &mut small), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+        let mut decoded = [0u32; 3];
+        assert_eq!(decode(&buf[..len - 1], &mut decoded), Err(ZigZagError::UnexpectedEof { offset: 2 }));
+
+        let list = PostingList::new(&buf[..len]).unwrap();
+        assert_eq!(union(&list, &list, &mut small), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+    }
+}
@@ -0,0 +1,313 @@
+//! Fixed linear prediction with Rice-coded residuals.
+//!
+//! Smooth signals such as temperatures or battery voltages are predicted from
+//! their previous samples with the FLAC fixed polynomial predictors. Only the
+//! prediction residuals are stored, ZigZag encoded and Rice coded with a
+//! parameter chosen for the block.
+//!
+//! Block layout:
+//!
+//! ```text
+//! +--------------------------+-------------------------+-----------------------+
+//! | order (2 bits), k (6 bits) | warm-up samples (varints) | Rice-coded residuals |
+//! +--------------------------+-------------------------+-----------------------+
+//! ```
+//!
+//! The number of samples is not stored, callers keep it alongside the block.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::predictor::{self, Predictor};
+//!
+//! let samples: Vec<i32> = (0..64).map(|i| 2000 + i * i / 4).collect();
+//! assert_eq!(predictor::best_predictor(&samples), Predictor::Order2);
+//!
+//! let mut block = [0u8; 128];
+//! let len = predictor::encode(&samples, &mut block).unwrap();
+//! assert!(len < samples.len());
+//!
+//! let mut decoded = [0i32; 64];
+//! predictor::decode(&block[..len], &mut decoded).unwrap();
+//! assert_eq!(&decoded[..], &samples[..]);
+//! # }
+//! ```
+
+use crate::bits::{BitReader, BitWriter};
+use crate::varint::{self, Varint};
+use crate::{Sample, ZigZag, ZigZagError};
+
+/// Quotients at or above this value are escaped and stored verbatim
+const RICE_ESCAPE: u64 = 32;
+
+/// Fixed polynomial predictor, named after the number of previous samples it uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predictor {
+    /// No prediction, residuals are the samples themselves
+    Order0,
+    /// Predicts the previous sample (plain delta coding)
+    Order1,
+    /// Linear extrapolation from the last two samples
+    Order2,
+    /// Quadratic extrapolation from the last three samples
+    Order3,
+}
+
+impl Predictor {
+    /// All predictors, ordered by the number of samples they use
+    pub const ALL: [Predictor; 4] = [Predictor::Order0, Predictor::Order1, Predictor::Order2, Predictor::Order3];
+
+    /// Number of previous samples the predictor needs
+    pub fn order(self) -> usize {
+        self as usize
+    }
+
+    /// Stable identifier of the predictor, suitable for storing in headers
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up a predictor by the identifier returned from [`Predictor::id`]
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    /// Predict the next sample from `history`, which holds at least `order()` samples, newest last
+    #[inline]
+    fn predict(self, history: &[i64]) -> i64 {
+        let n = history.len();
+        match self {
+            Predictor::Order0 => 0,
+            Predictor::Order1 => history[n - 1],
+            Predictor::Order2 => history[n - 1].wrapping_mul(2).wrapping_sub(history[n - 2]),
+            Predictor::Order3 => history[n - 1]
+                .wrapping_sub(history[n - 2])
+                .wrapping_mul(3)
+                .wrapping_add(history[n - 3]),
+        }
+    }
+
+    /// ZigZag encoded residual of `values[i]`
+    #[inline]
+    fn residual<T: Sample>(self, values: &[T], i: usize) -> u64 {
+        let mut history = [0i64; 3];
+        let order = self.order();
+        for (slot, value) in history[..order].iter_mut().zip(&values[i - order..i]) {
+            *slot = value.to_i64();
+        }
+        let prediction = self.predict(&history[..order]);
+        i64::zigzag_encode(values[i].to_i64().wrapping_sub(prediction))
+    }
+}
+
+/// Choose the predictor with the smallest residuals for `values`
+pub fn best_predictor<T: Sample>(values: &[T]) -> Predictor {
+    // Compare all predictors over the same range so the warm-up samples don't skew the result
+    let start = values.len().min(3);
+    let mut best = Predictor::Order0;
+    let mut best_cost = u64::MAX;
+    for predictor in Predictor::ALL {
+        let cost = (start..values.len())
+            .map(|i| predictor.residual(values, i))
+            .fold(0u64, |sum, residual| sum.saturating_add(residual));
+        if cost < best_cost {
+            best = predictor;
+            best_cost = cost;
+        }
+    }
+    best
+}
+
+/// Rice parameter that approximately minimizes the size of `values` with `predictor`
+fn rice_parameter<T: Sample>(values: &[T], predictor: Predictor) -> u32 {
+    let order = predictor.order();
+    if values.len() <= order {
+        return 0;
+    }
+    let sum = (order..values.len())
+        .map(|i| predictor.residual(values, i))
+        .fold(0u128, |sum, residual| sum + residual as u128);
+    let mean = (sum / (values.len() - order) as u128) as u64;
+    (64 - mean.leading_zeros()).saturating_sub(1)
+}
+
+/// Number of bytes [`encode_with`] writes for `values`
+pub fn encoded_len<T: Sample>(values: &[T], predictor: Predictor) -> usize {
+    let k = rice_parameter(values, predictor);
+    let warm_up = predictor.order().min(values.len());
+    let warm_up_len: usize = values[..warm_up].iter().map(|value| i64::zigzag_encode(value.to_i64()).varint_len()).sum();
+    let bits: u64 = (warm_up..values.len()).map(|i| rice_len(predictor.residual(values, i), k)).sum();
+    1 + warm_up_len + bits.div_ceil(8) as usize
+}
+
+/// Encode `values` with the predictor chosen by [`best_predictor`]
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode<T: Sample>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    encode_with(values, best_predictor(values), out)
+}
+
+/// Encode `values` with a specific predictor
+///
+/// # Arguments
+/// * `values` - Samples to encode
+/// * `predictor` - Predictor used to compute the residuals
+/// * `out` - Output buffer for the block
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode_with<T: Sample>(values: &[T], predictor: Predictor, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if out.is_empty() {
+        return Err(ZigZagError::BufferTooSmall { needed: 1, actual: 0 });
+    }
+    let k = rice_parameter(values, predictor);
+    out[0] = predictor.order() as u8 | (k as u8) << 2;
+
+    let warm_up = predictor.order().min(values.len());
+    let mut pos = 1;
+    for value in &values[..warm_up] {
+        pos += varint::encode(value.to_i64(), &mut out[pos..])?;
+    }
+
+    let mut writer = BitWriter::new(&mut out[pos..]);
+    for i in warm_up..values.len() {
+        write_rice(&mut writer, predictor.residual(values, i), k)?;
+    }
+    Ok(pos + writer.finish())
+}
+
+/// Decode `out.len()` samples from a block produced by [`encode`] or [`encode_with`]
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes consumed
+/// * `Err(ZigZagError::UnexpectedEof)` if the block ends before all samples were decoded
+pub fn decode<T: Sample>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError> {
+    let header = *input.first().ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+    let predictor = Predictor::ALL[(header & 0b11) as usize];
+    let k = (header >> 2) as u32;
+
+    let warm_up = predictor.order().min(out.len());
+    let mut history = [0i64; 3];
+    let mut pos = 1;
+    for (slot, history) in out[..warm_up].iter_mut().zip(history.iter_mut()) {
+        let (value, len) = varint::decode::<i64>(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        *slot = T::from_i64(value);
+        *history = value;
+        pos += len;
+    }
+
+    let mut reader = BitReader::new(&input[pos..]);
+    let order = predictor.order();
+    for slot in out[warm_up..].iter_mut() {
+        let residual = i64::zigzag_decode(read_rice(&mut reader, k).map_err(|err| varint::at_offset(err, pos))?);
+        let value = predictor.predict(&history[..order]).wrapping_add(residual);
+        if order > 0 {
+            history.copy_within(1..order, 0);
+            history[order - 1] = value;
+        }
+        *slot = T::from_i64(value);
+    }
+    Ok(pos + reader.byte_len())
+}
+
+/// Write `value` as a Rice code with parameter `k`, escaping large quotients
+fn write_rice(writer: &mut BitWriter<'_>, value: u64, k: u32) -> Result<(), ZigZagError> {
+    let quotient = value >> k;
+    if quotient >= RICE_ESCAPE {
+        writer.write(u64::MAX, RICE_ESCAPE as u32)?;
+        return writer.write(value, 64);
+    }
+    writer.write(u64::MAX, quotient as u32)?;
+    writer.write_bit(false)?;
+    writer.write(value, k)
+}
+
+/// Number of bits [`write_rice`] writes for `value`
+fn rice_len(value: u64, k: u32) -> u64 {
+    let quotient = value >> k;
+    if quotient >= RICE_ESCAPE {
+        RICE_ESCAPE + 64
+    } else {
+        quotient + 1 + k as u64
+    }
+}
+
+/// Read a Rice code written by [`write_rice`]
+fn read_rice(reader: &mut BitReader<'_>, k: u32) -> Result<u64, ZigZagError> {
+    let mut quotient = 0;
+    while quotient < RICE_ESCAPE && reader.read_bit()? {
+        quotient += 1;
+    }
+    if quotient == RICE_ESCAPE {
+        return reader.read(64);
+    }
+    Ok((quotient << k) | reader.read(k)?)
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32"))]
+    fn round_trip<T: Sample + PartialEq + core::fmt::Debug>(values: &[T], predictor: Predictor) {
+        let mut block = [0u8; 2048];
+        let len = encode_with(values, predictor, &mut block).unwrap();
+        assert_eq!(len, encoded_len(values, predictor));
+        let mut decoded = [T::from_i64(0); 256];
+        assert_eq!(decode(&block[..len], &mut decoded[..values.len()]), Ok(len));
+        assert_eq!(&decoded[..values.len()], values);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32"))]
+    fn test_predictor_round_trip_all_orders() {
+        let mut smooth = [0i32; 200];
+        for (i, value) in smooth.iter_mut().enumerate() {
+            *value = 25_000 + (i as i32 * 7) - (i as i32 * i as i32) / 50;
+        }
+        let extremes = [i64::MIN, i64::MAX, 0, -1, i64::MAX, i64::MIN, 1];
+        for predictor in Predictor::ALL {
+            round_trip(&smooth, predictor);
+            round_trip(&extremes, predictor);
+            round_trip(&[-5i8, 127, -128, 0], predictor);
+            round_trip::<i16>(&[], predictor);
+            round_trip(&[42i16], predictor);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_predictor_automatic_choice() {
+        let constant = [7i32; 16];
+        let ramp: [i32; 16] = core::array::from_fn(|i| i as i32 * 3);
+        let parabola: [i32; 16] = core::array::from_fn(|i| (i * i) as i32);
+        let cubic: [i64; 16] = core::array::from_fn(|i| (i * i * i) as i64);
+        assert_eq!(best_predictor(&[0i32; 16]), Predictor::Order0);
+        assert_eq!(best_predictor(&constant), Predictor::Order1);
+        assert_eq!(best_predictor(&ramp), Predictor::Order2);
+        assert_eq!(best_predictor(&parabola), Predictor::Order3);
+        assert_eq!(best_predictor(&cubic), Predictor::Order3);
+
+        // A smooth signal compresses far better with prediction than without
+        let signal: [i32; 128] = core::array::from_fn(|i| 3300 - (i as i32 * i as i32) / 8);
+        let mut block = [0u8; 1024];
+        let predicted = encode(&signal, &mut block).unwrap();
+        let plain = encode_with(&signal, Predictor::Order0, &mut block).unwrap();
+        assert!(predicted * 4 < plain);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_predictor_errors() {
+        let values = [1i32, 2, 3, 4, 5, 6, 7, 8];
+        let mut block = [0u8; 32];
+        let len = encode(&values, &mut block).unwrap();
+        let mut decoded = [0i32; 8];
+        assert!(matches!(decode(&block[..len - 1], &mut decoded), Err(ZigZagError::UnexpectedEof { .. })));
+        assert_eq!(decode(&[], &mut decoded), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+        assert!(matches!(encode(&values, &mut block[..2]), Err(ZigZagError::BufferTooSmall { .. })));
+    }
+}
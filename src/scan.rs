@@ -0,0 +1,210 @@
+//! Fast scans for varint boundaries.
+//!
+//! A varint ends at the first byte without a continuation bit, so the
+//! boundaries of a stream can be found from the high bits alone, without
+//! decoding any values. This module gathers the high bits of 16 bytes at a
+//! time into a mask, with `movemask` when the `simd` feature and SSE2 are
+//! available and with a multiply trick on 64-bit words otherwise. Counting
+//! then reduces to popcounts, and walking the boundaries to iterating set
+//! bits, which makes opening large columns several times faster than
+//! decoding them byte by byte.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::{scan, varint};
+//!
+//! let mut buf = [0u8; 16];
+//! let len = varint::encode_slice(&[-1i32, 64, -300], &mut buf).unwrap();
+//!
+//! assert_eq!(scan::count(&buf[..len]), 3);
+//! assert_eq!(scan::validate::<i32>(&buf[..len]), Ok(3));
+//! assert!(scan::boundaries(&buf[..len]).eq([1, 3, 5]));
+//! # }
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// Bytes examined per mask
+const CHUNK: usize = 16;
+
+/// High bits of the 16 bytes of `chunk`, byte `i` in bit `i`
+#[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2"))]
+#[inline]
+fn continuation_mask(chunk: &[u8; CHUNK]) -> u16 {
+    use core::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_movemask_epi8};
+    // SAFETY: `chunk` holds 16 readable bytes, `_mm_loadu_si128` has no
+    // alignment requirement and SSE2 is statically enabled for this build
+    unsafe { _mm_movemask_epi8(_mm_loadu_si128(chunk.as_ptr() as *const __m128i)) as u16 }
+}
+
+/// High bits of the 16 bytes of `chunk`, byte `i` in bit `i`
+#[cfg(not(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2")))]
+#[inline]
+fn continuation_mask(chunk: &[u8; CHUNK]) -> u16 {
+    let (low, high) = chunk.split_at(8);
+    gather_high_bits(u64::from_le_bytes(low.try_into().unwrap()))
+        | gather_high_bits(u64::from_le_bytes(high.try_into().unwrap())) << 8
+}
+
+/// Collect the high bit of each byte of `word` into the low 8 bits
+#[cfg(not(all(feature = "simd", target_arch = "x86_64", target_feature = "sse2")))]
+#[inline]
+fn gather_high_bits(word: u64) -> u16 {
+    // Byte j of the multiplier moves the bit of byte 7 - j into bit 56 + (7 - j),
+    // all other partial products land in distinct lower bits or overflow
+    (((word >> 7) & 0x0101_0101_0101_0101).wrapping_mul(0x0102_0408_1020_4080) >> 56) as u16
+}
+
+/// Bytes without a continuation bit in `bytes`, which holds fewer than 16 bytes
+#[inline]
+fn tail_mask(bytes: &[u8]) -> u16 {
+    bytes.iter().enumerate().fold(0, |mask, (i, &byte)| mask | (u16::from(byte & 0x80 == 0) << i))
+}
+
+/// Number of varints terminated in `input`
+///
+/// Like [`varint::count`], this does not validate that each varint fits into
+/// a particular integer type; use [`validate`] for that.
+pub fn count(input: &[u8]) -> usize {
+    let mut chunks = input.chunks_exact(CHUNK);
+    let full: usize =
+        chunks.by_ref().map(|chunk| (!continuation_mask(chunk.try_into().unwrap())).count_ones() as usize).sum();
+    full + tail_mask(chunks.remainder()).count_ones() as usize
+}
+
+/// Iterator over the end offsets of the varints in a stream, see [`boundaries`]
+#[derive(Debug, Clone)]
+pub struct Boundaries<'a> {
+    input: &'a [u8],
+    /// Offset of the chunk `mask` describes
+    base: usize,
+    /// Terminators of the current chunk not yet returned
+    mask: u16,
+    /// Offset of the next chunk to scan
+    next: usize,
+}
+
+impl Iterator for Boundaries<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        while self.mask == 0 {
+            if self.next >= self.input.len() {
+                return None;
+            }
+            self.base = self.next;
+            let rest = &self.input[self.next..];
+            if rest.len() >= CHUNK {
+                self.mask = !continuation_mask(rest[..CHUNK].try_into().unwrap());
+                self.next += CHUNK;
+            } else {
+                self.mask = tail_mask(rest);
+                self.next = self.input.len();
+            }
+        }
+        let bit = self.mask.trailing_zeros() as usize;
+        self.mask &= self.mask - 1;
+        Some(self.base + bit + 1)
+    }
+}
+
+/// Iterate over the offsets just past each terminated varint in `input`
+///
+/// The first varint starts at offset 0 and every other one at the previous
+/// boundary. Bytes after the last boundary belong to an unterminated varint.
+pub fn boundaries(input: &[u8]) -> Boundaries<'_> {
+    Boundaries { input, base: 0, mask: 0, next: 0 }
+}
+
+/// Walk the values of a ZigZag varint stream of `T` without decoding them
+///
+/// `visit` is called with the index and start offset of every value. Only
+/// varints of the maximum length are decoded, to check their last byte.
+pub(crate) fn walk<T, F>(input: &[u8], mut visit: F) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+    F: FnMut(usize, usize),
+{
+    let mut start = 0;
+    let mut count = 0;
+    for end in boundaries(input) {
+        if end - start >= T::UInt::MAX_LEN {
+            T::UInt::decode_varint(&input[start..end]).map_err(|err| varint::at_offset(err, start))?;
+        }
+        visit(count, start);
+        start = end;
+        count += 1;
+    }
+    if start < input.len() {
+        let err = T::UInt::decode_varint(&input[start..]).err().unwrap_or(ZigZagError::UnexpectedEof { offset: 0 });
+        return Err(varint::at_offset(err, start));
+    }
+    Ok(count)
+}
+
+/// Check that `input` is a well-formed ZigZag varint stream of `T`
+///
+/// # Returns
+/// * `Ok(count)` with the number of values in `input`
+/// * `Err(ZigZagError::VarintOverflow)` with the offset of the first value that does not fit into `T`
+/// * `Err(ZigZagError::UnexpectedEof)` with the offset of the last value if it is not terminated
+pub fn validate<T>(input: &[u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    walk::<T, _>(input, |_, _| {})
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[cfg(feature = "i64")]
+    fn sample_stream() -> Vec<u8> {
+        let values: Vec<i64> = (0..500).map(|i: i64| (i * 7919 % 1001 - 500) << (i % 50)).collect();
+        let mut buf = vec![0u8; varint::encoded_len(&values)];
+        varint::encode_slice(&values, &mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_scan_matches_bytewise() {
+        let buf = sample_stream();
+        for len in [0, 1, 15, 16, 17, 100, buf.len()] {
+            let input = &buf[..len];
+            let expected: Vec<usize> =
+                input.iter().enumerate().filter(|&(_, &byte)| byte & 0x80 == 0).map(|(i, _)| i + 1).collect();
+            assert_eq!(boundaries(input).collect::<Vec<_>>(), expected);
+            assert_eq!(count(input), expected.len());
+        }
+        let chunk: [u8; CHUNK] = core::array::from_fn(|i| if i % 3 == 0 { 0x80 | i as u8 } else { i as u8 });
+        assert_eq!(continuation_mask(&chunk), 0b1001_0010_0100_1001);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_validate() {
+        let buf = sample_stream();
+        assert_eq!(validate::<i64>(&buf), Ok(500));
+        assert_eq!(validate::<i64>(&[]), Ok(0));
+
+        // Values wider than 32 bits fail where the decoder fails
+        let expected = varint::decode_slice::<i32>(&buf, &mut [0i32; 500]).unwrap_err();
+        assert!(matches!(expected, ZigZagError::VarintOverflow { .. }));
+        assert_eq!(validate::<i32>(&buf), Err(expected));
+
+        // Last byte of a maximum length varint with bits beyond the type
+        assert_eq!(validate::<i16>(&[0x02, 0xff, 0xff, 0x04]), Err(ZigZagError::VarintOverflow { offset: 1 }));
+        assert_eq!(validate::<i16>(&[0x02, 0xff, 0xff, 0x03]), Ok(2));
+        assert_eq!(validate::<i16>(&[0x02, 0xff]), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+        assert_eq!(validate::<i16>(&[0x02, 0xff, 0xff, 0xff]), Err(ZigZagError::VarintOverflow { offset: 1 }));
+    }
+}
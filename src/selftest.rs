@@ -0,0 +1,183 @@
+//! Power-on self test of the core encode and decode paths.
+//!
+//! [`self_test`] runs a fixed set of ZigZag and varint round trips on the
+//! target: boundary values of every enabled width, known-answer vectors,
+//! the slice paths and the varint overflow check. It needs no allocation and
+//! takes a few microseconds, so it fits the power-on checks that safety
+//! standards require for library code. The inputs pass through
+//! [`core::hint::black_box`] so that the compiler cannot fold the checks
+//! away at build time.
+//!
+//! ```rust
+//! use zigzag_rs::selftest;
+//!
+//! selftest::self_test().expect("zigzag-rs self test failed");
+//! ```
+
+use core::fmt;
+
+#[allow(unused_imports)]
+use crate::varint::{self, Varint};
+#[allow(unused_imports)]
+use crate::{ZigZag, ZigZagError};
+
+/// Check of [`self_test`] that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// A value did not survive a ZigZag encode and decode
+    ZigZagRoundTrip {
+        /// Width of the integer type in bits
+        bits: u32,
+    },
+    /// A value did not encode to its known ZigZag form
+    ZigZagKnownAnswer {
+        /// Width of the integer type in bits
+        bits: u32,
+    },
+    /// The slice functions produced different results than the scalar ones
+    SliceRoundTrip {
+        /// Width of the integer type in bits
+        bits: u32,
+    },
+    /// A value did not survive a varint encode and decode
+    VarintRoundTrip {
+        /// Width of the integer type in bits
+        bits: u32,
+    },
+    /// A value did not encode to its known varint bytes
+    VarintKnownAnswer {
+        /// Width of the integer type in bits
+        bits: u32,
+    },
+    /// An oversized varint was accepted
+    VarintOverflow {
+        /// Width of the integer type in bits
+        bits: u32,
+    },
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (check, bits) = match *self {
+            SelfTestError::ZigZagRoundTrip { bits } => ("ZigZag round trip", bits),
+            SelfTestError::ZigZagKnownAnswer { bits } => ("ZigZag known answer", bits),
+            SelfTestError::SliceRoundTrip { bits } => ("slice round trip", bits),
+            SelfTestError::VarintRoundTrip { bits } => ("varint round trip", bits),
+            SelfTestError::VarintKnownAnswer { bits } => ("varint known answer", bits),
+            SelfTestError::VarintOverflow { bits } => ("varint overflow check", bits),
+        };
+        write!(f, "self test failed: {} for {}-bit integers", check, bits)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelfTestError {}
+
+#[allow(unused_macros)]
+macro_rules! check_width {
+    ($name:ident, $signed:ty, $unsigned:ty, $bits:expr) => {
+        fn $name() -> Result<(), SelfTestError> {
+            const BITS: u32 = $bits;
+            let values: [$signed; 9] = [
+                <$signed>::MIN, <$signed>::MIN + 1, -64, -1, 0, 1, 63, <$signed>::MAX - 1, <$signed>::MAX,
+            ];
+            // ZigZag forms of the values above
+            let encoded: [$unsigned; 9] = [
+                <$unsigned>::MAX, <$unsigned>::MAX - 2, 127, 1, 0, 2, 126, <$unsigned>::MAX - 3, <$unsigned>::MAX - 1,
+            ];
+            let values = core::hint::black_box(values);
+
+            let mut slice_encoded = [0 as $unsigned; 9];
+            let mut slice_decoded = [0 as $signed; 9];
+            for (i, &value) in values.iter().enumerate() {
+                let zigzag = <$signed>::zigzag_encode(value);
+                if zigzag != encoded[i] {
+                    return Err(SelfTestError::ZigZagKnownAnswer { bits: BITS });
+                }
+                if <$signed>::zigzag_decode(zigzag) != value {
+                    return Err(SelfTestError::ZigZagRoundTrip { bits: BITS });
+                }
+
+                let mut buf = [0u8; <$unsigned>::MAX_LEN];
+                let len = varint::encode(value, &mut buf).map_err(|_| SelfTestError::VarintRoundTrip { bits: BITS })?;
+                if len != zigzag.varint_len() {
+                    return Err(SelfTestError::VarintRoundTrip { bits: BITS });
+                }
+                if varint::decode::<$signed>(&buf[..len]) != Ok((value, len)) {
+                    return Err(SelfTestError::VarintRoundTrip { bits: BITS });
+                }
+            }
+
+            <$signed>::try_zigzag_encode_slice(&values, &mut slice_encoded)
+                .map_err(|_| SelfTestError::SliceRoundTrip { bits: BITS })?;
+            <$signed>::try_zigzag_decode_slice(&slice_encoded, &mut slice_decoded)
+                .map_err(|_| SelfTestError::SliceRoundTrip { bits: BITS })?;
+            if slice_encoded != encoded || slice_decoded != values {
+                return Err(SelfTestError::SliceRoundTrip { bits: BITS });
+            }
+
+            // -64 and 63 are the one-byte limits, 64 the first two-byte value
+            let mut buf = [0u8; 2];
+            let known = [(core::hint::black_box(-64 as $signed), &[0x7f][..]), (63, &[0x7e][..]), (64, &[0x80, 0x01][..])];
+            for (value, bytes) in known {
+                match varint::encode(value, &mut buf) {
+                    Ok(len) if &buf[..len] == bytes => {}
+                    _ => return Err(SelfTestError::VarintKnownAnswer { bits: BITS }),
+                }
+            }
+
+            // One continuation byte more than the type can hold
+            let oversized = core::hint::black_box([0xffu8; <$unsigned>::MAX_LEN + 1]);
+            if !matches!(varint::decode::<$signed>(&oversized), Err(ZigZagError::VarintOverflow { offset: 0 })) {
+                return Err(SelfTestError::VarintOverflow { bits: BITS });
+            }
+            Ok(())
+        }
+    };
+}
+
+#[cfg(feature = "i8")]
+check_width!(check_i8, i8, u8, 8);
+#[cfg(feature = "i16")]
+check_width!(check_i16, i16, u16, 16);
+#[cfg(feature = "i32")]
+check_width!(check_i32, i32, u32, 32);
+#[cfg(feature = "i64")]
+check_width!(check_i64, i64, u64, 64);
+#[cfg(feature = "i128")]
+check_width!(check_i128, i128, u128, 128);
+
+/// Run the self test for every enabled integer width
+///
+/// # Returns
+/// * `Ok(())` if every check passed
+/// * `Err(SelfTestError)` describing the first check that failed
+pub fn self_test() -> Result<(), SelfTestError> {
+    #[cfg(feature = "i8")]
+    check_i8()?;
+    #[cfg(feature = "i16")]
+    check_i16()?;
+    #[cfg(feature = "i32")]
+    check_i32()?;
+    #[cfg(feature = "i64")]
+    check_i64()?;
+    #[cfg(feature = "i128")]
+    check_i128()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert_eq!(self_test(), Ok(()));
+    }
+
+    #[test]
+    fn test_self_test_error_display() {
+        let error = SelfTestError::VarintOverflow { bits: 16 };
+        assert_eq!(std::format!("{}", error), "self test failed: varint overflow check for 16-bit integers");
+    }
+}
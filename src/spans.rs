@@ -0,0 +1,199 @@
+//! Offset and length arrays of variable-length columns.
+//!
+//! String and blob columns keep their bytes in one buffer and describe each
+//! entry by an `(offset, length)` span. This module stores both arrays in one
+//! block. Offsets are non-decreasing and stored as varint gaps. Each length
+//! is stored as the ZigZag varint of its difference to the gap to the next
+//! offset, so tightly packed columns cost one byte per length, and padded or
+//! overlapping entries still encode.
+//!
+//! ```text
+//! count (varint) | offset bytes (varint) | first offset (varint) | gap* (varint) | ZigZag(length - gap)*
+//! ```
+//!
+//! The last length has no following gap and is stored as ZigZag(length).
+//! The byte length of the offset section in the header lets readers reach
+//! the lengths without decoding the offsets.
+//!
+//! ```rust
+//! use zigzag_rs::spans;
+//!
+//! // "ab", "", "cde" packed back to back, then "xy" after 10 bytes of padding
+//! let offsets = [0u64, 2, 2, 15];
+//! let lengths = [2u64, 0, 3, 2];
+//! let mut buf = [0u8; 32];
+//! let len = spans::encode(&offsets, &lengths, &mut buf).unwrap();
+//! assert_eq!(len, 2 + 4 + 4);
+//!
+//! let (mut decoded_offsets, mut decoded_lengths) = ([0u64; 4], [0u64; 4]);
+//! assert_eq!(spans::decode(&buf[..len], &mut decoded_offsets, &mut decoded_lengths), Ok(4));
+//! assert_eq!((decoded_offsets, decoded_lengths), (offsets, lengths));
+//! ```
+
+use crate::varint::{self, Varint};
+use crate::{ZigZag, ZigZagError};
+
+/// Number of bytes [`encode`] writes
+///
+/// # Panics
+/// Panics if `offsets` and `lengths` have different lengths
+pub fn encoded_len(offsets: &[u64], lengths: &[u64]) -> usize {
+    assert_eq!(offsets.len(), lengths.len(), "offsets and lengths must have the same length");
+    let offsets_len = offsets_len(offsets);
+    let lengths_len: usize = residuals(offsets, lengths).map(|residual| residual.varint_len()).sum();
+    (offsets.len() as u64).varint_len() + (offsets_len as u64).varint_len() + offsets_len + lengths_len
+}
+
+/// Encode the spans of a column
+///
+/// # Arguments
+/// * `offsets` - Start of each entry, non-decreasing
+/// * `lengths` - Length of each entry
+/// * `out` - Output buffer, [`encoded_len`] bytes are needed
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::InvalidData)` with the index of the first out of order offset
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+///
+/// # Panics
+/// Panics if `offsets` and `lengths` have different lengths
+pub fn encode(offsets: &[u64], lengths: &[u64], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if let Some(index) = offsets.windows(2).position(|pair| pair[0] > pair[1]) {
+        return Err(ZigZagError::InvalidData { offset: index + 1 });
+    }
+    let needed = encoded_len(offsets, lengths);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let mut pos = (offsets.len() as u64).encode_varint(out)?;
+    pos += (offsets_len(offsets) as u64).encode_varint(&mut out[pos..])?;
+    let mut prev = 0;
+    for &offset in offsets {
+        pos += (offset - prev).encode_varint(&mut out[pos..])?;
+        prev = offset;
+    }
+    for residual in residuals(offsets, lengths) {
+        pos += residual.encode_varint(&mut out[pos..])?;
+    }
+    Ok(pos)
+}
+
+/// Number of spans stored in `input`
+///
+/// # Returns
+/// * `Ok(count)` with the number of spans
+/// * `Err(ZigZagError)` if the header is malformed
+pub fn count(input: &[u8]) -> Result<usize, ZigZagError> {
+    read_header(input).map(|(count, ..)| count)
+}
+
+/// Decode the spans in `input`
+///
+/// # Returns
+/// * `Ok(count)` with the number of spans decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `offsets` or `lengths` cannot hold all spans
+/// * `Err(ZigZagError)` with the byte offset of the problem if `input` is malformed
+pub fn decode(input: &[u8], offsets: &mut [u64], lengths: &mut [u64]) -> Result<usize, ZigZagError> {
+    let (count, mut pos, lengths_start) = read_header(input)?;
+    let actual = offsets.len().min(lengths.len());
+    if count > actual {
+        return Err(ZigZagError::BufferTooSmall { needed: count, actual });
+    }
+
+    let mut prev = 0u64;
+    for slot in offsets[..count].iter_mut() {
+        let (gap, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        prev = prev.checked_add(gap).ok_or(ZigZagError::InvalidData { offset: pos })?;
+        *slot = prev;
+        pos += len;
+    }
+    if pos != lengths_start {
+        return Err(ZigZagError::InvalidData { offset: pos });
+    }
+
+    for i in 0..count {
+        let (residual, len) = varint::decode::<i64>(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        let gap = if i + 1 < count { offsets[i + 1] - offsets[i] } else { 0 };
+        lengths[i] = gap.wrapping_add(residual as u64);
+        pos += len;
+    }
+    Ok(count)
+}
+
+/// Read the header, returning the count and the start of both sections
+fn read_header(input: &[u8]) -> Result<(usize, usize, usize), ZigZagError> {
+    let (count, mut pos) = u64::decode_varint(input)?;
+    let (offsets_len, len) = u64::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+    pos += len;
+    // Every offset takes at least one byte
+    if offsets_len < count || offsets_len > (input.len() - pos) as u64 {
+        return Err(ZigZagError::InvalidData { offset: pos - len });
+    }
+    Ok((count as usize, pos, pos + offsets_len as usize))
+}
+
+/// Byte length of the offset section
+fn offsets_len(offsets: &[u64]) -> usize {
+    let mut prev = 0;
+    offsets
+        .iter()
+        .map(|&offset| {
+            let gap = offset.wrapping_sub(prev);
+            prev = offset;
+            gap.varint_len()
+        })
+        .sum()
+}
+
+/// ZigZag encoded difference of each length to the gap to the next offset
+fn residuals<'a>(offsets: &'a [u64], lengths: &'a [u64]) -> impl Iterator<Item = u64> + 'a {
+    lengths.iter().enumerate().map(move |(i, &length)| {
+        let gap = offsets.get(i + 1).map_or(0, |&next| next.wrapping_sub(offsets[i]));
+        i64::zigzag_encode(length.wrapping_sub(gap) as i64)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_spans_round_trip() {
+        // Packed strings, an overlapping entry and a gap with padding
+        let mut offsets: Vec<u64> = (0..300).map(|i| i * 7).collect();
+        let mut lengths = vec![7u64; 300];
+        offsets.extend([2100, 2100, 5_000_000]);
+        lengths.extend([u64::MAX, 40, 0]);
+
+        let mut buf = vec![0u8; encoded_len(&offsets, &lengths)];
+        let len = encode(&offsets, &lengths, &mut buf).unwrap();
+        assert_eq!(len, buf.len());
+        assert_eq!(count(&buf), Ok(303));
+
+        let (mut decoded_offsets, mut decoded_lengths) = (vec![0u64; 303], vec![0u64; 303]);
+        assert_eq!(decode(&buf, &mut decoded_offsets, &mut decoded_lengths), Ok(303));
+        assert_eq!(decoded_offsets, offsets);
+        assert_eq!(decoded_lengths, lengths);
+    }
+
+    #[test]
+    fn test_spans_errors() {
+        let mut buf = [0u8; 16];
+        assert_eq!(encode(&[4, 2], &[1, 1], &mut buf), Err(ZigZagError::InvalidData { offset: 1 }));
+        assert_eq!(encode(&[0, 2], &[2, 2], &mut buf[..3]), Err(ZigZagError::BufferTooSmall { needed: 6, actual: 3 }));
+
+        let len = encode(&[0, 2], &[2, 2], &mut buf).unwrap();
+        let mut small = [0u64; 1];
+        let mut lengths = [0u64; 2];
+        assert_eq!(
+            decode(&buf[..len], &mut small, &mut lengths),
+            Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 })
+        );
+        // The offset section claims more bytes than the input holds
+        assert_eq!(count(&buf[..3]), Err(ZigZagError::InvalidData { offset: 1 }));
+    }
+}
@@ -0,0 +1,329 @@
+//! Timestamp columns with automatic resolution scaling.
+//!
+//! Clocks often tick in milliseconds or seconds while their timestamps are
+//! stored as nanoseconds, so every difference between consecutive values is
+//! a multiple of 10^6 or 10^9 and carries 20 or 30 useless low bits. This
+//! module finds the coarsest [`Resolution`] that divides every difference,
+//! divides it out and records it in a one-byte header. Decoding multiplies
+//! it back in, so the values round-trip exactly.
+//!
+//! ```text
+//! resolution (u8) | first value (ZigZag varint) | (difference / scale)* (ZigZag varint)
+//! ```
+//!
+//! The first value is stored unscaled, so only the differences need to be
+//! multiples of the resolution.
+//!
+//! ```rust
+//! use zigzag_rs::timestamp::{self, Resolution};
+//!
+//! // Nanosecond timestamps from a millisecond clock
+//! let base = 1_700_000_000_123_000_000i64;
+//! let times: Vec<i64> = (0..100).map(|i| base + i * 16_000_000).collect();
+//! assert_eq!(timestamp::resolution(&times), Resolution::Millis);
+//!
+//! let mut buf = [0u8; 256];
+//! let len = timestamp::encode(&times, &mut buf).unwrap();
+//! assert_eq!(len, 1 + 9 + 99);
+//!
+//! let mut decoded = [0i64; 100];
+//! assert_eq!(timestamp::decode(&buf[..len], &mut decoded), Ok(100));
+//! assert_eq!(&decoded[..], &times[..]);
+//! ```
+//!
+//! [`encode_times`] and [`decode_times`] accept time types directly through
+//! the [`TimeValue`] trait, which converts to and from nanoseconds.
+//! It is implemented for [`Duration`] and, with the `std` feature, for
+//! `SystemTime`; applications using other date-time libraries can implement it
+//! for their types in a few lines.
+//!
+//! ```rust
+//! use core::time::Duration;
+//! use zigzag_rs::timestamp;
+//!
+//! let uptimes = [Duration::from_millis(1500), Duration::from_millis(1750), Duration::from_secs(2)];
+//! let mut buf = [0u8; 16];
+//! let len = timestamp::encode_times(&uptimes, &mut buf).unwrap();
+//!
+//! let mut decoded = [Duration::ZERO; 3];
+//! assert_eq!(timestamp::decode_times(&buf[..len], &mut decoded), Ok(3));
+//! assert_eq!(decoded, uptimes);
+//! ```
+
+use core::time::Duration;
+
+use crate::varint::{self, Varint};
+use crate::{delta, ZigZag, ZigZagError};
+
+/// Common factor of all differences between consecutive timestamps
+///
+/// The names assume nanosecond timestamps. Timestamps in other units are
+/// scaled by the same powers of 1000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Resolution {
+    /// Differences are stored as they are
+    #[default]
+    Nanos = 0,
+    /// Differences are multiples of 1000
+    Micros = 1,
+    /// Differences are multiples of 1 000 000
+    Millis = 2,
+    /// Differences are multiples of 1 000 000 000
+    Seconds = 3,
+}
+
+impl Resolution {
+    /// Coarsest resolution first
+    const ALL: [Resolution; 4] = [Resolution::Seconds, Resolution::Millis, Resolution::Micros, Resolution::Nanos];
+
+    /// Factor the differences are divided by
+    pub fn scale(self) -> i64 {
+        match self {
+            Resolution::Nanos => 1,
+            Resolution::Micros => 1_000,
+            Resolution::Millis => 1_000_000,
+            Resolution::Seconds => 1_000_000_000,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|&resolution| resolution as u8 == value)
+    }
+}
+
+/// Coarsest resolution that divides every difference between consecutive values
+pub fn resolution(values: &[i64]) -> Resolution {
+    resolution_of(|| values.iter().copied())
+}
+
+/// Number of bytes [`encode`] needs for `values`
+pub fn encoded_len(values: &[i64]) -> usize {
+    encoded_len_at(|| values.iter().copied(), resolution(values))
+}
+
+/// Encode `values` with the coarsest resolution that fits them
+///
+/// # Arguments
+/// * `values` - Timestamps to encode
+/// * `out` - Output buffer for the encoded bytes
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode(values: &[i64], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    encode_nanos(|| values.iter().copied(), out)
+}
+
+/// Resolution recorded in the header of `input`
+///
+/// # Returns
+/// * `Ok(resolution)` with the resolution the differences were divided by
+/// * `Err(ZigZagError::UnexpectedEof)` if `input` is empty
+/// * `Err(ZigZagError::InvalidData)` for an unknown resolution
+pub fn read_resolution(input: &[u8]) -> Result<Resolution, ZigZagError> {
+    let &byte = input.first().ok_or(ZigZagError::UnexpectedEof { offset: 0 })?;
+    Resolution::from_u8(byte).ok_or(ZigZagError::InvalidData { offset: 0 })
+}
+
+/// Decode timestamps written by [`encode`]
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError)` with the byte offset of the problem if `input` is malformed
+pub fn decode(input: &[u8], out: &mut [i64]) -> Result<usize, ZigZagError> {
+    let scale = read_resolution(input)?.scale();
+    let count = delta::decode(&input[1..], out).map_err(|err| varint::at_offset(err, 1))?;
+    // The delta decoder summed the scaled differences, scale them back up
+    if let Some(&first) = out[..count].first() {
+        for value in out[1..count].iter_mut() {
+            *value = first.wrapping_add(value.wrapping_sub(first).wrapping_mul(scale));
+        }
+    }
+    Ok(count)
+}
+
+/// Time types that can be stored as `i64` nanoseconds
+pub trait TimeValue: Copy {
+    /// Nanoseconds since the epoch of the type, or `None` outside the range of `i64`
+    fn to_nanos(self) -> Option<i64>;
+
+    /// Value `nanos` nanoseconds after the epoch of the type, saturating at its limits
+    fn from_nanos(nanos: i64) -> Self;
+}
+
+impl TimeValue for Duration {
+    fn to_nanos(self) -> Option<i64> {
+        i64::try_from(self.as_nanos()).ok()
+    }
+
+    /// Negative values saturate to [`Duration::ZERO`]
+    fn from_nanos(nanos: i64) -> Self {
+        Duration::from_nanos(nanos.max(0) as u64)
+    }
+}
+
+/// Nanoseconds relative to `UNIX_EPOCH`, negative before it
+#[cfg(feature = "std")]
+impl TimeValue for std::time::SystemTime {
+    fn to_nanos(self) -> Option<i64> {
+        match self.duration_since(std::time::UNIX_EPOCH) {
+            Ok(after) => after.to_nanos(),
+            Err(before) => before.duration().to_nanos().map(|nanos| -nanos),
+        }
+    }
+
+    fn from_nanos(nanos: i64) -> Self {
+        let offset = Duration::from_nanos(nanos.unsigned_abs());
+        if nanos < 0 {
+            std::time::UNIX_EPOCH.checked_sub(offset)
+        } else {
+            std::time::UNIX_EPOCH.checked_add(offset)
+        }
+        .unwrap_or(std::time::UNIX_EPOCH)
+    }
+}
+
+/// Encode time values like [`encode`], converting them to nanoseconds
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::InvalidData)` with the index of the first value outside the
+///   range of `i64` nanoseconds
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode_times<T: TimeValue>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if let Some(index) = values.iter().position(|value| value.to_nanos().is_none()) {
+        return Err(ZigZagError::InvalidData { offset: index });
+    }
+    encode_nanos(|| values.iter().map(|value| value.to_nanos().unwrap_or_default()), out)
+}
+
+/// Decode timestamps written by [`encode`] or [`encode_times`] as time values
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError)` with the byte offset of the problem if `input` is malformed
+pub fn decode_times<T: TimeValue>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError> {
+    let scale = read_resolution(input)?.scale();
+    let needed = varint::count(&input[1..]);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    let mut pos = 1;
+    let mut prev = 0i64;
+    for (i, slot) in out[..needed].iter_mut().enumerate() {
+        let (value, len) = varint::decode::<i64>(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+        prev = if i == 0 { value } else { prev.wrapping_add(value.wrapping_mul(scale)) };
+        *slot = T::from_nanos(prev);
+        pos += len;
+    }
+    Ok(needed)
+}
+
+// The helpers below take a closure producing the values, so that time values
+// can be converted on each pass instead of into a buffer.
+
+fn resolution_of<I: Iterator<Item = i64>>(values: impl Fn() -> I) -> Resolution {
+    Resolution::ALL
+        .iter()
+        .copied()
+        .find(|resolution| differences(values()).all(|diff| diff % resolution.scale() == 0))
+        .unwrap_or_default()
+}
+
+fn encoded_len_at<I: Iterator<Item = i64>>(values: impl Fn() -> I, resolution: Resolution) -> usize {
+    let first_len = values().next().map_or(0, |first| i64::zigzag_encode(first).varint_len());
+    let diffs_len: usize = differences(values())
+        .map(|diff| i64::zigzag_encode(diff / resolution.scale()).varint_len())
+        .sum();
+    1 + first_len + diffs_len
+}
+
+fn encode_nanos<I: Iterator<Item = i64>>(values: impl Fn() -> I, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    let resolution = resolution_of(&values);
+    let needed = encoded_len_at(&values, resolution);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    out[0] = resolution as u8;
+    let mut pos = 1;
+    if let Some(first) = values().next() {
+        pos += varint::encode(first, &mut out[pos..])?;
+    }
+    for diff in differences(values()) {
+        pos += varint::encode(diff / resolution.scale(), &mut out[pos..])?;
+    }
+    Ok(pos)
+}
+
+/// Wrapping differences between consecutive values
+fn differences(values: impl Iterator<Item = i64>) -> impl Iterator<Item = i64> {
+    let mut prev = None;
+    values.filter_map(move |value| prev.replace(value).map(|prev| value.wrapping_sub(prev)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolution_detection() {
+        assert_eq!(resolution(&[]), Resolution::Seconds);
+        assert_eq!(resolution(&[7, 2_000_000_007]), Resolution::Seconds);
+        assert_eq!(resolution(&[0, 3_000, 1_000]), Resolution::Micros);
+        assert_eq!(resolution(&[0, 5_000_000, 5_000_001]), Resolution::Nanos);
+        // Differences wrap as in delta coding, this one is 10^6
+        assert_eq!(resolution(&[i64::MAX, i64::MIN + 999_999]), Resolution::Millis);
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let mut buf = [0u8; 64];
+        for values in [&[][..], &[-5], &[i64::MIN, i64::MAX - 999, 1_000_000 - 999], &[10, 4_000_010, 4_000_010, 10]] {
+            let len = encode(values, &mut buf).unwrap();
+            assert_eq!(len, encoded_len(values));
+            assert_eq!(read_resolution(&buf), Ok(resolution(values)));
+            let mut decoded = [0i64; 4];
+            assert_eq!(decode(&buf[..len], &mut decoded), Ok(values.len()));
+            assert_eq!(&decoded[..values.len()], values);
+        }
+
+        assert_eq!(decode(&[4], &mut [0i64; 1]), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(decode(&[0, 2, 0x80], &mut [0i64; 2]), Err(ZigZagError::UnexpectedEof { offset: 2 }));
+    }
+
+    #[test]
+    fn test_time_values() {
+        let durations = [Duration::from_secs(1), Duration::new(1, 500_000_000), Duration::ZERO];
+        let mut buf = [0u8; 32];
+        let len = encode_times(&durations, &mut buf).unwrap();
+        assert_eq!(read_resolution(&buf), Ok(Resolution::Millis));
+        // The nanosecond encoding of the same instants is identical
+        let nanos = [1_000_000_000i64, 1_500_000_000, 0];
+        assert_eq!(encode(&nanos, &mut [0u8; 32]), Ok(len));
+        let mut decoded = [Duration::MAX; 3];
+        assert_eq!(decode_times(&buf[..len], &mut decoded), Ok(3));
+        assert_eq!(decoded, durations);
+
+        assert_eq!(
+            encode_times(&[Duration::ZERO, Duration::MAX], &mut buf),
+            Err(ZigZagError::InvalidData { offset: 1 })
+        );
+        assert_eq!(decode_times(&buf[..len], &mut [Duration::ZERO; 2]), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_system_time_values() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let times = [UNIX_EPOCH - Duration::from_secs(86_400), UNIX_EPOCH, UNIX_EPOCH + Duration::from_micros(1_700_000_000_000_001)];
+        let mut buf = [0u8; 32];
+        let len = encode_times(&times, &mut buf).unwrap();
+        let mut decoded = [SystemTime::UNIX_EPOCH; 3];
+        assert_eq!(decode_times(&buf[..len], &mut decoded), Ok(3));
+        assert_eq!(decoded, times);
+    }
+}
@@ -0,0 +1,137 @@
+//! Unaligned ZigZag fields for mapping onto packet and DMA memory.
+//!
+//! Packet headers and DMA descriptors place fields at any byte offset and
+//! fix their byte order. The types here store the ZigZag form of a value as
+//! plain bytes in a fixed byte order, so they have an alignment of one and no
+//! invalid bit patterns. A struct of them can be viewed straight over a
+//! received buffer with [`cast::from_bytes`](crate::cast::from_bytes), and
+//! each field is decoded when it is read.
+//!
+//! ```text
+//! I32Le:  [u8; 4], ZigZag encoded, least significant byte first
+//! I32Be:  [u8; 4], ZigZag encoded, most significant byte first
+//! ```
+//!
+//! ```rust
+//! # #[cfg(all(feature = "i16", feature = "i32"))] {
+//! use zigzag_rs::cast;
+//! use zigzag_rs::unaligned::{I16Be, I32Le};
+//!
+//! let packet = [0xaa, 0x03, 0x00, 0x00, 0x00, 0x02, 0x58];
+//! // A 4-byte field right after a 1-byte tag
+//! let fields: &[I32Le] = cast::from_bytes(&packet[1..5]).unwrap();
+//! assert_eq!(fields[0].get(), -2);
+//!
+//! let offset: &[I16Be] = cast::from_bytes(&packet[5..]).unwrap();
+//! assert_eq!(offset[0].get(), 300);
+//! assert_eq!(I16Be::new(300).to_bytes(), [0x02, 0x58]);
+//! # }
+//! ```
+
+#[allow(unused_macros)]
+macro_rules! impl_unaligned {
+    ($name:ident, $signed:ty, $unsigned:ty, $to:ident, $from:ident, $order:literal) => {
+        #[doc = concat!("ZigZag encoded `", stringify!($signed), "` stored as ", $order, " bytes")]
+        #[repr(transparent)]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name([u8; core::mem::size_of::<$signed>()]);
+
+        impl $name {
+            /// Encode a value
+            pub fn new(value: $signed) -> Self {
+                $name(<$signed as crate::ZigZag>::zigzag_encode(value).$to())
+            }
+
+            /// Decode the value
+            pub fn get(self) -> $signed {
+                <$signed as crate::ZigZag>::zigzag_decode(<$unsigned>::$from(self.0))
+            }
+
+            /// Replace the value
+            pub fn set(&mut self, value: $signed) {
+                *self = Self::new(value);
+            }
+
+            /// Wrap the stored bytes of a field
+            pub fn from_bytes(bytes: [u8; core::mem::size_of::<$signed>()]) -> Self {
+                $name(bytes)
+            }
+
+            /// Stored bytes of the field
+            pub fn to_bytes(self) -> [u8; core::mem::size_of::<$signed>()] {
+                self.0
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.get()).finish()
+            }
+        }
+
+        impl From<$signed> for $name {
+            fn from(value: $signed) -> Self {
+                Self::new(value)
+            }
+        }
+
+        // SAFETY: a transparent byte array has no padding and no invalid bit patterns
+        unsafe impl crate::cast::Plain for $name {}
+    };
+}
+
+#[cfg(feature = "i16")]
+impl_unaligned!(I16Le, i16, u16, to_le_bytes, from_le_bytes, "little-endian");
+#[cfg(feature = "i16")]
+impl_unaligned!(I16Be, i16, u16, to_be_bytes, from_be_bytes, "big-endian");
+#[cfg(feature = "i32")]
+impl_unaligned!(I32Le, i32, u32, to_le_bytes, from_le_bytes, "little-endian");
+#[cfg(feature = "i32")]
+impl_unaligned!(I32Be, i32, u32, to_be_bytes, from_be_bytes, "big-endian");
+#[cfg(feature = "i64")]
+impl_unaligned!(I64Le, i64, u64, to_le_bytes, from_le_bytes, "little-endian");
+#[cfg(feature = "i64")]
+impl_unaligned!(I64Be, i64, u64, to_be_bytes, from_be_bytes, "big-endian");
+#[cfg(feature = "i128")]
+impl_unaligned!(I128Le, i128, u128, to_le_bytes, from_le_bytes, "little-endian");
+#[cfg(feature = "i128")]
+impl_unaligned!(I128Be, i128, u128, to_be_bytes, from_be_bytes, "big-endian");
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::cast;
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_unaligned_fields_round_trip() {
+        for value in [i64::MIN, -1, 0, 1, i64::MAX] {
+            assert_eq!(I64Le::new(value).get(), value);
+            assert_eq!(I64Be::new(value).get(), value);
+            let le = I64Le::new(value).to_bytes();
+            let mut be = I64Be::new(value).to_bytes();
+            be.reverse();
+            assert_eq!(le, be);
+        }
+        let mut field = I32Be::from(-1);
+        assert_eq!(field.to_bytes(), [0, 0, 0, 1]);
+        field.set(i32::MIN);
+        assert_eq!(field, I32Be::from_bytes([0xff; 4]));
+        assert_eq!(std::format!("{:?}", field), "I32Be(-2147483648)");
+        #[cfg(feature = "i128")]
+        assert_eq!(core::mem::align_of::<I128Le>(), 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i16", feature = "i32"))]
+    fn test_unaligned_fields_map_onto_buffers() {
+        let mut buffer = [0u8; 9];
+        let fields: &mut [I16Le] = cast::from_bytes_mut(&mut buffer[1..]).unwrap();
+        assert_eq!(fields.len(), 4);
+        fields[1].set(-300);
+        assert_eq!(buffer[3..5], [0x57, 0x02]);
+        // Any offset is aligned for a byte array
+        assert!(cast::from_bytes::<I32Be>(&buffer[3..7]).is_ok());
+    }
+}
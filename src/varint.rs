@@ -0,0 +1,938 @@
+//! Variable-length (LEB128) encoding of ZigZag-encoded values.
+//!
+//! Each value is first mapped to its unsigned ZigZag form and then written
+//! seven bits at a time, least significant group first. The high bit of every
+//! byte signals whether another byte follows, so small absolute values take a
+//! single byte regardless of the integer width.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::varint;
+//!
+//! let mut buf = [0u8; 16];
+//! let len = varint::encode_slice(&[-1i32, 64, -300], &mut buf).unwrap();
+//! assert_eq!(&buf[..len], &[0x01, 0x80, 0x01, 0xd7, 0x04]);
+//!
+//! let mut decoded = [0i32; 3];
+//! let count = varint::decode_slice(&buf[..len], &mut decoded).unwrap();
+//! assert_eq!(count, 3);
+//! assert_eq!(decoded, [-1, 64, -300]);
+//! # }
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+
+#[cfg(feature = "i64")]
+use crate::Sample;
+use crate::{ZigZag, ZigZagError};
+
+/// Trait for unsigned integers that can be written as LEB128 varints
+pub trait Varint: Sized + Copy {
+    /// Maximum number of bytes an encoded value can occupy
+    const MAX_LEN: usize;
+
+    /// Number of bytes needed to encode this value
+    fn varint_len(self) -> usize;
+
+    /// Encode the value into the start of `out`, returning the number of bytes written
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold the encoded value
+    fn encode_varint(self, out: &mut [u8]) -> Result<usize, ZigZagError>;
+
+    /// Decode a value from the start of `input`, returning it with the number of bytes read
+    ///
+    /// # Returns
+    /// * `Ok((value, len))` with the decoded value and the number of bytes consumed
+    /// * `Err(ZigZagError::UnexpectedEof)` if the input ends in the middle of the value
+    /// * `Err(ZigZagError::VarintOverflow)` if the value does not fit into `Self`
+    fn decode_varint(input: &[u8]) -> Result<(Self, usize), ZigZagError>;
+}
+
+/// Longest varint of any width this crate encodes: 37 bytes for the 256-bit
+/// integers of the `i256` feature, 19 bytes for 128 bits otherwise
+///
+/// Scratch buffers of this size hold one varint of any [`Varint`] type. A
+/// type with a larger [`Varint::MAX_LEN`] is rejected at compile time by the
+/// functions that encode or decode through such a buffer.
+pub const MAX_VARINT_LEN: usize = if cfg!(feature = "i256") { 37 } else { 19 };
+
+/// Buffer that holds one varint of `U`
+#[inline]
+pub(crate) fn scratch<U: Varint>() -> [u8; MAX_VARINT_LEN] {
+    const { assert!(U::MAX_LEN <= MAX_VARINT_LEN, "Varint::MAX_LEN exceeds MAX_VARINT_LEN") };
+    [0; MAX_VARINT_LEN]
+}
+
+/// Number of bytes a varint of `used_bits` significant bits occupies
+#[inline]
+fn len_for_bits(used_bits: u32) -> usize {
+    if used_bits == 0 { 1 } else { (used_bits as usize).div_ceil(7) }
+}
+
+// The widths up to 64 bits share these non-generic routines on `u64`, so a
+// binary using several widths carries one copy of the loops instead of one
+// per width.
+
+/// Encode `value` as a varint of exactly `len` bytes
+fn encode_u64(mut value: u64, len: usize, out: &mut [u8]) -> Result<usize, ZigZagError> {
+    if out.len() < len {
+        return Err(ZigZagError::BufferTooSmall { needed: len, actual: out.len() });
+    }
+    for byte in out[..len - 1].iter_mut() {
+        *byte = (value as u8 & 0x7f) | 0x80;
+        value >>= 7;
+    }
+    out[len - 1] = value as u8;
+    Ok(len)
+}
+
+/// Decode a varint holding at most `bits` significant bits
+fn decode_u64(input: &[u8], bits: u32) -> Result<(u64, usize), ZigZagError> {
+    let max_len = len_for_bits(bits);
+    let mut result = 0u64;
+    for (i, &byte) in input.iter().enumerate().take(max_len) {
+        let group = (byte & 0x7f) as u64;
+        if i == max_len - 1 {
+            // The last possible byte may only carry the remaining high bits
+            let remaining_bits = bits as usize - 7 * (max_len - 1);
+            if byte & 0x80 != 0 || (group >> remaining_bits) != 0 {
+                return Err(ZigZagError::VarintOverflow { offset: 0 });
+            }
+        }
+        result |= group << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+    }
+    Err(ZigZagError::UnexpectedEof { offset: 0 })
+}
+
+macro_rules! impl_varint {
+    ($unsigned:ty, $bits:expr) => {
+        impl Varint for $unsigned {
+            const MAX_LEN: usize = ($bits as usize).div_ceil(7);
+
+            #[inline]
+            fn varint_len(self) -> usize {
+                len_for_bits($bits - self.leading_zeros())
+            }
+
+            #[inline]
+            fn encode_varint(self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+                encode_u64(self as u64, self.varint_len(), out)
+            }
+
+            #[inline]
+            fn decode_varint(input: &[u8]) -> Result<(Self, usize), ZigZagError> {
+                let (value, len) = decode_u64(input, $bits)?;
+                Ok((value as $unsigned, len))
+            }
+        }
+    };
+}
+
+impl_varint!(u8, 8);
+impl_varint!(u16, 16);
+impl_varint!(u32, 32);
+impl_varint!(u64, 64);
+impl_varint!(usize, usize::BITS);
+
+#[cfg(feature = "i128")]
+impl Varint for u128 {
+    const MAX_LEN: usize = 128usize.div_ceil(7);
+
+    #[inline]
+    fn varint_len(self) -> usize {
+        len_for_bits(128 - self.leading_zeros())
+    }
+
+    fn encode_varint(self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+        let len = self.varint_len();
+        if out.len() < len {
+            return Err(ZigZagError::BufferTooSmall { needed: len, actual: out.len() });
+        }
+        let mut value = self;
+        for byte in out[..len - 1].iter_mut() {
+            *byte = (value as u8 & 0x7f) | 0x80;
+            value >>= 7;
+        }
+        out[len - 1] = value as u8;
+        Ok(len)
+    }
+
+    fn decode_varint(input: &[u8]) -> Result<(Self, usize), ZigZagError> {
+        let mut result = 0u128;
+        for (i, &byte) in input.iter().enumerate().take(Self::MAX_LEN) {
+            let group = (byte & 0x7f) as u128;
+            if i == Self::MAX_LEN - 1 {
+                let remaining_bits = 128 - 7 * (Self::MAX_LEN - 1);
+                if byte & 0x80 != 0 || (group >> remaining_bits) != 0 {
+                    return Err(ZigZagError::VarintOverflow { offset: 0 });
+                }
+            }
+            result |= group << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok((result, i + 1));
+            }
+        }
+        Err(ZigZagError::UnexpectedEof { offset: 0 })
+    }
+}
+
+/// Encode a single signed integer as a ZigZag varint
+///
+/// # Arguments
+/// * `value` - Signed integer to encode
+/// * `out` - Output buffer, the encoded bytes are written to its start
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` is too small
+pub fn encode<T>(value: T, out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    T::zigzag_encode(value).encode_varint(out)
+}
+
+/// Decode a single ZigZag varint from the start of `input`
+///
+/// # Returns
+/// * `Ok((value, len))` with the decoded value and the number of bytes consumed
+/// * `Err(ZigZagError::UnexpectedEof)` if the input ends in the middle of the value
+/// * `Err(ZigZagError::VarintOverflow)` if the value does not fit into `T`
+pub fn decode<T>(input: &[u8]) -> Result<(T, usize), ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    let (value, len) = T::UInt::decode_varint(input)?;
+    Ok((T::zigzag_decode(value), len))
+}
+
+/// Encode a slice of signed integers as consecutive ZigZag varints
+///
+/// # Arguments
+/// * `values` - Slice of signed integers to encode
+/// * `out` - Output buffer for the encoded bytes
+///
+/// # Returns
+/// * `Ok(len)` with the total number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode_slice<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let mut pos = 0;
+    for (i, &value) in values.iter().enumerate() {
+        match encode(value, &mut out[pos..]) {
+            Ok(len) => pos += len,
+            Err(_) => {
+                let needed = pos + encoded_len(&values[i..]);
+                return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+            }
+        }
+    }
+    Ok(pos)
+}
+
+/// Append `values` as consecutive ZigZag varints to the end of `out`
+///
+/// The bytes are written straight into the spare capacity of `out`, so
+/// repeated appends need neither a zero-initialized buffer nor an
+/// intermediate copy. `out` grows by exactly [`encoded_len`] bytes.
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes appended
+/// * `Err(err)` with the error of a value that could not be encoded, in which case nothing is appended
+///
+/// # Panics
+/// Panics if a [`Varint`] implementation writes a different number of bytes
+/// than its [`Varint::varint_len`] reports
+#[cfg(feature = "alloc")]
+pub fn encode_append<T>(values: &[T], out: &mut Vec<u8>) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let needed = encoded_len(values);
+    out.reserve(needed);
+    let spare = &mut out.spare_capacity_mut()[..needed];
+    let mut buf = scratch::<T::UInt>();
+    let mut pos = 0;
+    for &value in values {
+        let len = encode(value, &mut buf)?;
+        for (slot, &byte) in spare[pos..pos + len].iter_mut().zip(&buf[..len]) {
+            slot.write(byte);
+        }
+        pos += len;
+    }
+    assert_eq!(pos, needed, "Varint implementation wrote a different length than varint_len");
+    // SAFETY: the loop initialized the first `pos` spare bytes, which `reserve`
+    // made room for, and `pos` equals `needed`
+    unsafe { out.set_len(out.len() + needed) };
+    Ok(needed)
+}
+
+/// Decode all ZigZag varints in `input` into `out`
+///
+/// # Arguments
+/// * `input` - Encoded bytes, expected to contain only complete varints
+/// * `out` - Output slice to store decoded signed integers
+///
+/// # Returns
+/// * `Ok(count)` with the number of values decoded
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError::UnexpectedEof)` or `Err(ZigZagError::VarintOverflow)` with the
+///   offset of the offending value if `input` is malformed
+pub fn decode_slice<T>(input: &[u8], out: &mut [T]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    let needed = count(input);
+    if needed > out.len() {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let mut pos = 0;
+    let mut count = 0;
+    while pos < input.len() {
+        let (value, len) = decode::<T>(&input[pos..]).map_err(|err| at_offset(err, pos))?;
+        out[count] = value;
+        count += 1;
+        pos += len;
+    }
+    Ok(count)
+}
+
+/// Decode all ZigZag varints in `input` into a possibly narrower type, saturating
+///
+/// Where [`decode_slice`] rejects a value that does not fit into `T` with
+/// `VarintOverflow`, this clamps it to `T::MIN` or `T::MAX` and keeps going,
+/// which suits display and telemetry paths where one outlier should not
+/// drop the whole batch. Values are read as `i64`, so streams written with
+/// any width up to 64 bits can be decoded into any narrower one.
+///
+/// # Returns
+/// * `Ok((count, clamped))` with the number of values decoded and how many of them were clamped
+/// * `Err(ZigZagError::BufferTooSmall)` if `out` cannot hold all values in `input`
+/// * `Err(ZigZagError::UnexpectedEof)` or `Err(ZigZagError::VarintOverflow)` with the
+///   offset of the offending value if `input` is malformed or a value exceeds 64 bits
+#[cfg(feature = "i64")]
+pub fn decode_slice_saturating<T>(input: &[u8], out: &mut [T]) -> Result<(usize, usize), ZigZagError>
+where
+    T: Sample,
+{
+    let needed = count(input);
+    if needed > out.len() {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+
+    let shift = 64 - 8 * core::mem::size_of::<T>() as u32;
+    let (min, max) = (i64::MIN >> shift, i64::MAX >> shift);
+    let mut pos = 0;
+    let mut count = 0;
+    let mut clamped = 0;
+    while pos < input.len() {
+        let (value, len) = decode::<i64>(&input[pos..]).map_err(|err| at_offset(err, pos))?;
+        clamped += usize::from(value < min || value > max);
+        out[count] = T::from_i64(value.clamp(min, max));
+        count += 1;
+        pos += len;
+    }
+    Ok((count, clamped))
+}
+
+/// Append `values` as consecutive ZigZag varints to any byte collection
+///
+/// Works with every [`Extend<u8>`] collection, such as `Vec<u8>`, `SmallVec`
+/// or `heapless::Vec`, so protocol stacks can write frames into the buffer
+/// type they already use. Collections with a fixed capacity follow their own
+/// `Extend` behavior when they run full.
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes appended
+/// * `Err(err)` with the error of a value that could not be encoded, in which
+///   case the values before it have been appended
+pub fn encode_extend<T, E>(values: &[T], out: &mut E) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+    E: Extend<u8>,
+{
+    let mut buf = scratch::<T::UInt>();
+    let mut total = 0;
+    for &value in values {
+        let len = encode(value, &mut buf)?;
+        out.extend(buf[..len].iter().copied());
+        total += len;
+    }
+    Ok(total)
+}
+
+/// Decode all ZigZag varints in `input` and append them to any collection
+///
+/// `input` is validated before the first value is appended, so `out` is left
+/// unchanged on error.
+///
+/// # Returns
+/// * `Ok(count)` with the number of values appended
+/// * `Err(ZigZagError::UnexpectedEof)` or `Err(ZigZagError::VarintOverflow)` with the
+///   offset of the offending value if `input` is malformed
+pub fn decode_extend<T, E>(input: &[u8], out: &mut E) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+    E: Extend<T>,
+{
+    let count = crate::scan::validate::<T>(input)?;
+    let mut pos = 0;
+    // Validated above, so decoding cannot fail
+    out.extend(core::iter::from_fn(|| {
+        let (value, len) = decode::<T>(&input[pos..]).ok()?;
+        pos += len;
+        Some(value)
+    }));
+    Ok(count)
+}
+
+/// Number of bytes needed to encode all `values` as ZigZag varints
+pub fn encoded_len<T>(values: &[T]) -> usize
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    values.iter().map(|&value| T::zigzag_encode(value).varint_len()).sum()
+}
+
+/// Number of varints terminated in `input`
+///
+/// This only counts bytes without a continuation bit, it does not validate
+/// that each varint fits into a particular integer type. The bytes are
+/// scanned 16 at a time, see [`scan`](crate::scan).
+pub fn count(input: &[u8]) -> usize {
+    crate::scan::count(input)
+}
+
+/// First position at which two varint streams hold different values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the first differing value
+    pub index: usize,
+    /// Order of the first stream relative to the second at `index`. A stream
+    /// that ends there orders before one that continues.
+    pub ordering: Ordering,
+}
+
+/// Compare two ZigZag varint streams value by value without decoding them
+///
+/// Identical leading bytes are skipped in one pass and only the first
+/// differing value of each stream is decoded. Values before that point are
+/// counted but not validated.
+///
+/// # Arguments
+/// * `a` - First encoded stream
+/// * `b` - Second encoded stream
+///
+/// # Returns
+/// * `Ok(None)` if both streams hold the same values
+/// * `Ok(Some(divergence))` with the index of the first differing value and
+///   the lexicographic order of the streams
+/// * `Err(ZigZagError::UnexpectedEof)` or `Err(ZigZagError::VarintOverflow)` if a
+///   differing value is malformed, with its offset in the stream it was read from
+pub fn compare_streams<T>(a: &[u8], b: &[u8]) -> Result<Option<Divergence>, ZigZagError>
+where
+    T: ZigZag + Ord,
+    T::UInt: Varint,
+{
+    let (mut pos_a, mut pos_b, mut index) = (0, 0, 0);
+    loop {
+        let (rest_a, rest_b) = (&a[pos_a..], &b[pos_b..]);
+        let common = rest_a.iter().zip(rest_b).take_while(|(x, y)| x == y).count();
+        // Back up to the start of the value holding the first differing byte
+        let start = rest_a[..common].iter().rposition(|&byte| byte & 0x80 == 0).map_or(0, |i| i + 1);
+        index += count(&rest_a[..start]);
+        pos_a += start;
+        pos_b += start;
+
+        let ordering = match (pos_a == a.len(), pos_b == b.len()) {
+            (true, true) => return Ok(None),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => {
+                let (x, len_a) = decode::<T>(&a[pos_a..]).map_err(|err| at_offset(err, pos_a))?;
+                let (y, len_b) = decode::<T>(&b[pos_b..]).map_err(|err| at_offset(err, pos_b))?;
+                // Overlong encodings of the same value differ in bytes only
+                if x == y {
+                    pos_a += len_a;
+                    pos_b += len_b;
+                    index += 1;
+                    continue;
+                }
+                x.cmp(&y)
+            }
+        };
+        return Ok(Some(Divergence { index, ordering }));
+    }
+}
+
+/// Iterator yielding the ZigZag varint bytes of the values of an inner iterator, see [`encode_iter`]
+#[derive(Debug, Clone)]
+pub struct EncodeIter<I, T> {
+    iter: I,
+    buf: [u8; MAX_VARINT_LEN],
+    pos: usize,
+    len: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<I, T> Iterator for EncodeIter<I, T>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+    I: Iterator,
+    I::Item: Borrow<T>,
+{
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        if self.pos == self.len {
+            let value = *self.iter.next()?.borrow();
+            // The buffer holds any varint, so encoding does not fail
+            self.len = encode(value, &mut self.buf).ok()?;
+            self.pos = 0;
+        }
+        self.pos += 1;
+        Some(self.buf[self.pos - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.len - self.pos;
+        let (lower, upper) = self.iter.size_hint();
+        let upper = upper.and_then(|upper| upper.checked_mul(T::UInt::MAX_LEN)?.checked_add(pending));
+        (lower.saturating_add(pending), upper)
+    }
+}
+
+/// Iterate over the ZigZag varint bytes of the values of `iter`
+///
+/// The bytes of each value are produced as the iterator is consumed, so an
+/// encoded stream can be piped into any byte sink without a buffer. The
+/// bytes are the ones [`encode_slice`] writes.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::varint;
+///
+/// let bytes: Vec<u8> = varint::encode_iter::<i32, _>([-1, 64, -300]).collect();
+/// assert_eq!(bytes, [0x01, 0x80, 0x01, 0xd7, 0x04]);
+/// # }
+/// ```
+pub fn encode_iter<T, I>(iter: I) -> EncodeIter<I::IntoIter, T>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
+{
+    EncodeIter { iter: iter.into_iter(), buf: scratch::<T::UInt>(), pos: 0, len: 0, marker: PhantomData }
+}
+
+/// Iterator decoding the ZigZag varints in the bytes of an inner iterator, see [`decode_iter`]
+#[derive(Debug, Clone)]
+pub struct DecodeIter<I, T> {
+    iter: I,
+    // Offset of the next byte of the inner iterator
+    offset: usize,
+    failed: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<I, T> Iterator for DecodeIter<I, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+    I: Iterator,
+    I::Item: Borrow<u8>,
+{
+    type Item = Result<T, ZigZagError>;
+
+    fn next(&mut self) -> Option<Result<T, ZigZagError>> {
+        if self.failed {
+            return None;
+        }
+        let start = self.offset;
+        let mut buf = scratch::<T::UInt>();
+        let mut len = 0;
+        loop {
+            let Some(byte) = self.iter.next() else {
+                if len == 0 {
+                    return None;
+                }
+                self.failed = true;
+                return Some(Err(ZigZagError::UnexpectedEof { offset: start }));
+            };
+            let byte = *byte.borrow();
+            buf[len] = byte;
+            len += 1;
+            self.offset += 1;
+            // The decoder rejects a value that is still unterminated at its maximum length
+            if byte & 0x80 == 0 || len == T::UInt::MAX_LEN {
+                return Some(match T::UInt::decode_varint(&buf[..len]) {
+                    Ok((value, _)) => Ok(T::zigzag_decode(value)),
+                    Err(err) => {
+                        self.failed = true;
+                        Err(at_offset(err, start))
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl<I, T> core::iter::FusedIterator for DecodeIter<I, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+    I: core::iter::FusedIterator,
+    I::Item: Borrow<u8>,
+{
+}
+
+/// Decode the ZigZag varints in the bytes of `iter` one value at a time
+///
+/// Bytes are consumed as the iterator is, so a stream from a UART or a
+/// flash reader is decoded without a buffer or allocation. A malformed value
+/// is yielded as an error, after which the iterator ends.
+///
+/// # Returns
+/// An iterator yielding for each value
+/// * `Ok(value)` with the decoded value
+/// * `Err(ZigZagError::UnexpectedEof)` if the bytes end in the middle of the value
+/// * `Err(ZigZagError::VarintOverflow)` if the value does not fit into `T`
+///
+/// Errors carry the offset of the value in the bytes of `iter`.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "i32")] {
+/// use zigzag_rs::{varint, ZigZagError};
+///
+/// let bytes = [0x01, 0x80, 0x01, 0xd7];
+/// let mut values = varint::decode_iter::<i32, _>(bytes);
+/// assert_eq!(values.next(), Some(Ok(-1)));
+/// assert_eq!(values.next(), Some(Ok(64)));
+/// assert_eq!(values.next(), Some(Err(ZigZagError::UnexpectedEof { offset: 3 })));
+/// assert_eq!(values.next(), None);
+/// # }
+/// ```
+pub fn decode_iter<T, I>(iter: I) -> DecodeIter<I::IntoIter, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+    I: IntoIterator,
+    I::Item: Borrow<u8>,
+{
+    DecodeIter { iter: iter.into_iter(), offset: 0, failed: false, marker: PhantomData }
+}
+
+/// Shift the offset reported by a decode error by `base` bytes
+pub(crate) fn at_offset(err: ZigZagError, base: usize) -> ZigZagError {
+    match err {
+        ZigZagError::UnexpectedEof { offset } => ZigZagError::UnexpectedEof { offset: base + offset },
+        ZigZagError::VarintOverflow { offset } => ZigZagError::VarintOverflow { offset: base + offset },
+        ZigZagError::InvalidData { offset } => ZigZagError::InvalidData { offset: base + offset },
+        ZigZagError::InvalidSync { offset } => ZigZagError::InvalidSync { offset: base + offset },
+        other => other,
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32", feature = "i64", feature = "isize"))]
+    fn test_varint_round_trip_all_types() {
+        let mut buf = [0u8; 32];
+
+        for &value in [i8::MIN, -1, 0, 1, i8::MAX].iter() {
+            let len = encode(value, &mut buf).unwrap();
+            assert_eq!(decode::<i8>(&buf[..len]), Ok((value, len)));
+        }
+        for &value in [i32::MIN, -65, 64, 0, i32::MAX].iter() {
+            let len = encode(value, &mut buf).unwrap();
+            assert_eq!(decode::<i32>(&buf[..len]), Ok((value, len)));
+        }
+        for &value in [i64::MIN, i64::MAX].iter() {
+            let len = encode(value, &mut buf).unwrap();
+            assert_eq!(len, u64::MAX_LEN);
+            assert_eq!(decode::<i64>(&buf[..len]), Ok((value, len)));
+        }
+        #[cfg(feature = "i128")]
+        for &value in [i128::MIN, i128::MAX].iter() {
+            let len = encode(value, &mut buf).unwrap();
+            assert_eq!(len, u128::MAX_LEN);
+            assert_eq!(decode::<i128>(&buf[..len]), Ok((value, len)));
+        }
+        for &value in [isize::MIN, -1, 0, isize::MAX].iter() {
+            let len = encode(value, &mut buf).unwrap();
+            assert_eq!(decode::<isize>(&buf[..len]), Ok((value, len)));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_varint_known_bytes() {
+        let mut buf = [0u8; 4];
+        assert_eq!(encode(0i32, &mut buf), Ok(1));
+        assert_eq!(buf[0], 0x00);
+        assert_eq!(encode(-64i32, &mut buf), Ok(1));
+        assert_eq!(buf[0], 0x7f);
+        assert_eq!(encode(64i32, &mut buf), Ok(2));
+        assert_eq!(&buf[..2], &[0x80, 0x01]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32"))]
+    fn test_varint_errors() {
+        // Truncated value
+        assert_eq!(decode::<i32>(&[0x80, 0x80]), Err(ZigZagError::UnexpectedEof { offset: 0 }));
+
+        // Too many bytes for the type
+        assert_eq!(decode::<i8>(&[0xff, 0x01]), Ok((i8::MIN, 2)));
+        assert_eq!(decode::<i8>(&[0xff, 0x03]), Err(ZigZagError::VarintOverflow { offset: 0 }));
+        assert_eq!(
+            decode::<i32>(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+            Err(ZigZagError::VarintOverflow { offset: 0 })
+        );
+
+        // Too small output buffers
+        let mut small = [0u8; 2];
+        assert_eq!(
+            encode_slice(&[1i32, 1000, -1000], &mut small),
+            Err(ZigZagError::BufferTooSmall { needed: 5, actual: 2 })
+        );
+        let mut decoded = [0i32; 1];
+        assert_eq!(
+            decode_slice(&[0x02, 0x04], &mut decoded),
+            Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 })
+        );
+
+        // Errors in a slice report the offset of the offending value
+        let mut decoded = [0i32; 4];
+        assert_eq!(
+            decode_slice(&[0x02, 0x04, 0x80], &mut decoded),
+            Err(ZigZagError::UnexpectedEof { offset: 2 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64"))]
+    fn test_compare_streams() {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        let len_a = encode_slice(&[5i64, -300, 70_000, 1], &mut a).unwrap();
+        let len_b = encode_slice(&[5i64, -300, 70_001], &mut b).unwrap();
+        let (a, b) = (&a[..len_a], &b[..len_b]);
+
+        assert_eq!(compare_streams::<i64>(a, a), Ok(None));
+        assert_eq!(compare_streams::<i64>(a, b), Ok(Some(Divergence { index: 2, ordering: Ordering::Less })));
+        assert_eq!(compare_streams::<i64>(b, a), Ok(Some(Divergence { index: 2, ordering: Ordering::Greater })));
+        // A prefix orders first
+        assert_eq!(
+            compare_streams::<i64>(&a[..len_a - 1], a),
+            Ok(Some(Divergence { index: 3, ordering: Ordering::Less }))
+        );
+        // Signed order, although -1 (0x01) has a smaller first byte than 1 (0x02)
+        assert_eq!(
+            compare_streams::<i32>(&[0x01], &[0x02]),
+            Ok(Some(Divergence { index: 0, ordering: Ordering::Less }))
+        );
+        // An overlong encoding of the same value is not a divergence
+        assert_eq!(compare_streams::<i32>(&[0x82, 0x00, 0x04], &[0x02, 0x04]), Ok(None));
+        assert_eq!(
+            compare_streams::<i32>(&[0x02, 0x04], &[0x02, 0x84]),
+            Err(ZigZagError::UnexpectedEof { offset: 1 })
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64"))]
+    fn test_encode_iter_matches_slice_encoding() {
+        let values = [i64::MIN, -1, 0, 63, -64, 64, i64::MAX];
+        let mut buf = [0u8; 40];
+        let len = encode_slice(&values, &mut buf).unwrap();
+        let mut bytes = encode_iter::<i64, _>(&values);
+        assert_eq!(bytes.size_hint(), (7, Some(70)));
+        assert!(bytes.by_ref().take(3).eq(buf[..3].iter().copied()));
+        // Seven bytes of the first value are pending, and every other value takes at least one
+        assert_eq!(bytes.size_hint(), (13, Some(67)));
+        assert!(bytes.eq(buf[3..len].iter().copied()));
+        assert_eq!(encode_iter::<i8, _>(core::iter::empty::<i8>()).next(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i32", feature = "i64"))]
+    fn test_decode_iter_round_trip_and_errors() {
+        let values = [i32::MIN, -1, 0, 300, i32::MAX];
+        assert!(decode_iter::<i32, _>(encode_iter::<i32, _>(&values)).map(Result::unwrap).eq(values));
+
+        // 0x80 0x02 is 256, which does not fit into an i8
+        let mut bytes = decode_iter::<i8, _>(&[0x02, 0x80, 0x02, 0x02]);
+        assert_eq!(bytes.next(), Some(Ok(1)));
+        assert_eq!(bytes.next(), Some(Err(ZigZagError::VarintOverflow { offset: 1 })));
+        assert_eq!(bytes.next(), None);
+
+        // Unterminated at the maximum length of a u16 varint
+        let mut bytes = decode_iter::<i16, _>([0x00, 0xff, 0xff, 0xff]);
+        assert_eq!(bytes.nth(1), Some(Err(ZigZagError::VarintOverflow { offset: 1 })));
+        assert_eq!(decode_iter::<i64, _>(core::iter::empty::<u8>()).next(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "i256")]
+    fn test_iter_i256() {
+        use crate::i256::I256;
+
+        let values = [I256::MIN, I256::from_halves(-1, 5), I256::MAX];
+        let mut buf = [0u8; 3 * 37];
+        let len = encode_slice(&values, &mut buf).unwrap();
+        assert!(encode_iter::<I256, _>(&values).eq(buf[..len].iter().copied()));
+        assert!(decode_iter::<I256, _>(encode_iter::<I256, _>(&values)).map(Result::unwrap).eq(values));
+
+        // A run of continuation bytes longer than any varint
+        let mut bytes = decode_iter::<I256, _>([0xff; 40]);
+        assert_eq!(bytes.next(), Some(Err(ZigZagError::VarintOverflow { offset: 0 })));
+        assert_eq!(bytes.next(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i32", feature = "i64", feature = "alloc"))]
+    fn test_encode_append() {
+        let mut out = vec![0xaa];
+        assert_eq!(encode_append(&[-1i32, 64, -300], &mut out), Ok(5));
+        assert_eq!(encode_append::<i64>(&[], &mut out), Ok(0));
+        assert_eq!(out, [0xaa, 0x01, 0x80, 0x01, 0xd7, 0x04]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i128", feature = "alloc"))]
+    fn test_encode_append_i128() {
+        let mut out = vec![0xaa];
+        assert_eq!(encode_append(&[i128::MIN], &mut out), Ok(19));
+        let mut decoded = [0i128; 1];
+        assert_eq!(decode_slice(&out[1..], &mut decoded), Ok(1));
+        assert_eq!(decoded, [i128::MIN]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "Varint implementation wrote a different length than varint_len")]
+    fn test_encode_append_rejects_inexact_varint_len() {
+        /// Reports two bytes but writes one, which would leave bytes uninitialized
+        #[derive(Clone, Copy)]
+        struct Short;
+
+        impl Varint for Short {
+            const MAX_LEN: usize = 2;
+
+            fn varint_len(self) -> usize {
+                2
+            }
+
+            fn encode_varint(self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+                out[0] = 0;
+                Ok(1)
+            }
+
+            fn decode_varint(_: &[u8]) -> Result<(Self, usize), ZigZagError> {
+                Ok((Short, 1))
+            }
+        }
+
+        #[derive(Clone, Copy)]
+        struct Value;
+
+        impl ZigZag for Value {
+            type UInt = Short;
+
+            fn zigzag_encode(_: Self) -> Short {
+                Short
+            }
+
+            fn zigzag_decode(_: Short) -> Self {
+                Value
+            }
+        }
+
+        let _ = encode_append(&[Value, Value], &mut vec![]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i256", feature = "alloc"))]
+    fn test_append_and_extend_i256() {
+        use crate::i256::I256;
+
+        let values = [I256::MIN, I256::from_halves(-1, 5), I256::MAX];
+        let mut appended = vec![0xaa];
+        assert_eq!(encode_append(&values, &mut appended), Ok(encoded_len(&values)));
+        assert_eq!(encoded_len(&values[..1]), 37);
+        let mut extended = vec![0xaa];
+        assert_eq!(encode_extend(&values, &mut extended), Ok(appended.len() - 1));
+        assert_eq!(extended, appended);
+
+        let mut decoded = [I256::ZERO; 3];
+        assert_eq!(decode_slice(&appended[1..], &mut decoded), Ok(3));
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_extend_into_collections() {
+        let mut frame = std::vec![0x7e];
+        assert_eq!(encode_extend(&[-1i32, 64, -300], &mut frame), Ok(5));
+        assert_eq!(frame, [0x7e, 0x01, 0x80, 0x01, 0xd7, 0x04]);
+
+        let mut decoded: std::collections::VecDeque<i32> = [7].into();
+        assert_eq!(decode_extend::<i32, _>(&frame[1..], &mut decoded), Ok(3));
+        assert!(decoded.iter().eq(&[7, -1, 64, -300]));
+
+        // Nothing is appended from a malformed stream
+        assert_eq!(decode_extend::<i32, _>(&[0x02, 0x80], &mut decoded), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+        assert_eq!(decoded.len(), 4);
+    }
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i16", feature = "i64"))]
+    fn test_decode_slice_saturating() {
+        let wide = [-1i64, 40_000, -40_000, i64::MAX, i64::MIN, 300];
+        let mut buf = [0u8; 64];
+        let len = encode_slice(&wide, &mut buf).unwrap();
+        assert_eq!(decode_slice::<i16>(&buf[..len], &mut [0i16; 6]), Err(ZigZagError::VarintOverflow { offset: 1 }));
+
+        let mut narrow = [0i16; 6];
+        assert_eq!(decode_slice_saturating(&buf[..len], &mut narrow), Ok((6, 4)));
+        assert_eq!(narrow, [-1, i16::MAX, i16::MIN, i16::MAX, i16::MIN, 300]);
+        let mut same = [0i64; 6];
+        assert_eq!(decode_slice_saturating(&buf[..len], &mut same), Ok((6, 0)));
+        assert_eq!(same, wide);
+
+        assert_eq!(decode_slice_saturating(&buf[..len], &mut [0i8; 5]), Err(ZigZagError::BufferTooSmall { needed: 6, actual: 5 }));
+        assert_eq!(decode_slice_saturating(&[0x02, 0x80], &mut [0i8; 2]), Err(ZigZagError::UnexpectedEof { offset: 1 }));
+    }
+}
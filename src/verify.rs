@@ -0,0 +1,119 @@
+//! Round-trip verification that reports the first mismatch.
+//!
+//! Hardware bring-up and fuzzing harnesses need to know where corruption
+//! happened, not only that it did. [`verify_roundtrip`] encodes and decodes
+//! every value of a slice and [`compare`] checks decoded values against the
+//! originals; both stop at the first difference and return its index
+//! together with the two values as a [`MismatchAt`].
+//!
+//! ```rust
+//! # #[cfg(all(feature = "i16", feature = "i32"))] {
+//! use zigzag_rs::verify::{self, MismatchAt};
+//!
+//! assert_eq!(verify::verify_roundtrip(&[i32::MIN, -1, 0, i32::MAX]), Ok(()));
+//!
+//! // A bit flipped in transit
+//! let sent = [-1i16, 300, 7];
+//! let received = [-1i16, 301, 7];
+//! assert_eq!(verify::compare(&sent, &received), Err(MismatchAt { index: 1, original: 300, decoded: 301 }));
+//! # }
+//! ```
+
+use core::fmt;
+
+use crate::ZigZag;
+
+/// First value that did not survive a round trip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MismatchAt<T> {
+    /// Index of the value in the slice
+    pub index: usize,
+    /// Value before encoding
+    pub original: T,
+    /// Value after decoding
+    pub decoded: T,
+}
+
+impl<T: fmt::Display> fmt::Display for MismatchAt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "value {} decoded as {}, expected {}", self.index, self.decoded, self.original)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug + fmt::Display> std::error::Error for MismatchAt<T> {}
+
+/// Encode and decode every value of `values` and check it comes back unchanged
+///
+/// The encoded values pass through [`core::hint::black_box`], so the check
+/// runs on the target instead of being folded away at build time.
+///
+/// # Returns
+/// * `Ok(())` if every value survived the round trip
+/// * `Err(MismatchAt)` with the first value that did not
+pub fn verify_roundtrip<T>(values: &[T]) -> Result<(), MismatchAt<T>>
+where
+    T: ZigZag + Copy + PartialEq,
+{
+    for (index, &original) in values.iter().enumerate() {
+        let decoded = T::zigzag_decode(core::hint::black_box(T::zigzag_encode(original)));
+        if decoded != original {
+            return Err(MismatchAt { index, original, decoded });
+        }
+    }
+    Ok(())
+}
+
+/// Compare decoded values with the values they were encoded from
+///
+/// # Returns
+/// * `Ok(())` if the slices are equal
+/// * `Err(MismatchAt)` with the first index at which they differ
+///
+/// # Panics
+/// Panics if the slices have different lengths
+pub fn compare<T: Copy + PartialEq>(original: &[T], decoded: &[T]) -> Result<(), MismatchAt<T>> {
+    assert_eq!(original.len(), decoded.len(), "Original and decoded slices must have the same length");
+    match original.iter().zip(decoded).position(|(a, b)| a != b) {
+        Some(index) => Err(MismatchAt { index, original: original[index], decoded: decoded[index] }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i32", feature = "i64"))]
+    fn test_verify_roundtrip() {
+        assert_eq!(verify_roundtrip(&[i8::MIN, -1, 0, 1, i8::MAX]), Ok(()));
+        assert_eq!(verify_roundtrip::<i64>(&[]), Ok(()));
+        assert_eq!(verify_roundtrip(&[core::num::Wrapping(i32::MIN)]), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_compare_reports_first_mismatch() {
+        let original = [1i32, -2, 3, -4];
+        let mut encoded = [0u32; 4];
+        i32::zigzag_encode_slice(&original, &mut encoded);
+        encoded[2] ^= 1;
+        encoded[3] ^= 1;
+        let mut decoded = [0i32; 4];
+        i32::zigzag_decode_slice(&encoded, &mut decoded);
+
+        let mismatch = compare(&original, &decoded).unwrap_err();
+        assert_eq!(mismatch, MismatchAt { index: 2, original: 3, decoded: -4 });
+        assert_eq!(std::format!("{}", mismatch), "value 2 decoded as -4, expected 3");
+        assert_eq!(compare(&original, &original), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    #[should_panic(expected = "Original and decoded slices must have the same length")]
+    fn test_compare_panics_on_length_mismatch() {
+        let _ = compare(&[1i16], &[]);
+    }
+}
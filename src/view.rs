@@ -0,0 +1,653 @@
+//! Views that read ZigZag varint streams in place.
+//!
+//! [`EncodedSlice`] gives random access to the output of
+//! [`varint::encode_slice`](crate::varint::encode_slice). It uses a checkpoint
+//! index holding the byte offset of every `stride`-th value. A lookup
+//! decodes at most `stride` values from the nearest checkpoint, so point
+//! queries never touch the rest of the stream.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::{varint, view::{self, EncodedSlice}};
+//!
+//! let values: Vec<i32> = (-500..500).collect();
+//! let mut bytes = vec![0u8; varint::encoded_len(&values)];
+//! let len = varint::encode_slice(&values, &mut bytes).unwrap();
+//!
+//! let mut index = vec![0u32; view::index_len(values.len(), 16)];
+//! view::build_index::<i32>(&bytes[..len], 16, &mut index).unwrap();
+//!
+//! let slice = EncodedSlice::<i32>::new(&bytes[..len], &index, 16).unwrap();
+//! assert_eq!(slice.len(), 1000);
+//! assert_eq!(slice.get(742), Some(242));
+//! assert!(slice.range(10..13).eq([-490, -489, -488]));
+//! # }
+//! ```
+//!
+//! [`Offsets`] bundles the index with its stride, the value count and the
+//! stream length, and can be written next to the data in a compact form, so
+//! a file can be opened again without scanning the stream. Its segments
+//! split the stream into independently decodable parts.
+//!
+//! ```rust
+//! # #[cfg(feature = "i64")] {
+//! use zigzag_rs::{varint, view::{self, EncodedSlice, Offsets}};
+//!
+//! let values: Vec<i64> = (0..100).map(|i| i * 1000).collect();
+//! let mut bytes = vec![0u8; varint::encoded_len(&values)];
+//! varint::encode_slice(&values, &mut bytes).unwrap();
+//!
+//! let mut index = vec![0u32; view::index_len(values.len(), 32)];
+//! let offsets = Offsets::build::<i64>(&bytes, 32, &mut index).unwrap();
+//! let mut stored = [0u8; 32];
+//! let stored_len = offsets.write(&mut stored).unwrap();
+//!
+//! // Later, without scanning the values
+//! let mut index = [0u32; 4];
+//! let offsets = Offsets::read(&stored[..stored_len], &mut index).unwrap();
+//! assert_eq!((offsets.count(), offsets.segments()), (100, 4));
+//! let slice = EncodedSlice::<i64>::from_offsets(&bytes, offsets).unwrap();
+//! assert_eq!(slice.get(99), Some(99_000));
+//! # }
+//! ```
+//!
+//! [`DecodedView`] reads a stream written by [`encode_counted`], which starts
+//! with the number of values as a varint. Its length is therefore known
+//! without decoding the values. It is a cheap `Clone`able `ExactSizeIterator`
+//! that can stand in for a decoded slice.
+//!
+//! ```rust
+//! # #[cfg(feature = "i32")] {
+//! use zigzag_rs::view::{self, DecodedView};
+//!
+//! let mut bytes = [0u8; 16];
+//! let len = view::encode_counted(&[-3i32, 0, 300], &mut bytes).unwrap();
+//! let view = DecodedView::<i32>::new(&bytes[..len]).unwrap();
+//! assert_eq!(view.len(), 3);
+//! assert_eq!(view.clone().max(), Some(300));
+//! assert!(view.eq([-3, 0, 300]));
+//! # }
+//! ```
+
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+
+use crate::varint::{self, Varint};
+use crate::{scan, ZigZag, ZigZagError};
+
+/// Number of checkpoints an index for `count` values needs
+pub fn index_len(count: usize, stride: usize) -> usize {
+    count.div_ceil(stride.max(1))
+}
+
+/// Record the byte offset of every `stride`-th value of a varint stream
+///
+/// # Arguments
+/// * `input` - ZigZag varint stream
+/// * `stride` - Number of values between checkpoints, at least 1
+/// * `index` - Output for the checkpoints, [`index_len`] entries
+///
+/// # Returns
+/// * `Ok(count)` with the number of values in `input`
+/// * `Err(ZigZagError::BufferTooSmall)` if `index` is too small
+/// * `Err(ZigZagError)` with the offset of the offending value if `input` is malformed
+///
+/// # Panics
+/// Panics if `stride` is 0 or `input` is longer than `u32::MAX` bytes
+pub fn build_index<T>(input: &[u8], stride: usize, index: &mut [u32]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    assert!(stride > 0, "stride must be at least 1");
+    assert!(input.len() <= u32::MAX as usize, "input too long for a u32 index");
+    let needed = index_len(varint::count(input), stride);
+    if index.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: index.len() });
+    }
+
+    scan::walk::<T, _>(input, |count, start| {
+        if count % stride == 0 {
+            index[count / stride] = start as u32;
+        }
+    })
+}
+
+/// Checkpoint index of a varint stream together with its shape
+///
+/// Stored with [`Offsets::write`] as
+///
+/// ```text
+/// stride (varint) | count (varint) | byte length (varint) | checkpoint differences (varint)*
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offsets<'a> {
+    index: &'a [u32],
+    stride: usize,
+    count: usize,
+    byte_len: usize,
+}
+
+impl<'a> Offsets<'a> {
+    /// Scan `input` once and record the offset of every `stride`-th value into `index`
+    ///
+    /// # Returns
+    /// * `Ok(offsets)` borrowing the filled part of `index`
+    /// * `Err(ZigZagError::BufferTooSmall)` if `index` is shorter than [`index_len`]
+    /// * `Err(ZigZagError)` with the offset of the offending value if `input` is malformed
+    ///
+    /// # Panics
+    /// Panics if `stride` is 0 or `input` is longer than `u32::MAX` bytes
+    pub fn build<T>(input: &[u8], stride: usize, index: &'a mut [u32]) -> Result<Self, ZigZagError>
+    where
+        T: ZigZag,
+        T::UInt: Varint,
+    {
+        let count = build_index::<T>(input, stride, index)?;
+        let index = &index[..index_len(count, stride)];
+        Ok(Offsets { index, stride, count, byte_len: input.len() })
+    }
+
+    /// Number of values between checkpoints
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Number of values in the stream
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Length of the stream in bytes
+    pub fn byte_len(&self) -> usize {
+        self.byte_len
+    }
+
+    /// Byte offset of every `stride`-th value
+    pub fn checkpoints(&self) -> &'a [u32] {
+        self.index
+    }
+
+    /// Byte offset to start decoding at for value `i`, and the number of values to skip from there
+    pub fn locate(&self, i: usize) -> Option<(usize, usize)> {
+        (i < self.count).then(|| (self.index[i / self.stride] as usize, i % self.stride))
+    }
+
+    /// Number of segments, one per checkpoint
+    pub fn segments(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Byte range of segment `k`, holding values `k * stride` up to `(k + 1) * stride`
+    ///
+    /// Segments decode independently, for example on separate threads.
+    pub fn segment(&self, k: usize) -> Option<core::ops::Range<usize>> {
+        let start = *self.index.get(k)? as usize;
+        let end = self.index.get(k + 1).map_or(self.byte_len, |&end| end as usize);
+        Some(start..end)
+    }
+
+    /// Number of bytes [`Offsets::write`] needs
+    pub fn encoded_len(&self) -> usize {
+        let mut prev = 0;
+        let checkpoints: usize = self.index.iter().map(|&offset| {
+            let len = (offset - prev).varint_len();
+            prev = offset;
+            len
+        }).sum();
+        let shape = [self.stride, self.count, self.byte_len];
+        shape.iter().map(|&value| (value as u64).varint_len()).sum::<usize>() + checkpoints
+    }
+
+    /// Write the offsets in their stored form
+    ///
+    /// # Returns
+    /// * `Ok(len)` with the number of bytes written
+    /// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+    pub fn write(&self, out: &mut [u8]) -> Result<usize, ZigZagError> {
+        let needed = self.encoded_len();
+        if out.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+        }
+        let mut pos = (self.stride as u64).encode_varint(out)?;
+        pos += (self.count as u64).encode_varint(&mut out[pos..])?;
+        pos += (self.byte_len as u64).encode_varint(&mut out[pos..])?;
+        let mut prev = 0;
+        for &offset in self.index {
+            pos += (offset - prev).encode_varint(&mut out[pos..])?;
+            prev = offset;
+        }
+        Ok(pos)
+    }
+
+    /// Read offsets written by [`Offsets::write`], storing the checkpoints in `index`
+    ///
+    /// # Returns
+    /// * `Ok(offsets)` borrowing the filled part of `index`
+    /// * `Err(ZigZagError::BufferTooSmall)` if `index` cannot hold all checkpoints
+    /// * `Err(ZigZagError::InvalidData)` if the stride is 0 or the checkpoints exceed the stream length
+    /// * `Err(ZigZagError)` with the offset of the problem if `input` is malformed
+    pub fn read(input: &[u8], index: &'a mut [u32]) -> Result<Self, ZigZagError> {
+        let mut pos = 0;
+        let next = |pos: &mut usize| {
+            let (value, len) = u64::decode_varint(&input[*pos..]).map_err(|err| varint::at_offset(err, *pos))?;
+            *pos += len;
+            Ok::<_, ZigZagError>(value)
+        };
+        let stride = next(&mut pos)? as usize;
+        if stride == 0 {
+            return Err(ZigZagError::InvalidData { offset: 0 });
+        }
+        let count = next(&mut pos)? as usize;
+        let byte_len = next(&mut pos)? as usize;
+        let needed = index_len(count, stride);
+        if index.len() < needed {
+            return Err(ZigZagError::BufferTooSmall { needed, actual: index.len() });
+        }
+        let mut offset = 0u32;
+        for slot in index[..needed].iter_mut() {
+            let start = pos;
+            let (diff, len) = u32::decode_varint(&input[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+            offset = offset
+                .checked_add(diff)
+                .filter(|&offset| (offset as usize) < byte_len.max(1))
+                .ok_or(ZigZagError::InvalidData { offset: start })?;
+            *slot = offset;
+            pos += len;
+        }
+        Ok(Offsets { index: &index[..needed], stride, count, byte_len })
+    }
+}
+
+/// Random access view over a ZigZag varint stream and its checkpoint index
+#[derive(Debug)]
+pub struct EncodedSlice<'a, T> {
+    bytes: &'a [u8],
+    index: &'a [u32],
+    stride: usize,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Clone for EncodedSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EncodedSlice<'_, T> {}
+
+impl<'a, T> EncodedSlice<'a, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    /// Create a view from a stream and an index built by [`build_index`]
+    ///
+    /// The stream is checked once, so later lookups cannot fail.
+    ///
+    /// # Returns
+    /// * `Ok(view)` if `index` matches `bytes`
+    /// * `Err(ZigZagError::InvalidData)` at offset 0 if `stride` is 0
+    /// * `Err(ZigZagError::InvalidData)` with the position of the first mismatching checkpoint
+    /// * `Err(ZigZagError)` with the offset of the offending value if `bytes` is malformed
+    pub fn new(bytes: &'a [u8], index: &'a [u32], stride: usize) -> Result<Self, ZigZagError> {
+        if stride == 0 {
+            return Err(ZigZagError::InvalidData { offset: 0 });
+        }
+        let mut pos = 0;
+        let mut len = 0;
+        while pos < bytes.len() {
+            if len % stride == 0 && index.get(len / stride) != Some(&(pos as u32)) {
+                return Err(ZigZagError::InvalidData { offset: pos });
+            }
+            let (_, value_len) = varint::decode::<T>(&bytes[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+            pos += value_len;
+            len += 1;
+        }
+        if index.len() != index_len(len, stride) {
+            return Err(ZigZagError::InvalidData { offset: bytes.len() });
+        }
+        Ok(EncodedSlice { bytes, index, stride, len, _marker: PhantomData })
+    }
+
+    /// Create a view from a stream and offsets built for it
+    ///
+    /// # Returns
+    /// * `Ok(view)` if `offsets` match `bytes`
+    /// * `Err(ZigZagError::InvalidData)` if they do not
+    /// * `Err(ZigZagError)` with the offset of the offending value if `bytes` is malformed
+    pub fn from_offsets(bytes: &'a [u8], offsets: Offsets<'a>) -> Result<Self, ZigZagError> {
+        let slice = Self::new(bytes, offsets.index, offsets.stride)?;
+        if slice.len != offsets.count || bytes.len() != offsets.byte_len {
+            return Err(ZigZagError::InvalidData { offset: bytes.len() });
+        }
+        Ok(slice)
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the view holds no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Underlying encoded bytes
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Decode the value at position `i`
+    pub fn get(&self, i: usize) -> Option<T> {
+        if i >= self.len {
+            return None;
+        }
+        self.range(i..).next()
+    }
+
+    /// Iterate over all values
+    pub fn iter(&self) -> Iter<'a, T> {
+        self.range(..)
+    }
+
+    /// Iterate lazily over the values in `range`
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or decreasing
+    pub fn range(&self, range: impl RangeBounds<usize>) -> Iter<'a, T> {
+        // Saturating past `usize::MAX` keeps such bounds out of bounds for the assert below
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end && end <= self.len, "range out of bounds");
+
+        let mut iter = Iter { bytes: self.bytes, pos: 0, remaining: end - start, _marker: PhantomData };
+        if start < self.len {
+            iter.pos = self.index[start / self.stride] as usize;
+            for _ in 0..start % self.stride {
+                iter.pos += varint::decode::<T>(&self.bytes[iter.pos..]).map_or(1, |(_, len)| len);
+            }
+        }
+        iter
+    }
+}
+
+impl<'a, T> IntoIterator for EncodedSlice<'a, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator over a range of an [`EncodedSlice`]
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    bytes: &'a [u8],
+    pos: usize,
+    remaining: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { bytes: self.bytes, pos: self.pos, remaining: self.remaining, _marker: PhantomData }
+    }
+}
+
+impl<T> Iterator for Iter<'_, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        // The stream was validated when the view was created
+        let (value, len) = varint::decode::<T>(&self.bytes[self.pos..]).ok()?;
+        self.pos += len;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+}
+
+/// Encode values as a varint count followed by their ZigZag varints
+///
+/// # Returns
+/// * `Ok(len)` with the number of bytes written
+/// * `Err(ZigZagError::BufferTooSmall)` with the total number of bytes needed if `out` is too small
+pub fn encode_counted<T>(values: &[T], out: &mut [u8]) -> Result<usize, ZigZagError>
+where
+    T: ZigZag + Copy,
+    T::UInt: Varint,
+{
+    let header_len = (values.len() as u64).varint_len();
+    let needed = header_len + varint::encoded_len(values);
+    if out.len() < needed {
+        return Err(ZigZagError::BufferTooSmall { needed, actual: out.len() });
+    }
+    (values.len() as u64).encode_varint(out)?;
+    Ok(header_len + varint::encode_slice(values, &mut out[header_len..])?)
+}
+
+/// Lazily decoding iterator over a stream written by [`encode_counted`]
+///
+/// Bytes after the counted values are ignored, so views can be read from the
+/// front of a larger buffer; [`DecodedView::byte_len`] tells where the next data starts.
+#[derive(Debug)]
+pub struct DecodedView<'a, T> {
+    iter: Iter<'a, T>,
+    byte_len: usize,
+}
+
+impl<T> Clone for DecodedView<'_, T> {
+    fn clone(&self) -> Self {
+        DecodedView { iter: self.iter.clone(), byte_len: self.byte_len }
+    }
+}
+
+impl<'a, T> DecodedView<'a, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    /// Create a view over counted ZigZag varints
+    ///
+    /// The values are checked once without storing them, so iteration cannot fail.
+    ///
+    /// # Returns
+    /// * `Ok(view)` if `bytes` starts with a complete counted stream
+    /// * `Err(ZigZagError)` with the offset of the offending value if it is malformed or truncated
+    pub fn new(bytes: &'a [u8]) -> Result<Self, ZigZagError> {
+        let (count, mut pos) = u64::decode_varint(bytes)?;
+        let start = pos;
+        for _ in 0..count {
+            let (_, len) = varint::decode::<T>(&bytes[pos..]).map_err(|err| varint::at_offset(err, pos))?;
+            pos += len;
+        }
+        let iter = Iter { bytes: &bytes[..pos], pos: start, remaining: count as usize, _marker: PhantomData };
+        Ok(DecodedView { iter, byte_len: pos })
+    }
+
+    /// Number of bytes of the counted stream, including the header
+    pub fn byte_len(&self) -> usize {
+        self.byte_len
+    }
+}
+
+impl<T> Iterator for DecodedView<'_, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for DecodedView<'_, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+}
+
+impl<T> FusedIterator for DecodedView<'_, T>
+where
+    T: ZigZag,
+    T::UInt: Varint,
+{
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_encoded_slice_random_access() {
+        let values: Vec<i64> = (0..1000).map(|i| (i * i * 37) % 100_003 - 50_000).collect();
+        let mut bytes = vec![0u8; varint::encoded_len(&values)];
+        varint::encode_slice(&values, &mut bytes).unwrap();
+
+        for stride in [1, 7, 64, 2000] {
+            let mut index = vec![0u32; index_len(values.len(), stride)];
+            assert_eq!(build_index::<i64>(&bytes, stride, &mut index), Ok(1000));
+            let slice = EncodedSlice::<i64>::new(&bytes, &index, stride).unwrap();
+            for i in [0, 1, 6, 7, 63, 64, 500, 999] {
+                assert_eq!(slice.get(i), Some(values[i]));
+            }
+            assert_eq!(slice.get(1000), None);
+            assert!(slice.range(100..=199).eq(values[100..200].iter().copied()));
+            assert_eq!(slice.range(999..).len(), 1);
+            assert_eq!(slice.range(1000..).next(), None);
+            assert!(slice.iter().eq(values.iter().copied()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "i32")]
+    fn test_offsets_round_trip() {
+        let values: Vec<i32> = (0..1000).map(|i| (i * 7919) % 20_001 - 10_000).collect();
+        let mut bytes = vec![0u8; varint::encoded_len(&values)];
+        varint::encode_slice(&values, &mut bytes).unwrap();
+
+        let mut index = vec![0u32; index_len(values.len(), 100)];
+        let offsets = Offsets::build::<i32>(&bytes, 100, &mut index).unwrap();
+        assert_eq!((offsets.count(), offsets.segments(), offsets.byte_len()), (1000, 10, bytes.len()));
+        let mut stored = vec![0u8; offsets.encoded_len()];
+        assert_eq!(offsets.write(&mut stored), Ok(stored.len()));
+        let mut decoded_segments = Vec::new();
+        for k in 0..offsets.segments() {
+            let mut segment = [0i32; 100];
+            assert_eq!(varint::decode_slice(&bytes[offsets.segment(k).unwrap()], &mut segment), Ok(100));
+            decoded_segments.extend_from_slice(&segment);
+        }
+        assert_eq!(decoded_segments, values);
+        assert_eq!(offsets.locate(250), Some((offsets.checkpoints()[2] as usize, 50)));
+        assert_eq!(offsets.locate(1000), None);
+
+        let mut read_index = [0u32; 10];
+        let read = Offsets::read(&stored, &mut read_index).unwrap();
+        assert_eq!(read, offsets);
+        let slice = EncodedSlice::<i32>::from_offsets(&bytes, read).unwrap();
+        assert_eq!(slice.get(777), Some(values[777]));
+        assert!(EncodedSlice::<i32>::from_offsets(&bytes[..bytes.len() - 1], read).is_err());
+    }
+
+    #[test]
+    fn test_offsets_read_errors() {
+        assert_eq!(Offsets::read(&[0, 1, 1, 0], &mut [0u32; 1]), Err(ZigZagError::InvalidData { offset: 0 }));
+        assert_eq!(Offsets::read(&[2, 4, 4, 0, 2], &mut [0u32; 1]), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+        // The second checkpoint lies beyond the 4-byte stream
+        assert_eq!(Offsets::read(&[2, 4, 4, 0, 5], &mut [0u32; 2]), Err(ZigZagError::InvalidData { offset: 4 }));
+        assert_eq!(Offsets::read(&[2, 4, 4, 0], &mut [0u32; 2]), Err(ZigZagError::UnexpectedEof { offset: 4 }));
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    fn test_encoded_slice_errors() {
+        let bytes = [0x02, 0x80, 0x01, 0x03];
+        let mut index = [0u32; 1];
+        assert_eq!(build_index::<i16>(&bytes, 2, &mut index), Err(ZigZagError::BufferTooSmall { needed: 2, actual: 1 }));
+
+        let mut index = [0u32; 2];
+        assert_eq!(build_index::<i16>(&bytes, 2, &mut index), Ok(3));
+        assert_eq!(index, [0, 3]);
+        assert!(EncodedSlice::<i16>::new(&bytes, &index, 2).is_ok());
+        assert_eq!(EncodedSlice::<i16>::new(&bytes, &[0, 1], 2).unwrap_err(), ZigZagError::InvalidData { offset: 3 });
+        assert_eq!(EncodedSlice::<i16>::new(&bytes, &index[..1], 2).unwrap_err(), ZigZagError::InvalidData { offset: 3 });
+        assert_eq!(EncodedSlice::<i16>::new(&bytes[..2], &index, 2).unwrap_err(), ZigZagError::UnexpectedEof { offset: 1 });
+        assert_eq!(EncodedSlice::<i16>::new(&bytes, &index, 0).unwrap_err(), ZigZagError::InvalidData { offset: 0 });
+    }
+
+    #[test]
+    #[cfg(feature = "i16")]
+    #[should_panic(expected = "range out of bounds")]
+    fn test_encoded_slice_range_to_usize_max() {
+        let bytes = [0x02, 0x04];
+        let slice = EncodedSlice::<i16>::new(&bytes, &[0], 2).unwrap();
+        slice.range(..=usize::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "i8")]
+    fn test_decoded_view() {
+        let values: Vec<i8> = (-128..=127).collect();
+        let mut bytes = vec![0u8; 1024];
+        let len = encode_counted(&values, &mut bytes).unwrap();
+        // Trailing data after the stream is left alone
+        bytes[len] = 0xff;
+
+        let mut view = DecodedView::<i8>::new(&bytes[..len + 1]).unwrap();
+        assert_eq!(view.byte_len(), len);
+        assert_eq!(view.len(), 256);
+        let copy = view.clone();
+        assert_eq!(view.nth(10), Some(-118));
+        assert_eq!(view.len(), 245);
+        assert!(copy.eq(values.iter().copied()));
+
+        assert_eq!(encode_counted(&values, &mut bytes[..4]), Err(ZigZagError::BufferTooSmall { needed: len, actual: 4 }));
+        assert_eq!(DecodedView::<i8>::new(&bytes[..len - 1]).unwrap_err(), ZigZagError::UnexpectedEof { offset: len - 2 });
+        assert_eq!(DecodedView::<i8>::new(&[]).unwrap_err(), ZigZagError::UnexpectedEof { offset: 0 });
+    }
+}
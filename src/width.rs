@@ -0,0 +1,205 @@
+//! ZigZag coding for integers of arbitrary bit width.
+//!
+//! Audio and sensor formats use signed samples of 24, 40 or 48 bits, which
+//! Rust stores in the next wider native type. [`ZigZagN`] maps such values,
+//! held in an `i64`, to the ZigZag form of an `N`-bit integer and back, and
+//! rejects values that do not fit into `N` bits. The encoded value is below
+//! `2^N`, so it packs into an `N`-bit field. Where the width is only known
+//! at run time, as for the fields of a protocol description,
+//! [`zigzag_encode_bits`] and [`zigzag_decode_bits`] take it as an argument.
+//!
+//! ```rust
+//! use zigzag_rs::width::{ZigZagN, I24};
+//! use zigzag_rs::ZigZagError;
+//!
+//! let samples = [-8_388_608i64, -1, 0, 8_388_607];
+//! let mut encoded = [0u64; 4];
+//! I24::encode_slice(&samples, &mut encoded).unwrap();
+//! assert_eq!(encoded, [0xff_ffff, 1, 0, 0xff_fffe]);
+//!
+//! assert_eq!(I24::encode(8_388_608), Err(ZigZagError::OutOfRange { offset: 0 }));
+//! assert_eq!(ZigZagN::<3>::decode(5), Ok(-3));
+//!
+//! // A 5-bit field holds -16 to 15
+//! assert_eq!(zigzag_rs::width::zigzag_encode_bits(-16, 5), Ok(31));
+//! assert!(zigzag_rs::width::zigzag_encode_bits(16, 5).is_err());
+//! ```
+
+use crate::ZigZagError;
+
+/// ZigZag encode `value` as a signed integer of `bits` bits
+///
+/// # Returns
+/// * `Ok(encoded)` with the encoded value, below `2^bits`
+/// * `Err(ZigZagError::OutOfRange)` if `value` does not fit into `bits` bits
+///
+/// # Panics
+/// Panics if `bits` is not between 1 and 64
+pub const fn zigzag_encode_bits(value: i64, bits: u32) -> Result<u64, ZigZagError> {
+    assert!(bits >= 1 && bits <= 64, "width must be 1 to 64 bits");
+    if value < i64::MIN >> (64 - bits) || value > i64::MAX >> (64 - bits) {
+        return Err(ZigZagError::OutOfRange { offset: 0 });
+    }
+    Ok(((value << 1) ^ (value >> 63)) as u64)
+}
+
+/// Decode a value written by [`zigzag_encode_bits`] with the same `bits`
+///
+/// # Returns
+/// * `Ok(value)` with the decoded value
+/// * `Err(ZigZagError::OutOfRange)` if `encoded` is not below `2^bits`
+///
+/// # Panics
+/// Panics if `bits` is not between 1 and 64
+pub const fn zigzag_decode_bits(encoded: u64, bits: u32) -> Result<i64, ZigZagError> {
+    assert!(bits >= 1 && bits <= 64, "width must be 1 to 64 bits");
+    if encoded > u64::MAX >> (64 - bits) {
+        return Err(ZigZagError::OutOfRange { offset: 0 });
+    }
+    Ok((encoded >> 1) as i64 ^ -((encoded & 1) as i64))
+}
+
+/// ZigZag coding of `BITS`-bit signed integers, for `BITS` from 1 to 64
+///
+/// Using a width outside that range fails to compile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ZigZagN<const BITS: u32>;
+
+/// 24-bit signed integers, as used by PCM audio
+pub type I24 = ZigZagN<24>;
+/// 40-bit signed integers
+pub type I40 = ZigZagN<40>;
+/// 48-bit signed integers
+pub type I48 = ZigZagN<48>;
+
+impl<const BITS: u32> ZigZagN<BITS> {
+    const VALID: () = assert!(BITS >= 1 && BITS <= 64, "width must be 1 to 64 bits");
+
+    /// Smallest value of the width
+    pub const MIN: i64 = {
+        let () = Self::VALID;
+        i64::MIN >> (64 - BITS)
+    };
+    /// Largest value of the width
+    pub const MAX: i64 = {
+        let () = Self::VALID;
+        i64::MAX >> (64 - BITS)
+    };
+    /// Largest encoded value of the width, `2^BITS - 1`
+    pub const MAX_ENCODED: u64 = {
+        let () = Self::VALID;
+        u64::MAX >> (64 - BITS)
+    };
+
+    /// Whether `value` fits into `BITS` bits
+    pub const fn fits(value: i64) -> bool {
+        value >= Self::MIN && value <= Self::MAX
+    }
+
+    /// ZigZag encode a value of `BITS` bits
+    ///
+    /// # Returns
+    /// * `Ok(encoded)` with the encoded value, below `2^BITS`
+    /// * `Err(ZigZagError::OutOfRange)` if `value` does not fit into `BITS` bits
+    pub const fn encode(value: i64) -> Result<u64, ZigZagError> {
+        let () = Self::VALID;
+        zigzag_encode_bits(value, BITS)
+    }
+
+    /// Decode a value written by [`ZigZagN::encode`]
+    ///
+    /// # Returns
+    /// * `Ok(value)` with the decoded value
+    /// * `Err(ZigZagError::OutOfRange)` if `encoded` is not below `2^BITS`
+    pub const fn decode(encoded: u64) -> Result<i64, ZigZagError> {
+        let () = Self::VALID;
+        zigzag_decode_bits(encoded, BITS)
+    }
+
+    /// Encode every value of `values` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were encoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `values`
+    /// * `Err(ZigZagError::OutOfRange)` with the index of the first value that does not fit
+    pub fn encode_slice(values: &[i64], out: &mut [u64]) -> Result<(), ZigZagError> {
+        if out.len() < values.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: values.len(), actual: out.len() });
+        }
+        for (index, (&value, slot)) in values.iter().zip(out.iter_mut()).enumerate() {
+            *slot = Self::encode(value).map_err(|_| ZigZagError::OutOfRange { offset: index })?;
+        }
+        Ok(())
+    }
+
+    /// Decode every value of `encoded` into `out`
+    ///
+    /// # Returns
+    /// * `Ok(())` if all values were decoded
+    /// * `Err(ZigZagError::BufferTooSmall)` if `out` is shorter than `encoded`
+    /// * `Err(ZigZagError::OutOfRange)` with the index of the first value not below `2^BITS`
+    pub fn decode_slice(encoded: &[u64], out: &mut [i64]) -> Result<(), ZigZagError> {
+        if out.len() < encoded.len() {
+            return Err(ZigZagError::BufferTooSmall { needed: encoded.len(), actual: out.len() });
+        }
+        for (index, (&value, slot)) in encoded.iter().zip(out.iter_mut()).enumerate() {
+            *slot = Self::decode(value).map_err(|_| ZigZagError::OutOfRange { offset: index })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "i8", feature = "i64"))]
+    fn test_zigzag_n_matches_native_widths() {
+        for value in i8::MIN..=i8::MAX {
+            let encoded = ZigZagN::<8>::encode(value as i64).unwrap();
+            assert_eq!(encoded, ((value << 1) ^ (value >> 7)) as u8 as u64);
+            assert_eq!(ZigZagN::<8>::decode(encoded), Ok(value as i64));
+        }
+        for value in [i64::MIN, -1, 0, i64::MAX] {
+            let encoded = ZigZagN::<64>::encode(value).unwrap();
+            assert_eq!(ZigZagN::<64>::decode(encoded), Ok(value));
+        }
+        assert_eq!((ZigZagN::<1>::MIN, ZigZagN::<1>::MAX, ZigZagN::<1>::MAX_ENCODED), (-1, 0, 1));
+        assert_eq!(I48::encode(I48::MIN), Ok(I48::MAX_ENCODED));
+    }
+
+    #[test]
+    #[cfg(feature = "i64")]
+    fn test_zigzag_bits_runtime_width() {
+        // The minimal mapping of each width uses all of its codes exactly once
+        for bits in [1, 3, 5, 11] {
+            let mut seen = [false; 2048];
+            for value in (i64::MIN >> (64 - bits))..=(i64::MAX >> (64 - bits)) {
+                let encoded = zigzag_encode_bits(value, bits).unwrap();
+                assert!(!seen[encoded as usize]);
+                seen[encoded as usize] = true;
+                assert_eq!(zigzag_decode_bits(encoded, bits), Ok(value));
+            }
+            assert_eq!(seen.iter().filter(|&&seen| seen).count(), 1 << bits);
+            assert_eq!(zigzag_encode_bits(1 << (bits - 1), bits), Err(ZigZagError::OutOfRange { offset: 0 }));
+            assert_eq!(zigzag_decode_bits(1 << bits, bits), Err(ZigZagError::OutOfRange { offset: 0 }));
+        }
+        assert_eq!(zigzag_encode_bits(i64::MIN, 64), Ok(u64::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "width must be 1 to 64 bits")]
+    fn test_zigzag_bits_rejects_zero_width() {
+        let _ = zigzag_encode_bits(0, 0);
+    }
+
+    #[test]
+    fn test_zigzag_n_range_errors() {
+        assert_eq!(I24::encode(-8_388_609), Err(ZigZagError::OutOfRange { offset: 0 }));
+        assert_eq!(I24::decode(1 << 24), Err(ZigZagError::OutOfRange { offset: 0 }));
+        assert_eq!(I40::encode_slice(&[0, 1 << 39], &mut [0; 2]), Err(ZigZagError::OutOfRange { offset: 1 }));
+        assert_eq!(I40::decode_slice(&[0; 3], &mut [0; 2]), Err(ZigZagError::BufferTooSmall { needed: 3, actual: 2 }));
+        assert_eq!(ZigZagN::<5>::decode_slice(&[31, 32], &mut [0; 2]), Err(ZigZagError::OutOfRange { offset: 1 }));
+    }
+}
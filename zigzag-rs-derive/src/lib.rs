@@ -0,0 +1,245 @@
+//! Derive macros for `zigzag-rs`.
+//!
+//! Enable them through the `derive` feature of `zigzag-rs`, which re-exports
+//! them from its crate root. The macros parse their input without `syn` so
+//! that the crate stays dependency-free.
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Implement `ZigZag` for a newtype over a type that implements it
+///
+/// The struct must have exactly one field, such as `struct Celsius(i16)` or
+/// `#[repr(transparent)] struct Offset { bytes: i32 }`. The newtype encodes
+/// to the unsigned type of its field.
+#[proc_macro_derive(ZigZag)]
+pub fn derive_zigzag(input: TokenStream) -> TokenStream {
+    match parse_struct(input).and_then(|item| newtype_impl(&item)) {
+        Ok(code) => code.parse().expect("generated code is valid Rust"),
+        Err(message) => compile_error(&message),
+    }
+}
+
+/// Give a struct of integer fields a ZigZag encoded mirror
+///
+/// For `struct Reading { .. }` this generates `struct ReadingEncoded` with
+/// the same fields, each of the unsigned type its field encodes to, along
+/// with `Reading::encode(&self) -> ReadingEncoded` and
+/// `Reading::decode(ReadingEncoded) -> Reading`. Every field must be `Copy`
+/// and implement `ZigZag`.
+#[proc_macro_derive(ZigZagRecord)]
+pub fn derive_zigzag_record(input: TokenStream) -> TokenStream {
+    match parse_struct(input).and_then(|item| record_impl(&item)) {
+        Ok(code) => code.parse().expect("generated code is valid Rust"),
+        Err(message) => compile_error(&message),
+    }
+}
+
+/// Shape of the struct a derive was applied to
+struct Struct {
+    visibility: String,
+    name: String,
+    fields: Fields,
+}
+
+/// Visibility and type of a field
+struct Field {
+    visibility: String,
+    ty: String,
+}
+
+enum Fields {
+    /// Fields of a tuple struct
+    Tuple(Vec<Field>),
+    /// Names and fields of a struct with named fields
+    Named(Vec<(String, Field)>),
+}
+
+/// Parse a struct without generics into its name and fields
+fn parse_struct(input: TokenStream) -> Result<Struct, String> {
+    let mut tokens = input.into_iter();
+    // Skip attributes and keep the visibility up to the `struct` keyword
+    let mut head = Vec::new();
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => break,
+            Some(TokenTree::Ident(ident)) if matches!(ident.to_string().as_str(), "enum" | "union") => {
+                return Err("ZigZag can only be derived for structs".into());
+            }
+            Some(token) => head.push(token),
+            None => return Err("expected a struct".into()),
+        }
+    }
+    let visibility = split_visibility(&head).0;
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("expected a struct name".into()),
+    };
+    let fields = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            Fields::Tuple(split_fields(group.stream()).iter().map(|field| parse_field(field)).collect())
+        }
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            let mut named = Vec::new();
+            for field in split_fields(group.stream()) {
+                let colon = field
+                    .iter()
+                    .position(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ':'))
+                    .ok_or("expected a field name")?;
+                let Field { visibility, ty: name } = parse_field(&field[..colon]);
+                named.push((name, Field { visibility, ty: tokens_to_string(&field[colon + 1..]) }));
+            }
+            Fields::Named(named)
+        }
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err("ZigZag cannot be derived for generic structs".into());
+        }
+        _ => return Err("expected struct fields".into()),
+    };
+    Ok(Struct { visibility, name, fields })
+}
+
+/// Split the contents of a field list at its top-level commas
+fn split_fields(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut fields = Vec::new();
+    let mut current = Vec::new();
+    // Commas inside angle brackets separate generic arguments, not fields
+    let mut depth = 0usize;
+    for token in stream {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => depth = depth.saturating_sub(1),
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => {
+                fields.push(core::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(token);
+    }
+    if !current.is_empty() {
+        fields.push(current);
+    }
+    fields
+}
+
+/// Split leading `#[...]` attributes and a `pub` or `pub(...)` visibility off `tokens`
+///
+/// Returns the visibility and the remaining tokens; attributes are dropped.
+fn split_visibility(tokens: &[TokenTree]) -> (String, &[TokenTree]) {
+    let mut rest = tokens;
+    let mut visibility = String::new();
+    loop {
+        match rest {
+            [TokenTree::Punct(punct), TokenTree::Group(_), tail @ ..] if punct.as_char() == '#' => rest = tail,
+            [TokenTree::Ident(ident), TokenTree::Group(group), tail @ ..]
+                if ident.to_string() == "pub" && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                visibility = tokens_to_string(&rest[..2]);
+                rest = tail
+            }
+            [TokenTree::Ident(ident), tail @ ..] if ident.to_string() == "pub" => {
+                visibility = "pub".into();
+                rest = tail
+            }
+            _ => return (visibility, rest),
+        }
+    }
+}
+
+/// Parse the tokens of a tuple field, or of the part of a named field before its colon
+fn parse_field(tokens: &[TokenTree]) -> Field {
+    let (visibility, rest) = split_visibility(tokens);
+    Field { visibility, ty: tokens_to_string(rest) }
+}
+
+fn tokens_to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+fn newtype_impl(item: &Struct) -> Result<String, String> {
+    let name = &item.name;
+    let (ty, access, construct) = match &item.fields {
+        Fields::Tuple(fields) if fields.len() == 1 => (&fields[0].ty, "0".to_string(), format!("{name}(decoded)")),
+        Fields::Named(fields) if fields.len() == 1 => {
+            let (field, Field { ty, .. }) = &fields[0];
+            (ty, field.clone(), format!("{name} {{ {field}: decoded }}"))
+        }
+        _ => return Err("ZigZag can only be derived for structs with exactly one field".into()),
+    };
+    Ok(format!(
+        "impl ::zigzag_rs::ZigZag for {name} {{
+            type UInt = <{ty} as ::zigzag_rs::ZigZag>::UInt;
+
+            #[inline]
+            fn zigzag_encode(value: Self) -> Self::UInt {{
+                <{ty} as ::zigzag_rs::ZigZag>::zigzag_encode(value.{access})
+            }}
+
+            #[inline]
+            fn zigzag_decode(value: Self::UInt) -> Self {{
+                let decoded = <{ty} as ::zigzag_rs::ZigZag>::zigzag_decode(value);
+                {construct}
+            }}
+        }}"
+    ))
+}
+
+fn record_impl(item: &Struct) -> Result<String, String> {
+    let Struct { visibility, name, fields } = item;
+    let encoded = format!("{name}Encoded");
+    let uint = |ty: &str| format!("<{ty} as ::zigzag_rs::ZigZag>::UInt");
+    let encode = |ty: &str, access: &str| format!("<{ty} as ::zigzag_rs::ZigZag>::zigzag_encode(self.{access})");
+    let decode = |ty: &str, access: &str| format!("<{ty} as ::zigzag_rs::ZigZag>::zigzag_decode(encoded.{access})");
+    let field_doc = |field: &str| format!("#[doc = \"ZigZag encoded `{field}`\"]");
+    let (body, encode_body, decode_body) = match fields {
+        Fields::Tuple(fields) if !fields.is_empty() => {
+            let indexed = || fields.iter().enumerate();
+            let types: Vec<String> =
+                indexed().map(|(i, field)| format!("{} {} {}", field_doc(&i.to_string()), field.visibility, uint(&field.ty))).collect();
+            let encodes: Vec<String> = indexed().map(|(i, field)| encode(&field.ty, &i.to_string())).collect();
+            let decodes: Vec<String> = indexed().map(|(i, field)| decode(&field.ty, &i.to_string())).collect();
+            (
+                format!("({});", types.join(", ")),
+                format!("{encoded}({})", encodes.join(", ")),
+                format!("{name}({})", decodes.join(", ")),
+            )
+        }
+        Fields::Named(fields) if !fields.is_empty() => {
+            let types: Vec<String> = fields
+                .iter()
+                .map(|(field, Field { visibility, ty })| format!("{} {visibility} {field}: {}", field_doc(field), uint(ty)))
+                .collect();
+            let encodes: Vec<String> = fields.iter().map(|(field, Field { ty, .. })| format!("{field}: {}", encode(ty, field))).collect();
+            let decodes: Vec<String> = fields.iter().map(|(field, Field { ty, .. })| format!("{field}: {}", decode(ty, field))).collect();
+            (
+                format!("{{ {} }}", types.join(", ")),
+                format!("{encoded} {{ {} }}", encodes.join(", ")),
+                format!("{name} {{ {} }}", decodes.join(", ")),
+            )
+        }
+        _ => return Err("ZigZagRecord can only be derived for structs with fields".into()),
+    };
+    Ok(format!(
+        "#[doc = \"ZigZag encoded fields of [`{name}`]\"]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        {visibility} struct {encoded} {body}
+
+        impl {name} {{
+            #[doc = \"ZigZag encode every field\"]
+            #[inline]
+            {visibility} fn encode(&self) -> {encoded} {{
+                {encode_body}
+            }}
+
+            #[doc = \"Decode every field of a record written by `encode`\"]
+            #[inline]
+            {visibility} fn decode(encoded: {encoded}) -> Self {{
+                {decode_body}
+            }}
+        }}"
+    ))
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    format!("::core::compile_error!({message:?});").parse().expect("error message is a valid literal")
+}